@@ -0,0 +1,227 @@
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Attribute, DeriveInput, Error, Expr, Ident, Result, Token};
+
+//key = expression, path values may be constants of routes::path
+struct Arg {
+    key: Ident,
+    value: Expr,
+}
+
+impl Parse for Arg {
+    fn parse(input: ParseStream) -> Result<Arg> {
+        let key = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+        Ok(Arg {
+            key: key,
+            value: value,
+        })
+    }
+}
+
+struct Args {
+    attr: Attribute,
+    items: Vec<Arg>,
+}
+
+impl Args {
+    //#[event] has no arguments
+    fn parse(attr: &Attribute) -> Result<Args> {
+        let items = if attr.tokens.is_empty() {
+            Vec::new()
+        } else {
+            attr.parse_args_with(Punctuated::<Arg, Token![,]>::parse_terminated)?
+                .into_iter()
+                .collect()
+        };
+        Ok(Args {
+            attr: attr.clone(),
+            items: items,
+        })
+    }
+
+    fn check(&self, keys: &[&str]) -> Result<()> {
+        for item in &self.items {
+            if !keys.iter().any(|k| item.key == *k) {
+                return Err(Error::new(
+                    item.key.span(),
+                    format!(
+                        "unknown key {}, expected one of {}",
+                        item.key,
+                        keys.join(", ")
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Option<&Expr> {
+        self.items.iter().find(|i| i.key == key).map(|i| &i.value)
+    }
+
+    fn get_required(&self, key: &str) -> Result<&Expr> {
+        self.get(key)
+            .ok_or_else(|| Error::new(self.attr.span(), format!("{} is required", key)))
+    }
+}
+
+fn get_option(value: Option<&Expr>) -> TokenStream2 {
+    match value {
+        Some(v) => quote!(::std::option::Option::Some(#v)),
+        None => quote!(::std::option::Option::None),
+    }
+}
+
+fn get_str(value: Option<&Expr>, default: &str) -> TokenStream2 {
+    match value {
+        Some(v) => quote!(#v),
+        None => quote!(#default),
+    }
+}
+
+//command, event or reply, then every subscription of event
+fn get_kind(input: &DeriveInput) -> Result<(TokenStream2, Vec<TokenStream2>)> {
+    let mut kind = quote!(::my_rust_hyper::webapi::registry::Kind::Reply);
+    let mut kind_attr: Option<&Attribute> = None;
+    let mut subscriptions = Vec::<TokenStream2>::new();
+    for attr in &input.attrs {
+        let name = match attr.path.get_ident() {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        if (name == "command" || name == "event") && kind_attr.is_some() {
+            return Err(Error::new(
+                attr.span(),
+                "only one of command or event is allowed",
+            ));
+        }
+        match name.as_str() {
+            "command" => {
+                let args = Args::parse(attr)?;
+                args.check(&["service", "reply", "exec_mode", "path", "description"])?;
+                let service = get_option(args.get("service"));
+                let reply = args.get_required("reply")?;
+                let exec_mode = get_str(args.get("exec_mode"), "Any");
+                let path = get_option(args.get("path"));
+                let description = get_str(args.get("description"), "");
+                kind = quote! {
+                    ::my_rust_hyper::webapi::registry::Kind::Command {
+                        service: #service,
+                        reply: <#reply as ::my_rust_hyper::webapi::traits::ObjectType>::get_type_name(),
+                        exec_mode: #exec_mode,
+                        path: #path,
+                        description: #description,
+                    }
+                };
+                kind_attr = Some(attr);
+            }
+            "event" => {
+                let args = Args::parse(attr)?;
+                args.check(&["service", "description"])?;
+                let service = get_option(args.get("service"));
+                let description = get_str(args.get("description"), "");
+                kind = quote! {
+                    ::my_rust_hyper::webapi::registry::Kind::Event {
+                        service: #service,
+                        description: #description,
+                    }
+                };
+                kind_attr = Some(attr);
+            }
+            "subscription" => {
+                let args = Args::parse(attr)?;
+                args.check(&["service", "path"])?;
+                let service = args.get_required("service")?;
+                let path = get_option(args.get("path"));
+                subscriptions.push(quote! {
+                    ::my_rust_hyper::webapi::registry::Subscription {
+                        service: #service,
+                        path: #path,
+                    }
+                });
+            }
+            _ => {}
+        }
+    }
+    if subscriptions.len() > 0 && !kind_attr.map_or(false, |a| a.path.is_ident("event")) {
+        return Err(Error::new(
+            input.ident.span(),
+            "subscription is allowed for event only",
+        ));
+    }
+    Ok((kind, subscriptions))
+}
+
+fn get_version(input: &DeriveInput) -> Result<Option<TokenStream2>> {
+    for attr in &input.attrs {
+        if attr.path.is_ident("object_type") {
+            let args = Args::parse(attr)?;
+            args.check(&["version"])?;
+            return Ok(args.get("version").map(|v| {
+                quote! {
+                    fn get_schema_version() -> u32 {
+                        #v
+                    }
+                }
+            }));
+        }
+    }
+    Ok(None)
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2> {
+    if input.generics.params.len() > 0 {
+        return Err(Error::new(
+            input.generics.span(),
+            "generic types can not be registered",
+        ));
+    }
+    let ident = &input.ident;
+    let type_name = ident.to_string();
+    let version = get_version(input)?;
+    let (kind, subscriptions) = get_kind(input)?;
+    Ok(quote! {
+        impl ::my_rust_hyper::webapi::traits::ObjectType for #ident {
+            fn get_type_name() -> &'static str {
+                #type_name
+            }
+
+            #version
+        }
+
+        impl ::my_rust_hyper::webapi::registry::Declared for #ident {
+            fn get_declaration() -> ::my_rust_hyper::webapi::registry::Declaration {
+                ::my_rust_hyper::webapi::registry::Declaration {
+                    object_type: #type_name,
+                    kind: #kind,
+                    subscriptions: ::std::vec![#(#subscriptions),*],
+                    schema: ::my_rust_hyper::webapi::schema::make_versioned_schema::<#ident>,
+                }
+            }
+        }
+
+        ::my_rust_hyper::webapi::registry::inventory::submit! {
+            ::my_rust_hyper::webapi::registry::Registration(
+                <#ident as ::my_rust_hyper::webapi::registry::Declared>::get_declaration
+            )
+        }
+    })
+}
+
+//ObjectType impl, declaration of route and schema registered at start
+#[proc_macro_derive(ObjectType, attributes(object_type, command, event, subscription))]
+pub fn derive_object_type(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
@@ -0,0 +1,33 @@
+//router only, sample car commands are not handled
+//cargo run --example router -- --config appsettings.json
+#[macro_use]
+extern crate log;
+
+use my_rust_hyper::webapi::{config, server};
+use std::env;
+use std::process;
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let config = match config::Options::from_env_and_args(&args).and_then(|o| config::load(&o)) {
+        Ok(config) => config,
+        Err(errors) => {
+            for e in errors {
+                eprintln!("configuration error {}", e);
+            }
+            process::exit(2);
+        }
+    };
+    log4rs::init_file("log4rs.yml", Default::default()).unwrap();
+    for w in &config.warnings {
+        warn!("configuration {}", w);
+    }
+    let server = server::ServerBuilder::new(config)
+        .build()
+        .await
+        .expect("error while server initialize");
+    if let Err(e) = server.run(server::shutdown_signal()).await {
+        error!("server: {}", e);
+    }
+}
@@ -0,0 +1,242 @@
+-- TABLES
+CREATE TABLE car (
+	id INTEGER PRIMARY KEY AUTOINCREMENT,
+	car_name TEXT NOT NULL
+);
+
+CREATE UNIQUE INDEX car_car_name_idx ON car (car_name);
+
+CREATE TABLE usr (
+	id INTEGER PRIMARY KEY AUTOINCREMENT,
+	usr_name TEXT UNIQUE NOT NULL,
+	usr_password TEXT NOT NULL
+);
+
+INSERT INTO usr
+(usr_name, usr_password)
+VALUES('user1', 'pass1');
+
+INSERT INTO usr
+(usr_name, usr_password)
+VALUES('test', '1234567890');
+
+CREATE TABLE client_access (
+	source_service_name TEXT NOT NULL,
+	destination_service_name TEXT NOT NULL,
+	usr_name TEXT NOT NULL,
+	usr_password TEXT NOT NULL,
+	CONSTRAINT client_access_pk PRIMARY KEY (source_service_name, destination_service_name)
+);
+
+INSERT INTO client_access
+(source_service_name, destination_service_name, usr_name, usr_password)
+VALUES('*', '*', 'test', '1234567890');
+
+CREATE TABLE sended_async_command (
+	id TEXT NOT NULL,
+	object_type TEXT NOT NULL,
+	"service_name" TEXT NOT NULL,
+	"state" TEXT NOT NULL,
+	change_state_event INTEGER NOT NULL,
+	added_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+	state_changed_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+	CONSTRAINT sended_async_command_pk PRIMARY KEY (id)
+);
+
+CREATE INDEX sended_async_command_added_at_idx ON sended_async_command (added_at, object_type);
+
+CREATE TABLE sended_async_command_state_history (
+	command_id TEXT NOT NULL,
+	"state" TEXT NOT NULL,
+	added_at TIMESTAMP,
+	CONSTRAINT sended_async_command_state_history_pk PRIMARY KEY (command_id)
+);
+
+CREATE INDEX sended_async_command_state_history_added_at_idx ON sended_async_command_state_history
+	(command_id, added_at);
+
+CREATE TABLE received_async_command (
+	id TEXT NOT NULL,
+	object_type TEXT NOT NULL,
+	"service_name" TEXT NOT NULL,
+	request_body TEXT NOT NULL,
+	"state" TEXT NOT NULL,
+	change_state_event INTEGER NOT NULL,
+	reply_body TEXT NOT NULL,
+	proto TEXT NOT NULL,
+	added_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+	state_changed_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+	CONSTRAINT received_async_command_pk PRIMARY KEY (id)
+);
+
+CREATE INDEX received_async_command_added_at_idx ON received_async_command (added_at, object_type);
+
+CREATE TABLE received_async_command_state_history (
+	command_id TEXT NOT NULL,
+	"state" TEXT NOT NULL,
+	added_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+	CONSTRAINT received_async_command_state_history_pk PRIMARY KEY (command_id)
+);
+
+CREATE INDEX received_async_command_state_history_added_at_idx ON received_async_command_state_history
+	(command_id, added_at);
+
+CREATE TABLE "service" (
+	"name" TEXT NOT NULL,
+	"description" TEXT NOT NULL,
+	"priority" INTEGER NOT NULL,
+	added_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+	CONSTRAINT service_pk PRIMARY KEY ("name")
+);
+
+CREATE TABLE service_path (
+	"service_name" TEXT NOT NULL,
+	proto TEXT NOT NULL,
+	helth TEXT NOT NULL,
+	"schema" TEXT NOT NULL,
+	"reply_to" TEXT NOT NULL,
+	"state" TEXT NOT NULL,
+	"error" TEXT NOT NULL,
+	CONSTRAINT service_path_pk PRIMARY KEY ("service_name", proto),
+	CONSTRAINT service_path_service_name_fk FOREIGN KEY ("service_name") REFERENCES service("name")
+);
+
+CREATE TABLE service_command (
+	"service_name" TEXT NOT NULL,
+	object_type TEXT NOT NULL,
+	"description" TEXT NOT NULL,
+	exec_mode TEXT NOT NULL,
+	reply_type TEXT NOT NULL,
+	CONSTRAINT service_command_pk PRIMARY KEY ("service_name", object_type),
+	CONSTRAINT service_command_service_name_fk FOREIGN KEY ("service_name") REFERENCES service("name")
+);
+
+CREATE TABLE service_command_path (
+	"service_name" TEXT NOT NULL,
+	object_type TEXT NOT NULL,
+	proto TEXT NOT NULL,
+	"to" TEXT NOT NULL,
+	CONSTRAINT service_command_path_pk PRIMARY KEY ("service_name", object_type, proto),
+	CONSTRAINT service_command_path_sn_ot_fk FOREIGN KEY ("service_name", object_type)
+		REFERENCES service_command("service_name", object_type)
+);
+
+CREATE TABLE service_command_state (
+	"service_name" TEXT NOT NULL,
+	object_type TEXT NOT NULL,
+	"state" TEXT NOT NULL,
+	"description" TEXT NOT NULL,
+	CONSTRAINT service_command_state_pk PRIMARY KEY ("service_name", object_type, "state"),
+	CONSTRAINT service_command_state_sn_ot_fk FOREIGN KEY ("service_name", object_type)
+		REFERENCES service_command("service_name", object_type)
+);
+
+CREATE TABLE service_event (
+	"service_name" TEXT NOT NULL,
+	object_type TEXT NOT NULL,
+	"description" TEXT NOT NULL,
+	CONSTRAINT service_event_pk PRIMARY KEY ("service_name", object_type),
+	CONSTRAINT service_event_service_name_fk FOREIGN KEY ("service_name") REFERENCES service("name")
+);
+
+CREATE TABLE service_subscription (
+	"service_name" TEXT NOT NULL,
+	object_type TEXT NOT NULL,
+	CONSTRAINT service_subscription_pk PRIMARY KEY ("service_name", object_type),
+	CONSTRAINT service_subscription_service_name_fk FOREIGN KEY ("service_name") REFERENCES service("name")
+);
+
+CREATE TABLE service_subscription_path (
+	"service_name" TEXT NOT NULL,
+	object_type TEXT NOT NULL,
+	proto TEXT NOT NULL,
+	"to" TEXT NOT NULL,
+	CONSTRAINT service_subscription_path_pk PRIMARY KEY ("service_name", object_type, proto),
+	CONSTRAINT service_subscription_path_sn_ot_fk FOREIGN KEY ("service_name", object_type)
+		REFERENCES service_subscription("service_name", object_type)
+);
+
+-- VIEWS
+CREATE VIEW v_sended_async_command
+AS SELECT id,
+	object_type,
+	"service_name",
+	"state",
+	change_state_event,
+	added_at,
+	state_changed_at
+		FROM sended_async_command
+			ORDER BY added_at, object_type;
+
+CREATE VIEW v_sended_async_command_state_history
+AS SELECT command_id, "state", added_at
+		FROM sended_async_command_state_history
+			ORDER BY command_id, added_at;
+
+CREATE VIEW v_received_async_command
+AS SELECT id,
+	object_type,
+	"service_name",
+	request_body,
+	"state",
+	change_state_event,
+	reply_body,
+	proto,
+	added_at,
+	state_changed_at
+		FROM received_async_command
+			ORDER BY added_at, object_type;
+
+CREATE VIEW v_received_async_command_state_history
+AS SELECT command_id, "state", added_at
+		FROM received_async_command_state_history
+			ORDER BY command_id, added_at;
+
+CREATE VIEW v_service
+AS SELECT s."name", s."description", s.priority, 'Unavailable' as "state", s.added_at
+	FROM service s
+		ORDER BY s."name";
+
+CREATE VIEW v_service_command
+AS SELECT c.service_name,
+	s.priority,
+	c.object_type,
+	c.description,
+	c.exec_mode,
+	c.reply_type
+		FROM service_command c
+			JOIN service s ON s.name = c.service_name
+				ORDER BY c.object_type, s.priority;
+
+CREATE VIEW v_service_path
+AS SELECT p."service_name", p.proto, p.helth, p."schema", p.reply_to, p."state", p."error"
+	FROM service_path p
+		ORDER BY p."service_name", p.proto;
+
+CREATE VIEW v_service_command_path
+AS SELECT p."service_name", p.object_type, p.proto, p."to"
+	FROM service_command_path p
+		ORDER BY p.proto;
+
+CREATE VIEW v_service_command_state
+AS SELECT p."service_name", p.object_type, p."state", p.description
+	FROM service_command_state p
+		ORDER BY p."state";
+
+CREATE VIEW v_service_event
+AS SELECT e.service_name,
+	e.object_type,
+	e.description
+		FROM service_event e;
+
+CREATE VIEW v_service_subscription
+AS SELECT ss.service_name,
+	ss.object_type
+		FROM service_subscription ss
+			JOIN service sv ON sv.name = ss.service_name
+				ORDER BY ss.object_type;
+
+CREATE VIEW v_service_subscription_path
+AS SELECT p."service_name", p.object_type, p.proto, p."to"
+	FROM service_subscription_path p
+		ORDER BY p.proto;
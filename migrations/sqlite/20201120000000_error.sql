@@ -0,0 +1,87 @@
+-- TABLES
+CREATE TABLE error (
+	error_code TEXT NOT NULL,
+	lang TEXT NOT NULL,
+	error_name TEXT NOT NULL,
+	CONSTRAINT error_pk PRIMARY KEY (error_code, lang)
+);
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('ReplyOk', 'en', 'Ok');
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('ReplyOk', 'ru', 'Успешно');
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('AsyncOk', 'en', 'Async command registered');
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('AsyncOk', 'ru', 'Асинхронная команда зарегистрирована');
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('UnsupportedExecModeError', 'en', 'Unsupported exec mode error');
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('UnsupportedExecModeError', 'ru', 'Неподдерживаемый режим выполнения');
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('TooManyRequestsError', 'en', 'Too many requests error');
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('TooManyRequestsError', 'ru', 'Слишком много запросов');
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('TimeoutExpiredError', 'en', 'Timeout expired error');
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('TimeoutExpiredError', 'ru', 'Истекло время ожидания');
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('DatabaseError', 'en', 'Database error');
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('DatabaseError', 'ru', 'Ошибка базы данных');
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('NotFoundError', 'en', 'Some items with specified id is not found');
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('NotFoundError', 'ru', 'Некоторые элементы с указанным id не найдены');
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('BadRequestError', 'en', 'Bad request error');
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('BadRequestError', 'ru', 'Некорректный запрос');
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('RemoteServiceError', 'en', 'Remote service error');
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('RemoteServiceError', 'ru', 'Ошибка удаленного сервиса');
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('InternalError', 'en', 'Internal error');
+
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('InternalError', 'ru', 'Внутренняя ошибка');
@@ -0,0 +1,14 @@
+-- TABLES
+CREATE TABLE saga (
+	id TEXT NOT NULL,
+	saga_type TEXT NOT NULL,
+	correlation_id TEXT NOT NULL,
+	state TEXT NOT NULL,
+	step INTEGER NOT NULL,
+	data TEXT NOT NULL,
+	added_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+	state_changed_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+	CONSTRAINT saga_pk PRIMARY KEY (id)
+);
+
+CREATE INDEX saga_state_idx ON saga (state, added_at);
@@ -0,0 +1,13 @@
+-- TABLES
+CREATE TABLE idempotent_reply (
+	caller TEXT NOT NULL,
+	object_type TEXT NOT NULL,
+	correlation_id TEXT NOT NULL,
+	state TEXT NOT NULL,
+	status INTEGER NOT NULL,
+	reply_body TEXT NOT NULL,
+	added_at TIMESTAMP NOT NULL,
+	CONSTRAINT idempotent_reply_pk PRIMARY KEY (caller, object_type, correlation_id)
+);
+
+CREATE INDEX idempotent_reply_added_at_idx ON idempotent_reply (added_at);
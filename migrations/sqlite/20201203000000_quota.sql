@@ -0,0 +1,10 @@
+-- TABLES
+CREATE TABLE quota_usage (
+	caller TEXT NOT NULL,
+	object_type TEXT NOT NULL,
+	day TEXT NOT NULL,
+	usage_count INTEGER NOT NULL,
+	CONSTRAINT quota_usage_pk PRIMARY KEY (caller, object_type, day)
+);
+
+CREATE INDEX quota_usage_day_idx ON quota_usage (day);
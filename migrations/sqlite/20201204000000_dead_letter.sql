@@ -0,0 +1,13 @@
+-- TABLES
+CREATE TABLE dead_letter_event (
+	id TEXT NOT NULL,
+	object_type TEXT NOT NULL,
+	service_name TEXT NOT NULL,
+	correlation_id TEXT NOT NULL,
+	event_body TEXT NOT NULL,
+	error TEXT NOT NULL,
+	added_at TIMESTAMP NOT NULL,
+	CONSTRAINT dead_letter_event_pk PRIMARY KEY (id)
+);
+
+CREATE INDEX dead_letter_event_added_at_idx ON dead_letter_event (added_at);
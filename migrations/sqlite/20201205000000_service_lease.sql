@@ -0,0 +1,15 @@
+-- TABLES
+ALTER TABLE "service" ADD COLUMN lease INTEGER NULL;
+
+ALTER TABLE "service" ADD COLUMN lease_expires_at TIMESTAMP NULL;
+
+CREATE INDEX service_lease_expires_at_idx ON "service" (lease_expires_at);
+
+CREATE TABLE service_history (
+	"service_name" TEXT NOT NULL,
+	"state" TEXT NOT NULL,
+	"description" TEXT NOT NULL,
+	added_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX service_history_service_name_idx ON service_history ("service_name", added_at);
@@ -0,0 +1,7 @@
+-- TABLES
+ALTER TABLE saga ADD COLUMN owner TEXT NOT NULL DEFAULT '';
+
+-- sagas of earlier instances are recovered as orphaned
+ALTER TABLE saga ADD COLUMN lease_expires_at TIMESTAMP NOT NULL DEFAULT '1970-01-01 00:00:00';
+
+CREATE INDEX saga_owner_idx ON saga (owner, state);
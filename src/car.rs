@@ -0,0 +1,210 @@
+use async_trait::async_trait;
+use my_rust_hyper::webapi::{
+    commands, connectors, entities::car, errors, replies, routes::service, saga, traits::ObjectType,
+};
+use my_rust_hyper::{
+    get_error_add_int_ids_reply, get_error_reply, get_ok_add_int_ids_reply, get_ok_reply,
+};
+use serde::{Deserialize, Serialize};
+
+//ChangeCar saga input, previous values of changed cars restore them on compensation
+#[derive(Deserialize, Serialize)]
+pub struct ChangeCarData {
+    pub items: Vec<car::Car>,
+    pub previous: Vec<car::Car>,
+}
+
+struct ChangeCarStep;
+
+#[async_trait]
+impl saga::Step<ChangeCarData> for ChangeCarStep {
+    fn get_name(&self) -> &'static str {
+        "change cars"
+    }
+
+    async fn execute(
+        &self,
+        sc: &saga::SagaContext<'_>,
+        data: &ChangeCarData,
+    ) -> connectors::Result<errors::ErrorCode> {
+        sc.dc.car.change(data.items.clone()).await
+    }
+
+    async fn compensate(
+        &self,
+        sc: &saga::SagaContext<'_>,
+        data: &ChangeCarData,
+    ) -> connectors::Result<errors::ErrorCode> {
+        if data.previous.len() == 0 {
+            return Ok(errors::ErrorCode::ReplyOk);
+        }
+        sc.dc.car.change(data.previous.clone()).await
+    }
+}
+
+fn get_reserve(_data: &ChangeCarData) -> commands::car::ReserveCar {
+    commands::car::ReserveCar { services: vec![1] }
+}
+
+fn get_release(_data: &ChangeCarData) -> commands::car::ReleaseCar {
+    commands::car::ReleaseCar { services: vec![1] }
+}
+
+//reserve on remote service, then change locally; interrupted one is compensated on restart
+pub fn get_change_saga() -> saga::Saga<ChangeCarData> {
+    saga::Saga::new(
+        commands::car::ChangeCar::get_type_name(),
+        saga::Recovery::Compensate,
+        vec![
+            Box::new(saga::RemoteStep::new(
+                "reserve cars",
+                get_reserve,
+                get_release,
+            )),
+            Box::new(ChangeCarStep),
+        ],
+    )
+}
+
+pub struct GetCarHandler;
+
+#[async_trait]
+impl service::CommandHandler for GetCarHandler {
+    type Command = commands::car::GetCar;
+    type Reply = replies::car::GetCarReply;
+
+    async fn handle(
+        &self,
+        hc: &service::HandlerContext<'_>,
+        cmd: commands::car::GetCar,
+    ) -> connectors::Result<replies::car::GetCarReply> {
+        match hc.dc.car.get(cmd.ids).await {
+            Ok(r) => Ok(replies::car::GetCarReply {
+                error_code: errors::ErrorCode::ReplyOk,
+                error_name: None,
+                url: None,
+                items: Some(r),
+            }),
+            Err(e) => {
+                error!("get_car handler get car collection: {}", e);
+                let ec = errors::ErrorCode::DatabaseError;
+                Ok(replies::car::GetCarReply {
+                    error_code: ec.clone(),
+                    error_name: Some(errors::get_error_name(&hc.dc.error, &ec)),
+                    url: None,
+                    items: None,
+                })
+            }
+        }
+    }
+}
+
+pub struct AddCarHandler;
+
+#[async_trait]
+impl service::CommandHandler for AddCarHandler {
+    type Command = commands::car::AddCar;
+    type Reply = replies::common::AddIntIdsReply;
+
+    async fn handle(
+        &self,
+        hc: &service::HandlerContext<'_>,
+        cmd: commands::car::AddCar,
+    ) -> connectors::Result<replies::common::AddIntIdsReply> {
+        let (result, ids) = hc.dc.car.add(cmd.items).await?;
+        if result == errors::ErrorCode::ReplyOk {
+            Ok(get_ok_add_int_ids_reply!(ids.unwrap()))
+        } else {
+            Ok(get_error_add_int_ids_reply!(&result, hc.dc.error))
+        }
+    }
+}
+
+pub struct ChangeCarHandler;
+
+#[async_trait]
+impl service::CommandHandler for ChangeCarHandler {
+    type Command = commands::car::ChangeCar;
+    type Reply = replies::common::StandardReply;
+
+    async fn handle(
+        &self,
+        hc: &service::HandlerContext<'_>,
+        cmd: commands::car::ChangeCar,
+    ) -> connectors::Result<replies::common::StandardReply> {
+        let ids: Vec<i32> = cmd.items.iter().filter_map(|c| c.id).collect();
+        let previous = if ids.len() > 0 {
+            hc.dc.car.get(Some(ids)).await?
+        } else {
+            vec![]
+        };
+        let sc = saga::SagaContext {
+            dc: hc.dc,
+            ce: hc.ce,
+            ctx: hc.ctx,
+        };
+        let data = ChangeCarData {
+            items: cmd.items,
+            previous: previous,
+        };
+        let result: errors::ErrorCode = get_change_saga().run(&sc, data).await?;
+        if result == errors::ErrorCode::ReplyOk {
+            Ok(get_ok_reply!())
+        } else {
+            Ok(get_error_reply!(&result, hc.dc.error))
+        }
+    }
+}
+
+pub struct RemoveCarHandler;
+
+#[async_trait]
+impl service::CommandHandler for RemoveCarHandler {
+    type Command = commands::car::RemoveCar;
+    type Reply = replies::common::StandardReply;
+
+    async fn handle(
+        &self,
+        hc: &service::HandlerContext<'_>,
+        cmd: commands::car::RemoveCar,
+    ) -> connectors::Result<replies::common::StandardReply> {
+        let result: errors::ErrorCode = hc.dc.car.remove(cmd.ids).await?;
+        if result == errors::ErrorCode::ReplyOk {
+            Ok(get_ok_reply!())
+        } else {
+            Ok(get_error_reply!(&result, hc.dc.error))
+        }
+    }
+}
+
+pub struct ReserveCarHandler;
+
+#[async_trait]
+impl service::CommandHandler for ReserveCarHandler {
+    type Command = commands::car::ReserveCar;
+    type Reply = replies::common::StandardReply;
+
+    async fn handle(
+        &self,
+        _hc: &service::HandlerContext<'_>,
+        _cmd: commands::car::ReserveCar,
+    ) -> connectors::Result<replies::common::StandardReply> {
+        Ok(get_ok_reply!())
+    }
+}
+
+pub struct ReleaseCarHandler;
+
+#[async_trait]
+impl service::CommandHandler for ReleaseCarHandler {
+    type Command = commands::car::ReleaseCar;
+    type Reply = replies::common::StandardReply;
+
+    async fn handle(
+        &self,
+        _hc: &service::HandlerContext<'_>,
+        _cmd: commands::car::ReleaseCar,
+    ) -> connectors::Result<replies::common::StandardReply> {
+        Ok(get_ok_reply!())
+    }
+}
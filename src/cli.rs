@@ -0,0 +1,207 @@
+//calls command through router with settings of service
+//cargo run --bin my-rust-hyper-cli -- --config appsettings.json GetCar '{"filter": null, "ids": [1]}'
+use dotenv::dotenv;
+use my_rust_hyper::webapi::{
+    config, context, entities::executor::CommandSystemState, executors::ExecMode, server,
+};
+use std::env;
+use std::io::{self, Read};
+use std::process;
+use std::time::{Duration, Instant};
+use tokio::time::delay_for;
+use uuid::Uuid;
+
+const ENV_LOG_SETTINGS: &str = "MY_LOG_SETTINGS";
+const DEFAULT_POLL_INTERVAL: u64 = 1; //seconds
+const DEFAULT_POLL_TIMEOUT: u64 = 300; //seconds
+
+const USAGE: &str = "usage: my-rust-hyper-cli [--config FILE] [--env NAME] [--set KEY=VALUE]... \
+                     [--async] [--poll SECONDS] [--timeout SECONDS] OBJECT_TYPE [JSON]\n\
+                     request is read from stdin if JSON is omitted";
+
+struct Args {
+    object_type: String,
+    request: Option<String>,
+    is_async: bool,
+    poll_interval: Duration,
+    poll_timeout: Duration,
+    config: Vec<String>, //passed to config options
+}
+
+fn parse_seconds(flag: &str, value: Option<&String>) -> Result<Duration, String> {
+    value
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("{}: expected positive number of seconds", flag))
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut config = Vec::<String>::new();
+    let mut positional = Vec::<String>::new();
+    let mut is_async = false;
+    let mut poll_interval = Duration::from_secs(DEFAULT_POLL_INTERVAL);
+    let mut poll_timeout = Duration::from_secs(DEFAULT_POLL_TIMEOUT);
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        match flag {
+            "--async" => is_async = true,
+            "--poll" => {
+                i += 1;
+                poll_interval = parse_seconds(flag, args.get(i))?;
+            }
+            "--timeout" => {
+                i += 1;
+                poll_timeout = parse_seconds(flag, args.get(i))?;
+            }
+            "--config" | "--env" | "--set" => {
+                config.push(args[i].clone());
+                i += 1;
+                match args.get(i) {
+                    Some(v) => config.push(v.clone()),
+                    None => return Err(format!("{}: value is missing", flag)),
+                }
+            }
+            _ if flag.starts_with("--") => return Err(format!("{}: unknown flag", flag)),
+            _ => positional.push(args[i].clone()),
+        }
+        i += 1;
+    }
+    if positional.len() == 0 || positional.len() > 2 {
+        return Err("expected OBJECT_TYPE and optional JSON".to_string());
+    }
+    Ok(Args {
+        object_type: positional[0].clone(),
+        request: positional.get(1).cloned(),
+        is_async: is_async,
+        poll_interval: poll_interval,
+        poll_timeout: poll_timeout,
+        config: config,
+    })
+}
+
+fn exit_with_error(message: &str, is_usage: bool) -> ! {
+    eprintln!("{}", message);
+    if is_usage {
+        eprintln!("{}", USAGE);
+    }
+    process::exit(2);
+}
+
+fn print_json(value: &serde_json::Value) {
+    println!("{}", serde_json::to_string_pretty(value).unwrap());
+}
+
+#[tokio::main]
+async fn main() {
+    dotenv().ok();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args = match parse_args(&args) {
+        Ok(args) => args,
+        Err(e) => exit_with_error(&e, true),
+    };
+    let config =
+        match config::Options::from_env_and_args(&args.config).and_then(|o| config::load(&o)) {
+            Ok(config) => config,
+            Err(errors) => {
+                for e in errors {
+                    eprintln!("configuration error {}", e);
+                }
+                process::exit(2);
+            }
+        };
+    //quiet by default, output is the reply
+    if let Ok(log_setting_file) = env::var(ENV_LOG_SETTINGS) {
+        log4rs::init_file(log_setting_file, Default::default()).unwrap();
+    }
+    for w in &config.warnings {
+        eprintln!("configuration warning {}", w);
+    }
+
+    let request = match args.request {
+        Some(request) => request,
+        None => {
+            let mut request = String::new();
+            if let Err(e) = io::stdin().read_to_string(&mut request) {
+                exit_with_error(&format!("stdin: {}", e), false);
+            }
+            request
+        }
+    };
+    let request: serde_json::Value = match serde_json::from_str(&request) {
+        Ok(request) => request,
+        Err(e) => exit_with_error(&format!("bad request json: {}", e), false),
+    };
+
+    let server = match server::ServerBuilder::new(config)
+        .without_registration()
+        .build()
+        .await
+    {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("initialize: {}", e);
+            process::exit(1);
+        }
+    };
+    //async command id is correlation id of call
+    let correlation_id = Uuid::new_v4().to_hyphenated().to_string();
+    let ctx = context::Context::new(
+        &correlation_id,
+        None,
+        context::Span::new(&args.object_type, context::SpanKind::Internal),
+    );
+    let exec_mode = if args.is_async {
+        ExecMode::Async
+    } else {
+        ExecMode::Any
+    };
+    match server
+        .ce
+        .call_value(&ctx, &args.object_type, exec_mode, &request)
+        .await
+    {
+        Ok(reply) => print_json(&reply),
+        Err(e) => {
+            eprintln!("{}: {}", args.object_type, e);
+            process::exit(1);
+        }
+    }
+    if !args.is_async {
+        return;
+    }
+
+    let deadline = Instant::now() + args.poll_timeout;
+    let mut last_state: Option<String> = None;
+    loop {
+        match server
+            .ce
+            .get_sended_async_command_state(&correlation_id)
+            .await
+        {
+            Ok(state) => {
+                if last_state.as_ref() != Some(&state.state) {
+                    print_json(&serde_json::to_value(&state).unwrap());
+                    last_state = Some(state.state.clone());
+                }
+                if state.state == CommandSystemState::Completed.to_string() {
+                    return;
+                }
+            }
+            Err(e) => {
+                eprintln!("{} state: {}", args.object_type, e);
+                process::exit(1);
+            }
+        }
+        if Instant::now() >= deadline {
+            eprintln!(
+                "{} is not completed in {:?}",
+                args.object_type, args.poll_timeout
+            );
+            process::exit(1);
+        }
+        delay_for(args.poll_interval).await;
+    }
+}
@@ -0,0 +1,16 @@
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate strum_macros;
+//derived code refers to this crate by name
+extern crate self as my_rust_hyper;
+
+pub mod webapi;
+
+pub use webapi::access::AccessChecker;
+pub use webapi::executors::CommandExecutor;
+pub use webapi::publishers::EventPublisher;
+pub use webapi::router::Router;
+pub use webapi::server::{Server, ServerBuilder};
+pub use my_rust_hyper_derive::ObjectType;
+pub use webapi::traits::ObjectType;
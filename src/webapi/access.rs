@@ -6,6 +6,7 @@ pub struct AccessChecker {
     sa: HashMap<String, String>,
     cba: HashMap<String, String>,
     cba_common: Option<String>,
+    admin: Option<(String, String)>, //authorization token and user name
 }
 
 impl AccessChecker {
@@ -25,7 +26,11 @@ impl AccessChecker {
         } else if self.cba_common.is_some() {
             Ok(self.cba_common.as_ref().unwrap().clone())
         } else {
-            return Err(errors::UnknownServiceNameError.into());
+            return Err(errors::Error::with_context(
+                errors::ErrorKind::UnknownServiceName,
+                service_name,
+            )
+            .into());
         }
     }
 
@@ -55,6 +60,7 @@ impl AccessChecker {
             sa: sa,
             cba: cba,
             cba_common: cba_common,
+            admin: AccessChecker::get_admin_authorization_token(&access.authentication),
         })
     }
 
@@ -86,10 +92,39 @@ impl AccessChecker {
             sa: server_authorization,
             cba: cba,
             cba_common: cba_common,
+            admin: AccessChecker::get_admin_authorization_token(authentication),
         })
     }
 
+    fn get_admin_authorization_token(
+        authentication: &settings::Authentication,
+    ) -> Option<(String, String)> {
+        authentication.admin.as_ref().map(|a| {
+            (
+                AccessChecker::get_basic_authorization_token(&a.usr_name, &a.usr_password),
+                a.usr_name.clone(),
+            )
+        })
+    }
+
+    pub fn is_admin_enabled(&self) -> bool {
+        self.admin.is_some()
+    }
+
+    //admin is not a server user, commands are not allowed to it
+    pub fn get_admin_name_by_header(&self, header: &str) -> Option<&String> {
+        self.admin
+            .as_ref()
+            .filter(|(token, _)| token == header)
+            .map(|(_, name)| name)
+    }
+
     pub fn is_authorized_by_header(&self, header: &str) -> bool {
         *&self.sa.contains_key(header)
     }
+
+    //server user name of authorized header
+    pub fn get_usr_name_by_header(&self, header: &str) -> Option<&String> {
+        self.sa.get(header)
+    }
 }
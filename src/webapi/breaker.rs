@@ -0,0 +1,153 @@
+use super::entities::route::{BreakerState, ServiceBreaker};
+use super::{connectors, errors, settings};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::time;
+
+const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+const DEFAULT_COOL_DOWN: u64 = 30; //seconds
+const DEFAULT_CALL_TIMEOUT: u64 = 30; //seconds
+
+struct Breaker {
+    state: BreakerState,
+    failure_count: u32,
+    changed_at: Instant, //opened, or trial call started
+}
+
+//one breaker per called service
+pub struct CircuitBreakers {
+    failure_threshold: u32,
+    cool_down: Duration,
+    call_timeout: Duration,
+    breakers: Mutex<HashMap<String, Breaker>>,
+}
+
+impl CircuitBreakers {
+    pub fn new(circuit_breaker: Option<settings::CircuitBreaker>) -> CircuitBreakers {
+        let (failure_threshold, cool_down, call_timeout) = match circuit_breaker {
+            Some(c) => (c.failure_threshold, c.cool_down, c.call_timeout),
+            None => (
+                DEFAULT_FAILURE_THRESHOLD,
+                DEFAULT_COOL_DOWN,
+                DEFAULT_CALL_TIMEOUT,
+            ),
+        };
+        CircuitBreakers {
+            failure_threshold: failure_threshold,
+            cool_down: Duration::from_secs(cool_down),
+            call_timeout: Duration::from_secs(call_timeout),
+            breakers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    //open breaker lets one trial call through after cool down
+    fn before_call(&self, service_name: &str) -> connectors::Result<()> {
+        let mut breakers = self.breakers.lock().unwrap();
+        let b = breakers.entry(service_name.to_string()).or_insert(Breaker {
+            state: BreakerState::Closed,
+            failure_count: 0,
+            changed_at: Instant::now(),
+        });
+        //trial call of half-open breaker may be dropped, next one is allowed after cool down
+        if b.state != BreakerState::Closed && b.changed_at.elapsed() >= self.cool_down {
+            debug!("{} circuit breaker is half-open", service_name);
+            b.state = BreakerState::HalfOpen;
+            b.changed_at = Instant::now();
+            return Ok(());
+        }
+        match b.state {
+            BreakerState::Closed => Ok(()),
+            _ => Err(errors::Error::with_context(
+                errors::ErrorKind::CircuitOpen,
+                &format!("{} {} calls failed", service_name, b.failure_count),
+            )
+            .into()),
+        }
+    }
+
+    fn after_call(&self, service_name: &str, is_ok: bool) {
+        let mut breakers = self.breakers.lock().unwrap();
+        let b = match breakers.get_mut(service_name) {
+            Some(b) => b,
+            None => return,
+        };
+        if is_ok {
+            if b.state != BreakerState::Closed {
+                info!("{} circuit breaker is closed", service_name);
+            }
+            b.state = BreakerState::Closed;
+            b.failure_count = 0;
+            return;
+        }
+        b.failure_count += 1;
+        if b.state == BreakerState::HalfOpen || b.failure_count >= self.failure_threshold {
+            if b.state != BreakerState::Open {
+                warn!(
+                    "{} circuit breaker is open, {} calls failed",
+                    service_name, b.failure_count
+                );
+            }
+            b.state = BreakerState::Open;
+            b.changed_at = Instant::now();
+        }
+    }
+
+    //call of service fails fast if breaker is open, transport errors, 5xx and timeouts are counted
+    pub async fn call<F, T>(&self, service_name: &str, f: F) -> connectors::Result<T>
+    where
+        F: Future<Output = connectors::Result<T>>,
+    {
+        self.before_call(service_name)?;
+        match time::timeout(self.call_timeout, f).await {
+            Ok(Ok(r)) => {
+                self.after_call(service_name, true);
+                Ok(r)
+            }
+            Ok(Err(e)) => {
+                //service replied, request is rejected
+                let is_ok = match e.downcast_ref::<errors::Error>() {
+                    Some(e) => e.kind() == errors::ErrorKind::RejectedRequest,
+                    None => false,
+                };
+                self.after_call(service_name, is_ok);
+                Err(e)
+            }
+            Err(_) => {
+                self.after_call(service_name, false);
+                Err(errors::Error::with_context(
+                    errors::ErrorKind::TimeoutExpired,
+                    &format!("{} call", service_name),
+                )
+                .into())
+            }
+        }
+    }
+
+    pub fn get_state(&self, service_name: &str) -> BreakerState {
+        self.breakers
+            .lock()
+            .unwrap()
+            .get(service_name)
+            .map(|b| b.state)
+            .unwrap_or(BreakerState::Closed)
+    }
+
+    //called services sorted by name
+    pub fn get_breakers(&self) -> Vec<ServiceBreaker> {
+        let mut items: Vec<ServiceBreaker> = self
+            .breakers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, b)| ServiceBreaker {
+                service_name: name.clone(),
+                state: b.state,
+                failure_count: b.failure_count,
+            })
+            .collect();
+        items.sort_by(|a, b| a.service_name.cmp(&b.service_name));
+        items
+    }
+}
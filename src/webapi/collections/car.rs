@@ -1,9 +1,12 @@
-use super::super::{connectors, entities::car, errors, providers};
+use super::super::{connectors, entities::car, errors, providers, traits};
+use async_trait::async_trait;
 #[cfg(feature = "postgres")]
 use sqlx::postgres::PgPool;
+use sqlx::Done;
 #[cfg(feature = "mysql")]
 use sqlx::MySqlPool;
-use sqlx::Done;
+#[cfg(feature = "sqlite")]
+use sqlx::SqlitePool;
 use std::convert::TryFrom;
 use std::sync::Arc;
 
@@ -22,18 +25,28 @@ impl CarCollection {
             exp_helper: &helper,
         }
     }
+}
 
-    pub async fn get(&self, ids: Option<Vec<i32>>) -> connectors::Result<Vec<car::Car>> {
+#[async_trait]
+impl traits::CarStore for CarCollection {
+    async fn get(&self, ids: Option<Vec<i32>>) -> connectors::Result<Vec<car::Car>> {
         #[cfg(feature = "postgres")]
         let pool: &PgPool = &self.data_provider.pool;
         #[cfg(feature = "mysql")]
         let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
         if ids.is_none() {
-            Ok(
+            #[cfg(not(feature = "sqlite"))]
+            return Ok(
                 sqlx::query_as!(car::Car, r#"SELECT id as "id?",car_name FROM webapi.car"#)
                     .fetch_all(pool)
                     .await?,
-            )
+            );
+            #[cfg(feature = "sqlite")]
+            return Ok(sqlx::query_as(r#"SELECT id,car_name FROM car"#)
+                .fetch_all(pool)
+                .await?);
         } else {
             let query = self
                 .exp_helper
@@ -43,7 +56,7 @@ impl CarCollection {
         }
     }
 
-    pub async fn add(
+    async fn add(
         &self,
         items: Vec<car::Car>,
     ) -> connectors::Result<(errors::ErrorCode, Option<Vec<i32>>)> {
@@ -52,6 +65,8 @@ impl CarCollection {
         let pool: &PgPool = &self.data_provider.pool;
         #[cfg(feature = "mysql")]
         let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
         let mut tx = pool.begin().await?;
         for item in items {
             #[cfg(feature = "postgres")]
@@ -94,6 +109,19 @@ impl CarCollection {
                     return Ok((errors::ErrorCode::DatabaseError, None));
                 }
             };
+            #[cfg(feature = "sqlite")]
+            match sqlx::query(r#"INSERT INTO car ( car_name ) VALUES ( ? )"#)
+                .bind(item.car_name)
+                .execute(&mut tx)
+                .await
+            {
+                Ok(ret) => ids.push(ret.last_insert_rowid() as i32),
+                Err(e) => {
+                    tx.rollback().await.unwrap();
+                    error!("add_cars db insert: {}", e);
+                    return Ok((errors::ErrorCode::DatabaseError, None));
+                }
+            };
         }
         match tx.commit().await {
             Ok(_) => {}
@@ -105,11 +133,13 @@ impl CarCollection {
         Ok((errors::ErrorCode::ReplyOk, Some(ids)))
     }
 
-    pub async fn change(&self, items: Vec<car::Car>) -> connectors::Result<errors::ErrorCode> {
+    async fn change(&self, items: Vec<car::Car>) -> connectors::Result<errors::ErrorCode> {
         #[cfg(feature = "postgres")]
         let pool: &PgPool = &self.data_provider.pool;
         #[cfg(feature = "mysql")]
         let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
         let mut tx = pool.begin().await?;
         let mut count: u64 = 0;
         for item in &items {
@@ -145,6 +175,20 @@ impl CarCollection {
                     return Ok(errors::ErrorCode::DatabaseError);
                 }
             };
+            #[cfg(feature = "sqlite")]
+            match sqlx::query(r#"UPDATE car SET car_name = ? WHERE id = ?"#)
+                .bind(&item.car_name)
+                .bind(item.id.unwrap_or(0))
+                .execute(&mut tx)
+                .await
+            {
+                Ok(ret) => count += ret.rows_affected(),
+                Err(e) => {
+                    error!("update_cars db update: {}", e);
+                    tx.rollback().await?;
+                    return Ok(errors::ErrorCode::DatabaseError);
+                }
+            };
         }
         if items.len() == usize::try_from(count).unwrap() {
             match tx.commit().await {
@@ -161,11 +205,13 @@ impl CarCollection {
         }
     }
 
-    pub async fn remove(&self, ids: Vec<i32>) -> connectors::Result<errors::ErrorCode> {
+    async fn remove(&self, ids: Vec<i32>) -> connectors::Result<errors::ErrorCode> {
         #[cfg(feature = "postgres")]
         let pool: &PgPool = &self.data_provider.pool;
         #[cfg(feature = "mysql")]
         let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
         let mut tx = pool.begin().await?;
         match sqlx::query(&self.exp_helper.get_delete_int_exp("webapi.car", "id", &ids))
             .execute(&mut tx)
@@ -0,0 +1,136 @@
+use super::super::{connectors, entities::dead_letter, errors, providers, traits};
+use async_trait::async_trait;
+#[cfg(feature = "postgres")]
+use sqlx::postgres::PgPool;
+use sqlx::Done;
+#[cfg(feature = "mysql")]
+use sqlx::MySqlPool;
+#[cfg(feature = "sqlite")]
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+#[cfg(feature = "postgres")]
+const INSERT_EVENT: &str = r#"INSERT INTO webapi.dead_letter_event
+    ( id, object_type, service_name, correlation_id, event_body, error, added_at )
+        VALUES ( $1, $2, $3, $4, $5, $6, $7 )"#;
+#[cfg(any(feature = "mysql", feature = "sqlite"))]
+const INSERT_EVENT: &str = r#"INSERT INTO dead_letter_event
+    ( id, object_type, service_name, correlation_id, event_body, error, added_at )
+        VALUES ( ?, ?, ?, ?, ?, ?, ? )"#;
+
+#[cfg(feature = "postgres")]
+const DELETE_EVENT: &str = r#"DELETE FROM webapi.dead_letter_event WHERE id = $1"#;
+#[cfg(any(feature = "mysql", feature = "sqlite"))]
+const DELETE_EVENT: &str = r#"DELETE FROM dead_letter_event WHERE id = ?"#;
+
+pub struct DeadLetterCollection {
+    data_provider: Arc<providers::SqlDbProvider>,
+    exp_helper: &'static connectors::ExpHelper,
+}
+
+impl DeadLetterCollection {
+    pub fn new(
+        data_provider: Arc<providers::SqlDbProvider>,
+        helper: &'static connectors::ExpHelper,
+    ) -> DeadLetterCollection {
+        DeadLetterCollection {
+            data_provider: data_provider,
+            exp_helper: &helper,
+        }
+    }
+}
+
+#[async_trait]
+impl traits::DeadLetterStore for DeadLetterCollection {
+    async fn get(
+        &self,
+        ids: Option<Vec<String>>,
+    ) -> connectors::Result<Vec<dead_letter::DeadLetterEvent>> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        let query = match ids {
+            Some(ids) => self
+                .exp_helper
+                .get_select_str_exp("webapi.dead_letter_event", "id", &ids),
+            None => format!(
+                "SELECT * FROM {} ORDER BY added_at",
+                self.exp_helper.get_table_exp("webapi.dead_letter_event")
+            ),
+        };
+        let items: Vec<dead_letter::DeadLetterEvent> =
+            sqlx::query_as(&query).fetch_all(pool).await?;
+        Ok(items)
+    }
+
+    async fn get_page(
+        &self,
+        offset: u32,
+        limit: u32,
+    ) -> connectors::Result<Vec<dead_letter::DeadLetterEvent>> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        let query = format!(
+            "SELECT * FROM {} ORDER BY added_at DESC LIMIT {} OFFSET {}",
+            self.exp_helper.get_table_exp("webapi.dead_letter_event"),
+            limit,
+            offset
+        );
+        let items: Vec<dead_letter::DeadLetterEvent> =
+            sqlx::query_as(&query).fetch_all(pool).await?;
+        Ok(items)
+    }
+
+    async fn add(
+        &self,
+        item: dead_letter::DeadLetterEvent,
+    ) -> connectors::Result<errors::ErrorCode> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        match sqlx::query(INSERT_EVENT)
+            .bind(&item.id)
+            .bind(&item.object_type)
+            .bind(&item.service_name)
+            .bind(&item.correlation_id)
+            .bind(&item.event_body)
+            .bind(&item.error)
+            .bind(item.added_at)
+            .execute(pool)
+            .await
+        {
+            Ok(_) => Ok(errors::ErrorCode::ReplyOk),
+            Err(e) => {
+                error!("add_dead_letter db insert: {}", e);
+                Ok(errors::ErrorCode::DatabaseError)
+            }
+        }
+    }
+
+    async fn remove(&self, id: &str) -> connectors::Result<errors::ErrorCode> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        match sqlx::query(DELETE_EVENT).bind(id).execute(pool).await {
+            Ok(ret) if ret.rows_affected() == 1 => Ok(errors::ErrorCode::ReplyOk),
+            Ok(_) => Ok(errors::ErrorCode::NotFoundError),
+            Err(e) => {
+                error!("remove_dead_letter db delete: {}", e);
+                Ok(errors::ErrorCode::DatabaseError)
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use super::super::{connectors, entities::error, providers, traits};
+use async_trait::async_trait;
+#[cfg(feature = "postgres")]
+use sqlx::postgres::PgPool;
+#[cfg(feature = "mysql")]
+use sqlx::MySqlPool;
+#[cfg(feature = "sqlite")]
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+pub struct ErrorCollection {
+    data_provider: Arc<providers::SqlDbProvider>,
+    exp_helper: &'static connectors::ExpHelper,
+}
+
+impl ErrorCollection {
+    pub fn new(
+        data_provider: Arc<providers::SqlDbProvider>,
+        helper: &'static connectors::ExpHelper,
+    ) -> ErrorCollection {
+        ErrorCollection {
+            data_provider: data_provider,
+            exp_helper: &helper,
+        }
+    }
+}
+
+#[async_trait]
+impl traits::ErrorStore for ErrorCollection {
+    async fn get(&self, langs: Option<Vec<String>>) -> connectors::Result<Vec<error::Error>> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        if langs.is_none() {
+            #[cfg(not(feature = "sqlite"))]
+            return Ok(sqlx::query_as!(
+                error::Error,
+                r#"SELECT error_code, lang, error_name FROM webapi.error"#
+            )
+            .fetch_all(pool)
+            .await?);
+            #[cfg(feature = "sqlite")]
+            return Ok(
+                sqlx::query_as(r#"SELECT error_code, lang, error_name FROM error"#)
+                    .fetch_all(pool)
+                    .await?,
+            );
+        } else {
+            let query = self
+                .exp_helper
+                .get_select_str_exp("webapi.error", "lang", &langs.unwrap());
+            let items: Vec<error::Error> = sqlx::query_as(&query).fetch_all(pool).await?;
+            Ok(items)
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use super::super::{connectors, entities::executor, errors, providers};
+use super::super::{connectors, entities::executor, errors, providers, traits};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use futures::TryStreamExt;
 #[cfg(feature = "postgres")]
 use sqlx::postgres::PgPool;
@@ -6,6 +8,8 @@ use sqlx::Done;
 #[cfg(feature = "mysql")]
 use sqlx::MySqlPool;
 use sqlx::Row;
+#[cfg(feature = "sqlite")]
+use sqlx::SqlitePool;
 use std::convert::TryFrom;
 use std::sync::Arc;
 
@@ -24,8 +28,11 @@ impl SendedAsyncCommandCollection {
             exp_helper: &helper,
         }
     }
+}
 
-    pub async fn get(
+#[async_trait]
+impl traits::AsyncCommandStore<executor::SendedAsyncCommand> for SendedAsyncCommandCollection {
+    async fn get(
         &self,
         ids: Option<Vec<String>>,
     ) -> connectors::Result<Vec<executor::SendedAsyncCommand>> {
@@ -33,25 +40,50 @@ impl SendedAsyncCommandCollection {
         let pool: &PgPool = &self.data_provider.pool;
         #[cfg(feature = "mysql")]
         let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
         let mut items = Vec::<executor::SendedAsyncCommand>::new();
         if ids.is_none() {
-            let recs = sqlx::query!(
-                r#"SELECT id, object_type, "service_name", "state", change_state_event, added_at, state_changed_at 
-                    FROM webapi.v_sended_async_command"#
-            )
-            .fetch_all(pool)
-            .await?;
-            for rec in recs {
-                items.push(executor::SendedAsyncCommand {
-                    id: rec.id.unwrap(),
-                    object_type: rec.object_type.unwrap(),
-                    service_name: rec.service_name.unwrap(),
-                    state: rec.state.unwrap(),
-                    change_state_event: rec.change_state_event.unwrap(),
-                    added_at: rec.added_at.unwrap(),
-                    state_changed_at: rec.state_changed_at.unwrap(),
-                    history: None,
-                })
+            #[cfg(not(feature = "sqlite"))]
+            {
+                let recs = sqlx::query!(
+                    r#"SELECT id, object_type, "service_name", "state", change_state_event, added_at, state_changed_at 
+                        FROM webapi.v_sended_async_command"#
+                )
+                .fetch_all(pool)
+                .await?;
+                for rec in recs {
+                    items.push(executor::SendedAsyncCommand {
+                        id: rec.id.unwrap(),
+                        object_type: rec.object_type.unwrap(),
+                        service_name: rec.service_name.unwrap(),
+                        state: rec.state.unwrap(),
+                        change_state_event: rec.change_state_event.unwrap(),
+                        added_at: rec.added_at.unwrap(),
+                        state_changed_at: rec.state_changed_at.unwrap(),
+                        history: None,
+                    })
+                }
+            }
+            #[cfg(feature = "sqlite")]
+            {
+                let mut cursor = sqlx::query(
+                    r#"SELECT id, object_type, "service_name", "state", change_state_event, added_at, state_changed_at 
+                        FROM v_sended_async_command"#,
+                )
+                .fetch(pool);
+                while let Some(rec) = cursor.try_next().await? {
+                    items.push(executor::SendedAsyncCommand {
+                        id: rec.get(0),
+                        object_type: rec.get(1),
+                        service_name: rec.get(2),
+                        state: rec.get(3),
+                        change_state_event: rec.get(4),
+                        added_at: rec.get(5),
+                        state_changed_at: rec.get(6),
+                        history: None,
+                    })
+                }
             }
         } else {
             let query = self.exp_helper.get_select_str_exp(
@@ -74,21 +106,34 @@ impl SendedAsyncCommandCollection {
             }
         }
         for mut item in &mut items {
-            let recs = sqlx::query!(
+            #[cfg(not(feature = "sqlite"))]
+            let recs: Vec<(String, DateTime<Utc>)> = sqlx::query!(
                 r#"SELECT command_id, "state", added_at
                     FROM webapi.v_sended_async_command_state_history
                         WHERE command_id = $1"#,
                 &item.id
             )
             .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|rec| (rec.state.unwrap(), rec.added_at.unwrap()))
+            .collect();
+            #[cfg(feature = "sqlite")]
+            let recs: Vec<(String, DateTime<Utc>)> = sqlx::query_as(
+                r#"SELECT "state", added_at
+                    FROM v_sended_async_command_state_history
+                        WHERE command_id = ?"#,
+            )
+            .bind(&item.id)
+            .fetch_all(pool)
             .await?;
             if recs.len() > 0 {
                 let mut p = Vec::<executor::SendedAsyncCommandHistory>::new();
                 for rec in recs {
                     p.push(executor::SendedAsyncCommandHistory {
                         command_id: None,
-                        state: rec.state.as_ref().unwrap().to_string(),
-                        added_at: *rec.added_at.as_ref().unwrap(),
+                        state: rec.0,
+                        added_at: rec.1,
                     });
                 }
                 item.history = Some(p);
@@ -97,7 +142,7 @@ impl SendedAsyncCommandCollection {
         Ok(items)
     }
 
-    pub async fn add(
+    async fn add(
         &self,
         items: Vec<executor::SendedAsyncCommand>,
     ) -> connectors::Result<(errors::ErrorCode, Option<Vec<String>>)> {
@@ -106,6 +151,8 @@ impl SendedAsyncCommandCollection {
         let pool: &PgPool = &self.data_provider.pool;
         #[cfg(feature = "mysql")]
         let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
         let mut tx = pool.begin().await?;
         for command in items {
             #[cfg(feature = "postgres")]
@@ -150,6 +197,27 @@ impl SendedAsyncCommandCollection {
                     return Ok((errors::ErrorCode::DatabaseError, None));
                 }
             };
+            #[cfg(feature = "sqlite")]
+            match sqlx::query(
+                r#"INSERT INTO sended_async_command 
+                ( id, object_type, "service_name", "state", change_state_event ) 
+                    VALUES ( ?, ?, ?, ?, ? )"#,
+            )
+            .bind(&command.id)
+            .bind(&command.object_type)
+            .bind(&command.service_name)
+            .bind(&command.state)
+            .bind(&command.change_state_event)
+            .execute(&mut tx)
+            .await
+            {
+                Ok(_) => ids.push(command.id.clone()),
+                Err(e) => {
+                    tx.rollback().await.unwrap();
+                    error!("add_sended_async_commands db command insert: {}", e);
+                    return Ok((errors::ErrorCode::DatabaseError, None));
+                }
+            };
             if command.history.is_some() {
                 for history in &command.history.unwrap() {
                     #[cfg(feature = "postgres")]
@@ -190,6 +258,25 @@ impl SendedAsyncCommandCollection {
                             return Ok((errors::ErrorCode::DatabaseError, None));
                         }
                     };
+                    #[cfg(feature = "sqlite")]
+                    match sqlx::query(
+                        r#"INSERT INTO sended_async_command_state_history 
+                    ( command_id, "state", added_at ) 
+                        VALUES ( ?, ?, ? )"#,
+                    )
+                    .bind(&history.command_id)
+                    .bind(&history.state)
+                    .bind(&history.added_at)
+                    .execute(&mut tx)
+                    .await
+                    {
+                        Ok(_) => {}
+                        Err(e) => {
+                            tx.rollback().await.unwrap();
+                            error!("add_sended_async_commands db history insert: {}", e);
+                            return Ok((errors::ErrorCode::DatabaseError, None));
+                        }
+                    };
                 }
             }
         }
@@ -203,11 +290,41 @@ impl SendedAsyncCommandCollection {
         Ok((errors::ErrorCode::ReplyOk, Some(ids)))
     }
 
-    pub async fn remove(&self, ids: Vec<String>) -> connectors::Result<errors::ErrorCode> {
+    async fn get_page(
+        &self,
+        offset: u32,
+        limit: u32,
+    ) -> connectors::Result<Vec<executor::SendedAsyncCommand>> {
         #[cfg(feature = "postgres")]
         let pool: &PgPool = &self.data_provider.pool;
         #[cfg(feature = "mysql")]
         let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        let query = format!(
+            "SELECT id FROM {} ORDER BY added_at DESC LIMIT {} OFFSET {}",
+            self.exp_helper.get_table_exp("webapi.sended_async_command"),
+            limit,
+            offset
+        );
+        let recs: Vec<(String,)> = sqlx::query_as(&query).fetch_all(pool).await?;
+        if recs.len() == 0 {
+            return Ok(vec![]);
+        }
+        let mut items = self
+            .get(Some(recs.into_iter().map(|rec| rec.0).collect()))
+            .await?;
+        items.sort_by(|a, b| b.added_at.cmp(&a.added_at));
+        Ok(items)
+    }
+
+    async fn remove(&self, ids: Vec<String>) -> connectors::Result<errors::ErrorCode> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
         let mut tx = pool.begin().await?;
         match sqlx::query(&self.exp_helper.get_delete_str_exp(
             "webapi.sended_async_command_state_history",
@@ -272,7 +389,40 @@ impl ReceivedAsyncCommandCollection {
         }
     }
 
-    pub async fn get(
+    pub async fn change_state(
+        &self,
+        state: String,
+        ids: Vec<String>,
+    ) -> connectors::Result<(
+        errors::ErrorCode,
+        Option<Vec<(String, executor::AsyncCommandState)>>,
+    )> {
+        if state == executor::CommandSystemState::Initial.to_string()
+            || state == executor::CommandSystemState::Completed.to_string()
+        {
+            return Err(errors::Error::with_context(
+                errors::ErrorKind::UnknownAsyncCommandState,
+                &state,
+            )
+            .into());
+        }
+        // todo: set state for ids receive command, if already set, not update
+        // state must be exists in ServiceCommand.state else raise UnknownAsyncCommandStateError
+        // add state history
+        Ok((errors::ErrorCode::ReplyOk, None))
+    }
+
+    // pub async fn complete(
+    //     &self,
+    //     ids: Vec<String>,
+    // ) -> connectors::Result<errors::ErrorCode> {
+    // todo: set CommandSystemState.Completed state for ids command, if already set, not update
+    // }
+}
+
+#[async_trait]
+impl traits::AsyncCommandStore<executor::ReceivedAsyncCommand> for ReceivedAsyncCommandCollection {
+    async fn get(
         &self,
         ids: Option<Vec<String>>,
     ) -> connectors::Result<Vec<executor::ReceivedAsyncCommand>> {
@@ -280,29 +430,58 @@ impl ReceivedAsyncCommandCollection {
         let pool: &PgPool = &self.data_provider.pool;
         #[cfg(feature = "mysql")]
         let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
         let mut items = Vec::<executor::ReceivedAsyncCommand>::new();
         if ids.is_none() {
-            let recs = sqlx::query!(
-                r#"SELECT id, object_type, "service_name", request_body, "state", change_state_event,
-                    reply_body, proto, added_at, state_changed_at
-                        FROM webapi.v_received_async_command"#
-            )
-            .fetch_all(pool)
-            .await?;
-            for rec in recs {
-                items.push(executor::ReceivedAsyncCommand {
-                    id: rec.id.unwrap(),
-                    object_type: rec.object_type.unwrap(),
-                    service_name: rec.service_name.unwrap(),
-                    request_body: rec.request_body.unwrap(),
-                    state: rec.state.unwrap(),
-                    change_state_event: rec.change_state_event.unwrap(),
-                    reply_body: rec.reply_body.unwrap(),
-                    proto: rec.proto.unwrap(),
-                    added_at: rec.added_at.unwrap(),
-                    state_changed_at: rec.state_changed_at.unwrap(),
-                    history: None,
-                })
+            #[cfg(not(feature = "sqlite"))]
+            {
+                let recs = sqlx::query!(
+                    r#"SELECT id, object_type, "service_name", request_body, "state", change_state_event,
+                        reply_body, proto, added_at, state_changed_at
+                            FROM webapi.v_received_async_command"#
+                )
+                .fetch_all(pool)
+                .await?;
+                for rec in recs {
+                    items.push(executor::ReceivedAsyncCommand {
+                        id: rec.id.unwrap(),
+                        object_type: rec.object_type.unwrap(),
+                        service_name: rec.service_name.unwrap(),
+                        request_body: rec.request_body.unwrap(),
+                        state: rec.state.unwrap(),
+                        change_state_event: rec.change_state_event.unwrap(),
+                        reply_body: rec.reply_body.unwrap(),
+                        proto: rec.proto.unwrap(),
+                        added_at: rec.added_at.unwrap(),
+                        state_changed_at: rec.state_changed_at.unwrap(),
+                        history: None,
+                    })
+                }
+            }
+            #[cfg(feature = "sqlite")]
+            {
+                let mut cursor = sqlx::query(
+                    r#"SELECT id, object_type, "service_name", request_body, "state", change_state_event,
+                        reply_body, proto, added_at, state_changed_at
+                            FROM v_received_async_command"#,
+                )
+                .fetch(pool);
+                while let Some(rec) = cursor.try_next().await? {
+                    items.push(executor::ReceivedAsyncCommand {
+                        id: rec.get(0),
+                        object_type: rec.get(1),
+                        service_name: rec.get(2),
+                        request_body: rec.get(3),
+                        state: rec.get(4),
+                        change_state_event: rec.get(5),
+                        reply_body: rec.get(6),
+                        proto: rec.get(7),
+                        added_at: rec.get(8),
+                        state_changed_at: rec.get(9),
+                        history: None,
+                    })
+                }
             }
         } else {
             let query = self.exp_helper.get_select_str_exp(
@@ -328,21 +507,34 @@ impl ReceivedAsyncCommandCollection {
             }
         }
         for mut item in &mut items {
-            let recs = sqlx::query!(
+            #[cfg(not(feature = "sqlite"))]
+            let recs: Vec<(String, DateTime<Utc>)> = sqlx::query!(
                 r#"SELECT command_id, "state", added_at
                     FROM webapi.v_received_async_command_state_history
                         WHERE command_id = $1"#,
                 &item.id
             )
             .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|rec| (rec.state.unwrap(), rec.added_at.unwrap()))
+            .collect();
+            #[cfg(feature = "sqlite")]
+            let recs: Vec<(String, DateTime<Utc>)> = sqlx::query_as(
+                r#"SELECT "state", added_at
+                    FROM v_received_async_command_state_history
+                        WHERE command_id = ?"#,
+            )
+            .bind(&item.id)
+            .fetch_all(pool)
             .await?;
             if recs.len() > 0 {
                 let mut p = Vec::<executor::ReceivedAsyncCommandHistory>::new();
                 for rec in recs {
                     p.push(executor::ReceivedAsyncCommandHistory {
                         command_id: None,
-                        state: rec.state.as_ref().unwrap().to_string(),
-                        added_at: *rec.added_at.as_ref().unwrap(),
+                        state: rec.0,
+                        added_at: rec.1,
                     });
                 }
                 item.history = Some(p);
@@ -351,7 +543,7 @@ impl ReceivedAsyncCommandCollection {
         Ok(items)
     }
 
-    pub async fn add(
+    async fn add(
         &self,
         items: Vec<executor::ReceivedAsyncCommand>,
     ) -> connectors::Result<(errors::ErrorCode, Option<Vec<String>>)> {
@@ -360,6 +552,8 @@ impl ReceivedAsyncCommandCollection {
         let pool: &PgPool = &self.data_provider.pool;
         #[cfg(feature = "mysql")]
         let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
         let mut tx = pool.begin().await?;
         for command in items {
             #[cfg(feature = "postgres")]
@@ -410,6 +604,30 @@ impl ReceivedAsyncCommandCollection {
                     return Ok((errors::ErrorCode::DatabaseError, None));
                 }
             };
+            #[cfg(feature = "sqlite")]
+            match sqlx::query(
+                r#"INSERT INTO received_async_command 
+                ( id, object_type, "service_name", request_body, "state", change_state_event, reply_body, proto ) 
+                    VALUES ( ?, ?, ?, ?, ?, ?, ?, ? )"#,
+            )
+            .bind(&command.id)
+            .bind(&command.object_type)
+            .bind(&command.service_name)
+            .bind(&command.request_body)
+            .bind(&command.state)
+            .bind(&command.change_state_event)
+            .bind(&command.reply_body)
+            .bind(&command.proto)
+            .execute(&mut tx)
+            .await
+            {
+                Ok(_) => ids.push(command.id.clone()),
+                Err(e) => {
+                    tx.rollback().await.unwrap();
+                    error!("add_received_async_commands db command insert: {}", e);
+                    return Ok((errors::ErrorCode::DatabaseError, None));
+                }
+            };
             if command.history.is_some() {
                 for history in &command.history.unwrap() {
                     #[cfg(feature = "postgres")]
@@ -450,6 +668,25 @@ impl ReceivedAsyncCommandCollection {
                             return Ok((errors::ErrorCode::DatabaseError, None));
                         }
                     };
+                    #[cfg(feature = "sqlite")]
+                    match sqlx::query(
+                        r#"INSERT INTO received_async_command_state_history 
+                    ( command_id, "state", added_at ) 
+                        VALUES ( ?, ?, ? )"#,
+                    )
+                    .bind(&history.command_id)
+                    .bind(&history.state)
+                    .bind(&history.added_at)
+                    .execute(&mut tx)
+                    .await
+                    {
+                        Ok(_) => {}
+                        Err(e) => {
+                            tx.rollback().await.unwrap();
+                            error!("add_received_async_commands db history insert: {}", e);
+                            return Ok((errors::ErrorCode::DatabaseError, None));
+                        }
+                    };
                 }
             }
         }
@@ -463,37 +700,42 @@ impl ReceivedAsyncCommandCollection {
         Ok((errors::ErrorCode::ReplyOk, Some(ids)))
     }
 
-    pub async fn change_state(
+    async fn get_page(
         &self,
-        state: String,
-        ids: Vec<String>,
-    ) -> connectors::Result<(
-        errors::ErrorCode,
-        Option<Vec<(String, executor::AsyncCommandState)>>,
-    )> {
-        if state == executor::CommandSystemState::Initial.to_string()
-            || state == executor::CommandSystemState::Completed.to_string()
-        {
-            return Err(errors::UnknownAsyncCommandStateError.into());
+        offset: u32,
+        limit: u32,
+    ) -> connectors::Result<Vec<executor::ReceivedAsyncCommand>> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        let query = format!(
+            "SELECT id FROM {} ORDER BY added_at DESC LIMIT {} OFFSET {}",
+            self.exp_helper
+                .get_table_exp("webapi.received_async_command"),
+            limit,
+            offset
+        );
+        let recs: Vec<(String,)> = sqlx::query_as(&query).fetch_all(pool).await?;
+        if recs.len() == 0 {
+            return Ok(vec![]);
         }
-        // todo: set state for ids receive command, if already set, not update
-        // state must be exists in ServiceCommand.state else raise UnknownAsyncCommandStateError
-        // add state history
-        Ok((errors::ErrorCode::ReplyOk, None))
+        let mut items = self
+            .get(Some(recs.into_iter().map(|rec| rec.0).collect()))
+            .await?;
+        items.sort_by(|a, b| b.added_at.cmp(&a.added_at));
+        Ok(items)
     }
 
-    // pub async fn complete(
-    //     &self,
-    //     ids: Vec<String>,
-    // ) -> connectors::Result<errors::ErrorCode> {
-    // todo: set CommandSystemState.Completed state for ids command, if already set, not update
-    // }
-
-    pub async fn remove(&self, ids: Vec<String>) -> connectors::Result<errors::ErrorCode> {
+    async fn remove(&self, ids: Vec<String>) -> connectors::Result<errors::ErrorCode> {
         #[cfg(feature = "postgres")]
         let pool: &PgPool = &self.data_provider.pool;
         #[cfg(feature = "mysql")]
         let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
         let mut tx = pool.begin().await?;
         match sqlx::query(&self.exp_helper.get_delete_str_exp(
             "webapi.received_async_command_state_history",
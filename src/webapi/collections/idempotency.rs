@@ -0,0 +1,204 @@
+use super::super::{connectors, entities::idempotency, errors, providers, traits};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+#[cfg(feature = "postgres")]
+use sqlx::postgres::PgPool;
+use sqlx::Done;
+#[cfg(feature = "mysql")]
+use sqlx::MySqlPool;
+#[cfg(feature = "sqlite")]
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+//insert is skipped if key is taken, then stored reply is selected
+#[cfg(feature = "postgres")]
+const INSERT_REPLY: &str = r#"INSERT INTO webapi.idempotent_reply
+    ( caller, object_type, correlation_id, state, status, reply_body, added_at )
+        VALUES ( $1, $2, $3, $4, $5, $6, $7 ) ON CONFLICT DO NOTHING"#;
+#[cfg(feature = "mysql")]
+const INSERT_REPLY: &str = r#"INSERT IGNORE INTO idempotent_reply
+    ( caller, object_type, correlation_id, state, status, reply_body, added_at )
+        VALUES ( ?, ?, ?, ?, ?, ?, ? )"#;
+#[cfg(feature = "sqlite")]
+const INSERT_REPLY: &str = r#"INSERT OR IGNORE INTO idempotent_reply
+    ( caller, object_type, correlation_id, state, status, reply_body, added_at )
+        VALUES ( ?, ?, ?, ?, ?, ?, ? )"#;
+
+#[cfg(feature = "postgres")]
+const SELECT_REPLY: &str = r#"SELECT * FROM webapi.idempotent_reply
+    WHERE caller = $1 AND object_type = $2 AND correlation_id = $3"#;
+#[cfg(any(feature = "mysql", feature = "sqlite"))]
+const SELECT_REPLY: &str = r#"SELECT * FROM idempotent_reply
+    WHERE caller = ? AND object_type = ? AND correlation_id = ?"#;
+
+#[cfg(feature = "postgres")]
+const UPDATE_REPLY: &str = r#"UPDATE webapi.idempotent_reply
+    SET state = $1, status = $2, reply_body = $3
+        WHERE caller = $4 AND object_type = $5 AND correlation_id = $6"#;
+#[cfg(any(feature = "mysql", feature = "sqlite"))]
+const UPDATE_REPLY: &str = r#"UPDATE idempotent_reply
+    SET state = ?, status = ?, reply_body = ?
+        WHERE caller = ? AND object_type = ? AND correlation_id = ?"#;
+
+#[cfg(feature = "postgres")]
+const DELETE_REPLY: &str = r#"DELETE FROM webapi.idempotent_reply
+    WHERE caller = $1 AND object_type = $2 AND correlation_id = $3"#;
+#[cfg(any(feature = "mysql", feature = "sqlite"))]
+const DELETE_REPLY: &str = r#"DELETE FROM idempotent_reply
+    WHERE caller = ? AND object_type = ? AND correlation_id = ?"#;
+
+//retry takes key only once, taken one is not abandoned anymore
+#[cfg(feature = "postgres")]
+const UPDATE_ABANDONED: &str = r#"UPDATE webapi.idempotent_reply SET added_at = $1
+    WHERE caller = $2 AND object_type = $3 AND correlation_id = $4 AND state = $5 AND added_at < $6"#;
+#[cfg(any(feature = "mysql", feature = "sqlite"))]
+const UPDATE_ABANDONED: &str = r#"UPDATE idempotent_reply SET added_at = ?
+    WHERE caller = ? AND object_type = ? AND correlation_id = ? AND state = ? AND added_at < ?"#;
+
+#[cfg(feature = "postgres")]
+const DELETE_EXPIRED: &str = r#"DELETE FROM webapi.idempotent_reply WHERE added_at < $1"#;
+#[cfg(any(feature = "mysql", feature = "sqlite"))]
+const DELETE_EXPIRED: &str = r#"DELETE FROM idempotent_reply WHERE added_at < ?"#;
+
+pub struct IdempotencyCollection {
+    data_provider: Arc<providers::SqlDbProvider>,
+}
+
+impl IdempotencyCollection {
+    pub fn new(
+        data_provider: Arc<providers::SqlDbProvider>,
+        _helper: &'static connectors::ExpHelper,
+    ) -> IdempotencyCollection {
+        IdempotencyCollection {
+            data_provider: data_provider,
+        }
+    }
+}
+
+#[async_trait]
+impl traits::IdempotencyStore for IdempotencyCollection {
+    async fn get_or_add(
+        &self,
+        item: idempotency::IdempotentReply,
+    ) -> connectors::Result<Option<idempotency::IdempotentReply>> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        let ret = sqlx::query(INSERT_REPLY)
+            .bind(&item.caller)
+            .bind(&item.object_type)
+            .bind(&item.correlation_id)
+            .bind(&item.state)
+            .bind(item.status)
+            .bind(&item.reply_body)
+            .bind(item.added_at)
+            .execute(pool)
+            .await?;
+        if ret.rows_affected() == 1 {
+            return Ok(None);
+        }
+        Ok(sqlx::query_as(SELECT_REPLY)
+            .bind(&item.caller)
+            .bind(&item.object_type)
+            .bind(&item.correlation_id)
+            .fetch_optional(pool)
+            .await?)
+    }
+
+    async fn complete(
+        &self,
+        key: &idempotency::IdempotencyKey,
+        status: i32,
+        reply_body: &str,
+    ) -> connectors::Result<errors::ErrorCode> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        match sqlx::query(UPDATE_REPLY)
+            .bind(idempotency::IdempotentState::Completed.to_string())
+            .bind(status)
+            .bind(reply_body)
+            .bind(&key.caller)
+            .bind(&key.object_type)
+            .bind(&key.correlation_id)
+            .execute(pool)
+            .await
+        {
+            Ok(ret) if ret.rows_affected() == 1 => Ok(errors::ErrorCode::ReplyOk),
+            Ok(_) => Ok(errors::ErrorCode::NotFoundError),
+            Err(e) => {
+                error!("complete_idempotent_reply db update: {}", e);
+                Ok(errors::ErrorCode::DatabaseError)
+            }
+        }
+    }
+
+    async fn remove(
+        &self,
+        key: &idempotency::IdempotencyKey,
+    ) -> connectors::Result<errors::ErrorCode> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        match sqlx::query(DELETE_REPLY)
+            .bind(&key.caller)
+            .bind(&key.object_type)
+            .bind(&key.correlation_id)
+            .execute(pool)
+            .await
+        {
+            Ok(ret) if ret.rows_affected() == 1 => Ok(errors::ErrorCode::ReplyOk),
+            Ok(_) => Ok(errors::ErrorCode::NotFoundError),
+            Err(e) => {
+                error!("remove_idempotent_reply db delete: {}", e);
+                Ok(errors::ErrorCode::DatabaseError)
+            }
+        }
+    }
+
+    async fn take_over(
+        &self,
+        key: &idempotency::IdempotencyKey,
+        added_before: DateTime<Utc>,
+    ) -> connectors::Result<bool> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        let ret = sqlx::query(UPDATE_ABANDONED)
+            .bind(Utc::now())
+            .bind(&key.caller)
+            .bind(&key.object_type)
+            .bind(&key.correlation_id)
+            .bind(idempotency::IdempotentState::InProgress.to_string())
+            .bind(added_before)
+            .execute(pool)
+            .await?;
+        Ok(ret.rows_affected() == 1)
+    }
+
+    async fn remove_expired(&self, added_before: DateTime<Utc>) -> connectors::Result<u64> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        let ret = sqlx::query(DELETE_EXPIRED)
+            .bind(added_before)
+            .execute(pool)
+            .await?;
+        Ok(ret.rows_affected())
+    }
+}
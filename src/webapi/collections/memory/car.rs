@@ -0,0 +1,92 @@
+use super::super::super::{connectors, entities::car, errors, traits};
+use async_trait::async_trait;
+use std::sync::RwLock;
+
+pub struct CarCollection {
+    items: RwLock<Vec<car::Car>>,
+    last_id: RwLock<i32>,
+}
+
+impl CarCollection {
+    pub fn new() -> CarCollection {
+        CarCollection {
+            items: RwLock::new(Vec::<car::Car>::new()),
+            last_id: RwLock::new(0),
+        }
+    }
+}
+
+#[async_trait]
+impl traits::CarStore for CarCollection {
+    async fn get(&self, ids: Option<Vec<i32>>) -> connectors::Result<Vec<car::Car>> {
+        let items = self.items.read().unwrap();
+        Ok(match ids {
+            Some(ids) => items
+                .iter()
+                .filter(|item| ids.contains(&item.id.unwrap_or(0)))
+                .cloned()
+                .collect(),
+            None => items.clone(),
+        })
+    }
+
+    async fn add(
+        &self,
+        items: Vec<car::Car>,
+    ) -> connectors::Result<(errors::ErrorCode, Option<Vec<i32>>)> {
+        let mut store = self.items.write().unwrap();
+        let mut last_id = self.last_id.write().unwrap();
+        let mut ids = Vec::<i32>::new();
+        let mut added = Vec::<car::Car>::new();
+        for item in items {
+            //same as car_car_name_idx unique index
+            if store
+                .iter()
+                .chain(added.iter())
+                .any(|c| c.car_name == item.car_name)
+            {
+                error!(
+                    "add_cars memory insert: duplicate car name {}",
+                    item.car_name
+                );
+                return Ok((errors::ErrorCode::DatabaseError, None));
+            }
+            let id = *last_id + 1 + ids.len() as i32;
+            ids.push(id);
+            added.push(car::Car {
+                id: Some(id),
+                car_name: item.car_name,
+            });
+        }
+        *last_id += ids.len() as i32;
+        store.extend(added);
+        Ok((errors::ErrorCode::ReplyOk, Some(ids)))
+    }
+
+    async fn change(&self, items: Vec<car::Car>) -> connectors::Result<errors::ErrorCode> {
+        let mut store = self.items.write().unwrap();
+        if !items
+            .iter()
+            .all(|item| store.iter().any(|c| c.id.is_some() && c.id == item.id))
+        {
+            return Ok(errors::ErrorCode::NotFoundError);
+        }
+        for item in items {
+            for c in store.iter_mut() {
+                if c.id == item.id {
+                    c.car_name = item.car_name.clone();
+                }
+            }
+        }
+        Ok(errors::ErrorCode::ReplyOk)
+    }
+
+    async fn remove(&self, ids: Vec<i32>) -> connectors::Result<errors::ErrorCode> {
+        let mut store = self.items.write().unwrap();
+        if !ids.iter().all(|id| store.iter().any(|c| c.id == Some(*id))) {
+            return Ok(errors::ErrorCode::NotFoundError);
+        }
+        store.retain(|c| !ids.contains(&c.id.unwrap_or(0)));
+        Ok(errors::ErrorCode::ReplyOk)
+    }
+}
@@ -0,0 +1,71 @@
+use super::super::super::{connectors, entities::dead_letter, errors, traits};
+use async_trait::async_trait;
+use std::sync::RwLock;
+
+pub struct DeadLetterCollection {
+    items: RwLock<Vec<dead_letter::DeadLetterEvent>>,
+}
+
+impl DeadLetterCollection {
+    pub fn new() -> DeadLetterCollection {
+        DeadLetterCollection {
+            items: RwLock::new(Vec::<dead_letter::DeadLetterEvent>::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl traits::DeadLetterStore for DeadLetterCollection {
+    async fn get(
+        &self,
+        ids: Option<Vec<String>>,
+    ) -> connectors::Result<Vec<dead_letter::DeadLetterEvent>> {
+        let items = self.items.read().unwrap();
+        Ok(match ids {
+            Some(ids) => items
+                .iter()
+                .filter(|item| ids.contains(&item.id))
+                .cloned()
+                .collect(),
+            None => items.clone(),
+        })
+    }
+
+    async fn get_page(
+        &self,
+        offset: u32,
+        limit: u32,
+    ) -> connectors::Result<Vec<dead_letter::DeadLetterEvent>> {
+        let mut items = self.items.read().unwrap().clone();
+        items.sort_by(|a, b| b.added_at.cmp(&a.added_at));
+        Ok(items
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect())
+    }
+
+    async fn add(
+        &self,
+        item: dead_letter::DeadLetterEvent,
+    ) -> connectors::Result<errors::ErrorCode> {
+        let mut store = self.items.write().unwrap();
+        if store.iter().any(|e| e.id == item.id) {
+            error!("add_dead_letter memory insert: duplicate id {}", item.id);
+            return Ok(errors::ErrorCode::DatabaseError);
+        }
+        store.push(item);
+        Ok(errors::ErrorCode::ReplyOk)
+    }
+
+    async fn remove(&self, id: &str) -> connectors::Result<errors::ErrorCode> {
+        let mut store = self.items.write().unwrap();
+        let count = store.len();
+        store.retain(|e| e.id != id);
+        if store.len() < count {
+            Ok(errors::ErrorCode::ReplyOk)
+        } else {
+            Ok(errors::ErrorCode::NotFoundError)
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use super::super::super::{connectors, entities::error, traits};
+use async_trait::async_trait;
+use std::sync::RwLock;
+
+pub struct ErrorCollection {
+    items: RwLock<Vec<error::Error>>,
+}
+
+impl ErrorCollection {
+    pub fn new(items: Vec<error::Error>) -> ErrorCollection {
+        ErrorCollection {
+            items: RwLock::new(items),
+        }
+    }
+}
+
+#[async_trait]
+impl traits::ErrorStore for ErrorCollection {
+    async fn get(&self, langs: Option<Vec<String>>) -> connectors::Result<Vec<error::Error>> {
+        let items = self.items.read().unwrap();
+        Ok(match langs {
+            Some(langs) => items
+                .iter()
+                .filter(|item| langs.contains(&item.lang))
+                .cloned()
+                .collect(),
+            None => items.clone(),
+        })
+    }
+}
@@ -0,0 +1,147 @@
+use super::super::super::{connectors, entities::executor, errors, traits};
+use async_trait::async_trait;
+use std::sync::RwLock;
+
+pub struct SendedAsyncCommandCollection {
+    items: RwLock<Vec<executor::SendedAsyncCommand>>,
+}
+
+impl SendedAsyncCommandCollection {
+    pub fn new() -> SendedAsyncCommandCollection {
+        SendedAsyncCommandCollection {
+            items: RwLock::new(Vec::<executor::SendedAsyncCommand>::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl traits::AsyncCommandStore<executor::SendedAsyncCommand> for SendedAsyncCommandCollection {
+    async fn get(
+        &self,
+        ids: Option<Vec<String>>,
+    ) -> connectors::Result<Vec<executor::SendedAsyncCommand>> {
+        let items = self.items.read().unwrap();
+        Ok(match ids {
+            Some(ids) => items
+                .iter()
+                .filter(|item| ids.contains(&item.id))
+                .cloned()
+                .collect(),
+            None => items.clone(),
+        })
+    }
+
+    async fn get_page(
+        &self,
+        offset: u32,
+        limit: u32,
+    ) -> connectors::Result<Vec<executor::SendedAsyncCommand>> {
+        let mut items = self.items.read().unwrap().clone();
+        items.sort_by(|a, b| b.added_at.cmp(&a.added_at));
+        Ok(items
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect())
+    }
+
+    async fn add(
+        &self,
+        items: Vec<executor::SendedAsyncCommand>,
+    ) -> connectors::Result<(errors::ErrorCode, Option<Vec<String>>)> {
+        let mut store = self.items.write().unwrap();
+        for item in &items {
+            if store.iter().any(|c| c.id == item.id) {
+                error!(
+                    "add_sended_async_commands memory insert: duplicate id {}",
+                    item.id
+                );
+                return Ok((errors::ErrorCode::DatabaseError, None));
+            }
+        }
+        let ids = items.iter().map(|item| item.id.clone()).collect();
+        store.extend(items);
+        Ok((errors::ErrorCode::ReplyOk, Some(ids)))
+    }
+
+    async fn remove(&self, ids: Vec<String>) -> connectors::Result<errors::ErrorCode> {
+        let mut store = self.items.write().unwrap();
+        if !ids.iter().all(|id| store.iter().any(|c| &c.id == id)) {
+            return Ok(errors::ErrorCode::NotFoundError);
+        }
+        store.retain(|c| !ids.contains(&c.id));
+        Ok(errors::ErrorCode::ReplyOk)
+    }
+}
+
+pub struct ReceivedAsyncCommandCollection {
+    items: RwLock<Vec<executor::ReceivedAsyncCommand>>,
+}
+
+impl ReceivedAsyncCommandCollection {
+    pub fn new() -> ReceivedAsyncCommandCollection {
+        ReceivedAsyncCommandCollection {
+            items: RwLock::new(Vec::<executor::ReceivedAsyncCommand>::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl traits::AsyncCommandStore<executor::ReceivedAsyncCommand> for ReceivedAsyncCommandCollection {
+    async fn get(
+        &self,
+        ids: Option<Vec<String>>,
+    ) -> connectors::Result<Vec<executor::ReceivedAsyncCommand>> {
+        let items = self.items.read().unwrap();
+        Ok(match ids {
+            Some(ids) => items
+                .iter()
+                .filter(|item| ids.contains(&item.id))
+                .cloned()
+                .collect(),
+            None => items.clone(),
+        })
+    }
+
+    async fn get_page(
+        &self,
+        offset: u32,
+        limit: u32,
+    ) -> connectors::Result<Vec<executor::ReceivedAsyncCommand>> {
+        let mut items = self.items.read().unwrap().clone();
+        items.sort_by(|a, b| b.added_at.cmp(&a.added_at));
+        Ok(items
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect())
+    }
+
+    async fn add(
+        &self,
+        items: Vec<executor::ReceivedAsyncCommand>,
+    ) -> connectors::Result<(errors::ErrorCode, Option<Vec<String>>)> {
+        let mut store = self.items.write().unwrap();
+        for item in &items {
+            if store.iter().any(|c| c.id == item.id) {
+                error!(
+                    "add_received_async_commands memory insert: duplicate id {}",
+                    item.id
+                );
+                return Ok((errors::ErrorCode::DatabaseError, None));
+            }
+        }
+        let ids = items.iter().map(|item| item.id.clone()).collect();
+        store.extend(items);
+        Ok((errors::ErrorCode::ReplyOk, Some(ids)))
+    }
+
+    async fn remove(&self, ids: Vec<String>) -> connectors::Result<errors::ErrorCode> {
+        let mut store = self.items.write().unwrap();
+        if !ids.iter().all(|id| store.iter().any(|c| &c.id == id)) {
+            return Ok(errors::ErrorCode::NotFoundError);
+        }
+        store.retain(|c| !ids.contains(&c.id));
+        Ok(errors::ErrorCode::ReplyOk)
+    }
+}
@@ -0,0 +1,101 @@
+use super::super::super::{connectors, entities::idempotency, errors, traits};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::RwLock;
+
+pub struct IdempotencyCollection {
+    items: RwLock<Vec<idempotency::IdempotentReply>>,
+}
+
+impl IdempotencyCollection {
+    pub fn new() -> IdempotencyCollection {
+        IdempotencyCollection {
+            items: RwLock::new(Vec::<idempotency::IdempotentReply>::new()),
+        }
+    }
+}
+
+fn is_key(item: &idempotency::IdempotentReply, key: &idempotency::IdempotencyKey) -> bool {
+    item.caller == key.caller
+        && item.object_type == key.object_type
+        && item.correlation_id == key.correlation_id
+}
+
+#[async_trait]
+impl traits::IdempotencyStore for IdempotencyCollection {
+    async fn get_or_add(
+        &self,
+        item: idempotency::IdempotentReply,
+    ) -> connectors::Result<Option<idempotency::IdempotentReply>> {
+        let key = idempotency::IdempotencyKey {
+            caller: item.caller.clone(),
+            object_type: item.object_type.clone(),
+            correlation_id: item.correlation_id.clone(),
+        };
+        //check and insert under one lock, same as primary key
+        let mut store = self.items.write().unwrap();
+        match store.iter().find(|r| is_key(r, &key)) {
+            Some(r) => Ok(Some(r.clone())),
+            None => {
+                store.push(item);
+                Ok(None)
+            }
+        }
+    }
+
+    async fn complete(
+        &self,
+        key: &idempotency::IdempotencyKey,
+        status: i32,
+        reply_body: &str,
+    ) -> connectors::Result<errors::ErrorCode> {
+        let mut store = self.items.write().unwrap();
+        match store.iter_mut().find(|r| is_key(r, key)) {
+            Some(r) => {
+                r.state = idempotency::IdempotentState::Completed.to_string();
+                r.status = status;
+                r.reply_body = reply_body.to_string();
+                Ok(errors::ErrorCode::ReplyOk)
+            }
+            None => Ok(errors::ErrorCode::NotFoundError),
+        }
+    }
+
+    async fn remove(
+        &self,
+        key: &idempotency::IdempotencyKey,
+    ) -> connectors::Result<errors::ErrorCode> {
+        let mut store = self.items.write().unwrap();
+        if !store.iter().any(|r| is_key(r, key)) {
+            return Ok(errors::ErrorCode::NotFoundError);
+        }
+        store.retain(|r| !is_key(r, key));
+        Ok(errors::ErrorCode::ReplyOk)
+    }
+
+    async fn take_over(
+        &self,
+        key: &idempotency::IdempotencyKey,
+        added_before: DateTime<Utc>,
+    ) -> connectors::Result<bool> {
+        let mut store = self.items.write().unwrap();
+        match store.iter_mut().find(|r| {
+            is_key(r, key)
+                && r.state == idempotency::IdempotentState::InProgress.to_string()
+                && r.added_at < added_before
+        }) {
+            Some(r) => {
+                r.added_at = Utc::now();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn remove_expired(&self, added_before: DateTime<Utc>) -> connectors::Result<u64> {
+        let mut store = self.items.write().unwrap();
+        let count = store.len();
+        store.retain(|r| r.added_at >= added_before);
+        Ok((count - store.len()) as u64)
+    }
+}
@@ -0,0 +1,11 @@
+pub mod car;
+pub mod error;
+pub mod executor;
+pub mod idempotency;
+pub mod route;
+pub mod saga;
+pub mod usr;
+
+pub mod quota;
+
+pub mod dead_letter;
@@ -0,0 +1,64 @@
+use super::super::super::{connectors, entities::quota, traits};
+use async_trait::async_trait;
+use std::sync::RwLock;
+
+pub struct QuotaCollection {
+    items: RwLock<Vec<quota::QuotaUsage>>,
+}
+
+impl QuotaCollection {
+    pub fn new() -> QuotaCollection {
+        QuotaCollection {
+            items: RwLock::new(Vec::<quota::QuotaUsage>::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl traits::QuotaStore for QuotaCollection {
+    async fn add_usage(
+        &self,
+        caller: &str,
+        object_type: &str,
+        day: &str,
+    ) -> connectors::Result<i64> {
+        //find and increment under one lock, same as upsert
+        let mut store = self.items.write().unwrap();
+        match store
+            .iter_mut()
+            .find(|u| u.caller == caller && u.object_type == object_type && u.day == day)
+        {
+            Some(u) => {
+                u.usage_count += 1;
+                Ok(u.usage_count)
+            }
+            None => {
+                store.push(quota::QuotaUsage {
+                    caller: caller.to_string(),
+                    object_type: object_type.to_string(),
+                    day: day.to_string(),
+                    usage_count: 1,
+                });
+                Ok(1)
+            }
+        }
+    }
+
+    async fn get(&self, caller: &str, day: &str) -> connectors::Result<Vec<quota::QuotaUsage>> {
+        Ok(self
+            .items
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|u| u.caller == caller && u.day == day)
+            .cloned()
+            .collect())
+    }
+
+    async fn remove_expired(&self, day_before: &str) -> connectors::Result<u64> {
+        let mut store = self.items.write().unwrap();
+        let count = store.len();
+        store.retain(|u| u.day.as_str() >= day_before);
+        Ok((count - store.len()) as u64)
+    }
+}
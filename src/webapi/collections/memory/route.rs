@@ -0,0 +1,365 @@
+use super::super::super::{connectors, entities::route, errors, traits};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+struct ServiceItem {
+    route: route::Route,
+    added_at: DateTime<Utc>,
+    lease: Option<u32>,
+    lease_expires_at: Option<DateTime<Utc>>,
+}
+
+pub struct RouteCollection {
+    items: RwLock<Vec<ServiceItem>>,
+    history: RwLock<Vec<route::ServiceHistory>>,
+}
+
+impl RouteCollection {
+    pub fn new() -> RouteCollection {
+        RouteCollection {
+            items: RwLock::new(Vec::<ServiceItem>::new()),
+            history: RwLock::new(Vec::<route::ServiceHistory>::new()),
+        }
+    }
+}
+
+fn get_lease_expires_at(lease: Option<u32>) -> Option<DateTime<Utc>> {
+    lease.map(|l| Utc::now() + Duration::seconds(l as i64))
+}
+
+fn is_requested(services: &Option<Vec<String>>, service_name: &str) -> bool {
+    match services {
+        Some(services) => services.iter().any(|s| s == service_name),
+        None => true,
+    }
+}
+
+fn get_path(path: &Option<HashMap<String, String>>) -> HashMap<String, String> {
+    path.clone().unwrap_or_default()
+}
+
+#[async_trait]
+impl traits::RouteStore for RouteCollection {
+    async fn get_command(
+        &self,
+        services: Option<Vec<String>>,
+    ) -> connectors::Result<Vec<route::ServiceCommand>> {
+        let items = self.items.read().unwrap();
+        let mut commands = Vec::<route::ServiceCommand>::new();
+        for item in items.iter() {
+            let service_name = item.route.service_name.clone().unwrap_or_default();
+            if !is_requested(&services, &service_name) {
+                continue;
+            }
+            for command in &item.route.command {
+                let state = command.state.clone().unwrap_or_default();
+                commands.push(route::ServiceCommand {
+                    service_name: Some(service_name.clone()),
+                    priority: Some(item.route.priority),
+                    object_type: command.object_type.clone(),
+                    description: command.description.clone(),
+                    reply_type: command.reply_type.clone(),
+                    exec_mode: command.exec_mode.clone(),
+                    state: if state.len() > 0 { Some(state) } else { None },
+                    path: Some(get_path(&command.path)),
+                });
+            }
+        }
+        //same order as v_service_command
+        commands.sort_by(|a, b| {
+            a.object_type
+                .cmp(&b.object_type)
+                .then(a.priority.cmp(&b.priority))
+        });
+        Ok(commands)
+    }
+
+    async fn get_event(
+        &self,
+        services: Option<Vec<String>>,
+    ) -> connectors::Result<Vec<route::ServiceEvent>> {
+        let items = self.items.read().unwrap();
+        let mut events = Vec::<route::ServiceEvent>::new();
+        for item in items.iter() {
+            let service_name = item.route.service_name.clone().unwrap_or_default();
+            if !is_requested(&services, &service_name) {
+                continue;
+            }
+            for event in &item.route.event {
+                events.push(route::ServiceEvent {
+                    service_name: Some(service_name.clone()),
+                    object_type: event.object_type.clone(),
+                    description: event.description.clone(),
+                });
+            }
+        }
+        Ok(events)
+    }
+
+    async fn get_subscription(
+        &self,
+        services: Option<Vec<String>>,
+    ) -> connectors::Result<Vec<route::ServiceSubscription>> {
+        let items = self.items.read().unwrap();
+        let mut subscriptions = Vec::<route::ServiceSubscription>::new();
+        for item in items.iter() {
+            let service_name = item.route.service_name.clone().unwrap_or_default();
+            if !is_requested(&services, &service_name) {
+                continue;
+            }
+            for subscription in &item.route.subscription {
+                subscriptions.push(route::ServiceSubscription {
+                    service_name: Some(service_name.clone()),
+                    object_type: subscription.object_type.clone(),
+                    path: Some(get_path(&subscription.path)),
+                });
+            }
+        }
+        //same order as v_service_subscription
+        subscriptions.sort_by(|a, b| a.object_type.cmp(&b.object_type));
+        Ok(subscriptions)
+    }
+
+    async fn get_service(
+        &self,
+        names: Option<Vec<String>>,
+    ) -> connectors::Result<Vec<route::Service>> {
+        let items = self.items.read().unwrap();
+        let mut services = Vec::<route::Service>::new();
+        for item in items.iter() {
+            let name = item.route.service_name.clone().unwrap_or_default();
+            if !is_requested(&names, &name) {
+                continue;
+            }
+            services.push(route::Service {
+                name: name,
+                description: item.route.description.clone(),
+                priority: item.route.priority,
+                state: route::ServiceState::Unavailable.to_string(),
+                added_at: item.added_at,
+            });
+        }
+        services.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(services)
+    }
+
+    async fn get_service_path(
+        &self,
+        services: Option<Vec<String>>,
+    ) -> connectors::Result<Vec<route::ServicePath>> {
+        let items = self.items.read().unwrap();
+        let mut paths = Vec::<route::ServicePath>::new();
+        for item in items.iter() {
+            let service_name = item.route.service_name.clone().unwrap_or_default();
+            if !is_requested(&services, &service_name) {
+                continue;
+            }
+            for (proto, path) in item.route.path.clone().unwrap_or_default() {
+                paths.push(route::ServicePath {
+                    service_name: Some(service_name.clone()),
+                    proto: Some(proto),
+                    helth: path.helth,
+                    schema: path.schema,
+                    reply_to: path.reply_to,
+                    state: path.state,
+                    error: path.error,
+                    request: None,
+                    event: None,
+                });
+            }
+        }
+        //same order as v_service_path
+        paths.sort_by(|a, b| {
+            a.service_name
+                .cmp(&b.service_name)
+                .then(a.proto.cmp(&b.proto))
+        });
+        Ok(paths)
+    }
+
+    async fn get(&self, services: Option<Vec<String>>) -> connectors::Result<Vec<route::Route>> {
+        let items = self.items.read().unwrap();
+        let mut routes = Vec::<route::Route>::new();
+        for item in items.iter() {
+            let service_name = item.route.service_name.clone().unwrap_or_default();
+            if !is_requested(&services, &service_name) {
+                continue;
+            }
+            let mut path = HashMap::<String, route::ServicePath>::new();
+            for (proto, p) in item.route.path.clone().unwrap_or_default() {
+                path.insert(
+                    proto.clone(),
+                    route::ServicePath {
+                        service_name: None,
+                        proto: Some(proto),
+                        helth: p.helth,
+                        schema: p.schema,
+                        reply_to: p.reply_to,
+                        state: p.state,
+                        error: p.error,
+                        request: None,
+                        event: None,
+                    },
+                );
+            }
+            routes.push(route::Route {
+                service_name: Some(service_name),
+                description: item.route.description.clone(),
+                priority: item.route.priority,
+                command: item
+                    .route
+                    .command
+                    .iter()
+                    .map(|c| {
+                        let state = c.state.clone().unwrap_or_default();
+                        route::ServiceCommand {
+                            service_name: None,
+                            priority: None,
+                            object_type: c.object_type.clone(),
+                            description: c.description.clone(),
+                            reply_type: c.reply_type.clone(),
+                            exec_mode: c.exec_mode.clone(),
+                            state: if state.len() > 0 { Some(state) } else { None },
+                            path: Some(get_path(&c.path)),
+                        }
+                    })
+                    .collect(),
+                event: item
+                    .route
+                    .event
+                    .iter()
+                    .map(|e| route::ServiceEvent {
+                        service_name: None,
+                        object_type: e.object_type.clone(),
+                        description: e.description.clone(),
+                    })
+                    .collect(),
+                subscription: item
+                    .route
+                    .subscription
+                    .iter()
+                    .map(|s| route::ServiceSubscription {
+                        service_name: None,
+                        object_type: s.object_type.clone(),
+                        path: Some(get_path(&s.path)),
+                    })
+                    .collect(),
+                path: Some(path),
+            });
+        }
+        routes.sort_by(|a, b| a.service_name.cmp(&b.service_name));
+        Ok(routes)
+    }
+
+    async fn add(
+        &self,
+        items: Vec<route::Route>,
+        lease: Option<u32>,
+    ) -> connectors::Result<(errors::ErrorCode, Option<Vec<String>>)> {
+        let mut store = self.items.write().unwrap();
+        let mut names = Vec::<String>::new();
+        for item in &items {
+            //same as service_pk primary key
+            let service_name = item.service_name.clone().unwrap_or_default();
+            if names.contains(&service_name) {
+                error!(
+                    "add_routes memory upsert: duplicate service {}",
+                    service_name
+                );
+                return Ok((errors::ErrorCode::DatabaseError, None));
+            }
+            names.push(service_name);
+        }
+        //unchanged routes are kept with their added_at, lease is renewed anyway
+        let mut ids = Vec::<String>::new();
+        let added_at = Utc::now();
+        let lease_expires_at = get_lease_expires_at(lease);
+        for (item, service_name) in items.into_iter().zip(names) {
+            match store
+                .iter_mut()
+                .find(|s| s.route.service_name.as_ref() == Some(&service_name))
+            {
+                Some(s) => {
+                    s.lease = lease;
+                    s.lease_expires_at = lease_expires_at;
+                    if s.route.is_same(&item) {
+                        continue;
+                    }
+                    s.route = item;
+                }
+                None => store.push(ServiceItem {
+                    route: item,
+                    added_at: added_at,
+                    lease: lease,
+                    lease_expires_at: lease_expires_at,
+                }),
+            }
+            ids.push(service_name);
+        }
+        Ok((errors::ErrorCode::ReplyOk, Some(ids)))
+    }
+
+    async fn remove(&self, services: Vec<String>) -> connectors::Result<errors::ErrorCode> {
+        let mut store = self.items.write().unwrap();
+        if !services.iter().all(|name| {
+            store
+                .iter()
+                .any(|s| s.route.service_name.as_ref() == Some(name))
+        }) {
+            return Ok(errors::ErrorCode::NotFoundError);
+        }
+        store.retain(|s| !services.contains(&s.route.service_name.clone().unwrap_or_default()));
+        Ok(errors::ErrorCode::ReplyOk)
+    }
+
+    async fn renew(&self, services: Vec<String>) -> connectors::Result<errors::ErrorCode> {
+        let mut store = self.items.write().unwrap();
+        if !services.iter().all(|name| {
+            store
+                .iter()
+                .any(|s| s.route.service_name.as_ref() == Some(name) && s.lease.is_some())
+        }) {
+            return Ok(errors::ErrorCode::NotFoundError);
+        }
+        for item in store.iter_mut() {
+            if services.contains(&item.route.service_name.clone().unwrap_or_default()) {
+                item.lease_expires_at = get_lease_expires_at(item.lease);
+            }
+        }
+        Ok(errors::ErrorCode::ReplyOk)
+    }
+
+    async fn expire(&self, at: DateTime<Utc>) -> connectors::Result<Vec<String>> {
+        let mut store = self.items.write().unwrap();
+        let services: Vec<String> = store
+            .iter()
+            .filter(|s| s.lease_expires_at.map_or(false, |e| e < at))
+            .map(|s| s.route.service_name.clone().unwrap_or_default())
+            .collect();
+        store.retain(|s| !services.contains(&s.route.service_name.clone().unwrap_or_default()));
+        self.history
+            .write()
+            .unwrap()
+            .extend(services.iter().map(|s| route::ServiceHistory {
+                service_name: s.clone(),
+                state: route::ServiceState::Unavailable.to_string(),
+                description: "lease expired".to_string(),
+                added_at: at,
+            }));
+        Ok(services)
+    }
+
+    async fn get_history(
+        &self,
+        services: Option<Vec<String>>,
+    ) -> connectors::Result<Vec<route::ServiceHistory>> {
+        let history = self.history.read().unwrap();
+        Ok(history
+            .iter()
+            .filter(|h| is_requested(&services, &h.service_name))
+            .cloned()
+            .collect())
+    }
+}
@@ -0,0 +1,112 @@
+use super::super::super::{connectors, entities::saga, errors, traits};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::RwLock;
+
+pub struct SagaCollection {
+    items: RwLock<Vec<saga::Saga>>,
+}
+
+impl SagaCollection {
+    pub fn new() -> SagaCollection {
+        SagaCollection {
+            items: RwLock::new(Vec::<saga::Saga>::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl traits::SagaStore for SagaCollection {
+    async fn get(&self, ids: Option<Vec<String>>) -> connectors::Result<Vec<saga::Saga>> {
+        let items = self.items.read().unwrap();
+        Ok(match ids {
+            Some(ids) => items
+                .iter()
+                .filter(|item| ids.contains(&item.id))
+                .cloned()
+                .collect(),
+            None => items.clone(),
+        })
+    }
+
+    async fn get_expired(
+        &self,
+        states: Vec<saga::SagaState>,
+        at: DateTime<Utc>,
+    ) -> connectors::Result<Vec<saga::Saga>> {
+        let states: Vec<String> = states.iter().map(|s| s.to_string()).collect();
+        let items = self.items.read().unwrap();
+        Ok(items
+            .iter()
+            .filter(|item| states.contains(&item.state) && item.lease_expires_at < at)
+            .cloned()
+            .collect())
+    }
+
+    async fn add(&self, item: saga::Saga) -> connectors::Result<errors::ErrorCode> {
+        let mut store = self.items.write().unwrap();
+        if store.iter().any(|s| s.id == item.id) {
+            error!("add_saga memory insert: duplicate id {}", item.id);
+            return Ok(errors::ErrorCode::DatabaseError);
+        }
+        store.push(item);
+        Ok(errors::ErrorCode::ReplyOk)
+    }
+
+    async fn change_state(
+        &self,
+        id: &str,
+        state: saga::SagaState,
+        step: i32,
+    ) -> connectors::Result<errors::ErrorCode> {
+        let mut store = self.items.write().unwrap();
+        match store.iter_mut().find(|s| s.id == id) {
+            Some(item) => {
+                item.state = state.to_string();
+                item.step = step;
+                item.state_changed_at = Utc::now();
+                Ok(errors::ErrorCode::ReplyOk)
+            }
+            None => Ok(errors::ErrorCode::NotFoundError),
+        }
+    }
+
+    async fn claim(
+        &self,
+        id: &str,
+        owner: &str,
+        at: DateTime<Utc>,
+        lease_expires_at: DateTime<Utc>,
+    ) -> connectors::Result<errors::ErrorCode> {
+        let mut store = self.items.write().unwrap();
+        match store
+            .iter_mut()
+            .find(|s| s.id == id && is_unfinished(s) && s.lease_expires_at < at)
+        {
+            Some(item) => {
+                item.owner = owner.to_string();
+                item.lease_expires_at = lease_expires_at;
+                Ok(errors::ErrorCode::ReplyOk)
+            }
+            None => Ok(errors::ErrorCode::NotFoundError),
+        }
+    }
+
+    async fn renew(&self, owner: &str, lease_expires_at: DateTime<Utc>) -> connectors::Result<u64> {
+        let mut store = self.items.write().unwrap();
+        let mut count = 0;
+        for item in store
+            .iter_mut()
+            .filter(|s| s.owner == owner && is_unfinished(s))
+        {
+            item.lease_expires_at = lease_expires_at;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+fn is_unfinished(item: &saga::Saga) -> bool {
+    item.state == saga::SagaState::Running.to_string()
+        || item.state == saga::SagaState::Compensating.to_string()
+}
@@ -0,0 +1,30 @@
+use super::super::super::{connectors, entities::usr, traits};
+use async_trait::async_trait;
+use std::sync::RwLock;
+
+pub struct UsrCollection {
+    items: RwLock<Vec<usr::Usr>>,
+}
+
+impl UsrCollection {
+    pub fn new(items: Vec<usr::Usr>) -> UsrCollection {
+        UsrCollection {
+            items: RwLock::new(items),
+        }
+    }
+}
+
+#[async_trait]
+impl traits::UsrStore for UsrCollection {
+    async fn get(&self, ids: Option<Vec<i32>>) -> connectors::Result<Vec<usr::Usr>> {
+        let items = self.items.read().unwrap();
+        Ok(match ids {
+            Some(ids) => items
+                .iter()
+                .filter(|item| ids.contains(&item.id))
+                .cloned()
+                .collect(),
+            None => items.clone(),
+        })
+    }
+}
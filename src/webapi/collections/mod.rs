@@ -1,4 +1,21 @@
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub mod car;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub mod error;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 pub mod executor;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub mod idempotency;
+pub mod memory;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 pub mod route;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub mod saga;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 pub mod usr;
-pub mod car;
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub mod quota;
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub mod dead_letter;
@@ -0,0 +1,118 @@
+use super::super::{connectors, entities::quota, providers, traits};
+use async_trait::async_trait;
+#[cfg(feature = "postgres")]
+use sqlx::postgres::PgPool;
+use sqlx::Done;
+#[cfg(feature = "mysql")]
+use sqlx::MySqlPool;
+#[cfg(feature = "sqlite")]
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+//counter is added or incremented in one statement, then selected
+#[cfg(feature = "postgres")]
+const UPSERT_USAGE: &str = r#"INSERT INTO webapi.quota_usage
+    ( caller, object_type, day, usage_count ) VALUES ( $1, $2, $3, 1 )
+        ON CONFLICT ( caller, object_type, day )
+        DO UPDATE SET usage_count = webapi.quota_usage.usage_count + 1"#;
+#[cfg(feature = "mysql")]
+const UPSERT_USAGE: &str = r#"INSERT INTO quota_usage
+    ( caller, object_type, day, usage_count ) VALUES ( ?, ?, ?, 1 )
+        ON DUPLICATE KEY UPDATE usage_count = usage_count + 1"#;
+#[cfg(feature = "sqlite")]
+const UPSERT_USAGE: &str = r#"INSERT INTO quota_usage
+    ( caller, object_type, day, usage_count ) VALUES ( ?, ?, ?, 1 )
+        ON CONFLICT ( caller, object_type, day )
+        DO UPDATE SET usage_count = usage_count + 1"#;
+
+#[cfg(feature = "postgres")]
+const SELECT_USAGE_COUNT: &str = r#"SELECT usage_count FROM webapi.quota_usage
+    WHERE caller = $1 AND object_type = $2 AND day = $3"#;
+#[cfg(any(feature = "mysql", feature = "sqlite"))]
+const SELECT_USAGE_COUNT: &str = r#"SELECT usage_count FROM quota_usage
+    WHERE caller = ? AND object_type = ? AND day = ?"#;
+
+#[cfg(feature = "postgres")]
+const SELECT_USAGE: &str = r#"SELECT * FROM webapi.quota_usage
+    WHERE caller = $1 AND day = $2 ORDER BY object_type"#;
+#[cfg(any(feature = "mysql", feature = "sqlite"))]
+const SELECT_USAGE: &str = r#"SELECT * FROM quota_usage
+    WHERE caller = ? AND day = ? ORDER BY object_type"#;
+
+#[cfg(feature = "postgres")]
+const DELETE_EXPIRED: &str = r#"DELETE FROM webapi.quota_usage WHERE day < $1"#;
+#[cfg(any(feature = "mysql", feature = "sqlite"))]
+const DELETE_EXPIRED: &str = r#"DELETE FROM quota_usage WHERE day < ?"#;
+
+pub struct QuotaCollection {
+    data_provider: Arc<providers::SqlDbProvider>,
+}
+
+impl QuotaCollection {
+    pub fn new(
+        data_provider: Arc<providers::SqlDbProvider>,
+        _helper: &'static connectors::ExpHelper,
+    ) -> QuotaCollection {
+        QuotaCollection {
+            data_provider: data_provider,
+        }
+    }
+}
+
+#[async_trait]
+impl traits::QuotaStore for QuotaCollection {
+    async fn add_usage(
+        &self,
+        caller: &str,
+        object_type: &str,
+        day: &str,
+    ) -> connectors::Result<i64> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        sqlx::query(UPSERT_USAGE)
+            .bind(caller)
+            .bind(object_type)
+            .bind(day)
+            .execute(pool)
+            .await?;
+        let (count,): (i64,) = sqlx::query_as(SELECT_USAGE_COUNT)
+            .bind(caller)
+            .bind(object_type)
+            .bind(day)
+            .fetch_one(pool)
+            .await?;
+        Ok(count)
+    }
+
+    async fn get(&self, caller: &str, day: &str) -> connectors::Result<Vec<quota::QuotaUsage>> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        Ok(sqlx::query_as(SELECT_USAGE)
+            .bind(caller)
+            .bind(day)
+            .fetch_all(pool)
+            .await?)
+    }
+
+    async fn remove_expired(&self, day_before: &str) -> connectors::Result<u64> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        let ret = sqlx::query(DELETE_EXPIRED)
+            .bind(day_before)
+            .execute(pool)
+            .await?;
+        Ok(ret.rows_affected())
+    }
+}
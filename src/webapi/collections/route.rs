@@ -1,4 +1,6 @@
-use super::super::{connectors, entities::route, errors, providers};
+use super::super::{connectors, entities::route, errors, providers, traits};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
 use futures::TryStreamExt;
 #[cfg(feature = "postgres")]
 use sqlx::postgres::PgPool;
@@ -6,10 +8,60 @@ use sqlx::Done;
 #[cfg(feature = "mysql")]
 use sqlx::MySqlPool;
 use sqlx::Row;
+#[cfg(feature = "sqlite")]
+use sqlx::SqlitePool;
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::sync::Arc;
 
+#[cfg(feature = "postgres")]
+type Connection = sqlx::PgConnection;
+#[cfg(feature = "mysql")]
+type Connection = sqlx::MySqlConnection;
+#[cfg(feature = "sqlite")]
+type Connection = sqlx::SqliteConnection;
+
+#[cfg(feature = "postgres")]
+const UPDATE_LEASE_EXPIRES_AT: &str =
+    r#"UPDATE webapi.service SET lease_expires_at = $1 WHERE "name" = $2"#;
+#[cfg(feature = "mysql")]
+const UPDATE_LEASE_EXPIRES_AT: &str =
+    r#"UPDATE webapi.service SET lease_expires_at = ? WHERE name = ?"#;
+#[cfg(feature = "sqlite")]
+const UPDATE_LEASE_EXPIRES_AT: &str = r#"UPDATE service SET lease_expires_at = ? WHERE "name" = ?"#;
+
+//expired rows are locked, renewal waits until they are removed
+#[cfg(feature = "postgres")]
+const SELECT_EXPIRED: &str =
+    r#"SELECT "name" FROM webapi.service WHERE lease_expires_at < $1 FOR UPDATE"#;
+#[cfg(feature = "mysql")]
+const SELECT_EXPIRED: &str =
+    r#"SELECT name FROM webapi.service WHERE lease_expires_at < ? FOR UPDATE"#;
+#[cfg(feature = "sqlite")]
+const SELECT_EXPIRED: &str = r#"SELECT "name" FROM service WHERE lease_expires_at < ?"#;
+
+//route set of service, children first
+const SERVICE_TABLES: [(&str, &str); 8] = [
+    ("webapi.service_subscription_path", "service_name"),
+    ("webapi.service_subscription", "service_name"),
+    ("webapi.service_event", "service_name"),
+    ("webapi.service_command_state", "service_name"),
+    ("webapi.service_command_path", "service_name"),
+    ("webapi.service_command", "service_name"),
+    ("webapi.service_path", "service_name"),
+    ("webapi.service", "name"),
+];
+
+#[cfg(feature = "postgres")]
+const INSERT_HISTORY: &str = r#"INSERT INTO webapi.service_history
+    ( service_name, "state", description, added_at ) VALUES ( $1, $2, $3, $4 )"#;
+#[cfg(feature = "mysql")]
+const INSERT_HISTORY: &str = r#"INSERT INTO webapi.service_history
+    ( service_name, state, description, added_at ) VALUES ( ?, ?, ?, ? )"#;
+#[cfg(feature = "sqlite")]
+const INSERT_HISTORY: &str = r#"INSERT INTO service_history
+    ( service_name, "state", description, added_at ) VALUES ( ?, ?, ?, ? )"#;
+
 pub struct RouteCollection {
     exp_helper: &'static connectors::ExpHelper,
     data_provider: Arc<providers::SqlDbProvider>,
@@ -26,7 +78,263 @@ impl RouteCollection {
         }
     }
 
-    pub async fn get_command(
+    //reads route sets on given connection, add reads them in its transaction
+    async fn get_with(
+        &self,
+        conn: &mut Connection,
+        services: Option<Vec<String>>,
+    ) -> connectors::Result<Vec<route::Route>> {
+        let mut items = Vec::<route::Route>::new();
+        let is_services_some = services.is_some();
+        let query = self.exp_helper.get_select_str_exp(
+            "webapi.v_service",
+            "name",
+            &services.unwrap_or_default(),
+        );
+        let query = if !is_services_some {
+            format!(
+                r#"SELECT "name", "description", "priority" FROM {}"#,
+                self.exp_helper.get_table_exp("webapi.v_service")
+            )
+        } else {
+            query
+        };
+        //fetch services first, so nested queries don't wait for the connection held by a cursor
+        let service_recs = sqlx::query(&query).fetch_all(&mut *conn).await?;
+        for service_rec in service_recs {
+            let service_name: String = service_rec.get(0);
+            let mut commands = Vec::<route::ServiceCommand>::new();
+            #[cfg(not(feature = "sqlite"))]
+            let command_recs: Vec<(String, String, String, String)> = sqlx::query!(
+                r#"SELECT object_type, reply_type, description, exec_mode 
+                    FROM webapi.v_service_command WHERE service_name = $1"#,
+                &service_name,
+            )
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .map(|rec| {
+                (
+                    rec.object_type.unwrap(),
+                    rec.reply_type.unwrap(),
+                    rec.description.unwrap(),
+                    rec.exec_mode.unwrap(),
+                )
+            })
+            .collect();
+            #[cfg(feature = "sqlite")]
+            let command_recs: Vec<(String, String, String, String)> = sqlx::query_as(
+                r#"SELECT object_type, reply_type, description, exec_mode 
+                    FROM v_service_command WHERE service_name = ?"#,
+            )
+            .bind(&service_name)
+            .fetch_all(&mut *conn)
+            .await?;
+            for command_rec in command_recs {
+                #[cfg(not(feature = "sqlite"))]
+                let path_recs: Vec<(String, String)> = sqlx::query!(
+                    r#"SELECT proto, "to" 
+                        FROM webapi.v_service_command_path 
+                            WHERE service_name = $1 AND object_type = $2"#,
+                    &service_name,
+                    &command_rec.0
+                )
+                .fetch_all(&mut *conn)
+                .await?
+                .into_iter()
+                .map(|rec| (rec.proto.unwrap(), rec.to.unwrap()))
+                .collect();
+                #[cfg(feature = "sqlite")]
+                let path_recs: Vec<(String, String)> = sqlx::query_as(
+                    r#"SELECT proto, "to" 
+                        FROM v_service_command_path 
+                            WHERE service_name = ? AND object_type = ?"#,
+                )
+                .bind(&service_name)
+                .bind(&command_rec.0)
+                .fetch_all(&mut *conn)
+                .await?;
+                let mut p = HashMap::<String, String>::new();
+                for rec in path_recs {
+                    p.insert(rec.0, rec.1);
+                }
+                #[cfg(not(feature = "sqlite"))]
+                let state_recs: Vec<(String, String)> = sqlx::query!(
+                    r#"SELECT "state", "description" 
+                        FROM webapi.v_service_command_state 
+                            WHERE service_name = $1 AND object_type = $2"#,
+                    &service_name,
+                    &command_rec.0
+                )
+                .fetch_all(&mut *conn)
+                .await?
+                .into_iter()
+                .map(|rec| (rec.state.unwrap(), rec.description.unwrap()))
+                .collect();
+                #[cfg(feature = "sqlite")]
+                let state_recs: Vec<(String, String)> = sqlx::query_as(
+                    r#"SELECT "state", "description" 
+                        FROM v_service_command_state 
+                            WHERE service_name = ? AND object_type = ?"#,
+                )
+                .bind(&service_name)
+                .bind(&command_rec.0)
+                .fetch_all(&mut *conn)
+                .await?;
+                let mut sh = HashMap::<String, String>::new();
+                for rec in state_recs {
+                    sh.insert(rec.0, rec.1);
+                }
+                let s = if sh.len() > 0 { Some(sh) } else { None };
+                commands.push(route::ServiceCommand {
+                    service_name: None,
+                    priority: None,
+                    object_type: command_rec.0,
+                    reply_type: command_rec.1,
+                    description: command_rec.2,
+                    exec_mode: command_rec.3,
+                    path: Some(p),
+                    state: s,
+                });
+            }
+            let mut events = Vec::<route::ServiceEvent>::new();
+            #[cfg(not(feature = "sqlite"))]
+            let event_recs: Vec<(String, String)> = sqlx::query!(
+                r#"SELECT object_type, description FROM webapi.v_service_event WHERE service_name = $1"#,
+                &service_name
+            )
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .map(|rec| (rec.object_type.unwrap(), rec.description.unwrap()))
+            .collect();
+            #[cfg(feature = "sqlite")]
+            let event_recs: Vec<(String, String)> = sqlx::query_as(
+                r#"SELECT object_type, description FROM v_service_event WHERE service_name = ?"#,
+            )
+            .bind(&service_name)
+            .fetch_all(&mut *conn)
+            .await?;
+            for event_rec in event_recs {
+                events.push(route::ServiceEvent {
+                    service_name: None,
+                    object_type: event_rec.0,
+                    description: event_rec.1,
+                });
+            }
+            let mut subscriptions = Vec::<route::ServiceSubscription>::new();
+            #[cfg(not(feature = "sqlite"))]
+            let subscription_recs: Vec<(String,)> = sqlx::query!(
+                r#"SELECT object_type FROM webapi.v_service_subscription WHERE service_name = $1"#,
+                &service_name
+            )
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .map(|rec| (rec.object_type.unwrap(),))
+            .collect();
+            #[cfg(feature = "sqlite")]
+            let subscription_recs: Vec<(String,)> = sqlx::query_as(
+                r#"SELECT object_type FROM v_service_subscription WHERE service_name = ?"#,
+            )
+            .bind(&service_name)
+            .fetch_all(&mut *conn)
+            .await?;
+            for subscription_rec in subscription_recs {
+                #[cfg(not(feature = "sqlite"))]
+                let recs: Vec<(String, String)> = sqlx::query!(
+                    r#"SELECT proto, "to" FROM webapi.v_service_subscription_path 
+                        WHERE service_name = $1 AND object_type = $2"#,
+                    &service_name,
+                    &subscription_rec.0
+                )
+                .fetch_all(&mut *conn)
+                .await?
+                .into_iter()
+                .map(|rec| (rec.proto.unwrap(), rec.to.unwrap()))
+                .collect();
+                #[cfg(feature = "sqlite")]
+                let recs: Vec<(String, String)> = sqlx::query_as(
+                    r#"SELECT proto, "to" FROM v_service_subscription_path 
+                        WHERE service_name = ? AND object_type = ?"#,
+                )
+                .bind(&service_name)
+                .bind(&subscription_rec.0)
+                .fetch_all(&mut *conn)
+                .await?;
+                let mut p = HashMap::<String, String>::new();
+                for rec in recs {
+                    p.insert(rec.0, rec.1);
+                }
+                subscriptions.push(route::ServiceSubscription {
+                    service_name: None,
+                    object_type: subscription_rec.0,
+                    path: Some(p),
+                });
+            }
+            #[cfg(not(feature = "sqlite"))]
+            let recs: Vec<(String, String, String, String, String, String)> = sqlx::query!(
+                r#"SELECT proto, helth, schema, reply_to, "state", "error" 
+                    FROM webapi.v_service_path WHERE service_name = $1"#,
+                &service_name
+            )
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .map(|rec| {
+                (
+                    rec.proto.unwrap(),
+                    rec.helth.unwrap(),
+                    rec.schema.unwrap(),
+                    rec.reply_to.unwrap(),
+                    rec.state.unwrap(),
+                    rec.error.unwrap(),
+                )
+            })
+            .collect();
+            #[cfg(feature = "sqlite")]
+            let recs: Vec<(String, String, String, String, String, String)> = sqlx::query_as(
+                r#"SELECT proto, helth, "schema", reply_to, "state", "error" 
+                    FROM v_service_path WHERE service_name = ?"#,
+            )
+            .bind(&service_name)
+            .fetch_all(&mut *conn)
+            .await?;
+            let mut p = HashMap::<String, route::ServicePath>::new();
+            for rec in recs {
+                let proto = rec.0;
+                p.insert(
+                    proto.clone(),
+                    route::ServicePath {
+                        service_name: None,
+                        proto: Some(proto),
+                        helth: rec.1,
+                        schema: rec.2,
+                        reply_to: rec.3,
+                        state: rec.4,
+                        error: rec.5,
+                        request: None,
+                        event: None,
+                    },
+                );
+            }
+            items.push(route::Route {
+                service_name: Some(service_name),
+                description: service_rec.get(1),
+                priority: service_rec.get(2),
+                command: commands,
+                event: events,
+                subscription: subscriptions,
+                path: Some(p),
+            });
+        }
+        Ok(items)
+    }
+}
+
+#[async_trait]
+impl traits::RouteStore for RouteCollection {
+    async fn get_command(
         &self,
         services: Option<Vec<String>>,
     ) -> connectors::Result<Vec<route::ServiceCommand>> {
@@ -34,25 +342,50 @@ impl RouteCollection {
         let pool: &PgPool = &self.data_provider.pool;
         #[cfg(feature = "mysql")]
         let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
         let mut items = Vec::<route::ServiceCommand>::new();
         if services.is_none() {
-            let recs = sqlx::query!(
-                r#"SELECT service_name, priority, object_type, description, exec_mode, reply_type
-            FROM webapi.v_service_command"#,
-            )
-            .fetch_all(pool)
-            .await?;
-            for rec in recs {
-                items.push(route::ServiceCommand {
-                    service_name: rec.service_name,
-                    priority: rec.priority,
-                    object_type: rec.object_type.unwrap(),
-                    description: rec.description.unwrap(),
-                    exec_mode: rec.exec_mode.unwrap(),
-                    reply_type: rec.reply_type.unwrap(),
-                    path: None,
-                    state: None,
-                })
+            #[cfg(not(feature = "sqlite"))]
+            {
+                let recs = sqlx::query!(
+                    r#"SELECT service_name, priority, object_type, description, exec_mode, reply_type
+                FROM webapi.v_service_command"#,
+                )
+                .fetch_all(pool)
+                .await?;
+                for rec in recs {
+                    items.push(route::ServiceCommand {
+                        service_name: rec.service_name,
+                        priority: rec.priority,
+                        object_type: rec.object_type.unwrap(),
+                        description: rec.description.unwrap(),
+                        exec_mode: rec.exec_mode.unwrap(),
+                        reply_type: rec.reply_type.unwrap(),
+                        path: None,
+                        state: None,
+                    })
+                }
+            }
+            #[cfg(feature = "sqlite")]
+            {
+                let mut cursor = sqlx::query(
+                    r#"SELECT service_name, priority, object_type, description, exec_mode, reply_type
+                FROM v_service_command"#,
+                )
+                .fetch(pool);
+                while let Some(rec) = cursor.try_next().await? {
+                    items.push(route::ServiceCommand {
+                        service_name: rec.get(0),
+                        priority: rec.get(1),
+                        object_type: rec.get(2),
+                        description: rec.get(3),
+                        exec_mode: rec.get(4),
+                        reply_type: rec.get(5),
+                        path: None,
+                        state: None,
+                    })
+                }
             }
         } else {
             let query = self.exp_helper.get_select_str_exp(
@@ -75,39 +408,59 @@ impl RouteCollection {
             }
         }
         for mut item in &mut items {
-            let recs = sqlx::query!(
+            #[cfg(not(feature = "sqlite"))]
+            let recs: Vec<(String, String)> = sqlx::query!(
                 r#"SELECT proto, "to" FROM webapi.v_service_command_path 
                     WHERE "service_name" = $1 AND object_type = $2"#,
                 item.service_name.as_ref().unwrap(),
                 &item.object_type
             )
             .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|rec| (rec.proto.unwrap(), rec.to.unwrap()))
+            .collect();
+            #[cfg(feature = "sqlite")]
+            let recs: Vec<(String, String)> = sqlx::query_as(
+                r#"SELECT proto, "to" FROM v_service_command_path 
+                    WHERE "service_name" = ? AND object_type = ?"#,
+            )
+            .bind(item.service_name.as_ref().unwrap())
+            .bind(&item.object_type)
+            .fetch_all(pool)
             .await?;
             let mut p = HashMap::<String, String>::new();
             for rec in recs {
-                p.insert(
-                    rec.proto.as_ref().unwrap().to_string(),
-                    rec.to.as_ref().unwrap().to_string(),
-                );
+                p.insert(rec.0, rec.1);
             }
             item.path = Some(p);
         }
         for mut item in &mut items {
-            let recs = sqlx::query!(
+            #[cfg(not(feature = "sqlite"))]
+            let recs: Vec<(String, String)> = sqlx::query!(
                 r#"SELECT "state", "description" FROM webapi.v_service_command_state 
                     WHERE "service_name" = $1 AND object_type = $2"#,
                 item.service_name.as_ref().unwrap(),
                 &item.object_type
             )
             .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|rec| (rec.state.unwrap(), rec.description.unwrap()))
+            .collect();
+            #[cfg(feature = "sqlite")]
+            let recs: Vec<(String, String)> = sqlx::query_as(
+                r#"SELECT "state", "description" FROM v_service_command_state 
+                    WHERE "service_name" = ? AND object_type = ?"#,
+            )
+            .bind(item.service_name.as_ref().unwrap())
+            .bind(&item.object_type)
+            .fetch_all(pool)
             .await?;
             if recs.len() > 0 {
                 let mut p = HashMap::<String, String>::new();
                 for rec in recs {
-                    p.insert(
-                        rec.state.as_ref().unwrap().to_string(),
-                        rec.description.as_ref().unwrap().to_string(),
-                    );
+                    p.insert(rec.0, rec.1);
                 }
                 item.state = Some(p);
             }
@@ -115,7 +468,7 @@ impl RouteCollection {
         Ok(items)
     }
 
-    pub async fn get_event(
+    async fn get_event(
         &self,
         services: Option<Vec<String>>,
     ) -> connectors::Result<Vec<route::ServiceEvent>> {
@@ -123,14 +476,23 @@ impl RouteCollection {
         let pool: &PgPool = &self.data_provider.pool;
         #[cfg(feature = "mysql")]
         let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
         if services.is_none() {
-            Ok(sqlx::query_as!(
+            #[cfg(not(feature = "sqlite"))]
+            return Ok(sqlx::query_as!(
                 route::ServiceEvent,
                 r#"SELECT "service_name" as "service_name?", object_type as "object_type!", "description" as "description!"
             FROM webapi.v_service_event"#
             )
             .fetch_all(pool)
-            .await?)
+            .await?);
+            #[cfg(feature = "sqlite")]
+            return Ok(sqlx::query_as(
+                r#"SELECT "service_name", object_type, "description" FROM v_service_event"#,
+            )
+            .fetch_all(pool)
+            .await?);
         } else {
             let query = self.exp_helper.get_select_str_exp(
                 "webapi.v_service_event",
@@ -142,7 +504,7 @@ impl RouteCollection {
         }
     }
 
-    pub async fn get_subscription(
+    async fn get_subscription(
         &self,
         services: Option<Vec<String>>,
     ) -> connectors::Result<Vec<route::ServiceSubscription>> {
@@ -150,21 +512,40 @@ impl RouteCollection {
         let pool: &PgPool = &self.data_provider.pool;
         #[cfg(feature = "mysql")]
         let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
         let mut items = Vec::<route::ServiceSubscription>::new();
         if services.is_none() {
-            let recs = sqlx::query!(
-                r#"SELECT "service_name", object_type
-            FROM webapi."v_service_subscription"
-            "#,
-            )
-            .fetch_all(pool)
-            .await?;
-            for rec in recs {
-                items.push(route::ServiceSubscription {
-                    service_name: rec.service_name,
-                    object_type: rec.object_type.unwrap(),
-                    path: None,
-                })
+            #[cfg(not(feature = "sqlite"))]
+            {
+                let recs = sqlx::query!(
+                    r#"SELECT "service_name", object_type
+                FROM webapi."v_service_subscription"
+                "#,
+                )
+                .fetch_all(pool)
+                .await?;
+                for rec in recs {
+                    items.push(route::ServiceSubscription {
+                        service_name: rec.service_name,
+                        object_type: rec.object_type.unwrap(),
+                        path: None,
+                    })
+                }
+            }
+            #[cfg(feature = "sqlite")]
+            {
+                let mut cursor = sqlx::query(
+                    r#"SELECT "service_name", object_type FROM v_service_subscription"#,
+                )
+                .fetch(pool);
+                while let Some(rec) = cursor.try_next().await? {
+                    items.push(route::ServiceSubscription {
+                        service_name: rec.get(0),
+                        object_type: rec.get(1),
+                        path: None,
+                    })
+                }
             }
         } else {
             let query = self.exp_helper.get_select_str_exp(
@@ -182,27 +563,37 @@ impl RouteCollection {
             }
         }
         for mut item in &mut items {
-            let recs = sqlx::query!(
+            #[cfg(not(feature = "sqlite"))]
+            let recs: Vec<(String, String)> = sqlx::query!(
                 r#"SELECT proto, "to" FROM webapi.v_service_subscription_path 
                     WHERE "service_name" = $1 AND object_type = $2"#,
                 item.service_name.as_ref().unwrap(),
                 &item.object_type
             )
             .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|rec| (rec.proto.unwrap(), rec.to.unwrap()))
+            .collect();
+            #[cfg(feature = "sqlite")]
+            let recs: Vec<(String, String)> = sqlx::query_as(
+                r#"SELECT proto, "to" FROM v_service_subscription_path 
+                    WHERE "service_name" = ? AND object_type = ?"#,
+            )
+            .bind(item.service_name.as_ref().unwrap())
+            .bind(&item.object_type)
+            .fetch_all(pool)
             .await?;
             let mut p = HashMap::<String, String>::new();
             for rec in recs {
-                p.insert(
-                    rec.proto.as_ref().unwrap().to_string(),
-                    rec.to.as_ref().unwrap().to_string(),
-                );
+                p.insert(rec.0, rec.1);
             }
             item.path = Some(p);
         }
         Ok(items)
     }
 
-    pub async fn get_service(
+    async fn get_service(
         &self,
         names: Option<Vec<String>>,
     ) -> connectors::Result<Vec<route::Service>> {
@@ -210,14 +601,23 @@ impl RouteCollection {
         let pool: &PgPool = &self.data_provider.pool;
         #[cfg(feature = "mysql")]
         let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
         if names.is_none() {
-            Ok(sqlx::query_as!(
+            #[cfg(not(feature = "sqlite"))]
+            return Ok(sqlx::query_as!(
                 route::Service,
                 r#"SELECT name as "name!", description as "description!", priority as "priority!", "state" as "state!", added_at as "added_at!"
             FROM webapi.v_service"#
             )
             .fetch_all(pool)
-            .await?)
+            .await?);
+            #[cfg(feature = "sqlite")]
+            return Ok(sqlx::query_as(
+                r#"SELECT name, description, priority, "state", added_at FROM v_service"#,
+            )
+            .fetch_all(pool)
+            .await?);
         } else {
             let query =
                 self.exp_helper
@@ -227,7 +627,7 @@ impl RouteCollection {
         }
     }
 
-    pub async fn get_service_path(
+    async fn get_service_path(
         &self,
         services: Option<Vec<String>>,
     ) -> connectors::Result<Vec<route::ServicePath>> {
@@ -235,6 +635,8 @@ impl RouteCollection {
         let pool: &PgPool = &self.data_provider.pool;
         #[cfg(feature = "mysql")]
         let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
         let mut items = Vec::<route::ServicePath>::new();
         let is_services_some = services.is_some();
         let query = self.exp_helper.get_select_str_exp(
@@ -242,15 +644,16 @@ impl RouteCollection {
             "service_name",
             &services.unwrap_or_default(),
         );
-        let mut cursor = if !is_services_some {
-            sqlx::query(
+        let query = if !is_services_some {
+            format!(
                 r#"SELECT "service_name", proto, helth, "schema", reply_to, "state", "error"
-            FROM webapi.v_service_path"#,
+            FROM {}"#,
+                self.exp_helper.get_table_exp("webapi.v_service_path")
             )
-            .fetch(pool)
         } else {
-            sqlx::query(&query).fetch(pool)
+            query
         };
+        let mut cursor = sqlx::query(&query).fetch(pool);
         while let Some(rec) = cursor.try_next().await? {
             items.push(route::ServicePath {
                 service_name: rec.get(0),
@@ -267,164 +670,73 @@ impl RouteCollection {
         Ok(items)
     }
 
-    pub async fn get(
-        &self,
-        services: Option<Vec<String>>,
-    ) -> connectors::Result<Vec<route::Route>> {
+    async fn get(&self, services: Option<Vec<String>>) -> connectors::Result<Vec<route::Route>> {
         #[cfg(feature = "postgres")]
         let pool: &PgPool = &self.data_provider.pool;
         #[cfg(feature = "mysql")]
         let pool: &MySqlPool = &self.data_provider.pool;
-        let mut items = Vec::<route::Route>::new();
-        let is_services_some = services.is_some();
-        let query = self.exp_helper.get_select_str_exp(
-            "webapi.v_service",
-            "name",
-            &services.unwrap_or_default(),
-        );
-        let mut cursor = if !is_services_some {
-            sqlx::query(r#"SELECT "name", "description", "priority" FROM webapi.v_service"#)
-                .fetch(pool)
-        } else {
-            sqlx::query(&query).fetch(pool)
-        };
-        while let Some(service_rec) = cursor.try_next().await? {
-            let service_name: String = service_rec.get(0);
-            let mut commands = Vec::<route::ServiceCommand>::new();
-            let command_recs = sqlx::query!(
-                r#"SELECT object_type, reply_type, description, exec_mode 
-                    FROM webapi.v_service_command WHERE service_name = $1"#,
-                &service_name,
-            )
-            .fetch_all(pool)
-            .await?;
-            for command_rec in command_recs {
-                let path_recs = sqlx::query!(
-                    r#"SELECT proto, "to" 
-                        FROM webapi.v_service_command_path 
-                            WHERE service_name = $1 AND object_type = $2"#,
-                    &service_name,
-                    command_rec.object_type.as_ref().unwrap()
-                )
-                .fetch_all(pool)
-                .await?;
-                let mut p = HashMap::<String, String>::new();
-                for rec in path_recs {
-                    p.insert(rec.proto.unwrap(), rec.to.unwrap());
-                }
-                let state_recs = sqlx::query!(
-                    r#"SELECT "state", "description" 
-                        FROM webapi.v_service_command_state 
-                            WHERE service_name = $1 AND object_type = $2"#,
-                    &service_name,
-                    command_rec.object_type.as_ref().unwrap()
-                )
-                .fetch_all(pool)
-                .await?;
-                let mut sh = HashMap::<String, String>::new();
-                for rec in state_recs {
-                    sh.insert(rec.state.unwrap(), rec.description.unwrap());
-                }
-                let s = if sh.len() > 0 { Some(sh) } else { None };
-                commands.push(route::ServiceCommand {
-                    service_name: None,
-                    priority: None,
-                    object_type: command_rec.object_type.unwrap(),
-                    reply_type: command_rec.reply_type.unwrap(),
-                    description: command_rec.description.unwrap(),
-                    exec_mode: command_rec.exec_mode.unwrap(),
-                    path: Some(p),
-                    state: s,
-                });
-            }
-            let mut events = Vec::<route::ServiceEvent>::new();
-            let event_recs = sqlx::query!(
-                r#"SELECT object_type, description FROM webapi.v_service_event WHERE service_name = $1"#,
-                &service_name
-            )
-            .fetch_all(pool)
-            .await?;
-            for event_rec in event_recs {
-                events.push(route::ServiceEvent {
-                    service_name: None,
-                    object_type: event_rec.object_type.unwrap(),
-                    description: event_rec.description.unwrap(),
-                });
-            }
-            let mut subscriptions = Vec::<route::ServiceSubscription>::new();
-            let subscription_recs = sqlx::query!(
-                r#"SELECT object_type FROM webapi.v_service_subscription WHERE service_name = $1"#,
-                &service_name
-            )
-            .fetch_all(pool)
-            .await?;
-            for subscription_rec in subscription_recs {
-                let recs = sqlx::query!(
-                    r#"SELECT proto, "to" FROM webapi.v_service_subscription_path 
-                        WHERE service_name = $1 AND object_type = $2"#,
-                    &service_name,
-                    subscription_rec.object_type.as_ref().unwrap()
-                )
-                .fetch_all(pool)
-                .await?;
-                let mut p = HashMap::<String, String>::new();
-                for rec in recs {
-                    p.insert(rec.proto.unwrap(), rec.to.unwrap());
-                }
-                subscriptions.push(route::ServiceSubscription {
-                    service_name: None,
-                    object_type: subscription_rec.object_type.unwrap(),
-                    path: Some(p),
-                });
-            }
-            let recs = sqlx::query!(
-                r#"SELECT proto, helth, schema, reply_to, "state", "error" 
-                    FROM webapi.v_service_path WHERE service_name = $1"#,
-                &service_name
-            )
-            .fetch_all(pool)
-            .await?;
-            let mut p = HashMap::<String, route::ServicePath>::new();
-            for rec in recs {
-                let proto = rec.proto.unwrap();
-                p.insert(
-                    proto.clone(),
-                    route::ServicePath {
-                        service_name: None,
-                        proto: Some(proto),
-                        helth: rec.helth.unwrap(),
-                        schema: rec.schema.unwrap(),
-                        reply_to: rec.reply_to.unwrap(),
-                        state: rec.state.unwrap(),
-                        error: rec.error.unwrap(),
-                        request: None,
-                        event: None,
-                    },
-                );
-            }
-            items.push(route::Route {
-                service_name: Some(service_name),
-                description: service_rec.get(1),
-                priority: service_rec.get(2),
-                command: commands,
-                event: events,
-                subscription: subscriptions,
-                path: Some(p),
-            });
-        }
-        Ok(items)
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        let mut conn = pool.acquire().await?;
+        self.get_with(&mut conn, services).await
     }
 
-    pub async fn add(
+    async fn add(
         &self,
         items: Vec<route::Route>,
+        lease: Option<u32>,
     ) -> connectors::Result<(errors::ErrorCode, Option<Vec<String>>)> {
-        let mut ids = Vec::<String>::new();
         #[cfg(feature = "postgres")]
         let pool: &PgPool = &self.data_provider.pool;
         #[cfg(feature = "mysql")]
         let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        //unchanged routes are not rewritten
+        let names: Vec<String> = items
+            .iter()
+            .map(|r| r.service_name.clone().unwrap_or_default())
+            .collect();
         let mut tx = pool.begin().await?;
+        //concurrent registration of same service waits until this one is committed
+        #[cfg(not(feature = "sqlite"))]
+        {
+            let query = format!(
+                "SELECT name FROM {} WHERE name IN ({}) FOR UPDATE",
+                self.exp_helper.get_table_exp("webapi.service"),
+                self.exp_helper.get_params_exp(1, names.len())
+            );
+            let mut select = sqlx::query(&query);
+            for name in &names {
+                select = select.bind(name);
+            }
+            select.execute(&mut tx).await?;
+        }
+        let existing = self.get_with(&mut tx, Some(names.clone())).await?;
+        let items: Vec<route::Route> = items
+            .into_iter()
+            .filter(|r| !existing.iter().any(|e| e.is_same(r)))
+            .collect();
+        let ids: Vec<String> = items
+            .iter()
+            .map(|r| r.service_name.clone().unwrap_or_default())
+            .collect();
+        let replaced: Vec<String> = existing
+            .into_iter()
+            .filter_map(|e| e.service_name)
+            .filter(|name| ids.contains(name))
+            .collect();
+        if replaced.len() > 0 {
+            //changed route set is inserted again
+            for (table, column) in &SERVICE_TABLES {
+                let query = self.exp_helper.get_delete_str_exp(table, column, &replaced);
+                if let Err(e) = sqlx::query(&query).execute(&mut tx).await {
+                    tx.rollback().await.unwrap();
+                    error!("add_routes db {} delete: {}", table, e);
+                    return Ok((errors::ErrorCode::DatabaseError, None));
+                }
+            }
+        }
         for route in items {
             let service_name = route.service_name.unwrap();
             #[cfg(feature = "postgres")]
@@ -437,7 +749,7 @@ impl RouteCollection {
             .execute(&mut tx)
             .await
             {
-                Ok(_) => ids.push(service_name.clone()),
+                Ok(_) => {}
                 Err(e) => {
                     tx.rollback().await.unwrap();
                     error!("add_routes db service insert: {}", e);
@@ -452,7 +764,24 @@ impl RouteCollection {
                 .execute(&mut tx)
                 .await
             {
-                Ok(_) => ids.push(service_name.clone()),
+                Ok(_) => {}
+                Err(e) => {
+                    tx.rollback().await.unwrap();
+                    error!("add_routes db service insert: {}", e);
+                    return Ok((errors::ErrorCode::DatabaseError, None));
+                }
+            };
+            #[cfg(feature = "sqlite")]
+            match sqlx::query(
+                r#"INSERT INTO service ( "name", description, priority ) VALUES ( ?, ?, ? )"#,
+            )
+            .bind(&service_name)
+            .bind(&route.description)
+            .bind(route.priority)
+            .execute(&mut tx)
+            .await
+            {
+                Ok(_) => {}
                 Err(e) => {
                     tx.rollback().await.unwrap();
                     error!("add_routes db service insert: {}", e);
@@ -476,7 +805,7 @@ impl RouteCollection {
                     .execute(&mut tx)
                     .await
                     {
-                        Ok(_) => ids.push(service_name.clone()),
+                        Ok(_) => {}
                         Err(e) => {
                             tx.rollback().await.unwrap();
                             error!("add_routes db service_path insert: {}", e);
@@ -499,7 +828,30 @@ impl RouteCollection {
                 .execute(&mut tx)
                 .await
                 {
-                    Ok(_) => ids.push(service_name.clone()),
+                    Ok(_) => {}
+                    Err(e) => {
+                        tx.rollback().await.unwrap();
+                        error!("add_routes db service_path insert: {}", e);
+                        return Ok((errors::ErrorCode::DatabaseError, None));
+                    }
+                };
+                #[cfg(feature = "sqlite")]
+                match sqlx::query(
+                    r#"INSERT INTO service_path 
+                        ( "service_name", proto, helth, "schema", "reply_to", "state", "error" ) 
+                        VALUES ( ?, ?, ?, ?, ?, ?, ? )"#,
+                )
+                .bind(&service_name)
+                .bind(path.0)
+                .bind(&(path.1).helth)
+                .bind(&(path.1).schema)
+                .bind(&(path.1).reply_to)
+                .bind(&(path.1).state)
+                .bind(&(path.1).error)
+                .execute(&mut tx)
+                .await
+                {
+                    Ok(_) => {}
                     Err(e) => {
                         tx.rollback().await.unwrap();
                         error!("add_routes db service_path insert: {}", e);
@@ -539,7 +891,25 @@ impl RouteCollection {
                     .execute(&mut tx)
                     .await
                 {
-                    Ok(_) => ids.push(service_name.clone()),
+                    Ok(_) => {}
+                    Err(e) => {
+                        tx.rollback().await.unwrap();
+                        error!("add_routes db command insert: {}", e);
+                        return Ok((errors::ErrorCode::DatabaseError, None));
+                    }
+                };
+                #[cfg(feature = "sqlite")]
+                match sqlx::query(r#"INSERT INTO service_command ( service_name, object_type, reply_type, description, exec_mode ) 
+                    VALUES ( ?, ?, ?, ?, ? )"#)
+                    .bind(&service_name)
+                    .bind(&command.object_type)
+                    .bind(&command.reply_type)
+                    .bind(&command.description)
+                    .bind(&command.exec_mode)
+                    .execute(&mut tx)
+                    .await
+                {
+                    Ok(_) => {},
                     Err(e) => {
                         tx.rollback().await.unwrap();
                         error!("add_routes db command insert: {}", e);
@@ -559,7 +929,7 @@ impl RouteCollection {
                     .execute(&mut tx)
                     .await
                     {
-                        Ok(_) => ids.push(service_name.clone()),
+                        Ok(_) => {}
                         Err(e) => {
                             tx.rollback().await.unwrap();
                             error!("add_routes db command_path insert: {}", e);
@@ -576,7 +946,24 @@ impl RouteCollection {
                         .execute(&mut tx)
                         .await
                     {
-                        Ok(_) => ids.push(service_name.clone()),
+                        Ok(_) => {}
+                        Err(e) => {
+                            tx.rollback().await.unwrap();
+                            error!("add_routes db command_path insert: {}", e);
+                            return Ok((errors::ErrorCode::DatabaseError, None));
+                        }
+                    };
+                    #[cfg(feature = "sqlite")]
+                    match sqlx::query(r#"INSERT INTO service_command_path ( "service_name", object_type, proto, "to" ) 
+                        VALUES ( ?, ?, ?, ? )"#)
+                        .bind(&service_name)
+                        .bind(&command.object_type)
+                        .bind(&path.0)
+                        .bind(&path.1)
+                        .execute(&mut tx)
+                        .await
+                    {
+                        Ok(_) => {}
                         Err(e) => {
                             tx.rollback().await.unwrap();
                             error!("add_routes db command_path insert: {}", e);
@@ -598,7 +985,7 @@ impl RouteCollection {
                     .execute(&mut tx)
                     .await
                     {
-                        Ok(_) => ids.push(service_name.clone()),
+                        Ok(_) => {}
                         Err(e) => {
                             tx.rollback().await.unwrap();
                             error!("add_routes db command_state insert: {}", e);
@@ -615,7 +1002,24 @@ impl RouteCollection {
                         .execute(&mut tx)
                         .await
                     {
-                        Ok(_) => ids.push(service_name.clone()),
+                        Ok(_) => {}
+                        Err(e) => {
+                            tx.rollback().await.unwrap();
+                            error!("add_routes db command_state insert: {}", e);
+                            return Ok((errors::ErrorCode::DatabaseError, None));
+                        }
+                    };
+                        #[cfg(feature = "sqlite")]
+                    match sqlx::query(r#"INSERT INTO service_command_state ( "service_name", object_type, "state", "description" ) 
+                        VALUES ( ?, ?, ?, ? )"#)
+                        .bind(&service_name)
+                        .bind(&command.object_type)
+                        .bind(&state.0)
+                        .bind(&state.1)
+                        .execute(&mut tx)
+                        .await
+                    {
+                        Ok(_) => {}
                         Err(e) => {
                             tx.rollback().await.unwrap();
                             error!("add_routes db command_state insert: {}", e);
@@ -655,7 +1059,25 @@ impl RouteCollection {
                 .execute(&mut tx)
                 .await
                 {
-                    Ok(_) => ids.push(route.service_name),
+                    Ok(_) => {}
+                    Err(e) => {
+                        tx.rollback().await.unwrap();
+                        error!("add_routes db event insert: {}", e);
+                        return Ok((errors::ErrorCode::DatabaseError, None));
+                    }
+                };
+                #[cfg(feature = "sqlite")]
+                match sqlx::query(
+                    r#"INSERT INTO service_event ( service_name, object_type, description ) 
+                    VALUES ( ?, ?, ? )"#,
+                )
+                .bind(&service_name)
+                .bind(&event.object_type)
+                .bind(&event.description)
+                .execute(&mut tx)
+                .await
+                {
+                    Ok(_) => {}
                     Err(e) => {
                         tx.rollback().await.unwrap();
                         error!("add_routes db event insert: {}", e);
@@ -687,7 +1109,21 @@ impl RouteCollection {
                     .execute(&mut tx)
                     .await
                 {
-                    Ok(_) => ids.push(route.service_name),
+                    Ok(_) => {}
+                    Err(e) => {
+                        tx.rollback().await.unwrap();
+                        error!("add_routes db subscription insert: {}", e);
+                        return Ok((errors::ErrorCode::DatabaseError, None));
+                    }
+                };
+                #[cfg(feature = "sqlite")]
+                match sqlx::query(r#"INSERT INTO service_subscription ( service_name, object_type ) VALUES ( ?, ? )"#)
+                    .bind(&service_name)
+                    .bind(&subscription.object_type)
+                    .execute(&mut tx)
+                    .await
+                {
+                    Ok(_) => {},
                     Err(e) => {
                         tx.rollback().await.unwrap();
                         error!("add_routes db subscription insert: {}", e);
@@ -707,7 +1143,7 @@ impl RouteCollection {
                     .execute(&mut tx)
                     .await
                     {
-                        Ok(_) => ids.push(service_name.clone()),
+                        Ok(_) => {}
                         Err(e) => {
                             tx.rollback().await.unwrap();
                             error!("add_routes db subscription_path insert: {}", e);
@@ -724,7 +1160,24 @@ impl RouteCollection {
                         .execute(&mut tx)
                         .await
                     {
-                        Ok(_) => ids.push(service_name.clone()),
+                        Ok(_) => {}
+                        Err(e) => {
+                            tx.rollback().await.unwrap();
+                            error!("add_routes db subscription_path insert: {}", e);
+                            return Ok((errors::ErrorCode::DatabaseError, None));
+                        }
+                    };
+                    #[cfg(feature = "sqlite")]
+                    match sqlx::query(r#"INSERT INTO service_subscription_path ( "service_name", object_type, proto, "to" ) 
+                        VALUES ( ?, ?, ?, ? )"#)
+                        .bind(&service_name)
+                        .bind(&subscription.object_type)
+                        .bind(&path.0)
+                        .bind(&path.1)
+                        .execute(&mut tx)
+                        .await
+                    {
+                        Ok(_) => {}
                         Err(e) => {
                             tx.rollback().await.unwrap();
                             error!("add_routes db subscription_path insert: {}", e);
@@ -734,6 +1187,29 @@ impl RouteCollection {
                 }
             }
         }
+        //lease of unchanged routes is renewed too
+        #[cfg(feature = "postgres")]
+        let query = format!(
+            r#"UPDATE webapi.service SET lease = $1, lease_expires_at = $2 WHERE "name" IN ({})"#,
+            self.exp_helper.get_params_exp(3, names.len())
+        );
+        #[cfg(not(feature = "postgres"))]
+        let query = format!(
+            r#"UPDATE {} SET lease = ?, lease_expires_at = ? WHERE name IN ({})"#,
+            self.exp_helper.get_table_exp("webapi.service"),
+            self.exp_helper.get_params_exp(3, names.len())
+        );
+        let mut update = sqlx::query(&query)
+            .bind(lease.map(|l| l as i32))
+            .bind(lease.map(|l| Utc::now() + Duration::seconds(l as i64)));
+        for name in &names {
+            update = update.bind(name);
+        }
+        if let Err(e) = update.execute(&mut tx).await {
+            tx.rollback().await.unwrap();
+            error!("add_routes db lease update: {}", e);
+            return Ok((errors::ErrorCode::DatabaseError, None));
+        }
         match tx.commit().await {
             Ok(_) => {}
             Err(e) => {
@@ -744,11 +1220,13 @@ impl RouteCollection {
         Ok((errors::ErrorCode::ReplyOk, Some(ids)))
     }
 
-    pub async fn remove(&self, services: Vec<String>) -> connectors::Result<errors::ErrorCode> {
+    async fn remove(&self, services: Vec<String>) -> connectors::Result<errors::ErrorCode> {
         #[cfg(feature = "postgres")]
         let pool: &PgPool = &self.data_provider.pool;
         #[cfg(feature = "mysql")]
         let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
         let mut tx = pool.begin().await?;
         match sqlx::query(&self.exp_helper.get_delete_str_exp(
             "webapi.service_subscription_path",
@@ -920,4 +1398,133 @@ impl RouteCollection {
             }
         }
     }
+
+    async fn renew(&self, mut services: Vec<String>) -> connectors::Result<errors::ErrorCode> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        services.sort();
+        services.dedup();
+        let query = format!(
+            r#"SELECT name, lease FROM {} WHERE lease IS NOT NULL AND name IN ({})"#,
+            self.exp_helper.get_table_exp("webapi.service"),
+            self.exp_helper.get_str_ids_exp(&services)
+        );
+        let mut tx = pool.begin().await?;
+        let leases: Vec<(String, i32)> = sqlx::query_as(&query).fetch_all(&mut tx).await?;
+        if leases.len() != services.len() {
+            tx.rollback().await?;
+            return Ok(errors::ErrorCode::NotFoundError);
+        }
+        for (name, lease) in leases {
+            if let Err(e) = sqlx::query(UPDATE_LEASE_EXPIRES_AT)
+                .bind(Utc::now() + Duration::seconds(lease as i64))
+                .bind(&name)
+                .execute(&mut tx)
+                .await
+            {
+                tx.rollback().await?;
+                error!("renew_routes db lease update: {}", e);
+                return Ok(errors::ErrorCode::DatabaseError);
+            }
+        }
+        match tx.commit().await {
+            Ok(_) => Ok(errors::ErrorCode::ReplyOk),
+            Err(e) => {
+                error!("renew_routes db commit: {}", e);
+                Ok(errors::ErrorCode::DatabaseError)
+            }
+        }
+    }
+
+    async fn expire(&self, at: DateTime<Utc>) -> connectors::Result<Vec<String>> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        let mut tx = pool.begin().await?;
+        let recs: Vec<(String,)> = sqlx::query_as(SELECT_EXPIRED)
+            .bind(at)
+            .fetch_all(&mut tx)
+            .await?;
+        let services: Vec<String> = recs.into_iter().map(|rec| rec.0).collect();
+        if services.len() == 0 {
+            tx.rollback().await?;
+            return Ok(services);
+        }
+        for (table, column) in &SERVICE_TABLES[..SERVICE_TABLES.len() - 1] {
+            let query = self.exp_helper.get_delete_str_exp(table, column, &services);
+            if let Err(e) = sqlx::query(&query).execute(&mut tx).await {
+                tx.rollback().await?;
+                error!("expire_routes db {} delete: {}", table, e);
+                return Err(e.into());
+            }
+        }
+        //service renewed after select is kept, check is repeated next time
+        #[cfg(feature = "postgres")]
+        let query = format!(
+            "{} AND lease_expires_at < $1",
+            self.exp_helper
+                .get_delete_str_exp("webapi.service", "name", &services)
+        );
+        #[cfg(not(feature = "postgres"))]
+        let query = format!(
+            "{} AND lease_expires_at < ?",
+            self.exp_helper
+                .get_delete_str_exp("webapi.service", "name", &services)
+        );
+        let ret = sqlx::query(&query).bind(at).execute(&mut tx).await?;
+        if usize::try_from(ret.rows_affected()).unwrap() != services.len() {
+            tx.rollback().await?;
+            warn!("expire_routes: lease of {:?} is renewed", services);
+            return Ok(vec![]);
+        }
+        for service_name in &services {
+            if let Err(e) = sqlx::query(INSERT_HISTORY)
+                .bind(service_name)
+                .bind(route::ServiceState::Unavailable.to_string())
+                .bind("lease expired")
+                .bind(at)
+                .execute(&mut tx)
+                .await
+            {
+                tx.rollback().await?;
+                error!("expire_routes db history insert: {}", e);
+                return Err(e.into());
+            }
+        }
+        tx.commit().await?;
+        Ok(services)
+    }
+
+    async fn get_history(
+        &self,
+        services: Option<Vec<String>>,
+    ) -> connectors::Result<Vec<route::ServiceHistory>> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        let query = match services {
+            Some(services) => self.exp_helper.get_select_str_exp(
+                "webapi.service_history",
+                "service_name",
+                &services,
+            ),
+            None => format!(
+                "SELECT * FROM {}",
+                self.exp_helper.get_table_exp("webapi.service_history")
+            ),
+        };
+        let mut items: Vec<route::ServiceHistory> = sqlx::query_as(&query).fetch_all(pool).await?;
+        items.sort_by(|a, b| a.added_at.cmp(&b.added_at));
+        Ok(items)
+    }
 }
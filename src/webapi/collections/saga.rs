@@ -0,0 +1,212 @@
+use super::super::{connectors, entities::saga, errors, providers, traits};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+#[cfg(feature = "postgres")]
+use sqlx::postgres::PgPool;
+use sqlx::Done;
+#[cfg(feature = "mysql")]
+use sqlx::MySqlPool;
+#[cfg(feature = "sqlite")]
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+#[cfg(feature = "postgres")]
+const INSERT_SAGA: &str = r#"INSERT INTO webapi.saga
+    ( id, saga_type, correlation_id, state, step, data, owner, lease_expires_at )
+        VALUES ( $1, $2, $3, $4, $5, $6, $7, $8 )"#;
+#[cfg(any(feature = "mysql", feature = "sqlite"))]
+const INSERT_SAGA: &str = r#"INSERT INTO saga
+    ( id, saga_type, correlation_id, state, step, data, owner, lease_expires_at )
+        VALUES ( ?, ?, ?, ?, ?, ?, ?, ? )"#;
+
+#[cfg(feature = "postgres")]
+const UPDATE_SAGA_STATE: &str = r#"UPDATE webapi.saga
+    SET state = $1, step = $2, state_changed_at = CURRENT_TIMESTAMP WHERE id = $3"#;
+#[cfg(any(feature = "mysql", feature = "sqlite"))]
+const UPDATE_SAGA_STATE: &str = r#"UPDATE saga
+    SET state = ?, step = ?, state_changed_at = CURRENT_TIMESTAMP WHERE id = ?"#;
+
+//unfinished states are SagaState::Running and SagaState::Compensating
+#[cfg(feature = "postgres")]
+const CLAIM_SAGA: &str = r#"UPDATE webapi.saga SET owner = $1, lease_expires_at = $2
+    WHERE id = $3 AND state IN ( 'Running', 'Compensating' ) AND lease_expires_at < $4"#;
+#[cfg(any(feature = "mysql", feature = "sqlite"))]
+const CLAIM_SAGA: &str = r#"UPDATE saga SET owner = ?, lease_expires_at = ?
+    WHERE id = ? AND state IN ( 'Running', 'Compensating' ) AND lease_expires_at < ?"#;
+
+#[cfg(feature = "postgres")]
+const RENEW_SAGA: &str = r#"UPDATE webapi.saga SET lease_expires_at = $1
+    WHERE owner = $2 AND state IN ( 'Running', 'Compensating' )"#;
+#[cfg(any(feature = "mysql", feature = "sqlite"))]
+const RENEW_SAGA: &str = r#"UPDATE saga SET lease_expires_at = ?
+    WHERE owner = ? AND state IN ( 'Running', 'Compensating' )"#;
+
+pub struct SagaCollection {
+    data_provider: Arc<providers::SqlDbProvider>,
+    exp_helper: &'static connectors::ExpHelper,
+}
+
+impl SagaCollection {
+    pub fn new(
+        data_provider: Arc<providers::SqlDbProvider>,
+        helper: &'static connectors::ExpHelper,
+    ) -> SagaCollection {
+        SagaCollection {
+            data_provider: data_provider,
+            exp_helper: &helper,
+        }
+    }
+}
+
+#[async_trait]
+impl traits::SagaStore for SagaCollection {
+    async fn get(&self, ids: Option<Vec<String>>) -> connectors::Result<Vec<saga::Saga>> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        let query = match ids {
+            Some(ids) => self
+                .exp_helper
+                .get_select_str_exp("webapi.saga", "id", &ids),
+            None => format!(
+                "SELECT * FROM {}",
+                self.exp_helper.get_table_exp("webapi.saga")
+            ),
+        };
+        let items: Vec<saga::Saga> = sqlx::query_as(&query).fetch_all(pool).await?;
+        Ok(items)
+    }
+
+    async fn get_expired(
+        &self,
+        states: Vec<saga::SagaState>,
+        at: DateTime<Utc>,
+    ) -> connectors::Result<Vec<saga::Saga>> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        let states: Vec<String> = states.iter().map(|s| s.to_string()).collect();
+        //saga_state_idx
+        #[cfg(feature = "postgres")]
+        let query = format!(
+            "{} AND lease_expires_at < $1 ORDER BY added_at",
+            self.exp_helper
+                .get_select_str_exp("webapi.saga", "state", &states)
+        );
+        #[cfg(not(feature = "postgres"))]
+        let query = format!(
+            "{} AND lease_expires_at < ? ORDER BY added_at",
+            self.exp_helper
+                .get_select_str_exp("webapi.saga", "state", &states)
+        );
+        let items: Vec<saga::Saga> = sqlx::query_as(&query).bind(at).fetch_all(pool).await?;
+        Ok(items)
+    }
+
+    async fn add(&self, item: saga::Saga) -> connectors::Result<errors::ErrorCode> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        match sqlx::query(INSERT_SAGA)
+            .bind(&item.id)
+            .bind(&item.saga_type)
+            .bind(&item.correlation_id)
+            .bind(&item.state)
+            .bind(item.step)
+            .bind(&item.data)
+            .bind(&item.owner)
+            .bind(item.lease_expires_at)
+            .execute(pool)
+            .await
+        {
+            Ok(_) => Ok(errors::ErrorCode::ReplyOk),
+            Err(e) => {
+                error!("add_saga db insert: {}", e);
+                Ok(errors::ErrorCode::DatabaseError)
+            }
+        }
+    }
+
+    async fn change_state(
+        &self,
+        id: &str,
+        state: saga::SagaState,
+        step: i32,
+    ) -> connectors::Result<errors::ErrorCode> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        match sqlx::query(UPDATE_SAGA_STATE)
+            .bind(state.to_string())
+            .bind(step)
+            .bind(id)
+            .execute(pool)
+            .await
+        {
+            Ok(ret) if ret.rows_affected() == 1 => Ok(errors::ErrorCode::ReplyOk),
+            Ok(_) => Ok(errors::ErrorCode::NotFoundError),
+            Err(e) => {
+                error!("change_saga_state db update: {}", e);
+                Ok(errors::ErrorCode::DatabaseError)
+            }
+        }
+    }
+
+    async fn claim(
+        &self,
+        id: &str,
+        owner: &str,
+        at: DateTime<Utc>,
+        lease_expires_at: DateTime<Utc>,
+    ) -> connectors::Result<errors::ErrorCode> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        //one of instances recovering at once updates the row
+        match sqlx::query(CLAIM_SAGA)
+            .bind(owner)
+            .bind(lease_expires_at)
+            .bind(id)
+            .bind(at)
+            .execute(pool)
+            .await
+        {
+            Ok(ret) if ret.rows_affected() == 1 => Ok(errors::ErrorCode::ReplyOk),
+            Ok(_) => Ok(errors::ErrorCode::NotFoundError),
+            Err(e) => {
+                error!("claim_saga db update: {}", e);
+                Ok(errors::ErrorCode::DatabaseError)
+            }
+        }
+    }
+
+    async fn renew(&self, owner: &str, lease_expires_at: DateTime<Utc>) -> connectors::Result<u64> {
+        #[cfg(feature = "postgres")]
+        let pool: &PgPool = &self.data_provider.pool;
+        #[cfg(feature = "mysql")]
+        let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
+        let ret = sqlx::query(RENEW_SAGA)
+            .bind(lease_expires_at)
+            .bind(owner)
+            .execute(pool)
+            .await?;
+        Ok(ret.rows_affected())
+    }
+}
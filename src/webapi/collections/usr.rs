@@ -1,8 +1,11 @@
-use super::super::{connectors, entities::usr, providers};
+use super::super::{connectors, entities::usr, providers, traits};
+use async_trait::async_trait;
 #[cfg(feature = "postgres")]
 use sqlx::postgres::PgPool;
 #[cfg(feature = "mysql")]
 use sqlx::MySqlPool;
+#[cfg(feature = "sqlite")]
+use sqlx::SqlitePool;
 use std::sync::Arc;
 
 pub struct UsrCollection {
@@ -20,19 +23,31 @@ impl UsrCollection {
             exp_helper: &helper,
         }
     }
+}
 
-    pub async fn get(&self, ids: Option<Vec<i32>>) -> connectors::Result<Vec<usr::Usr>> {
+#[async_trait]
+impl traits::UsrStore for UsrCollection {
+    async fn get(&self, ids: Option<Vec<i32>>) -> connectors::Result<Vec<usr::Usr>> {
         #[cfg(feature = "postgres")]
         let pool: &PgPool = &self.data_provider.pool;
         #[cfg(feature = "mysql")]
         let pool: &MySqlPool = &self.data_provider.pool;
+        #[cfg(feature = "sqlite")]
+        let pool: &SqlitePool = &self.data_provider.pool;
         if ids.is_none() {
-            Ok(sqlx::query_as!(
+            #[cfg(not(feature = "sqlite"))]
+            return Ok(sqlx::query_as!(
                 usr::Usr,
                 r#"SELECT id,usr_name,usr_password FROM webapi.usr"#
             )
             .fetch_all(pool)
-            .await?)
+            .await?);
+            #[cfg(feature = "sqlite")]
+            return Ok(
+                sqlx::query_as(r#"SELECT id,usr_name,usr_password FROM usr"#)
+                    .fetch_all(pool)
+                    .await?,
+            );
         } else {
             let query = self
                 .exp_helper
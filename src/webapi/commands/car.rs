@@ -1,66 +1,85 @@
-use super::super::{entities::car, traits};
+use super::super::{entities::car, replies, routes::path};
+use my_rust_hyper_derive::ObjectType;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 // use std::collections::HashMap;
 
-#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Deserialize, Serialize, Clone, JsonSchema, ObjectType)]
+#[command(
+    service = "webapi",
+    reply = replies::car::GetCarReply,
+    exec_mode = "Sync",
+    path = path::CAR_GET,
+    description = "cars"
+)]
 pub struct GetCar {
     pub filter: Option<String>,
     pub ids: Option<Vec<i32>>,
 }
 
-impl traits::ObjectType for GetCar {
-    fn get_type_name() -> &'static str {
-        "GetCar"
-    }
-}
-
 // impl traits::CommandState for GetCar {
 //     fn get_states() -> &'static HashMap<&'static str, &'static str> {
 //         [("one", "one"), ("two", "two")]
 //     }
 // }
 
-#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Deserialize, Serialize, Clone, JsonSchema, ObjectType)]
+#[command(
+    service = "webapi",
+    reply = replies::common::AddIntIdsReply,
+    exec_mode = "Sync",
+    path = path::CAR_ADD,
+    description = "add cars"
+)]
 pub struct AddCar {
     pub items: Vec<car::Car>,
 }
 
-impl traits::ObjectType for AddCar {
-    fn get_type_name() -> &'static str {
-        "AddCar"
-    }
-}
-
-#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Deserialize, Serialize, Clone, JsonSchema, ObjectType)]
+#[command(
+    service = "webapi",
+    reply = replies::common::StandardReply,
+    exec_mode = "Sync",
+    path = path::CAR_CHANGE,
+    description = "change cars"
+)]
 pub struct ChangeCar {
     pub items: Vec<car::Car>,
 }
 
-impl traits::ObjectType for ChangeCar {
-    fn get_type_name() -> &'static str {
-        "ChangeCar"
-    }
-}
-
-#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Deserialize, Serialize, Clone, JsonSchema, ObjectType)]
+#[command(
+    service = "webapi",
+    reply = replies::common::StandardReply,
+    exec_mode = "Sync",
+    path = path::CAR_REMOVE,
+    description = "remove cars"
+)]
 pub struct RemoveCar {
     pub ids: Vec<i32>,
 }
 
-impl traits::ObjectType for RemoveCar {
-    fn get_type_name() -> &'static str {
-        "RemoveCar"
-    }
-}
-
-#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Deserialize, Serialize, Clone, JsonSchema, ObjectType)]
+#[command(
+    service = "webapi",
+    reply = replies::common::StandardReply,
+    exec_mode = "Sync",
+    path = path::CAR_RESERVE,
+    description = "reserve cars"
+)]
 pub struct ReserveCar {
     pub services: Vec<i32>,
 }
 
-impl traits::ObjectType for ReserveCar {
-    fn get_type_name() -> &'static str {
-        "ReserveCar"
-    }
+//compensates ReserveCar
+#[derive(Deserialize, Serialize, Clone, JsonSchema, ObjectType)]
+#[command(
+    service = "webapi",
+    reply = replies::common::StandardReply,
+    exec_mode = "Sync",
+    path = path::CAR_RELEASE,
+    description = "release reserved cars"
+)]
+pub struct ReleaseCar {
+    pub services: Vec<i32>,
 }
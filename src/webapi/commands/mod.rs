@@ -1,2 +1,18 @@
+use super::traits::ObjectType;
+
+pub mod car;
 pub mod route;
-pub mod car;
\ No newline at end of file
+
+//commands changing stored routes or items
+pub fn is_mutating(object_type: &str) -> bool {
+    [
+        route::AddRoute::get_type_name(),
+        route::RemoveRoute::get_type_name(),
+        car::AddCar::get_type_name(),
+        car::ChangeCar::get_type_name(),
+        car::RemoveCar::get_type_name(),
+        car::ReserveCar::get_type_name(),
+        car::ReleaseCar::get_type_name(),
+    ]
+    .contains(&object_type)
+}
@@ -1,85 +1,133 @@
-use super::super::{entities::route, traits};
+use super::super::{entities::route, replies, routes::path};
+use my_rust_hyper_derive::ObjectType;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Deserialize, Serialize, Clone, JsonSchema, ObjectType)]
+#[command(
+    service = "router",
+    reply = replies::route::GetRouteReply,
+    exec_mode = "Sync",
+    path = path::ROUTER_ROUTE_GET,
+    description = "service routes"
+)]
 pub struct GetRoute {
     pub filter: Option<String>,
     pub services: Option<Vec<String>>,
 }
 
-impl traits::ObjectType for GetRoute {
-    fn get_type_name() -> &'static str {
-        "GetRoute"
-    }
-}
-
-#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Deserialize, Serialize, Clone, JsonSchema, ObjectType)]
+#[command(
+    service = "router",
+    reply = replies::common::AddStrIdsReply,
+    exec_mode = "Sync",
+    path = path::ROUTER_ROUTE_ADD,
+    description = "add service routes"
+)]
 pub struct AddRoute {
     pub items: Vec<route::Route>,
+    #[serde(default)]
+    pub lease: Option<u32>, //seconds, routes never expire if not set
 }
 
-impl traits::ObjectType for AddRoute {
-    fn get_type_name() -> &'static str {
-        "AddRoute"
-    }
+//heartbeat of services registered with lease
+#[derive(Deserialize, Serialize, Clone, JsonSchema, ObjectType)]
+#[command(
+    service = "router",
+    reply = replies::common::StandardReply,
+    exec_mode = "Sync",
+    path = path::ROUTER_ROUTE_RENEW,
+    description = "renew service route lease"
+)]
+pub struct RenewRoute {
+    pub services: Vec<String>,
 }
 
-#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Deserialize, Serialize, Clone, JsonSchema, ObjectType)]
+#[command(
+    service = "router",
+    reply = replies::common::StandardReply,
+    exec_mode = "Sync",
+    path = path::ROUTER_ROUTE_REMOVE,
+    description = "remove service routes"
+)]
 pub struct RemoveRoute {
     pub services: Vec<String>,
 }
 
-impl traits::ObjectType for RemoveRoute {
-    fn get_type_name() -> &'static str {
-        "RemoveRoute"
-    }
-}
-
-#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Deserialize, Serialize, Clone, JsonSchema, ObjectType)]
+#[command(
+    service = "router",
+    reply = replies::route::GetServiceCommandReply,
+    exec_mode = "Sync",
+    path = path::ROUTER_COMMAND_GET,
+    description = "service commands"
+)]
 pub struct GetServiceCommand {
     pub filter: Option<String>,
     pub services: Option<Vec<String>>,
 }
 
-impl traits::ObjectType for GetServiceCommand {
-    fn get_type_name() -> &'static str {
-        "GetServiceCommand"
-    }
-}
-
-#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Deserialize, Serialize, Clone, JsonSchema, ObjectType)]
+#[command(
+    service = "router",
+    reply = replies::route::GetServiceEventReply,
+    exec_mode = "Sync",
+    path = path::ROUTER_EVENT_GET,
+    description = "service events"
+)]
 pub struct GetServiceEvent {
     pub filter: Option<String>,
     pub services: Option<Vec<String>>,
 }
 
-impl traits::ObjectType for GetServiceEvent {
-    fn get_type_name() -> &'static str {
-        "GetServiceEvent"
-    }
-}
-
-#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Deserialize, Serialize, Clone, JsonSchema, ObjectType)]
+#[command(
+    service = "router",
+    reply = replies::route::GetServiceSubscriptionReply,
+    exec_mode = "Sync",
+    path = path::ROUTER_SUBSCIBTION_GET,
+    description = "service subscriptions"
+)]
 pub struct GetServiceSubscription {
     pub filter: Option<String>,
     pub services: Option<Vec<String>>,
 }
 
-impl traits::ObjectType for GetServiceSubscription {
-    fn get_type_name() -> &'static str {
-        "GetServiceSubscription"
-    }
-}
-
-#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Deserialize, Serialize, Clone, JsonSchema, ObjectType)]
+#[command(
+    reply = replies::route::GetServiceReply,
+    path = path::ROUTER_SERVICE_GET,
+    description = "services"
+)]
 pub struct GetService {
     pub filter: Option<String>,
     pub names: Option<Vec<String>>,
 }
 
-impl traits::ObjectType for GetService {
-    fn get_type_name() -> &'static str {
-        "GetService"
-    }
+#[derive(Deserialize, Serialize, Clone, JsonSchema, ObjectType)]
+#[command(
+    service = "router",
+    reply = replies::route::GetServiceErrorReply,
+    exec_mode = "Sync",
+    path = path::ROUTER_ERROR_GET,
+    description = "error names of services"
+)]
+pub struct GetServiceError {
+    pub filter: Option<String>,
+    pub services: Option<Vec<String>>,
+    pub lang: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, JsonSchema, ObjectType)]
+#[command(
+    service = "router",
+    reply = replies::route::GetServiceSchemaReply,
+    exec_mode = "Sync",
+    path = path::ROUTER_SCHEMA_GET,
+    description = "cached schemas of service"
+)]
+pub struct GetServiceSchema {
+    pub service_name: String,
+    pub object_type: Option<String>, //schema list if not set
 }
@@ -0,0 +1,446 @@
+use super::settings;
+use serde_json::{Map, Value};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::Path;
+
+const DEFAULT_APP_SETTINGS: &str = "appsettings.json";
+const ENV_APP_SETTINGS: &str = "MY_APP_SETTINGS";
+//environment specific file, appsettings.{env}.json
+const ENV_APP_ENV: &str = "MY_APP_ENV";
+//MY_APP__database__pg__connection_string=...
+const ENV_OVERRIDE_PREFIX: &str = "MY_APP__";
+const ENV_OVERRIDE_SEPARATOR: &str = "__";
+const JSON_VALUE: &str = "json:";
+
+const DEFAULT_HOST: &str = "127.0.0.1";
+const ENV_HOST: &str = "MY_BIN_HOST";
+const DEFAULT_PORT: u16 = 3456;
+const ENV_PORT: &str = "PORT";
+
+//"${env:NAME}" or "${file:/run/secrets/name}" instead of plaintext value
+const SECRET_ENV: &str = "${env:";
+const SECRET_FILE: &str = "${file:";
+
+#[cfg(feature = "postgres")]
+pub const DATABASE_KEY: &str = "pg";
+#[cfg(feature = "mysql")]
+pub const DATABASE_KEY: &str = "mysql";
+#[cfg(feature = "sqlite")]
+pub const DATABASE_KEY: &str = "sqlite";
+#[cfg(not(any(feature = "postgres", feature = "mysql", feature = "sqlite")))]
+pub const DATABASE_KEY: &str = "memory";
+
+//bad key and what is wrong with it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    pub key: String,
+    pub message: String,
+}
+
+impl ConfigError {
+    fn new(key: &str, message: &str) -> ConfigError {
+        ConfigError {
+            key: key.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.key, self.message)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Vec<ConfigError>>;
+
+//layers from lowest priority: file, environment file, env overrides, cli overrides
+#[derive(Debug, Default)]
+pub struct Options {
+    pub file: String,
+    pub env: Option<String>,
+    pub overrides: Vec<(String, String)>,
+    pub host: Option<String>,
+    pub port: Option<String>,
+    pub check_config: bool,
+}
+
+pub struct Config {
+    pub app_settings: settings::AppSettings,
+    pub database: settings::Database,
+    pub host: String, //host:port
+    pub addr: SocketAddr,
+    //logging is not initialized while loading, caller reports them
+    pub warnings: Vec<ConfigError>,
+}
+
+pub const USAGE: &str = "usage: my-rust-hyper [--config FILE] [--env NAME] \
+                         [--set KEY=VALUE]... [--check-config]";
+
+impl Options {
+    //cli flags win over env vars
+    pub fn from_env_and_args(args: &[String]) -> Result<Options> {
+        let mut options = Options {
+            file: env::var(ENV_APP_SETTINGS).unwrap_or(DEFAULT_APP_SETTINGS.to_string()),
+            env: env::var(ENV_APP_ENV).ok(),
+            overrides: get_env_overrides(env::vars()),
+            host: env::var(ENV_HOST).ok(),
+            port: env::var(ENV_PORT).ok(),
+            check_config: false,
+        };
+        let mut i = 0;
+        while i < args.len() {
+            let flag = args[i].as_str();
+            let mut next = || {
+                i += 1;
+                args.get(i)
+                    .cloned()
+                    .ok_or_else(|| vec![ConfigError::new(flag, "value is missing")])
+            };
+            match flag {
+                "--config" => options.file = next()?,
+                "--env" => options.env = Some(next()?),
+                "--set" => {
+                    let kv = next()?;
+                    match kv.find('=') {
+                        Some(n) => options
+                            .overrides
+                            .push((kv[..n].to_string(), kv[n + 1..].to_string())),
+                        None => {
+                            return Err(vec![ConfigError::new(&kv, "expected KEY=VALUE")]);
+                        }
+                    }
+                }
+                "--check-config" => options.check_config = true,
+                _ => return Err(vec![ConfigError::new(flag, "unknown flag")]),
+            }
+            i += 1;
+        }
+        Ok(options)
+    }
+}
+
+//dotted keys, sorted for stable priority
+pub fn get_env_overrides<I>(vars: I) -> Vec<(String, String)>
+where
+    I: Iterator<Item = (String, String)>,
+{
+    let mut overrides: Vec<(String, String)> = vars
+        .filter(|(k, _)| k.starts_with(ENV_OVERRIDE_PREFIX))
+        .map(|(k, v)| {
+            (
+                k[ENV_OVERRIDE_PREFIX.len()..].replace(ENV_OVERRIDE_SEPARATOR, "."),
+                v,
+            )
+        })
+        .collect();
+    overrides.sort();
+    overrides
+}
+
+//objects are merged by key, other values replaced
+pub fn merge(base: &mut Value, layer: Value) {
+    match (base, layer) {
+        (Value::Object(base), Value::Object(layer)) => {
+            for (k, v) in layer {
+                match base.get_mut(&k) {
+                    Some(b) => merge(b, v),
+                    None => {
+                        base.insert(k, v);
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer,
+    }
+}
+
+//value is kept as string, it is parsed as json if it replaces non string value
+//or is marked with json: prefix, MY_APP__rate_limit__route__AddCar=json:{"rate":1,"burst":1}
+pub fn set_value(root: &mut Value, key: &str, value: &str) -> std::result::Result<(), ConfigError> {
+    let mut node = root;
+    for part in key.split('.') {
+        if part.len() == 0 {
+            return Err(ConfigError::new(key, "empty key part"));
+        }
+        if !node.is_object() {
+            *node = Value::Object(Map::new());
+        }
+        node = node
+            .as_object_mut()
+            .unwrap()
+            .entry(part)
+            .or_insert(Value::Null);
+    }
+    *node = if value.starts_with(JSON_VALUE) {
+        serde_json::from_str(&value[JSON_VALUE.len()..])
+            .map_err(|e| ConfigError::new(key, &e.to_string()))?
+    } else {
+        match node {
+            Value::Null | Value::String(_) => Value::String(value.to_string()),
+            //bad value is reported by key on deserialize
+            _ => serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string())),
+        }
+    };
+    Ok(())
+}
+
+fn resolve_secret(key: &str, value: &str) -> std::result::Result<Option<String>, ConfigError> {
+    if !value.ends_with('}') {
+        return Ok(None);
+    }
+    if value.starts_with(SECRET_ENV) {
+        let name = &value[SECRET_ENV.len()..value.len() - 1];
+        return env::var(name)
+            .map(Some)
+            .map_err(|_| ConfigError::new(key, &format!("env var {} is not set", name)));
+    }
+    if value.starts_with(SECRET_FILE) {
+        let path = &value[SECRET_FILE.len()..value.len() - 1];
+        return fs::read_to_string(path)
+            .map(|s| Some(s.trim_end_matches(&['\r', '\n'][..]).to_string()))
+            .map_err(|e| ConfigError::new(key, &format!("secret file {}: {}", path, e)));
+    }
+    Ok(None)
+}
+
+//replace secret references in place, every unresolved one is reported
+pub fn resolve_secrets(value: &mut Value, key: &str, errors: &mut Vec<ConfigError>) {
+    let child_key = |k: &str| {
+        if key.len() == 0 {
+            k.to_string()
+        } else {
+            format!("{}.{}", key, k)
+        }
+    };
+    match value {
+        Value::String(s) => match resolve_secret(key, s) {
+            Ok(Some(secret)) => *s = secret,
+            Ok(None) => {}
+            Err(e) => errors.push(e),
+        },
+        Value::Object(map) => {
+            for (k, v) in map.iter_mut() {
+                resolve_secrets(v, &child_key(k), errors);
+            }
+        }
+        Value::Array(items) => {
+            for (i, v) in items.iter_mut().enumerate() {
+                resolve_secrets(v, &child_key(&i.to_string()), errors);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn read_json(file: &str) -> std::result::Result<Value, ConfigError> {
+    let text = fs::read_to_string(file).map_err(|e| ConfigError::new(file, &e.to_string()))?;
+    serde_json::from_str(&text).map_err(|e| ConfigError::new(file, &e.to_string()))
+}
+
+//appsettings.json -> appsettings.dev.json
+fn get_env_file(file: &str, env: &str) -> String {
+    let path = Path::new(file);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or(file);
+    let name = match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!("{}.{}.{}", stem, env, ext),
+        None => format!("{}.{}", stem, env),
+    };
+    path.with_file_name(name).to_string_lossy().to_string()
+}
+
+pub fn load(options: &Options) -> Result<Config> {
+    let mut value = read_json(&options.file).map_err(|e| vec![e])?;
+    let mut warnings = Vec::<ConfigError>::new();
+    if let Some(env) = &options.env {
+        let env_file = get_env_file(&options.file, env);
+        if Path::new(&env_file).exists() {
+            merge(&mut value, read_json(&env_file).map_err(|e| vec![e])?);
+        } else {
+            warnings.push(ConfigError::new(
+                &env_file,
+                "environment settings not found",
+            ));
+        }
+    }
+    let mut errors = Vec::<ConfigError>::new();
+    for (k, v) in &options.overrides {
+        if let Err(e) = set_value(&mut value, k, v) {
+            errors.push(e);
+        }
+    }
+    resolve_secrets(&mut value, "", &mut errors);
+    if errors.len() > 0 {
+        return Err(errors);
+    }
+    let mut config = parse(value, options.host.as_deref(), options.port.as_deref())?;
+    config.warnings = warnings;
+    Ok(config)
+}
+
+//deserialize merged value and check what serde can not
+pub fn parse(value: Value, host: Option<&str>, port: Option<&str>) -> Result<Config> {
+    let mut app_settings: settings::AppSettings =
+        serde_path_to_error::deserialize(value).map_err(|e| {
+            vec![ConfigError::new(
+                &e.path().to_string(),
+                &e.inner().to_string(),
+            )]
+        })?;
+    let mut errors = validate(&app_settings);
+    let port = match port {
+        Some(p) => match p.parse::<u16>() {
+            Ok(p) => p,
+            Err(e) => {
+                errors.push(ConfigError::new(ENV_PORT, &e.to_string()));
+                DEFAULT_PORT
+            }
+        },
+        None => DEFAULT_PORT,
+    };
+    let host = format!("{}:{}", host.unwrap_or(DEFAULT_HOST), port);
+    let addr = match host.parse::<SocketAddr>() {
+        Ok(addr) => Some(addr),
+        Err(e) => {
+            errors.push(ConfigError::new(ENV_HOST, &format!("{} {}", host, e)));
+            None
+        }
+    };
+    if errors.len() > 0 {
+        return Err(errors);
+    }
+    let database = app_settings.database.remove(DATABASE_KEY).unwrap();
+    Ok(Config {
+        app_settings: app_settings,
+        database: database,
+        host: host,
+        addr: addr.unwrap(),
+        warnings: Vec::<ConfigError>::new(),
+    })
+}
+
+fn validate(app_settings: &settings::AppSettings) -> Vec<ConfigError> {
+    let mut errors = Vec::<ConfigError>::new();
+    if !app_settings.database.contains_key(DATABASE_KEY) {
+        errors.push(ConfigError::new(
+            &format!("database.{}", DATABASE_KEY),
+            "is required by enabled database feature",
+        ));
+    }
+    if app_settings.access.authentication.server.len() == 0 {
+        errors.push(ConfigError::new(
+            "access.authentication.server",
+            "at least one user is required",
+        ));
+    }
+    if let Some(admin) = &app_settings.access.authentication.admin {
+        if admin.usr_name.len() == 0 || admin.usr_password.len() == 0 {
+            errors.push(ConfigError::new(
+                "access.authentication.admin",
+                "user name and password are required",
+            ));
+        }
+    }
+    //router builds command and subscription paths from them
+    for (proto, path) in &app_settings.path {
+        if path.request.is_none() {
+            errors.push(ConfigError::new(
+                &format!("path.{}.request", proto),
+                "is required",
+            ));
+        }
+        if path.event.is_none() {
+            errors.push(ConfigError::new(
+                &format!("path.{}.event", proto),
+                "is required",
+            ));
+        }
+    }
+    if let Some(tracing) = &app_settings.tracing {
+        if tracing.exporter == settings::TraceExporter::File && tracing.file.is_none() {
+            errors.push(ConfigError::new(
+                "tracing.file",
+                "is required by File exporter",
+            ));
+        }
+    }
+    if let Some(idempotency) = &app_settings.idempotency {
+        if idempotency.retention == 0 {
+            errors.push(ConfigError::new(
+                "idempotency.retention",
+                "must be greater than 0",
+            ));
+        }
+    }
+    if let Some(rate_limit) = &app_settings.rate_limit {
+        validate_rate_limit_rule("rate_limit", &rate_limit.default, &mut errors);
+        for (object_type, rule) in &rate_limit.route {
+            validate_rate_limit_rule(
+                &format!("rate_limit.route.{}", object_type),
+                rule,
+                &mut errors,
+            );
+        }
+    }
+    if let Some(circuit_breaker) = &app_settings.circuit_breaker {
+        if circuit_breaker.failure_threshold == 0 {
+            errors.push(ConfigError::new(
+                "circuit_breaker.failure_threshold",
+                "must be greater than 0",
+            ));
+        }
+        if circuit_breaker.call_timeout == 0 {
+            errors.push(ConfigError::new(
+                "circuit_breaker.call_timeout",
+                "must be greater than 0",
+            ));
+        }
+    }
+    if let Some(lease) = &app_settings.lease {
+        if lease.heartbeat == 0 || lease.heartbeat >= lease.ttl as u64 {
+            errors.push(ConfigError::new(
+                "lease.heartbeat",
+                "must be greater than 0 and less than lease.ttl",
+            ));
+        }
+    }
+    if let Some(shutdown) = &app_settings.shutdown {
+        if shutdown.drain_timeout == 0 {
+            errors.push(ConfigError::new(
+                "shutdown.drain_timeout",
+                "must be greater than 0",
+            ));
+        }
+    }
+    errors
+}
+
+fn validate_rate_limit_rule(
+    key: &str,
+    rule: &settings::RateLimitRule,
+    errors: &mut Vec<ConfigError>,
+) {
+    if !(rule.rate > 0.0) {
+        errors.push(ConfigError::new(
+            &format!("{}.rate", key),
+            "must be greater than 0",
+        ));
+    }
+    if rule.burst == 0 {
+        errors.push(ConfigError::new(
+            &format!("{}.burst", key),
+            "must be greater than 0",
+        ));
+    }
+    if let Some(quota) = rule.daily_quota {
+        if quota <= 0 {
+            errors.push(ConfigError::new(
+                &format!("{}.daily_quota", key),
+                "must be greater than 0",
+            ));
+        }
+    }
+}
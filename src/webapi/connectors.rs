@@ -1,18 +1,25 @@
-#[cfg(not(test))]
 use super::collections;
-#[cfg(test)]
-use super::tests::fakes;
-use super::{entities, settings};
+use super::{entities, errors, providers, settings, traits};
 use std::collections::HashMap;
-#[cfg(not(test))]
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 use std::sync::Arc;
+use strum::IntoEnumIterator;
 
 pub type Result<T, E = Box<dyn std::error::Error>> = std::result::Result<T, E>;
 
-#[cfg(not(test))]
+#[cfg(feature = "sqlite")]
+const DB_SCHEMA_PREFIX: &str = "webapi.";
+
+//connection string for in-memory stores, no database required
+pub const MEMORY_CONNECTION_STRING: &str = "memory";
+
+//error catalog language used when requested one is unknown
+pub const DEFAULT_LANG: &str = "en";
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 pub struct ExpHelper;
 
-#[cfg(not(test))]
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 impl ExpHelper {
     fn new() -> &'static ExpHelper {
         &ExpHelper {}
@@ -29,21 +36,40 @@ impl ExpHelper {
         result
     }
 
+    //quotes are doubled, ids may come from request body
     pub fn get_str_ids_exp(&self, ids: &Vec<String>) -> String {
         let mut result: String = String::with_capacity(100);
         for item in ids {
             if result.len() != 0 {
                 result.push(',');
             }
-            result.push_str(&format!("'{}'", item.to_string()));
+            #[cfg(feature = "mysql")]
+            let item = item.replace('\\', "\\\\");
+            result.push_str(&format!("'{}'", item.replace('\'', "''")));
         }
         result
     }
 
+    //placeholders of count bound values, first is number of first one
+    pub fn get_params_exp(&self, first: usize, count: usize) -> String {
+        #[cfg(feature = "postgres")]
+        let params: Vec<String> = (first..first + count).map(|n| format!("${}", n)).collect();
+        #[cfg(not(feature = "postgres"))]
+        let params: Vec<String> = (first..first + count).map(|_| "?".to_string()).collect();
+        params.join(",")
+    }
+
+    pub fn get_table_exp<'a>(&self, table: &'a str) -> &'a str {
+        //sqlite has no schemas, all tables live in the main database
+        #[cfg(feature = "sqlite")]
+        let table = table.trim_start_matches(DB_SCHEMA_PREFIX);
+        table
+    }
+
     pub fn get_select_int_exp(&self, table: &str, field: &str, ids: &Vec<i32>) -> String {
         format!(
             "SELECT * FROM {} WHERE {} IN ({})",
-            table,
+            self.get_table_exp(table),
             field,
             self.get_int_ids_as_exp(ids)
         )
@@ -52,7 +78,7 @@ impl ExpHelper {
     pub fn get_delete_int_exp(&self, table: &str, field: &str, ids: &Vec<i32>) -> String {
         format!(
             "DELETE FROM {} WHERE {} IN ({})",
-            table,
+            self.get_table_exp(table),
             field,
             self.get_int_ids_as_exp(ids)
         )
@@ -61,7 +87,7 @@ impl ExpHelper {
     pub fn get_select_str_exp(&self, table: &str, field: &str, ids: &Vec<String>) -> String {
         format!(
             "SELECT * FROM {} WHERE {} IN ({})",
-            table,
+            self.get_table_exp(table),
             field,
             self.get_str_ids_exp(ids)
         )
@@ -70,7 +96,7 @@ impl ExpHelper {
     pub fn get_delete_str_exp(&self, table: &str, field: &str, ids: &Vec<String>) -> String {
         format!(
             "DELETE FROM {} WHERE {} IN ({})",
-            table,
+            self.get_table_exp(table),
             field,
             self.get_str_ids_exp(ids)
         )
@@ -79,77 +105,222 @@ impl ExpHelper {
 
 pub struct DataConnector {
     pub error: HashMap<String, String>,
-    #[cfg(not(test))]
-    pub usr: collections::usr::UsrCollection,
-    #[cfg(test)]
-    pub usr: fakes::usr::UsrCollection,
-    #[cfg(not(test))]
-    pub car: collections::car::CarCollection,
-    #[cfg(test)]
-    pub car: fakes::car::CarCollection,
-    #[cfg(not(test))]
-    pub route: collections::route::RouteCollection,
-    #[cfg(test)]
-    pub route: fakes::route::RouteCollection,
-    #[cfg(not(test))]
-    pub sended_async_command: collections::executor::SendedAsyncCommandCollection,
-    #[cfg(test)]
-    pub sended_async_command: fakes::executor::SendedAsyncCommandCollection,
-    #[cfg(not(test))]
-    pub received_async_command: collections::executor::ReceivedAsyncCommandCollection,
-    #[cfg(test)]
-    pub received_async_command: fakes::executor::ReceivedAsyncCommandCollection,
+    pub error_catalog: HashMap<String, HashMap<String, String>>,
+    error_store: Box<dyn traits::ErrorStore>,
+    pub usr: Box<dyn traits::UsrStore>,
+    pub car: Box<dyn traits::CarStore>,
+    pub route: Box<dyn traits::RouteStore>,
+    pub sended_async_command: Box<traits::SendedAsyncCommandStore>,
+    pub received_async_command: Box<traits::ReceivedAsyncCommandStore>,
+    pub saga: Box<dyn traits::SagaStore>,
+    pub idempotency: Box<dyn traits::IdempotencyStore>,
+    pub quota: Box<dyn traits::QuotaStore>,
+    pub dead_letter: Box<dyn traits::DeadLetterStore>,
+    #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+    db_provider: Option<Arc<providers::SqlDbProvider>>,
 }
 
 impl DataConnector {
     pub async fn new(
         _error: Option<HashMap<String, String>>,
-        _db: &settings::Database
+        db: &settings::Database,
+        authentication: &settings::Authentication,
     ) -> Result<DataConnector> {
-        #[cfg(not(test))]
-        let _exp_helper: &'static ExpHelper = &ExpHelper::new();
-        #[cfg(all(not(test), feature = "postgres"))]
-        let dp = super::providers::SqlDbProvider::new(&_db.connection_string).await?;
-        #[cfg(all(not(test), feature = "mysql"))]
-        let dp = super::providers::SqlDbProvider::new(&_db.connection_string).await?;
-        let mut error = HashMap::<String, String>::new();
+        let mut dc = if db.connection_string == MEMORY_CONNECTION_STRING {
+            debug!("using in-memory stores");
+            DataConnector::new_memory(authentication)
+        } else {
+            DataConnector::new_sql(db).await?
+        };
+        dc.error_catalog = DataConnector::_errors_as_catalog(dc.error_store.get(None).await?);
+        DataConnector::check_error_catalog(&dc.error_catalog)?;
+        let mut error = dc.error_catalog.get(DEFAULT_LANG).unwrap().clone();
+        //settings override default language names
         if _error.is_some() {
             error.extend(_error.unwrap());
         }
-        #[cfg(not(test))]
-        error.extend(DataConnector::_errors_as_hashmap(dp.get_errors().await?));
-        #[cfg(not(test))]
-        let _dp_arc = Arc::new(dp);
+        dc.error = error;
+        Ok(dc)
+    }
+
+    fn new_memory(authentication: &settings::Authentication) -> DataConnector {
+        let mut usrs = Vec::<entities::usr::Usr>::new();
+        for item in &authentication.server {
+            usrs.push(entities::usr::Usr {
+                id: usrs.len() as i32 + 1,
+                usr_name: item.0.to_string(),
+                usr_password: item.1.to_string(),
+            });
+        }
+        DataConnector {
+            error: HashMap::<String, String>::new(),
+            error_catalog: HashMap::<String, HashMap<String, String>>::new(),
+            error_store: Box::new(collections::memory::error::ErrorCollection::new(
+                providers::get_default_errors(),
+            )),
+            usr: Box::new(collections::memory::usr::UsrCollection::new(usrs)),
+            car: Box::new(collections::memory::car::CarCollection::new()),
+            route: Box::new(collections::memory::route::RouteCollection::new()),
+            sended_async_command: Box::new(
+                collections::memory::executor::SendedAsyncCommandCollection::new(),
+            ),
+            received_async_command: Box::new(
+                collections::memory::executor::ReceivedAsyncCommandCollection::new(),
+            ),
+            saga: Box::new(collections::memory::saga::SagaCollection::new()),
+            idempotency: Box::new(collections::memory::idempotency::IdempotencyCollection::new()),
+            quota: Box::new(collections::memory::quota::QuotaCollection::new()),
+            dead_letter: Box::new(collections::memory::dead_letter::DeadLetterCollection::new()),
+            #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+            db_provider: None,
+        }
+    }
+
+    //database answers, in-memory stores always do
+    #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+    pub async fn ping(&self) -> Result<()> {
+        match &self.db_provider {
+            Some(dp) => dp.ping().await,
+            None => Ok(()),
+        }
+    }
+
+    #[cfg(not(any(feature = "postgres", feature = "mysql", feature = "sqlite")))]
+    pub async fn ping(&self) -> Result<()> {
+        Ok(())
+    }
+
+    //pool size and idle connections, none for in-memory stores
+    #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+    pub fn get_pool_stats(&self) -> Option<(u32, usize)> {
+        self.db_provider.as_ref().map(|dp| dp.get_pool_stats())
+    }
+
+    #[cfg(not(any(feature = "postgres", feature = "mysql", feature = "sqlite")))]
+    pub fn get_pool_stats(&self) -> Option<(u32, usize)> {
+        None
+    }
+
+    #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+    async fn new_sql(db: &settings::Database) -> Result<DataConnector> {
+        let exp_helper: &'static ExpHelper = &ExpHelper::new();
+        let dp = providers::SqlDbProvider::new(&db.connection_string).await?;
+        let dp_arc = Arc::new(dp);
         Ok(DataConnector {
-            error: error,
-            #[cfg(not(test))]
-            usr: collections::usr::UsrCollection::new(_dp_arc.clone(), &_exp_helper),
-            #[cfg(test)]
-            usr: fakes::usr::UsrCollection::new(),
-            #[cfg(not(test))]
-            car: collections::car::CarCollection::new(_dp_arc.clone(), &_exp_helper),
-            #[cfg(test)]
-            car: fakes::car::CarCollection::new(),
-            #[cfg(not(test))]
-            route: collections::route::RouteCollection::new(_dp_arc.clone(), &_exp_helper),
-            #[cfg(test)]
-            route: fakes::route::RouteCollection::new(),
-            #[cfg(not(test))]
-            sended_async_command: collections::executor::SendedAsyncCommandCollection::new(_dp_arc.clone(), &_exp_helper),
-            #[cfg(test)]
-            sended_async_command: fakes::executor::SendedAsyncCommandCollection::new(),
-            #[cfg(not(test))]
-            received_async_command: collections::executor::ReceivedAsyncCommandCollection::new(_dp_arc.clone(), &_exp_helper),
-            #[cfg(test)]
-            received_async_command: fakes::executor::ReceivedAsyncCommandCollection::new(),
+            error: HashMap::<String, String>::new(),
+            error_catalog: HashMap::<String, HashMap<String, String>>::new(),
+            error_store: Box::new(collections::error::ErrorCollection::new(
+                dp_arc.clone(),
+                &exp_helper,
+            )),
+            usr: Box::new(collections::usr::UsrCollection::new(
+                dp_arc.clone(),
+                &exp_helper,
+            )),
+            car: Box::new(collections::car::CarCollection::new(
+                dp_arc.clone(),
+                &exp_helper,
+            )),
+            route: Box::new(collections::route::RouteCollection::new(
+                dp_arc.clone(),
+                &exp_helper,
+            )),
+            sended_async_command: Box::new(
+                collections::executor::SendedAsyncCommandCollection::new(
+                    dp_arc.clone(),
+                    &exp_helper,
+                ),
+            ),
+            received_async_command: Box::new(
+                collections::executor::ReceivedAsyncCommandCollection::new(
+                    dp_arc.clone(),
+                    &exp_helper,
+                ),
+            ),
+            saga: Box::new(collections::saga::SagaCollection::new(
+                dp_arc.clone(),
+                &exp_helper,
+            )),
+            idempotency: Box::new(collections::idempotency::IdempotencyCollection::new(
+                dp_arc.clone(),
+                &exp_helper,
+            )),
+            quota: Box::new(collections::quota::QuotaCollection::new(
+                dp_arc.clone(),
+                &exp_helper,
+            )),
+            dead_letter: Box::new(collections::dead_letter::DeadLetterCollection::new(
+                dp_arc.clone(),
+                &exp_helper,
+            )),
+            db_provider: Some(dp_arc),
         })
     }
 
-    fn _errors_as_hashmap(items: Vec<entities::error::Error>) -> HashMap<String, String> {
-        let mut error = HashMap::<String, String>::new();
+    #[cfg(not(any(feature = "postgres", feature = "mysql", feature = "sqlite")))]
+    async fn new_sql(_db: &settings::Database) -> Result<DataConnector> {
+        error!(
+            "database feature is not enabled, use \"{}\" connection string",
+            MEMORY_CONNECTION_STRING
+        );
+        Err(errors::Error::new(errors::ErrorKind::UnsupportedDatabase).into())
+    }
+
+    //every error code must be named in default language, other languages fallback to it
+    pub fn check_error_catalog(catalog: &HashMap<String, HashMap<String, String>>) -> Result<()> {
+        let default = match catalog.get(DEFAULT_LANG) {
+            Some(default) => default,
+            None => {
+                return Err(errors::Error::with_context(
+                    errors::ErrorKind::UnsetRequiredValue,
+                    &format!("error catalog has no {} language", DEFAULT_LANG),
+                )
+                .into())
+            }
+        };
+        for ec in errors::ErrorCode::iter() {
+            let code = ec.to_string();
+            if !default.contains_key(&code) {
+                return Err(errors::Error::with_context(
+                    errors::ErrorKind::UnsetRequiredValue,
+                    &format!("error catalog has no {} name for {}", DEFAULT_LANG, code),
+                )
+                .into());
+            }
+            for (lang, names) in catalog {
+                if !names.contains_key(&code) {
+                    warn!("error catalog has no {} name for {}", lang, code);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    //unknown languages fallback to default
+    pub fn get_error_lang<'a>(&self, lang: Option<&'a str>) -> &'a str {
+        match lang {
+            Some(lang) if self.error_catalog.contains_key(lang) => lang,
+            _ => DEFAULT_LANG,
+        }
+    }
+
+    pub fn get_error_names(&self, lang: Option<&str>) -> &HashMap<String, String> {
+        match self.get_error_lang(lang) {
+            DEFAULT_LANG => &self.error,
+            lang => self.error_catalog.get(lang).unwrap(),
+        }
+    }
+
+    fn _errors_as_catalog(
+        items: Vec<entities::error::Error>,
+    ) -> HashMap<String, HashMap<String, String>> {
+        let mut catalog = HashMap::<String, HashMap<String, String>>::new();
         for item in items {
-            error.insert(item.error_code, item.error_name);
+            catalog
+                .entry(item.lang)
+                .or_insert_with(HashMap::<String, String>::new)
+                .insert(item.error_code, item.error_name);
         }
-        error
+        catalog
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,131 @@
+use chrono::{DateTime, Utc};
+use hyper::HeaderMap;
+use uuid::Uuid;
+
+//w3c trace context header
+pub const TRACEPARENT: &str = "traceparent";
+const TRACE_VERSION: &str = "00";
+const TRACE_FLAG_SAMPLED: &str = "01";
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum SpanKind {
+    Server,   //received request
+    Client,   //called command
+    Producer, //published event
+    Internal, //background work, saga recovery
+}
+
+#[derive(Debug, Clone)]
+pub struct Span {
+    pub trace_id: String, //32 hex digits
+    pub span_id: String,  //16 hex digits
+    pub parent_span_id: Option<String>,
+    pub name: String,
+    pub kind: SpanKind,
+    pub start: DateTime<Utc>,
+}
+
+fn new_id(len: usize) -> String {
+    let id = Uuid::new_v4().to_simple().to_string();
+    id[..len].to_string()
+}
+
+fn is_hex_id(id: &str, len: usize) -> bool {
+    id.len() == len
+        && id
+            .chars()
+            .all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase())
+        && id.chars().any(|c| c != '0')
+}
+
+impl Span {
+    pub fn new(name: &str, kind: SpanKind) -> Span {
+        Span {
+            trace_id: new_id(32),
+            span_id: new_id(16),
+            parent_span_id: None,
+            name: name.to_string(),
+            kind: kind,
+            start: Utc::now(),
+        }
+    }
+
+    pub fn child(&self, name: &str, kind: SpanKind) -> Span {
+        Span {
+            trace_id: self.trace_id.clone(),
+            span_id: new_id(16),
+            parent_span_id: Some(self.span_id.clone()),
+            name: name.to_string(),
+            kind: kind,
+            start: Utc::now(),
+        }
+    }
+
+    //"00-{trace_id}-{parent_id}-{flags}", none if header is invalid
+    pub fn from_traceparent(traceparent: &str, name: &str, kind: SpanKind) -> Option<Span> {
+        let parts: Vec<&str> = traceparent.trim().split('-').collect();
+        if parts.len() < 4
+            || parts[0] != TRACE_VERSION
+            || !is_hex_id(parts[1], 32)
+            || !is_hex_id(parts[2], 16)
+        {
+            return None;
+        }
+        Some(Span {
+            trace_id: parts[1].to_string(),
+            span_id: new_id(16),
+            parent_span_id: Some(parts[2].to_string()),
+            name: name.to_string(),
+            kind: kind,
+            start: Utc::now(),
+        })
+    }
+
+    pub fn to_traceparent(&self) -> String {
+        format!(
+            "{}-{}-{}-{}",
+            TRACE_VERSION, self.trace_id, self.span_id, TRACE_FLAG_SAMPLED
+        )
+    }
+}
+
+//request scope values passed to handlers, executor and publisher
+#[derive(Debug, Clone)]
+pub struct Context {
+    pub correlation_id: String,
+    pub caller: Option<String>, //calling service name
+    pub span: Span,
+}
+
+impl Context {
+    pub fn new(correlation_id: &str, caller: Option<&str>, span: Span) -> Context {
+        Context {
+            correlation_id: correlation_id.to_string(),
+            caller: caller.map(|c| c.to_string()),
+            span: span,
+        }
+    }
+
+    //new trace if traceparent header is missing or invalid
+    pub fn from_request(
+        correlation_id: &str,
+        caller: Option<&str>,
+        headers: &HeaderMap,
+        name: &str,
+    ) -> Context {
+        let span = headers
+            .get(TRACEPARENT)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| Span::from_traceparent(v, name, SpanKind::Server))
+            .unwrap_or_else(|| Span::new(name, SpanKind::Server));
+        Context::new(correlation_id, caller, span)
+    }
+
+    pub fn child(&self, name: &str, kind: SpanKind) -> Context {
+        Context {
+            correlation_id: self.correlation_id.clone(),
+            caller: self.caller.clone(),
+            span: self.span.child(name, kind),
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+//event not delivered to subscriber, kept until replayed or removed
+#[derive(Deserialize, Serialize, Clone, FromRow, JsonSchema)]
+pub struct DeadLetterEvent {
+    pub id: String,
+    pub object_type: String,
+    pub service_name: String, //subscriber
+    pub correlation_id: String,
+    pub event_body: String, //json array of events as sent
+    pub error: String,      //last delivery error
+    pub added_at: DateTime<Utc>,
+}
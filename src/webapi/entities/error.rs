@@ -1,7 +1,10 @@
-use serde::Serialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
 
-#[derive(Serialize)]
+#[derive(Deserialize, Serialize, Clone, FromRow, JsonSchema)]
 pub struct Error {
     pub error_code: String,
+    pub lang: String,
     pub error_name: String,
 }
@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use strum_macros::EnumString;
+
+#[derive(
+    Deserialize, Serialize, Debug, PartialEq, Copy, Clone, EnumString, ToString, JsonSchema,
+)]
+pub enum IdempotentState {
+    InProgress, //original request is executed, no reply yet
+    Completed,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone, JsonSchema)]
+pub struct IdempotencyKey {
+    pub caller: String, //user name, and service name after colon if set
+    pub object_type: String,
+    pub correlation_id: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, FromRow, JsonSchema)]
+pub struct IdempotentReply {
+    pub caller: String,
+    pub object_type: String,
+    pub correlation_id: String,
+    pub state: String,
+    pub status: i32,        //http status of stored reply
+    pub reply_body: String, //empty while in progress
+    pub added_at: DateTime<Utc>,
+}
@@ -1,6 +1,12 @@
 pub mod error;
-pub mod usr;
 pub mod executor;
+pub mod idempotency;
 pub mod route;
+pub mod saga;
+pub mod usr;
 
 pub mod car;
+
+pub mod quota;
+
+pub mod dead_letter;
@@ -0,0 +1,12 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+//requests of user to object type during one utc day
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone, FromRow, JsonSchema)]
+pub struct QuotaUsage {
+    pub caller: String,
+    pub object_type: String,
+    pub day: String, //yyyy-mm-dd
+    pub usage_count: i64,
+}
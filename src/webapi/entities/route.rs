@@ -1,13 +1,29 @@
+use super::error;
 use chrono::{DateTime, Utc};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Deserialize, Serialize, Debug, PartialEq, Copy, Clone, ToString, JsonSchema)]
 pub enum ServiceState {
     Alive,
-    Unavailable
+    Unavailable,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Copy, Clone, ToString, JsonSchema)]
+pub enum BreakerState {
+    Closed,   //calls are passed
+    Open,     //calls fail fast until cool down ends
+    HalfOpen, //one trial call decides next state
+}
+
+//circuit breaker of called service, absent until first call
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+pub struct ServiceBreaker {
+    pub service_name: String,
+    pub state: BreakerState,
+    pub failure_count: u32, //consecutive failed calls
 }
 
 #[derive(Deserialize, Serialize, Clone, FromRow, JsonSchema)]
@@ -15,12 +31,69 @@ pub struct Route {
     pub service_name: Option<String>,
     pub description: String,
     pub priority: i32,
+    #[serde(default)] //declared by derive(ObjectType) if absent
     pub command: Vec<ServiceCommand>,
+    #[serde(default)]
     pub event: Vec<ServiceEvent>,
+    #[serde(default)]
     pub subscription: Vec<ServiceSubscription>,
     pub path: Option<HashMap<String, ServicePath>>,
 }
 
+//same route set regardless of order, absent and empty maps are equal
+impl Route {
+    pub fn is_same(&self, other: &Route) -> bool {
+        self.service_name == other.service_name
+            && self.description == other.description
+            && self.priority == other.priority
+            && is_same_items(&self.command, &other.command, |a, b| {
+                a.object_type == b.object_type
+                    && a.description == b.description
+                    && a.reply_type == b.reply_type
+                    && a.exec_mode == b.exec_mode
+                    && get_map(&a.path) == get_map(&b.path)
+                    && get_map(&a.state) == get_map(&b.state)
+            })
+            && is_same_items(&self.event, &other.event, |a, b| {
+                a.object_type == b.object_type && a.description == b.description
+            })
+            && is_same_items(&self.subscription, &other.subscription, |a, b| {
+                a.object_type == b.object_type && get_map(&a.path) == get_map(&b.path)
+            })
+            && get_paths(&self.path) == get_paths(&other.path)
+    }
+}
+
+//object type is unique in service route
+fn is_same_items<T>(a: &[T], b: &[T], is_same: fn(&T, &T) -> bool) -> bool {
+    a.len() == b.len() && a.iter().all(|x| b.iter().any(|y| is_same(x, y)))
+}
+
+fn get_map(map: &Option<HashMap<String, String>>) -> BTreeMap<&str, &str> {
+    map.iter()
+        .flat_map(|m| m.iter())
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect()
+}
+
+fn get_paths(map: &Option<HashMap<String, ServicePath>>) -> BTreeMap<&str, [&str; 5]> {
+    map.iter()
+        .flat_map(|m| m.iter())
+        .map(|(k, v)| {
+            (
+                k.as_str(),
+                [
+                    v.helth.as_str(),
+                    v.schema.as_str(),
+                    v.reply_to.as_str(),
+                    v.state.as_str(),
+                    v.error.as_str(),
+                ],
+            )
+        })
+        .collect()
+}
+
 #[derive(Deserialize, Serialize, Clone, FromRow, JsonSchema)]
 pub struct ServicePath {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -47,9 +120,51 @@ pub struct Service {
     pub added_at: DateTime<Utc>,
 }
 
+//state change of service, e.g. route removed on lapsed lease
+#[derive(Deserialize, Serialize, Clone, FromRow, JsonSchema)]
+pub struct ServiceHistory {
+    pub service_name: String,
+    pub state: String,
+    pub description: String,
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+pub struct ServiceError {
+    pub service_name: String,
+    pub items: Vec<error::Error>,
+}
+
+#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+pub struct ServiceSchema {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_name: Option<String>,
+    pub object_type: String,
+    pub version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<serde_json::Value>, //not set in schema list
+}
+
 #[derive(Deserialize, Serialize, Clone, FromRow, JsonSchema)]
 pub struct ServiceHelth {
     pub state: ServiceState,
+    pub uptime: i64, //seconds since start
+}
+
+#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+pub struct ComponentHelth {
+    pub name: String,
+    pub state: ServiceState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+//alive only if every component is alive
+#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+pub struct ServiceReadiness {
+    pub state: ServiceState,
+    pub uptime: i64,
+    pub components: Vec<ComponentHelth>,
 }
 
 #[derive(Deserialize, Serialize, Clone, FromRow, JsonSchema)]
@@ -91,7 +206,7 @@ pub struct CommandRoute {
     pub reply_type: String,
     pub exec_mode: String,
     pub state: HashMap<String, String>, // state/description
-    pub path: HashMap<String, String>, // proto/to
+    pub path: HashMap<String, String>,  // proto/to
 }
 
 #[derive(Clone)]
@@ -106,4 +221,4 @@ pub struct ClientAccess {
     pub service_name: String,
     pub usr_name: String,
     pub usr_password: String,
-}
\ No newline at end of file
+}
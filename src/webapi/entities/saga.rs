@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use strum_macros::EnumString;
+
+#[derive(
+    Deserialize, Serialize, Debug, PartialEq, Copy, Clone, EnumString, ToString, JsonSchema,
+)]
+pub enum SagaState {
+    Running,      //step is the one being executed
+    Compensating, //step is count of executed steps left to compensate
+    Completed,
+    Compensated,
+}
+
+#[derive(Deserialize, Serialize, Clone, FromRow, JsonSchema)]
+pub struct Saga {
+    pub id: String,
+    pub saga_type: String,
+    pub correlation_id: String,
+    pub state: String,
+    pub step: i32,
+    pub data: String,  //saga input as json, steps and compensations are made from it
+    pub owner: String, //instance which runs saga, renews lease while it is alive
+    pub lease_expires_at: DateTime<Utc>,
+    pub added_at: DateTime<Utc>,
+    pub state_changed_at: DateTime<Utc>,
+}
@@ -1,395 +1,210 @@
+use super::replies;
+use hyper::{header, Body, Response, StatusCode};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::error;
 use std::fmt;
 
-#[derive(Deserialize, Serialize, Debug, PartialEq, Copy, Clone, ToString, JsonSchema)]
+#[derive(Deserialize, Serialize, Debug, PartialEq, Copy, Clone, ToString, EnumIter, JsonSchema)]
 pub enum ErrorCode {
-    ReplyOk, //command success result
-    AsyncOk, //async command success registered
+    ReplyOk,                  //command success result
+    AsyncOk,                  //async command success registered
     UnsupportedExecModeError, //if not support sync/async call mode
     TooManyRequestsError, //if async queue or reply queue full, http proto return HTTP-status 429
     TimeoutExpiredError,
     DatabaseError, //internal error, database level
     NotFoundError,
-}
-
-#[derive(Debug, Clone)]
-pub struct ChannelError;
-
-impl fmt::Display for ChannelError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "channel error")
-    }
-}
-
-impl error::Error for ChannelError {
-    fn description(&self) -> &str {
-        "channel error"
-    }
-
-    fn cause(&self) -> Option<&(dyn error::Error)> {
-        // Generic error, underlying cause isn't tracked.
-        None
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct UnsupportedProtoError;
-
-impl fmt::Display for UnsupportedProtoError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "unsupported proto error")
-    }
-}
-
-impl error::Error for UnsupportedProtoError {
-    fn description(&self) -> &str {
-        "supported proto error"
-    }
-
-    fn cause(&self) -> Option<&(dyn error::Error)> {
-        // Generic error, underlying cause isn't tracked.
-        None
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct ProtoProviderError;
-
-impl fmt::Display for ProtoProviderError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "proto provider error")
-    }
-}
-
-impl error::Error for ProtoProviderError {
-    fn description(&self) -> &str {
-        "proto provider error"
-    }
-
-    fn cause(&self) -> Option<&(dyn error::Error)> {
-        // Generic error, underlying cause isn't tracked.
-        None
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct SignalSendError;
-
-impl fmt::Display for SignalSendError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "signal send error")
-    }
-}
-
-impl error::Error for SignalSendError {
-    fn description(&self) -> &str {
-        "signal send error"
-    }
-
-    fn cause(&self) -> Option<&(dyn error::Error)> {
-        // Generic error, underlying cause isn't tracked.
-        None
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct EventSendError;
-
-impl fmt::Display for EventSendError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "event send error")
-    }
-}
-
-impl error::Error for EventSendError {
-    fn description(&self) -> &str {
-        "event send error"
-    }
-
-    fn cause(&self) -> Option<&(dyn error::Error)> {
-        // Generic error, underlying cause isn't tracked.
-        None
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct HandlerError;
-
-impl fmt::Display for HandlerError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "handler error")
-    }
-}
-
-impl error::Error for HandlerError {
-    fn description(&self) -> &str {
-        "handler error"
-    }
-
-    fn cause(&self) -> Option<&(dyn error::Error)> {
-        // Generic error, underlying cause isn't tracked.
-        None
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct UnknownServiceNameError;
-
-impl fmt::Display for UnknownServiceNameError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "unknown service name error")
-    }
-}
-
-impl error::Error for UnknownServiceNameError {
-    fn description(&self) -> &str {
-        "unknown service name error"
-    }
-
-    fn cause(&self) -> Option<&(dyn error::Error)> {
-        // Generic error, underlying cause isn't tracked.
-        None
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct UnknownCommandError;
-
-impl fmt::Display for UnknownCommandError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "unknown command error")
-    }
-}
-
-impl error::Error for UnknownCommandError {
-    fn description(&self) -> &str {
-        "unknown command error"
-    }
-
-    fn cause(&self) -> Option<&(dyn error::Error)> {
-        // Generic error, underlying cause isn't tracked.
-        None
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct UnknownEventError;
-
-impl fmt::Display for UnknownEventError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "unknown event error")
-    }
-}
-
-impl error::Error for UnknownEventError {
-    fn description(&self) -> &str {
-        "unknown event error"
-    }
-
-    fn cause(&self) -> Option<&(dyn error::Error)> {
-        // Generic error, underlying cause isn't tracked.
-        None
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct BadReplyCommandError;
-
-impl fmt::Display for BadReplyCommandError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "bad reply command error")
-    }
-}
-
-impl error::Error for BadReplyCommandError {
-    fn description(&self) -> &str {
-        "bad reply command error"
-    }
-
-    fn cause(&self) -> Option<&(dyn error::Error)> {
-        // Generic error, underlying cause isn't tracked.
-        None
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct GeRemoteRouterError;
-
-impl fmt::Display for GeRemoteRouterError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "get from remote router error")
-    }
-}
-
-impl error::Error for GeRemoteRouterError {
-    fn description(&self) -> &str {
-        "get from remote router error"
-    }
-
-    fn cause(&self) -> Option<&(dyn error::Error)> {
-        // Generic error, underlying cause isn't tracked.
-        None
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct UnsetRequiredValueError;
-
-impl fmt::Display for UnsetRequiredValueError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "required value not set")
-    }
-}
-
-impl error::Error for UnsetRequiredValueError {
-    fn description(&self) -> &str {
-        "required value not set"
-    }
-
-    fn cause(&self) -> Option<&(dyn error::Error)> {
-        // Generic error, underlying cause isn't tracked.
-        None
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct TooManyRequestsError;
-
-impl fmt::Display for TooManyRequestsError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "too many request error")
-    }
-}
-
-impl error::Error for TooManyRequestsError {
-    fn description(&self) -> &str {
-        "too many request error"
+    BadRequestError,    //unknown command, service, proto or bad request params
+    RemoteServiceError, //remote service or router is unavailable or reply is bad
+    InternalError,      //internal error, service level
+}
+
+#[derive(Debug, PartialEq, Copy, Clone, ToString)]
+pub enum ErrorKind {
+    Channel,
+    UnsupportedProto,
+    ProtoProvider,
+    RejectedRequest, //remote service replied with 4xx, it is not failure of service
+    SignalSend,
+    EventSend,
+    Handler,
+    UnsupportedDatabase,
+    UnknownServiceName,
+    UnknownCommand,
+    UnknownEvent,
+    BadReplyCommand,
+    GetRemoteRouter,
+    UnsetRequiredValue,
+    TooManyRequests,
+    TimeoutExpired,
+    UnknownErrorCode,
+    AsyncCommandNotFound,
+    DataIntegrity,
+    UnsupportedExecMode,
+    UnknownAsyncCommandState,
+    SagaState,
+    DuplicateRequest,
+    CircuitOpen,
+    BadRequestCommand,
+    DeadLetterNotFound,
+}
+
+impl ErrorKind {
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            ErrorKind::UnsupportedProto
+            | ErrorKind::UnknownServiceName
+            | ErrorKind::UnknownCommand
+            | ErrorKind::UnknownEvent
+            | ErrorKind::UnknownErrorCode
+            | ErrorKind::UnknownAsyncCommandState
+            | ErrorKind::DuplicateRequest
+            | ErrorKind::BadRequestCommand => ErrorCode::BadRequestError,
+            ErrorKind::ProtoProvider
+            | ErrorKind::RejectedRequest
+            | ErrorKind::BadReplyCommand
+            | ErrorKind::GetRemoteRouter
+            | ErrorKind::CircuitOpen => ErrorCode::RemoteServiceError,
+            ErrorKind::TooManyRequests => ErrorCode::TooManyRequestsError,
+            ErrorKind::TimeoutExpired => ErrorCode::TimeoutExpiredError,
+            ErrorKind::AsyncCommandNotFound | ErrorKind::DeadLetterNotFound => {
+                ErrorCode::NotFoundError
+            }
+            ErrorKind::UnsupportedExecMode => ErrorCode::UnsupportedExecModeError,
+            _ => ErrorCode::InternalError,
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ErrorKind::UnsupportedProto
+            | ErrorKind::UnknownServiceName
+            | ErrorKind::UnknownCommand
+            | ErrorKind::UnknownEvent
+            | ErrorKind::UnknownErrorCode
+            | ErrorKind::UnknownAsyncCommandState
+            | ErrorKind::AsyncCommandNotFound
+            | ErrorKind::DeadLetterNotFound
+            | ErrorKind::UnsupportedExecMode
+            | ErrorKind::BadRequestCommand => StatusCode::BAD_REQUEST,
+            ErrorKind::ProtoProvider
+            | ErrorKind::RejectedRequest
+            | ErrorKind::BadReplyCommand
+            | ErrorKind::GetRemoteRouter => StatusCode::BAD_GATEWAY,
+            ErrorKind::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
+            ErrorKind::TimeoutExpired => StatusCode::GATEWAY_TIMEOUT,
+            ErrorKind::DuplicateRequest => StatusCode::CONFLICT,
+            ErrorKind::CircuitOpen => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    //lowercase words, "UnknownCommand" -> "unknown command"
+    fn description(&self) -> String {
+        let mut result = String::new();
+        for c in self.to_string().chars() {
+            if c.is_uppercase() && result.len() != 0 {
+                result.push(' ');
+            }
+            result.extend(c.to_lowercase());
+        }
+        result
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Error {
+    kind: ErrorKind,
+    context: Option<String>,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind) -> Error {
+        Error {
+            kind: kind,
+            context: None,
+        }
+    }
+
+    pub fn with_context(kind: ErrorKind, context: &str) -> Error {
+        Error {
+            kind: kind,
+            context: Some(context.to_string()),
+        }
     }
 
-    fn cause(&self) -> Option<&(dyn error::Error)> {
-        // Generic error, underlying cause isn't tracked.
-        None
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct TimeoutExpiredError;
-
-impl fmt::Display for TimeoutExpiredError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "timeout expired error")
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
     }
-}
 
-impl error::Error for TimeoutExpiredError {
-    fn description(&self) -> &str {
-        "timeout expired error"
+    pub fn context(&self) -> Option<&String> {
+        self.context.as_ref()
     }
 
-    fn cause(&self) -> Option<&(dyn error::Error)> {
-        // Generic error, underlying cause isn't tracked.
-        None
+    pub fn error_code(&self) -> ErrorCode {
+        self.kind.error_code()
     }
-}
-
-#[derive(Debug, Clone)]
-pub struct UnknownErrorCodeError;
-
-impl fmt::Display for UnknownErrorCodeError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "unknown error code error")
-    }
-}
-
-impl error::Error for UnknownErrorCodeError {
-    fn description(&self) -> &str {
-        "unknown error code error"
-    }
-
-    fn cause(&self) -> Option<&(dyn error::Error)> {
-        // Generic error, underlying cause isn't tracked.
-        None
-    }
-}
 
-#[derive(Debug, Clone)]
-pub struct AsyncCommandNotFoundError;
-
-impl fmt::Display for AsyncCommandNotFoundError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "async command not found error")
+    pub fn status(&self) -> StatusCode {
+        self.kind.status()
     }
-}
 
-impl error::Error for AsyncCommandNotFoundError {
-    fn description(&self) -> &str {
-        "async command not found error"
+    pub fn to_reply(&self, error: &HashMap<String, String>) -> replies::common::StandardReply {
+        replies::common::StandardReply {
+            error_code: self.error_code(),
+            error_name: Some(match error.get(&self.error_code().to_string()) {
+                Some(name) => name.clone(),
+                None => self.to_string(),
+            }),
+        }
     }
 
-    fn cause(&self) -> Option<&(dyn error::Error)> {
-        // Generic error, underlying cause isn't tracked.
-        None
+    pub fn to_response(&self, error: &HashMap<String, String>) -> Response<Body> {
+        Response::builder()
+            .status(self.status())
+            .header(header::CONTENT_TYPE, "application/json; charset=utf-8")
+            .body(Body::from(
+                serde_json::to_string(&self.to_reply(error)).unwrap(),
+            ))
+            .unwrap()
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct DataIntegrityError;
-
-impl fmt::Display for DataIntegrityError {
+impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "data integrity error")
+        match &self.context {
+            Some(context) => write!(f, "{} error: {}", self.kind.description(), context),
+            None => write!(f, "{} error", self.kind.description()),
+        }
     }
 }
 
-impl error::Error for DataIntegrityError {
-    fn description(&self) -> &str {
-        "data integrity error"
-    }
-
-    fn cause(&self) -> Option<&(dyn error::Error)> {
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         // Generic error, underlying cause isn't tracked.
         None
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct UnsupportedExecModeError;
-
-impl fmt::Display for UnsupportedExecModeError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "unsupported exec mode error")
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Error {
+        Error::new(kind)
     }
 }
 
-impl error::Error for UnsupportedExecModeError {
-    fn description(&self) -> &str {
-        "unsupported exec mode error"
-    }
-
-    fn cause(&self) -> Option<&(dyn error::Error)> {
-        // Generic error, underlying cause isn't tracked.
-        None
+//untyped errors are internal
+pub fn from_boxed(e: &Box<dyn error::Error>) -> Error {
+    match e.downcast_ref::<Error>() {
+        Some(e) => e.clone(),
+        None => Error::with_context(ErrorKind::Handler, &e.to_string()),
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct UnknownAsyncCommandStateError;
-
-impl fmt::Display for UnknownAsyncCommandStateError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "unknown async command state error")
-    }
-}
-
-impl error::Error for UnknownAsyncCommandStateError {
-    fn description(&self) -> &str {
-        "unknown async command state error"
-    }
-
-    fn cause(&self) -> Option<&(dyn error::Error)> {
-        // Generic error, underlying cause isn't tracked.
-        None
+//never panic on missing error name, fallback to error code
+pub fn get_error_name(error: &HashMap<String, String>, error_code: &ErrorCode) -> String {
+    match error.get(&error_code.to_string()) {
+        Some(name) => name.clone(),
+        None => error_code.to_string(),
     }
 }
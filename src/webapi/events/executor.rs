@@ -1,14 +1,11 @@
-use super::super::{traits, entities};
+use super::super::{entities, routes::path};
+use my_rust_hyper_derive::ObjectType;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Deserialize, Serialize, Clone, JsonSchema, ObjectType)]
+#[event(description = "async command state change event")]
+#[subscription(service = "webapi", path = path::EVENT_ON_ASYNC_COMMAND_STATE_CHANGE)]
 pub struct OnAsyncCommandStateChange {
     pub commands: Vec<entities::executor::AsyncCommandState>,
 }
-
-impl traits::ObjectType for OnAsyncCommandStateChange {
-    fn get_type_name() -> &'static str {
-        "OnAsyncCommandStateChange"
-    }
-}
\ No newline at end of file
@@ -1,2 +1,2 @@
+pub mod executor;
 pub mod route;
-pub mod executor;
\ No newline at end of file
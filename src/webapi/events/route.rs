@@ -1,25 +1,18 @@
-use super::super::traits;
+use super::super::routes::path;
+use my_rust_hyper_derive::ObjectType;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Deserialize, Serialize, Clone, JsonSchema, ObjectType)]
+#[event(service = "webapi", description = "service unavailable event")]
+#[subscription(service = "router", path = path::ROUTER_EVENT_ON_SERVICE_UNAVAILABLE)]
 pub struct OnServiceUnavailable {
     pub services: Vec<String>,
 }
 
-impl traits::ObjectType for OnServiceUnavailable {
-    fn get_type_name() -> &'static str {
-        "OnServiceUnavailable"
-    }
-}
-
-#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Deserialize, Serialize, Clone, JsonSchema, ObjectType)]
+#[event(service = "router", description = "route update event")]
+#[subscription(service = "webapi", path = path::EVENT_ON_ROUTE_UPDATE)]
 pub struct OnRouteUpdate {
     pub services: Vec<String>,
 }
-
-impl traits::ObjectType for OnRouteUpdate {
-    fn get_type_name() -> &'static str {
-        "OnRouteUpdate"
-    }
-}
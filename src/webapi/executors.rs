@@ -1,11 +1,15 @@
-use super::{access, connectors, entities, errors, providers, router, traits, workers};
+use super::{
+    access, connectors, context, entities, errors, providers, router, tracer, traits, validator,
+    workers,
+};
 use bytes::buf::ext::BufExt;
+use bytes::Bytes;
+use chrono::Utc;
 use hyper::Body;
 use serde::{de, ser};
 use std::collections::HashMap;
 //use std::str::FromStr;
 use std::sync::Arc;
-use tokio::sync::mpsc;
 use uuid::Uuid;
 
 #[derive(Debug, PartialEq, Copy, Clone, ToString)]
@@ -20,7 +24,9 @@ pub struct CommandExecutor {
     ac: Arc<access::AccessChecker>,
     rt: Arc<router::Router>,
     hp: providers::HttpProvider,
-    _cs: mpsc::Sender<workers::SignalCode>,
+    tr: Arc<tracer::Tracer>,
+    _cs: workers::SignalSender,
+    instance_id: String, //owner of sagas run by this process
 }
 
 impl CommandExecutor {
@@ -28,24 +34,32 @@ impl CommandExecutor {
         dc: Arc<connectors::DataConnector>,
         ac: Arc<access::AccessChecker>,
         rt: Arc<router::Router>,
-        cs: mpsc::Sender<workers::SignalCode>,
+        tr: Arc<tracer::Tracer>,
+        cs: workers::SignalSender,
     ) -> connectors::Result<CommandExecutor> {
+        let hp = providers::HttpProvider::new(rt.metrics.clone()).await?;
         Ok(CommandExecutor {
             dc: dc,
             ac: ac,
             rt: rt,
-            hp: providers::HttpProvider::new().await?,
+            hp: hp,
+            tr: tr,
             _cs: cs,
+            instance_id: Uuid::new_v4().to_hyphenated().to_string(),
         })
     }
 
+    pub fn get_instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
     pub async fn send_signal(&self, signal_code: workers::SignalCode) -> connectors::Result<()> {
         let mut s = self._cs.clone();
         match s.send(signal_code).await {
             Ok(_) => Ok({}),
             Err(e) => {
                 error!("command executor: {}", e);
-                return Err(errors::SignalSendError.into());
+                return Err(errors::Error::new(errors::ErrorKind::SignalSend).into());
             }
         }
     }
@@ -68,7 +82,7 @@ impl CommandExecutor {
                 state_changed_at: c[0].state_changed_at,
             })
         } else {
-            Err(errors::AsyncCommandNotFoundError.into())
+            Err(errors::Error::with_context(errors::ErrorKind::AsyncCommandNotFound, id).into())
         }
     }
 
@@ -88,7 +102,7 @@ impl CommandExecutor {
                 state_changed_at: c[0].state_changed_at,
             })
         } else {
-            Err(errors::AsyncCommandNotFoundError.into())
+            Err(errors::Error::with_context(errors::ErrorKind::AsyncCommandNotFound, id).into())
         }
     }
 
@@ -103,56 +117,87 @@ impl CommandExecutor {
             .await?;
         if sac.len() == 1 {
             let command = self.rt.get_command(&sac[0].object_type)?;
-            let cid = Uuid::new_v4().to_hyphenated().to_string();
-            if command
-                .path
-                .contains_key(&providers::Proto::http.to_string())
-            {
-                let mut prop = HashMap::<&str, &str>::new();
-                prop.insert("correlation_id", &cid);
-                prop.insert("async_command_id", &id);
-                let token = self
-                    .ac
-                    .get_client_basic_authorization_token(command.service_name.as_ref().unwrap())?;
-                let sp = self
-                    .rt
-                    .get_service_path(
-                        &command.service_name.as_ref().unwrap(),
-                        providers::Proto::http,
-                    )?
-                    .state;
-                let response = self
-                    .hp
-                    .execute(
-                        &self
-                            .rt
-                            .get_service_path(
-                                &command.service_name.as_ref().unwrap(),
-                                providers::Proto::http,
-                            )?
-                            .state,
-                        prop,
-                        token,
-                        Body::empty(),
-                    )
-                    .await?;
-                let reader = hyper::body::aggregate(response).await?.reader();
-                let reply: Option<entities::executor::AsyncCommandState> =
-                    serde_json::from_reader(reader).unwrap_or(None);
-                if reply.is_some() {
-                    Ok(reply.unwrap())
-                } else {
-                    Err(errors::BadReplyCommandError.into())
-                }
+            self.call_state(&command, id).await
+        } else {
+            Err(errors::Error::with_context(errors::ErrorKind::AsyncCommandNotFound, id).into())
+        }
+    }
+
+    async fn call_state(
+        &self,
+        command: &entities::route::CommandRoute,
+        id: &str,
+    ) -> connectors::Result<entities::executor::AsyncCommandState> {
+        let cid = Uuid::new_v4().to_hyphenated().to_string();
+        if command
+            .path
+            .contains_key(&providers::Proto::http.to_string())
+        {
+            let mut prop = HashMap::<&str, &str>::new();
+            prop.insert("correlation_id", &cid);
+            prop.insert("async_command_id", &id);
+            let token = self
+                .ac
+                .get_client_basic_authorization_token(command.service_name.as_ref().unwrap())?;
+            let service_name = command.service_name.as_ref().unwrap();
+            let sp = self
+                .rt
+                .get_service_path(service_name, providers::Proto::http)?
+                .state;
+            let response = self
+                .rt
+                .breaker
+                .call(
+                    service_name,
+                    self.hp.execute(&sp, prop, token, Body::empty()),
+                )
+                .await?;
+            let reader = hyper::body::aggregate(response).await?.reader();
+            let reply: Option<entities::executor::AsyncCommandState> =
+                serde_json::from_reader(reader).unwrap_or(None);
+            if reply.is_some() {
+                Ok(reply.unwrap())
             } else {
-                Err(errors::UnsupportedProtoError.into())
+                Err(errors::Error::new(errors::ErrorKind::BadReplyCommand).into())
             }
         } else {
-            Err(errors::AsyncCommandNotFoundError.into())
+            Err(errors::Error::new(errors::ErrorKind::UnsupportedProto).into())
+        }
+    }
+
+    fn validate_reply(&self, reply_type: &str, body: &[u8]) -> connectors::Result<()> {
+        let root = match self.rt.schema.get(reply_type) {
+            Some(root) => root,
+            None => return Ok(()),
+        };
+        let violations = match serde_json::from_slice::<serde_json::Value>(body) {
+            Ok(value) => validator::validate(root, &value),
+            Err(e) => {
+                return Err(errors::Error::with_context(
+                    errors::ErrorKind::BadReplyCommand,
+                    &format!("{} {}", reply_type, e),
+                )
+                .into())
+            }
+        };
+        if violations.len() > 0 {
+            let context = violations
+                .iter()
+                .map(|v| format!("{} {}", v.path, v.message))
+                .collect::<Vec<String>>()
+                .join(", ");
+            error!("{} reply schema violations: {}", reply_type, context);
+            return Err(errors::Error::with_context(
+                errors::ErrorKind::BadReplyCommand,
+                &format!("{} {}", reply_type, context),
+            )
+            .into());
         }
+        Ok(())
     }
 
-    pub async fn call<T, R>(&self, request: T) -> connectors::Result<R>
+    //client span is child of request context, inbound correlation id is passed through
+    pub async fn call<T, R>(&self, ctx: &context::Context, request: T) -> connectors::Result<R>
     where
         T: ser::Serialize,
         T: traits::ObjectType,
@@ -164,34 +209,170 @@ impl CommandExecutor {
             .path
             .contains_key(&providers::Proto::http.to_string())
         {
-            let cid = Uuid::new_v4().to_hyphenated().to_string();
+            let span_ctx = ctx.child(T::get_type_name(), context::SpanKind::Client);
             let mut prop = HashMap::<&str, &str>::new();
-            prop.insert("correlation_id", &cid);
+            prop.insert("correlation_id", &ctx.correlation_id);
             prop.insert("object_type", T::get_type_name());
+            if let Some(service_name) = self.rt.get_service_name() {
+                prop.insert("service_name", service_name);
+            }
             let token = self
                 .ac
                 .get_client_basic_authorization_token(command.service_name.as_ref().unwrap())?;
-            let response = self
-                .hp
-                .execute(
-                    &command
-                        .path
-                        .get(&providers::Proto::http.to_string())
-                        .unwrap(),
-                    prop,
-                    token,
-                    Body::from(serde_json::to_string(&request).unwrap()),
-                )
-                .await?;
-            let reader = hyper::body::aggregate(response).await?.reader();
-            let reply: Option<R> = serde_json::from_reader(reader).unwrap_or(None);
-            if reply.is_some() {
-                Ok(reply.unwrap())
-            } else {
-                Err(errors::BadReplyCommandError.into())
-            }
+            let res = self
+                .call_http::<T, R>(&command, prop, token, &request, &span_ctx)
+                .await;
+            self.tr.export(&span_ctx, None, res.is_err());
+            res
         } else {
-            Err(errors::UnsupportedProtoError.into())
+            Err(errors::Error::new(errors::ErrorKind::UnsupportedProto).into())
+        }
+    }
+
+    async fn call_http<T, R>(
+        &self,
+        command: &entities::route::CommandRoute,
+        prop: HashMap<&str, &str>,
+        token: String,
+        request: &T,
+        span_ctx: &context::Context,
+    ) -> connectors::Result<R>
+    where
+        T: ser::Serialize,
+        T: traits::ObjectType,
+        R: for<'de> de::Deserialize<'de>,
+        R: traits::ObjectType,
+    {
+        //reading of reply body is limited by call timeout too
+        let bytes = self
+            .rt
+            .breaker
+            .call(command.service_name.as_ref().unwrap(), async {
+                let response = self
+                    .hp
+                    .execute_with_trace(
+                        &command
+                            .path
+                            .get(&providers::Proto::http.to_string())
+                            .unwrap(),
+                        prop,
+                        token,
+                        Body::from(serde_json::to_string(request).unwrap()),
+                        Some(&span_ctx.span.to_traceparent()),
+                    )
+                    .await?;
+                Ok::<Bytes, Box<dyn std::error::Error>>(hyper::body::to_bytes(response).await?)
+            })
+            .await?;
+        if self.rt.validation.reply {
+            self.validate_reply(R::get_type_name(), &bytes)?;
+        }
+        let reply: Option<R> = serde_json::from_slice(&bytes).unwrap_or(None);
+        if reply.is_some() {
+            Ok(reply.unwrap())
+        } else {
+            Err(errors::Error::new(errors::ErrorKind::BadReplyCommand).into())
+        }
+    }
+
+    //untyped call, request is checked by schema of command, reply is any json
+    pub async fn call_value(
+        &self,
+        ctx: &context::Context,
+        object_type: &str,
+        exec_mode: ExecMode,
+        request: &serde_json::Value,
+    ) -> connectors::Result<serde_json::Value> {
+        let command = self.rt.get_command(object_type)?;
+        if exec_mode != ExecMode::Any
+            && command.exec_mode != ExecMode::Any.to_string()
+            && command.exec_mode != exec_mode.to_string()
+        {
+            return Err(errors::Error::with_context(
+                errors::ErrorKind::UnsupportedExecMode,
+                &format!("{} {}", object_type, exec_mode.to_string()),
+            )
+            .into());
+        }
+        let root = self.rt.get_command_schema(object_type).await?;
+        let violations = validator::validate(&root, request);
+        if violations.len() > 0 {
+            let context = violations
+                .iter()
+                .map(|v| format!("{} {}", v.path, v.message))
+                .collect::<Vec<String>>()
+                .join(", ");
+            return Err(errors::Error::with_context(
+                errors::ErrorKind::BadRequestCommand,
+                &format!("{} {}", object_type, context),
+            )
+            .into());
+        }
+        let to = match command.path.get(&providers::Proto::http.to_string()) {
+            Some(to) => to,
+            None => return Err(errors::Error::new(errors::ErrorKind::UnsupportedProto).into()),
+        };
+        let span_ctx = ctx.child(object_type, context::SpanKind::Client);
+        let mode = exec_mode.to_string();
+        let mut prop = HashMap::<&str, &str>::new();
+        prop.insert("correlation_id", &ctx.correlation_id);
+        prop.insert("object_type", object_type);
+        if exec_mode != ExecMode::Any {
+            prop.insert("exec_mode", &mode);
+        }
+        if let Some(service_name) = self.rt.get_service_name() {
+            prop.insert("service_name", service_name);
+        }
+        let service_name = command.service_name.as_ref().unwrap();
+        let token = self.ac.get_client_basic_authorization_token(service_name)?;
+        let res = self
+            .rt
+            .breaker
+            .call(service_name, async {
+                let response = self
+                    .hp
+                    .execute_with_trace(
+                        to,
+                        prop,
+                        token,
+                        Body::from(request.to_string()),
+                        Some(&span_ctx.span.to_traceparent()),
+                    )
+                    .await?;
+                Ok::<Bytes, Box<dyn std::error::Error>>(hyper::body::to_bytes(response).await?)
+            })
+            .await
+            .and_then(|bytes| {
+                if self.rt.validation.reply {
+                    self.validate_reply(&command.reply_type, &bytes)?;
+                }
+                match serde_json::from_slice::<serde_json::Value>(&bytes) {
+                    Ok(reply) => Ok(reply),
+                    Err(e) => Err(errors::Error::with_context(
+                        errors::ErrorKind::BadReplyCommand,
+                        &format!("{} {}", command.reply_type, e),
+                    )
+                    .into()),
+                }
+            });
+        self.tr.export(&span_ctx, None, res.is_err());
+        let reply = res?;
+        //async command id is correlation id of call, its state is polled by id
+        if exec_mode == ExecMode::Async {
+            self.dc
+                .sended_async_command
+                .add(vec![entities::executor::SendedAsyncCommand {
+                    id: ctx.correlation_id.clone(),
+                    object_type: object_type.to_string(),
+                    service_name: service_name.to_string(),
+                    state: entities::executor::CommandSystemState::Initial.to_string(),
+                    change_state_event: 0,
+                    added_at: Utc::now(),
+                    state_changed_at: Utc::now(),
+                    history: None,
+                }])
+                .await?;
         }
+        Ok(reply)
     }
 }
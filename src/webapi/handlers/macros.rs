@@ -86,7 +86,7 @@ macro_rules! get_error_reply {
     ($ec:expr, $en:expr) => {
         replies::common::StandardReply {
             error_code: $ec.clone(),
-            error_name: Some($en.get(&$ec.to_string()).unwrap().clone()),
+            error_name: Some(errors::get_error_name(&$en, &$ec)),
         }
     };
 }
@@ -97,7 +97,7 @@ macro_rules! get_error_reply_events {
         (
             replies::common::StandardReply {
                 error_code: $ec.clone(),
-                error_name: Some($en.get(&$ec.to_string()).unwrap().clone()),
+                error_name: Some(errors::get_error_name(&$en, &$ec)),
             },
             None,
         )
@@ -109,7 +109,7 @@ macro_rules! get_error_add_int_ids_reply {
     ($ec:expr, $en:expr) => {
         replies::common::AddIntIdsReply {
             error_code: $ec.clone(),
-            error_name: Some($en.get(&$ec.to_string()).unwrap().clone()),
+            error_name: Some(errors::get_error_name(&$en, &$ec)),
             ids: None,
         }
     };
@@ -121,7 +121,7 @@ macro_rules! get_error_add_int_ids_reply_events {
         (
             replies::common::AddIntIdsReply {
                 error_code: $ec.clone(),
-                error_name: Some($en.get(&$ec.to_string()).unwrap().clone()),
+                error_name: Some(errors::get_error_name(&$en, &$ec)),
                 ids: None,
             },
             None,
@@ -131,25 +131,25 @@ macro_rules! get_error_add_int_ids_reply_events {
 
 #[macro_export]
 macro_rules! get_error_add_str_ids_reply {
+    ($ec:expr, $en:expr) => {
+        replies::common::AddStrIdsReply {
+            error_code: $ec.clone(),
+            error_name: Some(errors::get_error_name(&$en, &$ec)),
+            ids: None,
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! get_error_add_str_ids_reply_events {
     ($ec:expr, $en:expr) => {
         (
             replies::common::AddStrIdsReply {
                 error_code: $ec.clone(),
-                error_name: Some($en.get(&$ec.to_string()).unwrap().clone()),
+                error_name: Some(errors::get_error_name(&$en, &$ec)),
                 ids: None,
             },
             None,
         )
     };
 }
-
-#[macro_export]
-macro_rules! get_error_add_str_ids_reply_events {
-    ($ec:expr, $en:expr) => {
-        replies::common::AddStrIdsReply {
-            error_code: $ec.clone(),
-            error_name: Some($en.get(&$ec.as_isize()).unwrap().clone()),
-            ids: None,
-        }
-    };
-}
@@ -1,6 +1,6 @@
-#[macro_use] mod macros;
+#[macro_use]
+mod macros;
 
-pub mod route;
 pub mod executor;
+pub mod route;
 pub mod usr;
-pub mod car;
@@ -1,4 +1,5 @@
 use super::super::{commands, connectors, entities, errors, events, replies, router};
+use chrono::Utc;
 
 pub async fn get(
     dc: &connectors::DataConnector,
@@ -16,7 +17,7 @@ pub async fn get(
             let ec = errors::ErrorCode::DatabaseError;
             Ok(replies::route::GetRouteReply {
                 error_code: ec.clone(),
-                error_name: Some(dc.error.get(&ec.to_string()).unwrap().clone()),
+                error_name: Some(errors::get_error_name(&dc.error, &ec)),
                 url: None,
                 items: None,
             })
@@ -40,7 +41,7 @@ pub async fn get_command(
             let ec = errors::ErrorCode::DatabaseError;
             Ok(replies::route::GetServiceCommandReply {
                 error_code: ec.clone(),
-                error_name: Some(dc.error.get(&ec.to_string()).unwrap().clone()),
+                error_name: Some(errors::get_error_name(&dc.error, &ec)),
                 url: None,
                 items: None,
             })
@@ -64,7 +65,7 @@ pub async fn get_event(
             let ec = errors::ErrorCode::DatabaseError;
             Ok(replies::route::GetServiceEventReply {
                 error_code: ec.clone(),
-                error_name: Some(dc.error.get(&ec.to_string()).unwrap().clone()),
+                error_name: Some(errors::get_error_name(&dc.error, &ec)),
                 url: None,
                 items: None,
             })
@@ -88,7 +89,7 @@ pub async fn get_subscription(
             let ec = errors::ErrorCode::DatabaseError;
             Ok(replies::route::GetServiceSubscriptionReply {
                 error_code: ec.clone(),
-                error_name: Some(dc.error.get(&ec.to_string()).unwrap().clone()),
+                error_name: Some(errors::get_error_name(&dc.error, &ec)),
                 url: None,
                 items: None,
             })
@@ -96,41 +97,116 @@ pub async fn get_subscription(
     }
 }
 
-pub fn get_helth() -> connectors::Result<entities::route::ServiceHelth> {
-    Ok(entities::route::ServiceHelth {
-        state: entities::route::ServiceState::Alive,
-    })
+pub fn get_helth(rt: &router::Router) -> connectors::Result<entities::route::ServiceHelth> {
+    Ok(rt.helth.get_liveness())
 }
 
 pub fn get_error(
     dc: &connectors::DataConnector,
     error_code: &str,
+    lang: Option<&str>,
 ) -> connectors::Result<entities::error::Error> {
-    if dc.error.contains_key(error_code) {
-        Ok(entities::error::Error {
+    let lang = dc.get_error_lang(lang);
+    match dc.get_error_names(Some(lang)).get(error_code) {
+        Some(name) => Ok(entities::error::Error {
             error_code: error_code.to_string(),
-            error_name: dc.error.get(error_code).unwrap().clone(),
-        })
-    } else {
-        Err(errors::UnknownErrorCodeError.into())
+            lang: lang.to_string(),
+            error_name: name.clone(),
+        }),
+        //partial languages fallback to default name
+        None => match dc.error.get(error_code) {
+            Some(name) => Ok(entities::error::Error {
+                error_code: error_code.to_string(),
+                lang: connectors::DEFAULT_LANG.to_string(),
+                error_name: name.clone(),
+            }),
+            None => Err(errors::Error::with_context(
+                errors::ErrorKind::UnknownErrorCode,
+                error_code,
+            )
+            .into()),
+        },
+    }
+}
+
+pub fn get_errors(
+    dc: &connectors::DataConnector,
+    lang: Option<&str>,
+) -> connectors::Result<Vec<entities::error::Error>> {
+    let mut items = Vec::<entities::error::Error>::new();
+    for item in dc.error.keys() {
+        items.push(get_error(dc, item, lang)?);
+    }
+    items.sort_by(|a, b| a.error_code.cmp(&b.error_code));
+    Ok(items)
+}
+
+pub async fn get_service_error(
+    rt: &router::Router,
+    cmd: commands::route::GetServiceError,
+) -> connectors::Result<replies::route::GetServiceErrorReply> {
+    let items = rt
+        .get_service_error(cmd.services, cmd.lang.as_ref().map(|l| l.as_str()))
+        .await?;
+    Ok(replies::route::GetServiceErrorReply {
+        error_code: errors::ErrorCode::ReplyOk,
+        error_name: None,
+        url: None,
+        items: Some(items),
+    })
+}
+
+pub async fn get_service_schema(
+    rt: &router::Router,
+    cmd: commands::route::GetServiceSchema,
+) -> connectors::Result<replies::route::GetServiceSchemaReply> {
+    let items = match cmd.object_type {
+        Some(object_type) => vec![
+            rt.get_service_schema(&cmd.service_name, &object_type)
+                .await?,
+        ],
+        None => rt.get_service_schema_list(&cmd.service_name).await?,
+    };
+    Ok(replies::route::GetServiceSchemaReply {
+        error_code: errors::ErrorCode::ReplyOk,
+        error_name: None,
+        url: None,
+        items: Some(items),
+    })
+}
+
+//stored state is not maintained, health is known from calls of service
+pub fn get_service_state(rt: &router::Router, name: &str) -> entities::route::ServiceState {
+    //todo: remote call helth service
+    //open breaker fails calls of service
+    match rt.breaker.get_state(name) {
+        entities::route::BreakerState::Open => entities::route::ServiceState::Unavailable,
+        _ => entities::route::ServiceState::Alive,
     }
 }
 
 pub async fn get_service(
     dc: &connectors::DataConnector,
+    rt: &router::Router,
     cmd: commands::route::GetService,
 ) -> connectors::Result<replies::route::GetServiceReply> {
     match dc.route.get_service(cmd.names).await {
         Ok(mut items) => {
             for mut item in &mut items {
-                //todo: remote call helth service
-                item.state = entities::route::ServiceState::Alive.to_string();
+                item.state = get_service_state(rt, &item.name).to_string();
             }
+            let breakers = rt
+                .breaker
+                .get_breakers()
+                .into_iter()
+                .filter(|b| items.iter().any(|i| i.name == b.service_name))
+                .collect();
             Ok(replies::route::GetServiceReply {
                 error_code: errors::ErrorCode::ReplyOk,
                 error_name: None,
                 url: None,
                 items: Some(items),
+                breakers: Some(breakers),
             })
         }
         Err(e) => {
@@ -138,9 +214,10 @@ pub async fn get_service(
             let ec = errors::ErrorCode::DatabaseError;
             Ok(replies::route::GetServiceReply {
                 error_code: ec.clone(),
-                error_name: Some(dc.error.get(&ec.to_string()).unwrap().clone()),
+                error_name: Some(errors::get_error_name(&dc.error, &ec)),
                 url: None,
                 items: None,
+                breakers: None,
             })
         }
     }
@@ -153,16 +230,23 @@ pub async fn add(
     replies::common::AddStrIdsReply,
     Option<Vec<events::route::OnRouteUpdate>>,
 )> {
-    let (result, ids) = dc.route.add(cmd.items).await?;
+    let services: Vec<String> = cmd
+        .items
+        .iter()
+        .map(|r| r.service_name.clone().unwrap_or_default())
+        .collect();
+    let (result, ids) = dc.route.add(cmd.items, cmd.lease).await?;
     if result == errors::ErrorCode::ReplyOk {
-        let v1 = ids.unwrap();
-        let v2 = v1.clone();
-        Ok(get_ok_add_str_ids_reply_events!(
-            v1,
-            Some(vec![events::route::OnRouteUpdate { services: v2 }])
-        ))
+        //subscribers are not notified of same route registered again
+        let changed = ids.unwrap();
+        let events = if changed.len() > 0 {
+            Some(vec![events::route::OnRouteUpdate { services: changed }])
+        } else {
+            None
+        };
+        Ok(get_ok_add_str_ids_reply_events!(services, events))
     } else {
-        Ok(get_error_add_str_ids_reply!(&result, dc.error))
+        Ok(get_error_add_str_ids_reply_events!(&result, dc.error))
     }
 }
 
@@ -185,6 +269,32 @@ pub async fn remove(
     }
 }
 
+pub async fn renew(
+    dc: &connectors::DataConnector,
+    cmd: commands::route::RenewRoute,
+) -> connectors::Result<replies::common::StandardReply> {
+    let result: errors::ErrorCode = dc.route.renew(cmd.services).await?;
+    if result == errors::ErrorCode::ReplyOk {
+        Ok(get_ok_reply!())
+    } else {
+        Ok(get_error_reply!(&result, dc.error))
+    }
+}
+
+//removes routes of lapsed leases, router turns OnServiceUnavailable into OnRouteUpdate
+pub async fn expire(
+    dc: &connectors::DataConnector,
+) -> connectors::Result<Option<Vec<events::route::OnServiceUnavailable>>> {
+    let services = dc.route.expire(Utc::now()).await?;
+    if services.len() == 0 {
+        return Ok(None);
+    }
+    warn!("routes of {:?} are removed on expired lease", services);
+    Ok(Some(vec![events::route::OnServiceUnavailable {
+        services: services,
+    }]))
+}
+
 pub async fn on_service_unavailable(
     _dc: &connectors::DataConnector,
     _rt: &router::Router,
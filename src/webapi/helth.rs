@@ -0,0 +1,114 @@
+use super::{connectors, entities::route, router, workers};
+use chrono::{DateTime, Utc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+pub struct HelthChecker {
+    started_at: DateTime<Utc>,
+    is_shutting_down: AtomicBool,
+    workers: RwLock<Vec<Arc<workers::WorkerState>>>,
+}
+
+fn get_component(name: &str, res: connectors::Result<()>) -> route::ComponentHelth {
+    match res {
+        Ok(_) => route::ComponentHelth {
+            name: name.to_string(),
+            state: route::ServiceState::Alive,
+            message: None,
+        },
+        Err(e) => {
+            warn!("readiness {}: {}", name, e);
+            route::ComponentHelth {
+                name: name.to_string(),
+                state: route::ServiceState::Unavailable,
+                message: Some(e.to_string()),
+            }
+        }
+    }
+}
+
+impl HelthChecker {
+    pub fn new() -> HelthChecker {
+        HelthChecker {
+            started_at: Utc::now(),
+            is_shutting_down: AtomicBool::new(false),
+            workers: RwLock::new(Vec::new()),
+        }
+    }
+
+    //spawned workers only, none in tests
+    pub fn add_worker(&self, state: Arc<workers::WorkerState>) {
+        self.workers.write().unwrap().push(state);
+    }
+
+    //not ready while requests are drained
+    pub fn set_shutting_down(&self) {
+        self.is_shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    pub fn get_uptime(&self) -> i64 {
+        (Utc::now() - self.started_at).num_seconds()
+    }
+
+    //process answers, nothing else is checked
+    pub fn get_liveness(&self) -> route::ServiceHelth {
+        route::ServiceHelth {
+            state: route::ServiceState::Alive,
+            uptime: self.get_uptime(),
+        }
+    }
+
+    pub async fn get_readiness(
+        &self,
+        dc: &connectors::DataConnector,
+        rt: &router::Router,
+    ) -> route::ServiceReadiness {
+        let mut components = vec![get_component("database", dc.ping().await)];
+        if self.is_shutting_down.load(Ordering::SeqCst) {
+            components.push(route::ComponentHelth {
+                name: "service".to_string(),
+                state: route::ServiceState::Unavailable,
+                message: Some("shutting down".to_string()),
+            });
+        }
+        if !rt.is_local {
+            components.push(get_component("router", rt.ping_remote_router().await));
+        }
+        let workers: Vec<Arc<workers::WorkerState>> = self.workers.read().unwrap().clone();
+        for w in workers {
+            let message = format!("{} of {} signals pending", w.pending(), w.capacity);
+            let state = if !w.is_running() || w.is_saturated() {
+                warn!(
+                    "readiness {}: running {}, {}",
+                    w.name,
+                    w.is_running(),
+                    message
+                );
+                route::ServiceState::Unavailable
+            } else {
+                route::ServiceState::Alive
+            };
+            components.push(route::ComponentHelth {
+                name: w.name.to_string(),
+                state: state,
+                message: Some(if w.is_running() {
+                    message
+                } else {
+                    "not running".to_string()
+                }),
+            });
+        }
+        route::ServiceReadiness {
+            state: if components
+                .iter()
+                .all(|c| c.state == route::ServiceState::Alive)
+            {
+                route::ServiceState::Alive
+            } else {
+                route::ServiceState::Unavailable
+            },
+            uptime: self.get_uptime(),
+            components: components,
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use super::entities::idempotency::{IdempotencyKey, IdempotentReply, IdempotentState};
+use super::{connectors, errors, settings};
+use chrono::Utc;
+use std::time::{Duration, Instant};
+use tokio::time;
+
+//replayed reply is marked for caller
+pub const REPLAYED_HEADER: &str = "idempotent-replayed";
+
+const DEFAULT_RETENTION: u64 = 86400; //seconds
+const DEFAULT_WAIT_TIMEOUT: u64 = 5; //seconds
+const WAIT_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, PartialEq)]
+pub enum Begin {
+    New,                 //execute request, then complete or cancel key
+    Replay(u16, String), //status and body of original reply
+    InProgress,          //original request is not finished after wait timeout
+}
+
+pub struct IdempotencyChecker {
+    retention: chrono::Duration,
+    wait_timeout: Duration,
+}
+
+impl IdempotencyChecker {
+    pub fn new(idempotency: Option<settings::Idempotency>) -> IdempotencyChecker {
+        let (retention, wait_timeout) = match idempotency {
+            Some(i) => (i.retention, i.wait_timeout),
+            None => (DEFAULT_RETENTION, DEFAULT_WAIT_TIMEOUT),
+        };
+        IdempotencyChecker {
+            retention: chrono::Duration::seconds(retention as i64),
+            wait_timeout: Duration::from_secs(wait_timeout),
+        }
+    }
+
+    //takes key, or waits while original request with same key is in progress
+    pub async fn begin(
+        &self,
+        dc: &connectors::DataConnector,
+        key: &IdempotencyKey,
+    ) -> connectors::Result<Begin> {
+        let deadline = Instant::now() + self.wait_timeout;
+        //original request is abandoned if it was not finished in wait timeout before retry
+        let abandoned_before =
+            Utc::now() - chrono::Duration::seconds(self.wait_timeout.as_secs() as i64);
+        loop {
+            let item = IdempotentReply {
+                caller: key.caller.clone(),
+                object_type: key.object_type.clone(),
+                correlation_id: key.correlation_id.clone(),
+                state: IdempotentState::InProgress.to_string(),
+                status: 0,
+                reply_body: String::new(),
+                added_at: Utc::now(),
+            };
+            match dc.idempotency.get_or_add(item).await? {
+                None => return Ok(Begin::New),
+                Some(r) if r.state == IdempotentState::Completed.to_string() => {
+                    return Ok(Begin::Replay(r.status as u16, r.reply_body))
+                }
+                Some(r) if r.added_at < abandoned_before => {
+                    if dc.idempotency.take_over(key, abandoned_before).await? {
+                        warn!(
+                            "correlation id {} {} abandoned request is taken over",
+                            key.correlation_id, key.object_type
+                        );
+                        return Ok(Begin::New);
+                    }
+                }
+                Some(_) if Instant::now() >= deadline => return Ok(Begin::InProgress),
+                Some(_) => time::delay_for(WAIT_INTERVAL).await,
+            }
+        }
+    }
+
+    //stored replies are removed by worker after retention
+    pub async fn remove_expired(&self, dc: &connectors::DataConnector) -> connectors::Result<()> {
+        let count = dc
+            .idempotency
+            .remove_expired(Utc::now() - self.retention)
+            .await?;
+        if count > 0 {
+            debug!("{} expired idempotent replies removed", count);
+        }
+        Ok(())
+    }
+
+    //reply is returned to repeated requests until retention expires
+    pub async fn complete(
+        &self,
+        dc: &connectors::DataConnector,
+        key: &IdempotencyKey,
+        status: u16,
+        reply_body: &str,
+    ) -> connectors::Result<()> {
+        match dc
+            .idempotency
+            .complete(key, status as i32, reply_body)
+            .await?
+        {
+            errors::ErrorCode::ReplyOk => {}
+            ec => warn!(
+                "correlation id {} complete idempotent reply: {}",
+                key.correlation_id,
+                ec.to_string()
+            ),
+        }
+        Ok(())
+    }
+
+    //failed request frees key, so repeated one is executed again
+    pub async fn cancel(
+        &self,
+        dc: &connectors::DataConnector,
+        key: &IdempotencyKey,
+    ) -> connectors::Result<()> {
+        match dc.idempotency.remove(key).await? {
+            errors::ErrorCode::ReplyOk => {}
+            ec => warn!(
+                "correlation id {} cancel idempotent reply: {}",
+                key.correlation_id,
+                ec.to_string()
+            ),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use super::{connectors, settings};
+use chrono::{Duration, Utc};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+const DAY_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug, PartialEq)]
+pub enum Check {
+    Allowed,
+    Limited(u64), //seconds to retry after
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+pub struct RateLimiter {
+    rate_limit: Option<settings::RateLimit>,
+    //by caller and object type, kept in memory, refilled on restart
+    buckets: Mutex<HashMap<(String, String), Bucket>>,
+    cleaned_day: Mutex<String>,
+}
+
+//seconds until next utc day, at least one
+fn get_seconds_to_next_day() -> u64 {
+    let now = Utc::now();
+    let next_day = (now.date() + Duration::days(1)).and_hms(0, 0, 0);
+    (next_day - now).num_seconds().max(1) as u64
+}
+
+impl RateLimiter {
+    pub fn new(rate_limit: Option<settings::RateLimit>) -> RateLimiter {
+        RateLimiter {
+            rate_limit: rate_limit,
+            buckets: Mutex::new(HashMap::new()),
+            cleaned_day: Mutex::new(String::new()),
+        }
+    }
+
+    //route rule replaces default one, none if limits are disabled
+    pub fn get_rule(&self, object_type: &str) -> Option<&settings::RateLimitRule> {
+        self.rate_limit
+            .as_ref()
+            .map(|r| r.route.get(object_type).unwrap_or(&r.default))
+    }
+
+    //takes token, refilled with rate since last request
+    fn take_token(&self, rule: &settings::RateLimitRule, caller: &str, object_type: &str) -> Check {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets
+            .entry((caller.to_string(), object_type.to_string()))
+            .or_insert(Bucket {
+                tokens: rule.burst as f64,
+                updated_at: now,
+            });
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rule.rate).min(rule.burst as f64);
+        bucket.updated_at = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Check::Allowed
+        } else {
+            Check::Limited(((1.0 - bucket.tokens) / rule.rate).ceil().max(1.0) as u64)
+        }
+    }
+
+    //counters of previous days are removed once a day
+    async fn remove_expired_usage(
+        &self,
+        dc: &connectors::DataConnector,
+        day: &str,
+    ) -> connectors::Result<()> {
+        {
+            let mut cleaned_day = self.cleaned_day.lock().unwrap();
+            if cleaned_day.as_str() == day {
+                return Ok(());
+            }
+            *cleaned_day = day.to_string();
+        }
+        let count = dc.quota.remove_expired(day).await?;
+        if count > 0 {
+            debug!("{} expired quota counters removed", count);
+        }
+        Ok(())
+    }
+
+    //rate is checked first, request over rate is not counted in daily quota
+    pub async fn check(
+        &self,
+        dc: &connectors::DataConnector,
+        caller: &str,
+        object_type: &str,
+    ) -> connectors::Result<Check> {
+        let rule = match self.get_rule(object_type) {
+            Some(rule) => *rule,
+            None => return Ok(Check::Allowed),
+        };
+        if let Check::Limited(retry_after) = self.take_token(&rule, caller, object_type) {
+            return Ok(Check::Limited(retry_after));
+        }
+        let quota = match rule.daily_quota {
+            Some(quota) => quota,
+            None => return Ok(Check::Allowed),
+        };
+        let day = Utc::now().format(DAY_FORMAT).to_string();
+        self.remove_expired_usage(dc, &day).await?;
+        if dc.quota.add_usage(caller, object_type, &day).await? > quota {
+            return Ok(Check::Limited(get_seconds_to_next_day()));
+        }
+        Ok(Check::Allowed)
+    }
+}
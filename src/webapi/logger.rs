@@ -0,0 +1,53 @@
+use super::commands;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+//log4rs logger names, route them to own appenders in log settings
+pub const ACCESS_TARGET: &str = "access";
+pub const AUDIT_TARGET: &str = "audit";
+
+//one json line per received request
+#[derive(Serialize)]
+pub struct AccessRecord<'a> {
+    pub time: DateTime<Utc>,
+    pub method: &'a str,
+    pub path: &'a str,
+    pub object_type: Option<&'a str>,
+    pub correlation_id: Option<&'a str>,
+    pub caller: Option<&'a str>,
+    pub usr: Option<&'a str>,
+    pub status: u16,
+    pub latency_ms: f64,
+}
+
+//one json line per mutating command, whatever its result
+#[derive(Serialize)]
+pub struct AuditRecord<'a> {
+    pub time: DateTime<Utc>,
+    pub object_type: &'a str,
+    pub correlation_id: Option<&'a str>,
+    pub caller: Option<&'a str>,
+    pub usr: Option<&'a str>,
+    pub status: u16,
+}
+
+//every mutating command
+pub fn is_audited(object_type: &str) -> bool {
+    commands::is_mutating(object_type)
+}
+
+pub fn access(record: &AccessRecord) {
+    info!(
+        target: ACCESS_TARGET,
+        "{}",
+        serde_json::to_string(record).unwrap()
+    );
+}
+
+pub fn audit(record: &AuditRecord) {
+    info!(
+        target: AUDIT_TARGET,
+        "{}",
+        serde_json::to_string(record).unwrap()
+    );
+}
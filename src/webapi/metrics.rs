@@ -0,0 +1,342 @@
+use super::{connectors, entities::route};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const PREFIX: &str = "webapi";
+//seconds, same as prometheus client defaults
+const BUCKET_COUNT: usize = 11;
+const DURATION_BUCKETS: [f64; BUCKET_COUNT] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; BUCKET_COUNT],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        for (i, le) in DURATION_BUCKETS.iter().enumerate() {
+            if value <= *le {
+                self.buckets[i] += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+//label values in declaration order, sorted output
+type Labels = Vec<String>;
+
+//in-process registry rendered in prometheus text format
+pub struct Metrics {
+    request: Mutex<BTreeMap<Labels, u64>>,
+    request_duration: Mutex<BTreeMap<Labels, Histogram>>,
+    outbound: Mutex<BTreeMap<Labels, u64>>,
+    outbound_duration: Mutex<BTreeMap<Labels, Histogram>>,
+    delivery: Mutex<BTreeMap<Labels, u64>>,
+    delivery_failure: Mutex<BTreeMap<Labels, u64>>,
+    worker_signal: Mutex<BTreeMap<Labels, u64>>,
+}
+
+fn inc(map: &Mutex<BTreeMap<Labels, u64>>, labels: Labels) {
+    *map.lock().unwrap().entry(labels).or_insert(0) += 1;
+}
+
+fn observe(map: &Mutex<BTreeMap<Labels, Histogram>>, labels: Labels, duration: Duration) {
+    map.lock()
+        .unwrap()
+        .entry(labels)
+        .or_default()
+        .observe(duration.as_secs_f64());
+}
+
+//backslash, quote and newline must be escaped in label values
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_labels(names: &[&str], values: &[String], extra: Option<(&str, &str)>) -> String {
+    let mut pairs: Vec<String> = names
+        .iter()
+        .zip(values.iter())
+        .map(|(n, v)| format!("{}=\"{}\"", n, escape(v)))
+        .collect();
+    if let Some((n, v)) = extra {
+        pairs.push(format!("{}=\"{}\"", n, v));
+    }
+    if pairs.len() == 0 {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+fn write_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    writeln!(out, "# HELP {}_{} {}", PREFIX, name, help).unwrap();
+    writeln!(out, "# TYPE {}_{} {}", PREFIX, name, kind).unwrap();
+}
+
+fn write_counter(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    names: &[&str],
+    map: &Mutex<BTreeMap<Labels, u64>>,
+) {
+    write_header(out, name, "counter", help);
+    for (labels, value) in map.lock().unwrap().iter() {
+        writeln!(
+            out,
+            "{}_{}{} {}",
+            PREFIX,
+            name,
+            format_labels(names, labels, None),
+            value
+        )
+        .unwrap();
+    }
+}
+
+fn write_histogram(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    names: &[&str],
+    map: &Mutex<BTreeMap<Labels, Histogram>>,
+) {
+    write_header(out, name, "histogram", help);
+    for (labels, h) in map.lock().unwrap().iter() {
+        for (i, le) in DURATION_BUCKETS.iter().enumerate() {
+            writeln!(
+                out,
+                "{}_{}_bucket{} {}",
+                PREFIX,
+                name,
+                format_labels(names, labels, Some(("le", &le.to_string()))),
+                h.buckets[i]
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            "{}_{}_bucket{} {}",
+            PREFIX,
+            name,
+            format_labels(names, labels, Some(("le", "+Inf"))),
+            h.count
+        )
+        .unwrap();
+        let labels = format_labels(names, labels, None);
+        writeln!(out, "{}_{}_sum{} {}", PREFIX, name, labels, h.sum).unwrap();
+        writeln!(out, "{}_{}_count{} {}", PREFIX, name, labels, h.count).unwrap();
+    }
+}
+
+fn write_gauge(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    names: &[&str],
+    items: Vec<(Labels, f64)>,
+) {
+    write_header(out, name, "gauge", help);
+    for (labels, value) in items {
+        writeln!(
+            out,
+            "{}_{}{} {}",
+            PREFIX,
+            name,
+            format_labels(names, &labels, None),
+            value
+        )
+        .unwrap();
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Metrics {
+        Metrics {
+            request: Mutex::new(BTreeMap::new()),
+            request_duration: Mutex::new(BTreeMap::new()),
+            outbound: Mutex::new(BTreeMap::new()),
+            outbound_duration: Mutex::new(BTreeMap::new()),
+            delivery: Mutex::new(BTreeMap::new()),
+            delivery_failure: Mutex::new(BTreeMap::new()),
+            worker_signal: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    //service_route request, path must be a known route to keep label set bounded
+    pub fn add_request(&self, method: &str, path: &str, status: u16, duration: Duration) {
+        inc(
+            &self.request,
+            vec![method.to_string(), path.to_string(), status.to_string()],
+        );
+        observe(
+            &self.request_duration,
+            vec![method.to_string(), path.to_string()],
+            duration,
+        );
+    }
+
+    //http provider call, status is "error" if no response received
+    pub fn add_outbound(&self, target: &str, status: &str, duration: Duration) {
+        inc(&self.outbound, vec![target.to_string(), status.to_string()]);
+        observe(&self.outbound_duration, vec![target.to_string()], duration);
+    }
+
+    //event publisher delivery to subscription
+    pub fn add_delivery(&self, service_name: &str, object_type: &str, is_ok: bool) {
+        let labels = vec![service_name.to_string(), object_type.to_string()];
+        if !is_ok {
+            inc(&self.delivery_failure, labels.clone());
+        }
+        inc(&self.delivery, labels);
+    }
+
+    pub fn add_worker_signal(&self, worker: &str, signal: &str) {
+        inc(
+            &self.worker_signal,
+            vec![worker.to_string(), signal.to_string()],
+        );
+    }
+
+    pub async fn to_text(
+        &self,
+        dc: &connectors::DataConnector,
+        breakers: Vec<route::ServiceBreaker>,
+    ) -> String {
+        let mut out = String::new();
+        write_counter(
+            &mut out,
+            "http_requests_total",
+            "Received requests by route and status.",
+            &["method", "path", "status"],
+            &self.request,
+        );
+        write_histogram(
+            &mut out,
+            "http_request_duration_seconds",
+            "Received request latency by route.",
+            &["method", "path"],
+            &self.request_duration,
+        );
+        write_counter(
+            &mut out,
+            "outbound_requests_total",
+            "Sent requests by target service address and status.",
+            &["target", "status"],
+            &self.outbound,
+        );
+        write_histogram(
+            &mut out,
+            "outbound_request_duration_seconds",
+            "Sent request latency by target service address.",
+            &["target"],
+            &self.outbound_duration,
+        );
+        write_counter(
+            &mut out,
+            "event_deliveries_total",
+            "Published events by subscriber service and object type.",
+            &["service", "object_type"],
+            &self.delivery,
+        );
+        write_counter(
+            &mut out,
+            "event_delivery_failures_total",
+            "Failed event deliveries by subscriber service and object type.",
+            &["service", "object_type"],
+            &self.delivery_failure,
+        );
+        write_counter(
+            &mut out,
+            "worker_signals_total",
+            "Signals handled by background workers.",
+            &["worker", "signal"],
+            &self.worker_signal,
+        );
+        write_gauge(
+            &mut out,
+            "async_commands",
+            "Stored async commands by direction and state.",
+            &["direction", "state"],
+            get_async_command_count(dc).await,
+        );
+        write_gauge(
+            &mut out,
+            "circuit_breaker_state",
+            "Circuit breakers of called services, 1 for current state.",
+            &["service", "state"],
+            get_breaker_state(breakers),
+        );
+        if let Some((size, idle)) = dc.get_pool_stats() {
+            write_gauge(
+                &mut out,
+                "db_pool_connections",
+                "Database pool connections by state.",
+                &["state"],
+                vec![
+                    (vec!["active".to_string()], (size as usize - idle) as f64),
+                    (vec!["idle".to_string()], idle as f64),
+                ],
+            );
+        }
+        out
+    }
+}
+
+//every state of every breaker, so state change is seen as 1 -> 0
+fn get_breaker_state(breakers: Vec<route::ServiceBreaker>) -> Vec<(Labels, f64)> {
+    let states = [
+        route::BreakerState::Closed,
+        route::BreakerState::Open,
+        route::BreakerState::HalfOpen,
+    ];
+    let mut items = Vec::<(Labels, f64)>::new();
+    for b in breakers {
+        for s in states.iter() {
+            items.push((
+                vec![b.service_name.clone(), s.to_string()],
+                if *s == b.state { 1.0 } else { 0.0 },
+            ));
+        }
+    }
+    items
+}
+
+//queue depth, store errors are logged and skipped
+async fn get_async_command_count(dc: &connectors::DataConnector) -> Vec<(Labels, f64)> {
+    let mut count = HashMap::<(&str, String), usize>::new();
+    match dc.sended_async_command.get(None).await {
+        Ok(items) => {
+            for item in items {
+                *count.entry(("sended", item.state)).or_insert(0) += 1;
+            }
+        }
+        Err(e) => error!("metrics sended async command: {}", e),
+    }
+    match dc.received_async_command.get(None).await {
+        Ok(items) => {
+            for item in items {
+                *count.entry(("received", item.state)).or_insert(0) += 1;
+            }
+        }
+        Err(e) => error!("metrics received async command: {}", e),
+    }
+    let mut items: Vec<(Labels, f64)> = count
+        .into_iter()
+        .map(|((d, s), c)| (vec![d.to_string(), s], c as f64))
+        .collect();
+    items.sort_by(|a, b| a.0.cmp(&b.0));
+    items
+}
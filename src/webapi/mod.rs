@@ -1,23 +1,36 @@
-pub mod entities;
+pub mod access;
 pub mod commands;
-pub mod replies;
+pub mod config;
+pub mod entities;
+pub mod errors;
 pub mod events;
-pub mod traits;
-pub mod schema;
-pub mod access;
+pub mod handlers;
+pub mod openapi;
+pub mod replies;
 pub mod routes;
-pub mod errors;
+pub mod schema;
 pub mod settings;
-pub mod handlers;
+pub mod traits;
+pub mod validator;
 
-#[cfg(not(test))]
 pub mod collections;
 
-pub mod providers;
+pub mod breaker;
 pub mod connectors;
-pub mod router;
-pub mod publishers;
+pub mod context;
 pub mod executors;
+pub mod helth;
+pub mod idempotency;
+pub mod limiter;
+pub mod logger;
+pub mod metrics;
+pub mod providers;
+pub mod publishers;
+pub mod registry;
+pub mod router;
+pub mod saga;
+pub mod server;
+pub mod tracer;
 pub mod workers;
 
 #[cfg(test)]
@@ -0,0 +1,384 @@
+use super::{entities, registry, replies, routes::path, traits::ObjectType};
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+pub const OPENAPI_VERSION: &str = "3.0.3";
+const SCHEMA_REF: &str = "#/components/schemas/";
+const PARAMETER_REF: &str = "#/components/parameters/";
+const SECURITY_SCHEME: &str = "basicAuth";
+
+enum Content {
+    Empty,
+    Any,
+    Object(&'static str),
+    Array(&'static str),
+    ObjectOrArray(&'static str),
+}
+
+struct Operation {
+    path: &'static str,
+    tag: &'static str,
+    description: &'static str,
+    params: Vec<&'static str>,
+    request: Content,
+    reply: Content,
+}
+
+impl Operation {
+    fn new(
+        path: &'static str,
+        tag: &'static str,
+        description: &'static str,
+        request: Content,
+        reply: Content,
+    ) -> Operation {
+        Operation {
+            path: path,
+            tag: tag,
+            description: description,
+            params: vec![],
+            request: request,
+            reply: reply,
+        }
+    }
+
+    fn with_params(mut self, params: Vec<&'static str>) -> Operation {
+        self.params = params;
+        self
+    }
+}
+
+//every post route of routes::service
+fn get_operations() -> Vec<Operation> {
+    let mut items = vec![
+        Operation::new(
+            path::HELTH,
+            "service",
+            "service helth",
+            Content::Empty,
+            Content::Object("ServiceHelth"),
+        ),
+        Operation::new(
+            path::SCHEMA,
+            "service",
+            "json schema of command, reply or event, schema list if object_type is not set",
+            Content::Empty,
+            Content::Any,
+        )
+        .with_params(vec!["object_type"]),
+        Operation::new(
+            path::ERROR,
+            "service",
+            "error name, all error names if error_code is not set",
+            Content::Empty,
+            Content::ObjectOrArray("Error"),
+        )
+        .with_params(vec!["error_code", "lang"]),
+        Operation::new(
+            path::STATE,
+            "service",
+            "received async command state",
+            Content::Empty,
+            Content::Object("AsyncCommandState"),
+        )
+        .with_params(vec!["async_command_id"]),
+        Operation::new(
+            path::USR_ITEMS,
+            "usr",
+            "users",
+            Content::Empty,
+            Content::Array("Usr"),
+        ),
+        Operation::new(
+            path::USR_SIGHN_IN,
+            "usr",
+            "sign in",
+            Content::Empty,
+            Content::Object(replies::common::StandardReply::get_type_name()),
+        ),
+        Operation::new(
+            path::USR_SIGHN_UP,
+            "usr",
+            "sign up",
+            Content::Empty,
+            Content::Object(replies::common::StandardReply::get_type_name()),
+        ),
+    ];
+    for d in registry::get_declarations() {
+        //command route is its own path, first path segment is tag
+        if let registry::Kind::Command {
+            reply,
+            path: Some(path),
+            description,
+            ..
+        } = d.kind
+        {
+            items.push(Operation::new(
+                path,
+                path.split('/').find(|s| s.len() > 0).unwrap_or("service"),
+                description,
+                Content::Object(d.object_type),
+                Content::Object(reply),
+            ));
+        }
+        //events are received by path of subscription
+        let description = match d.kind {
+            registry::Kind::Event { description, .. } => description,
+            _ => "",
+        };
+        for s in d.subscriptions.iter().filter_map(|s| s.path) {
+            if !items.iter().any(|op| op.path == s) {
+                items.push(Operation::new(
+                    s,
+                    "event",
+                    description,
+                    Content::Array(d.object_type),
+                    Content::Object(replies::common::StandardReply::get_type_name()),
+                ));
+            }
+        }
+    }
+    items
+}
+
+//request body type by post route path, array of items for events, none if route has no body
+pub fn make_request_type() -> HashMap<&'static str, Option<(&'static str, bool)>> {
+    get_operations()
+        .into_iter()
+        .map(|op| {
+            let request = match op.request {
+                Content::Object(name) => Some((name, false)),
+                Content::Array(name) => Some((name, true)),
+                _ => None,
+            };
+            (op.path, request)
+        })
+        .collect()
+}
+
+//not commands, replies or events, but returned by service routes
+fn make_entity_schema() -> HashMap<&'static str, RootSchema> {
+    let mut hm = HashMap::<&str, RootSchema>::new();
+    hm.insert("ServiceHelth", schema_for!(entities::route::ServiceHelth));
+    hm.insert("Error", schema_for!(entities::error::Error));
+    hm.insert("Usr", schema_for!(entities::usr::Usr));
+    hm.insert(
+        "AsyncCommandState",
+        schema_for!(entities::executor::AsyncCommandState),
+    );
+    hm
+}
+
+fn get_parameters() -> Value {
+    json!({
+        "correlation_id": {
+            "name": "correlation_id",
+            "in": "query",
+            "required": true,
+            "description": "request correlation id, returned with reply and events",
+            "schema": { "type": "string" }
+        },
+        "object_type": {
+            "name": "object_type",
+            "in": "query",
+            "required": false,
+            "description": "command, reply or event type name, schema list if not set",
+            "schema": { "type": "string" }
+        },
+        "error_code": {
+            "name": "error_code",
+            "in": "query",
+            "required": false,
+            "schema": { "type": "string" }
+        },
+        "lang": {
+            "name": "lang",
+            "in": "query",
+            "required": false,
+            "description": "error names language, Accept-Language header is used if not set",
+            "schema": { "type": "string" }
+        },
+        "async_command_id": {
+            "name": "async_command_id",
+            "in": "query",
+            "required": true,
+            "schema": { "type": "string" }
+        }
+    })
+}
+
+//draft-07 schemars output to openapi 3.0 schema object
+fn to_openapi_schema(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut result = Map::new();
+            for (k, v) in map {
+                match (k.as_str(), v) {
+                    ("$schema", _) | ("definitions", _) => {}
+                    ("$ref", Value::String(r)) => {
+                        result.insert(k, Value::String(r.replace("#/definitions/", SCHEMA_REF)));
+                    }
+                    ("type", Value::Array(types)) => {
+                        let len = types.len();
+                        let mut types: Vec<Value> = types
+                            .into_iter()
+                            .filter(|t| t.as_str() != Some("null"))
+                            .collect();
+                        if types.len() != len {
+                            result.insert("nullable".to_string(), Value::Bool(true));
+                        }
+                        if types.len() == 1 {
+                            result.insert(k, types.remove(0));
+                        } else {
+                            result.insert(k, Value::Array(types));
+                        }
+                    }
+                    ("anyOf", Value::Array(items)) => {
+                        let len = items.len();
+                        let items: Vec<Value> = items
+                            .into_iter()
+                            .filter(|i| i.get("type").and_then(|t| t.as_str()) != Some("null"))
+                            .map(to_openapi_schema)
+                            .collect();
+                        if items.len() != len {
+                            result.insert("nullable".to_string(), Value::Bool(true));
+                        }
+                        if items.len() == 1 {
+                            result.insert("allOf".to_string(), Value::Array(items));
+                        } else {
+                            result.insert(k, Value::Array(items));
+                        }
+                    }
+                    (_, v) => {
+                        result.insert(k, to_openapi_schema(v));
+                    }
+                }
+            }
+            Value::Object(result)
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(to_openapi_schema).collect()),
+        v => v,
+    }
+}
+
+fn add_schema(schemas: &mut Map<String, Value>, name: &str, root: &RootSchema) {
+    schemas.insert(
+        name.to_string(),
+        to_openapi_schema(serde_json::to_value(&root.schema).unwrap()),
+    );
+    for (dn, ds) in &root.definitions {
+        schemas.insert(
+            dn.to_string(),
+            to_openapi_schema(serde_json::to_value(ds).unwrap()),
+        );
+    }
+}
+
+//unknown type names are described as any object
+fn get_content_schema(schemas: &Map<String, Value>, content: &Content) -> Option<Value> {
+    let schema_ref = |name: &str| {
+        if schemas.contains_key(name) {
+            json!({ "$ref": format!("{}{}", SCHEMA_REF, name) })
+        } else {
+            json!({ "type": "object" })
+        }
+    };
+    match content {
+        Content::Empty => None,
+        Content::Any => Some(json!({ "type": "object" })),
+        Content::Object(name) => Some(schema_ref(name)),
+        Content::Array(name) => Some(json!({ "type": "array", "items": schema_ref(name) })),
+        Content::ObjectOrArray(name) => Some(json!({
+            "oneOf": [
+                schema_ref(name),
+                { "type": "array", "items": schema_ref(name) }
+            ]
+        })),
+    }
+}
+
+fn get_json_content(schema: Value) -> Value {
+    json!({ "application/json": { "schema": schema } })
+}
+
+//"/router/route/get" -> "routerRouteGet"
+fn get_operation_id(path: &str) -> String {
+    let mut result = String::new();
+    for word in path
+        .split(|c: char| c == '/' || c == '_')
+        .filter(|w| w.len() > 0)
+    {
+        let mut chars = word.chars();
+        if result.len() == 0 {
+            result.push_str(word);
+        } else if let Some(c) = chars.next() {
+            result.extend(c.to_uppercase());
+            result.push_str(chars.as_str());
+        }
+    }
+    result
+}
+
+pub fn make_openapi(schema: &HashMap<&'static str, RootSchema>) -> Value {
+    let mut schemas = Map::new();
+    for (name, root) in schema {
+        add_schema(&mut schemas, name, root);
+    }
+    for (name, root) in &make_entity_schema() {
+        add_schema(&mut schemas, name, root);
+    }
+    let error_reply = json!({ "$ref": format!("{}{}", SCHEMA_REF, replies::common::StandardReply::get_type_name()) });
+    let bad_request_reply = json!({
+        "$ref": format!("{}{}", SCHEMA_REF, replies::common::BadRequestReply::get_type_name())
+    });
+    let mut paths = Map::new();
+    for op in get_operations() {
+        let mut params = vec![json!({ "$ref": format!("{}correlation_id", PARAMETER_REF) })];
+        for p in &op.params {
+            params.push(json!({ "$ref": format!("{}{}", PARAMETER_REF, p) }));
+        }
+        let mut post = json!({
+            "tags": [op.tag],
+            "operationId": get_operation_id(op.path),
+            "description": op.description,
+            "parameters": params,
+            "responses": {
+                "200": { "description": "reply" },
+                "400": {
+                    "description": "bad request, violations of request schema if validation is enabled",
+                    "content": get_json_content(bad_request_reply.clone())
+                },
+                "401": { "description": "unauthorized" },
+                "default": {
+                    "description": "error reply",
+                    "content": get_json_content(error_reply.clone())
+                }
+            }
+        });
+        if let Some(s) = get_content_schema(&schemas, &op.request) {
+            post["requestBody"] = json!({ "required": true, "content": get_json_content(s) });
+        }
+        if let Some(s) = get_content_schema(&schemas, &op.reply) {
+            post["responses"]["200"]["content"] = get_json_content(s);
+        }
+        paths.insert(op.path.to_string(), json!({ "post": post }));
+    }
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": {
+            "title": "Microservice",
+            "version": env!("CARGO_PKG_VERSION")
+        },
+        "paths": paths,
+        "components": {
+            "schemas": schemas,
+            "parameters": get_parameters(),
+            "securitySchemes": {
+                SECURITY_SCHEME: { "type": "http", "scheme": "basic" }
+            }
+        },
+        "security": [{ SECURITY_SCHEME: [] }]
+    })
+}
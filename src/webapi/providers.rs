@@ -1,25 +1,30 @@
-use super::{connectors, errors, entities};
-use hyper::{Body, Client, Request, Method, StatusCode};
+use super::{connectors, context, entities, errors, metrics};
+use hyper::{Body, Client, Method, Request, StatusCode};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::Arc;
-#[cfg(feature = "postgres")]
-use sqlx::PgPool;
+#[cfg(feature = "sqlite")]
+use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
 #[cfg(feature = "mysql")]
 use sqlx::MySqlPool;
+#[cfg(feature = "postgres")]
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
 
 #[derive(Deserialize, Serialize, Debug, PartialEq, Copy, Clone, ToString, JsonSchema)]
 pub enum Proto {
     http,
-    mq
+    mq,
 }
 
-pub struct HttpProvider;
+pub struct HttpProvider {
+    metrics: Arc<metrics::Metrics>,
+}
 
 impl HttpProvider {
-    pub async fn new() -> connectors::Result<HttpProvider> {
-        Ok(HttpProvider {})
+    pub async fn new(metrics: Arc<metrics::Metrics>) -> connectors::Result<HttpProvider> {
+        Ok(HttpProvider { metrics: metrics })
     }
 
     pub async fn execute(
@@ -28,31 +33,67 @@ impl HttpProvider {
         prop: HashMap<&str, &str>,
         bat: String,
         body: Body,
+    ) -> connectors::Result<Body> {
+        self.execute_with_trace(to, prop, bat, body, None).await
+    }
+
+    pub async fn execute_with_trace(
+        &self,
+        to: &str,
+        prop: HashMap<&str, &str>,
+        bat: String,
+        body: Body,
+        traceparent: Option<&str>,
     ) -> connectors::Result<Body> {
         let mut uri = String::from("");
-        for item in prop.iter(){
+        for item in prop.iter() {
             if uri.len() != 0 {
                 uri.push_str(&format!("&{}={}", item.0, item.1));
-            }
-            else
-            {
+            } else {
                 uri.push_str(&format!("?{}={}", item.0, item.1));
             }
         }
-        let req = Request::builder()
+        let mut req = Request::builder()
             .method(Method::POST)
             .uri(format!("{}{}", to, uri))
-            .header("Authorization", bat)
-            .body(body)
-            .expect("request builder");
+            .header("Authorization", bat);
+        if let Some(traceparent) = traceparent {
+            req = req.header(context::TRACEPARENT, traceparent);
+        }
+        let req = req.body(body).expect("request builder");
+        //target service address, host and port of uri
+        let target = req
+            .uri()
+            .authority()
+            .map(|a| a.to_string())
+            .unwrap_or_default();
         let client = Client::new();
-        let resp = client.request(req).await?;
+        let started_at = Instant::now();
+        let resp = match client.request(req).await {
+            Ok(resp) => {
+                self.metrics
+                    .add_outbound(&target, resp.status().as_str(), started_at.elapsed());
+                resp
+            }
+            Err(e) => {
+                self.metrics
+                    .add_outbound(&target, "error", started_at.elapsed());
+                return Err(e.into());
+            }
+        };
         let (parts, body) = resp.into_parts();
         if parts.status == StatusCode::OK {
             Ok(body)
-        }
-        else{
-            Err(errors::ProtoProviderError.into())
+        } else {
+            Err(errors::Error::with_context(
+                if parts.status.is_client_error() {
+                    errors::ErrorKind::RejectedRequest
+                } else {
+                    errors::ErrorKind::ProtoProvider
+                },
+                &format!("{} reply {}", to, parts.status),
+            )
+            .into())
         }
     }
 }
@@ -76,37 +117,121 @@ impl MqProvider {
     }
 }
 
-#[cfg(not(test))]
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 pub struct SqlDbProvider {
     #[cfg(feature = "postgres")]
     pub pool: Arc<PgPool>,
     #[cfg(feature = "mysql")]
     pub pool: Arc<MySqlPool>,
+    #[cfg(feature = "sqlite")]
+    pub pool: Arc<SqlitePool>,
 }
 
-#[cfg(not(test))]
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 impl SqlDbProvider {
     pub async fn new(connection_string: &String) -> connectors::Result<SqlDbProvider> {
-        debug!("connection string {}", connection_string);
         #[cfg(feature = "postgres")]
         let pool = PgPool::connect(&connection_string).await?;
         #[cfg(feature = "mysql")]
         let pool = MySqlPool::connect(&connection_string).await?;
+        #[cfg(feature = "sqlite")]
+        let pool = SqlDbProvider::connect_sqlite(&connection_string).await?;
         Ok(SqlDbProvider {
             pool: Arc::new(pool),
         })
     }
 
-    pub async fn get_errors(&self) -> connectors::Result<Vec<entities::error::Error>> {
-        Ok(
-            vec![entities::error::Error{
-                error_code: errors::ErrorCode::DatabaseError.to_string(),
-                error_name: "Database error".to_string()
-            },
-            entities::error::Error{
-                error_code: errors::ErrorCode::NotFoundError.to_string(),
-                error_name: "Some items with specified id is not found".to_string()
-            }]
-        )
+    pub async fn ping(&self) -> connectors::Result<()> {
+        sqlx::query("SELECT 1").execute(&*self.pool).await?;
+        Ok(())
+    }
+
+    pub fn get_pool_stats(&self) -> (u32, usize) {
+        (self.pool.size(), self.pool.num_idle())
+    }
+
+    #[cfg(feature = "sqlite")]
+    async fn connect_sqlite(connection_string: &String) -> connectors::Result<SqlitePool> {
+        //in-memory database lives only as long as its connection, so keep exactly one
+        let pool = if connection_string.contains(":memory:") {
+            SqlitePoolOptions::new()
+                .min_connections(1)
+                .max_connections(1)
+                .idle_timeout(None)
+                .max_lifetime(None)
+                .connect(&connection_string)
+                .await?
+        } else {
+            SqlitePoolOptions::new().connect(&connection_string).await?
+        };
+        sqlx::migrate!("migrations/sqlite").run(&pool).await?;
+        debug!("sqlite migrations applied");
+        Ok(pool)
+    }
+}
+
+//same as error table seed, used by in-memory stores
+pub fn get_default_errors() -> Vec<entities::error::Error> {
+    let names = vec![
+        (errors::ErrorCode::ReplyOk, "Ok", "Успешно"),
+        (
+            errors::ErrorCode::AsyncOk,
+            "Async command registered",
+            "Асинхронная команда зарегистрирована",
+        ),
+        (
+            errors::ErrorCode::UnsupportedExecModeError,
+            "Unsupported exec mode error",
+            "Неподдерживаемый режим выполнения",
+        ),
+        (
+            errors::ErrorCode::TooManyRequestsError,
+            "Too many requests error",
+            "Слишком много запросов",
+        ),
+        (
+            errors::ErrorCode::TimeoutExpiredError,
+            "Timeout expired error",
+            "Истекло время ожидания",
+        ),
+        (
+            errors::ErrorCode::DatabaseError,
+            "Database error",
+            "Ошибка базы данных",
+        ),
+        (
+            errors::ErrorCode::NotFoundError,
+            "Some items with specified id is not found",
+            "Некоторые элементы с указанным id не найдены",
+        ),
+        (
+            errors::ErrorCode::BadRequestError,
+            "Bad request error",
+            "Некорректный запрос",
+        ),
+        (
+            errors::ErrorCode::RemoteServiceError,
+            "Remote service error",
+            "Ошибка удаленного сервиса",
+        ),
+        (
+            errors::ErrorCode::InternalError,
+            "Internal error",
+            "Внутренняя ошибка",
+        ),
+    ];
+    let mut items = Vec::<entities::error::Error>::new();
+    for (error_code, en, ru) in names {
+        items.push(entities::error::Error {
+            error_code: error_code.to_string(),
+            lang: "en".to_string(),
+            error_name: en.to_string(),
+        });
+        items.push(entities::error::Error {
+            error_code: error_code.to_string(),
+            lang: "ru".to_string(),
+            error_name: ru.to_string(),
+        });
     }
+    items
 }
@@ -1,27 +1,37 @@
-use super::{access, connectors, errors, providers, router, traits, workers};
+use super::{
+    access, connectors, context, entities, errors, providers, router, tracer, traits, workers,
+};
+use chrono::Utc;
 use hyper::Body;
 use serde::ser;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use uuid::Uuid;
 
 pub struct EventPublisher {
+    dc: Arc<connectors::DataConnector>,
     ac: Arc<access::AccessChecker>,
     rt: Arc<router::Router>,
     hp: providers::HttpProvider,
-    _cs: mpsc::Sender<workers::SignalCode>,
+    tr: Arc<tracer::Tracer>,
+    _cs: workers::SignalSender,
 }
 
 impl EventPublisher {
     pub async fn new(
+        dc: Arc<connectors::DataConnector>,
         ac: Arc<access::AccessChecker>,
         rt: Arc<router::Router>,
-        cs: mpsc::Sender<workers::SignalCode>,
+        tr: Arc<tracer::Tracer>,
+        cs: workers::SignalSender,
     ) -> connectors::Result<EventPublisher> {
+        let hp = providers::HttpProvider::new(rt.metrics.clone()).await?;
         Ok(EventPublisher {
+            dc: dc,
             ac: ac,
             rt: rt,
-            hp: providers::HttpProvider::new().await?,
+            hp: hp,
+            tr: tr,
             _cs: cs,
         })
     }
@@ -32,16 +42,20 @@ impl EventPublisher {
             Ok(_) => Ok({}),
             Err(e) => {
                 error!("event publisher: {}", e);
-                return Err(errors::SignalSendError.into());
+                return Err(errors::Error::new(errors::ErrorKind::SignalSend).into());
             }
         }
     }
 
-    pub async fn send<T>(&self, correlation_id: &str, items: Vec<T>) -> connectors::Result<()>
+    pub async fn send<T>(&self, ctx: &context::Context, items: Vec<T>) -> connectors::Result<()>
     where
         T: ser::Serialize,
         T: traits::ObjectType,
     {
+        let correlation_id = ctx.correlation_id.as_str();
+        let span_ctx = ctx.child(T::get_type_name(), context::SpanKind::Producer);
+        let traceparent = span_ctx.span.to_traceparent();
+        let mut is_error = false;
         debug!(
             "correlation id {} publish {} count event {}",
             correlation_id,
@@ -49,46 +63,156 @@ impl EventPublisher {
             T::get_type_name()
         );
         if let Some(s) = self.rt.get_subscriptions(T::get_type_name()) {
+            let body = serde_json::to_string(&items).unwrap();
             for item in s {
-                if item.path.contains_key(&providers::Proto::http.to_string()) {
-                    let mut prop = HashMap::<&str, &str>::new();
-                    prop.insert(
-                        "correlation_id",
+                //boxed error is not Send, it must not live across await
+                let res = self
+                    .deliver(
+                        &item,
+                        T::get_type_name(),
                         correlation_id,
-                    );
-                    prop.insert("object_type", T::get_type_name());
-                    let token = self.ac.get_client_basic_authorization_token(
-                        item.service_name.as_ref().unwrap(),
-                    )?;
-                    match self
-                        .hp
-                        .execute(
-                            item.path.get(&providers::Proto::http.to_string()).unwrap(),
-                            prop,
-                            token,
-                            Body::from(serde_json::to_string(&items).unwrap()),
-                        )
-                        .await
-                    {
-                        Ok(_) => {}
-                        Err(e) => {
-                            warn!(
-                                "correlation id {} object type {} send error {}",
-                                correlation_id,
-                                T::get_type_name(),
-                                e
-                            );
-                        }
-                    }
-                } else {
+                        &traceparent,
+                        &body,
+                    )
+                    .await
+                    .map_err(|e| errors::from_boxed(&e));
+                if let Err(e) = res {
+                    is_error = true;
                     warn!(
-                        "correlation id {} object type {} supported proto not found error",
+                        "correlation id {} object type {} send error {}",
                         correlation_id,
-                        T::get_type_name()
+                        T::get_type_name(),
+                        e
                     );
+                    //kept for replay, publishing is not failed
+                    self.add_dead_letter(
+                        &item,
+                        T::get_type_name(),
+                        correlation_id,
+                        &body,
+                        &e.to_string(),
+                    )
+                    .await;
                 }
             }
         }
+        self.tr.export(&span_ctx, None, is_error);
         Ok({})
     }
+
+    //dead letter is removed after successful delivery to its subscriber
+    pub async fn replay(&self, ctx: &context::Context, id: &str) -> connectors::Result<()> {
+        let items = self.dc.dead_letter.get(Some(vec![id.to_string()])).await?;
+        let event = match items.into_iter().next() {
+            Some(event) => event,
+            None => {
+                return Err(
+                    errors::Error::with_context(errors::ErrorKind::DeadLetterNotFound, id).into(),
+                )
+            }
+        };
+        let subscription = self.rt.get_subscriptions(&event.object_type).and_then(|s| {
+            s.into_iter()
+                .find(|i| i.service_name.as_ref() == Some(&event.service_name))
+        });
+        let subscription = match subscription {
+            Some(subscription) => subscription,
+            None => {
+                return Err(errors::Error::with_context(
+                    errors::ErrorKind::UnknownEvent,
+                    &format!("{} {}", event.service_name, event.object_type),
+                )
+                .into())
+            }
+        };
+        let span_ctx = ctx.child(&event.object_type, context::SpanKind::Producer);
+        let res = self
+            .deliver(
+                &subscription,
+                &event.object_type,
+                &event.correlation_id,
+                &span_ctx.span.to_traceparent(),
+                &event.event_body,
+            )
+            .await
+            .map_err(|e| errors::from_boxed(&e));
+        self.tr.export(&span_ctx, None, res.is_err());
+        if let Err(e) = res {
+            return Err(e.into());
+        }
+        self.dc.dead_letter.remove(id).await?;
+        info!(
+            "correlation id {} dead letter {} replayed to {}",
+            ctx.correlation_id, id, event.service_name
+        );
+        Ok({})
+    }
+
+    async fn deliver(
+        &self,
+        item: &entities::route::SubscriptionRoute,
+        object_type: &str,
+        correlation_id: &str,
+        traceparent: &str,
+        body: &str,
+    ) -> connectors::Result<()> {
+        let to = match item.path.get(&providers::Proto::http.to_string()) {
+            Some(to) => to,
+            None => return Err(errors::Error::new(errors::ErrorKind::UnsupportedProto).into()),
+        };
+        let service_name = item.service_name.as_ref().unwrap();
+        let mut prop = HashMap::<&str, &str>::new();
+        prop.insert("correlation_id", correlation_id);
+        prop.insert("object_type", object_type);
+        if let Some(service_name) = self.rt.get_service_name() {
+            prop.insert("service_name", service_name);
+        }
+        let token = self.ac.get_client_basic_authorization_token(service_name)?;
+        let res = self
+            .hp
+            .execute_with_trace(
+                to,
+                prop,
+                token,
+                Body::from(body.to_string()),
+                Some(traceparent),
+            )
+            .await;
+        self.rt
+            .metrics
+            .add_delivery(service_name, object_type, res.is_ok());
+        res.map(|_| ())
+    }
+
+    async fn add_dead_letter(
+        &self,
+        item: &entities::route::SubscriptionRoute,
+        object_type: &str,
+        correlation_id: &str,
+        body: &str,
+        error: &str,
+    ) {
+        let event = entities::dead_letter::DeadLetterEvent {
+            id: Uuid::new_v4().to_hyphenated().to_string(),
+            object_type: object_type.to_string(),
+            service_name: item.service_name.clone().unwrap_or_default(),
+            correlation_id: correlation_id.to_string(),
+            event_body: body.to_string(),
+            error: error.to_string(),
+            added_at: Utc::now(),
+        };
+        match self.dc.dead_letter.add(event).await {
+            Ok(errors::ErrorCode::ReplyOk) => {}
+            Ok(ec) => error!(
+                "correlation id {} object type {} dead letter: {}",
+                correlation_id,
+                object_type,
+                ec.to_string()
+            ),
+            Err(e) => error!(
+                "correlation id {} object type {} dead letter: {}",
+                correlation_id, object_type, e
+            ),
+        }
+    }
 }
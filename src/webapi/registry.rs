@@ -0,0 +1,134 @@
+use super::entities::route;
+use schemars::schema::RootSchema;
+use std::collections::HashMap;
+
+//used by code of derive(ObjectType)
+pub use inventory;
+
+pub enum Kind {
+    Command {
+        service: Option<&'static str>, //none if command is not routed
+        reply: &'static str,
+        exec_mode: &'static str,
+        path: Option<&'static str>, //own path instead of service request path
+        description: &'static str,
+    },
+    Event {
+        service: Option<&'static str>,
+        description: &'static str,
+    },
+    Reply,
+}
+
+pub struct Subscription {
+    pub service: &'static str,
+    pub path: Option<&'static str>, //own path instead of service event path
+}
+
+//route and schema of object type, declared by attributes of derive(ObjectType)
+pub struct Declaration {
+    pub object_type: &'static str,
+    pub kind: Kind,
+    pub subscriptions: Vec<Subscription>,
+    pub schema: fn() -> RootSchema,
+}
+
+pub trait Declared {
+    fn get_declaration() -> Declaration;
+}
+
+pub struct Registration(pub fn() -> Declaration);
+
+inventory::collect!(Registration);
+
+pub fn get_declarations() -> Vec<Declaration> {
+    let mut items: Vec<Declaration> = inventory::iter::<Registration>
+        .into_iter()
+        .map(|r| (r.0)())
+        .collect();
+    items.sort_by(|a, b| a.object_type.cmp(b.object_type));
+    items
+}
+
+fn get_path(path: &str) -> HashMap<String, String> {
+    let mut hm = HashMap::<String, String>::new();
+    hm.insert(
+        super::providers::Proto::http.to_string(),
+        format!("http://{{host}}{}", path),
+    );
+    hm
+}
+
+fn get_route<'a>(
+    service: &'a mut HashMap<String, route::Route>,
+    name: &str,
+) -> &'a mut route::Route {
+    service
+        .entry(name.to_string())
+        .or_insert_with(|| route::Route {
+            service_name: Some(name.to_string()),
+            description: String::new(),
+            priority: 0,
+            command: Vec::new(),
+            event: Vec::new(),
+            subscription: Vec::new(),
+            path: None,
+        })
+}
+
+//declared routes missing in settings, settings win on same object type
+pub fn add_routes(service: &mut HashMap<String, route::Route>) {
+    for d in get_declarations() {
+        match d.kind {
+            Kind::Command {
+                service: Some(name),
+                reply,
+                exec_mode,
+                path,
+                description,
+            } => {
+                let item = get_route(service, name);
+                if !item.command.iter().any(|c| c.object_type == d.object_type) {
+                    item.command.push(route::ServiceCommand {
+                        service_name: None,
+                        priority: None,
+                        object_type: d.object_type.to_string(),
+                        description: description.to_string(),
+                        reply_type: reply.to_string(),
+                        exec_mode: exec_mode.to_string(),
+                        state: None,
+                        path: path.map(get_path),
+                    });
+                }
+            }
+            Kind::Event {
+                service: Some(name),
+                description,
+            } => {
+                let item = get_route(service, name);
+                if !item.event.iter().any(|e| e.object_type == d.object_type) {
+                    item.event.push(route::ServiceEvent {
+                        service_name: None,
+                        object_type: d.object_type.to_string(),
+                        description: description.to_string(),
+                    });
+                }
+            }
+            _ => {}
+        }
+        for s in &d.subscriptions {
+            let item = get_route(service, s.service);
+            if !item
+                .subscription
+                .iter()
+                .any(|i| i.object_type == d.object_type)
+            {
+                item.subscription.push(route::ServiceSubscription {
+                    service_name: None,
+                    object_type: d.object_type.to_string(),
+                    path: s.path.map(get_path),
+                });
+            }
+        }
+    }
+}
@@ -1,8 +1,9 @@
-use super::super::{entities::car, errors, traits};
+use super::super::{entities::car, errors};
+use my_rust_hyper_derive::ObjectType;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, JsonSchema)]
+#[derive(Deserialize, Serialize, JsonSchema, ObjectType)]
 pub struct GetCarReply {
     pub error_code: errors::ErrorCode,
 
@@ -15,9 +16,3 @@ pub struct GetCarReply {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub items: Option<Vec<car::Car>>,
 }
-
-impl traits::ObjectType for GetCarReply {
-    fn get_type_name() -> &'static str {
-        "GetCarReply"
-    }
-}
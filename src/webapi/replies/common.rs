@@ -1,8 +1,9 @@
-use super::super::{errors, traits};
+use super::super::errors;
+use my_rust_hyper_derive::ObjectType;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, JsonSchema)]
+#[derive(Deserialize, Serialize, JsonSchema, ObjectType)]
 pub struct StandardReply {
     pub error_code: errors::ErrorCode,
 
@@ -16,13 +17,7 @@ impl StandardReply {
     }
 }
 
-impl traits::ObjectType for StandardReply {
-    fn get_type_name() -> &'static str {
-        "StandardReply"
-    }
-}
-
-#[derive(Deserialize, Serialize, JsonSchema)]
+#[derive(Deserialize, Serialize, JsonSchema, ObjectType)]
 pub struct AddIntIdsReply {
     pub error_code: errors::ErrorCode,
 
@@ -33,13 +28,7 @@ pub struct AddIntIdsReply {
     pub ids: Option<Vec<i32>>,
 }
 
-impl traits::ObjectType for AddIntIdsReply {
-    fn get_type_name() -> &'static str {
-        "AddIntIdsReply"
-    }
-}
-
-#[derive(Deserialize, Serialize, JsonSchema)]
+#[derive(Deserialize, Serialize, JsonSchema, ObjectType)]
 pub struct AddStrIdsReply {
     pub error_code: errors::ErrorCode,
 
@@ -56,8 +45,18 @@ impl AddStrIdsReply {
     }
 }
 
-impl traits::ObjectType for AddStrIdsReply {
-    fn get_type_name() -> &'static str {
-        "AddStrIdsReply"
-    }
+#[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
+pub struct Violation {
+    pub path: String, //json pointer of invalid value
+    pub message: String,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, ObjectType)]
+pub struct BadRequestReply {
+    pub error_code: errors::ErrorCode,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_name: Option<String>,
+
+    pub violations: Vec<Violation>,
 }
@@ -1,3 +1,3 @@
-pub mod common;
 pub mod car;
-pub mod route;
\ No newline at end of file
+pub mod common;
+pub mod route;
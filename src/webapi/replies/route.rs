@@ -1,8 +1,9 @@
-use super::super::{entities::route, errors, traits};
+use super::super::{entities::route, errors};
+use my_rust_hyper_derive::ObjectType;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, JsonSchema)]
+#[derive(Deserialize, Serialize, JsonSchema, ObjectType)]
 pub struct GetRouteReply {
     pub error_code: errors::ErrorCode,
 
@@ -16,13 +17,7 @@ pub struct GetRouteReply {
     pub items: Option<Vec<route::Route>>,
 }
 
-impl traits::ObjectType for GetRouteReply {
-    fn get_type_name() -> &'static str {
-        "GetRouteReply"
-    }
-}
-
-#[derive(Deserialize, Serialize, JsonSchema)]
+#[derive(Deserialize, Serialize, JsonSchema, ObjectType)]
 pub struct GetServiceCommandReply {
     pub error_code: errors::ErrorCode,
 
@@ -36,13 +31,7 @@ pub struct GetServiceCommandReply {
     pub items: Option<Vec<route::ServiceCommand>>,
 }
 
-impl traits::ObjectType for GetServiceCommandReply {
-    fn get_type_name() -> &'static str {
-        "GetServiceCommandReply"
-    }
-}
-
-#[derive(Deserialize, Serialize, JsonSchema)]
+#[derive(Deserialize, Serialize, JsonSchema, ObjectType)]
 pub struct GetServiceEventReply {
     pub error_code: errors::ErrorCode,
 
@@ -56,13 +45,7 @@ pub struct GetServiceEventReply {
     pub items: Option<Vec<route::ServiceEvent>>,
 }
 
-impl traits::ObjectType for GetServiceEventReply {
-    fn get_type_name() -> &'static str {
-        "GetServiceEventReply"
-    }
-}
-
-#[derive(Deserialize, Serialize, JsonSchema)]
+#[derive(Deserialize, Serialize, JsonSchema, ObjectType)]
 pub struct GetServiceSubscriptionReply {
     pub error_code: errors::ErrorCode,
 
@@ -76,13 +59,7 @@ pub struct GetServiceSubscriptionReply {
     pub items: Option<Vec<route::ServiceSubscription>>,
 }
 
-impl traits::ObjectType for GetServiceSubscriptionReply {
-    fn get_type_name() -> &'static str {
-        "GetServiceSubscriptionReply"
-    }
-}
-
-#[derive(Deserialize, Serialize, JsonSchema)]
+#[derive(Deserialize, Serialize, JsonSchema, ObjectType)]
 pub struct GetServiceReply {
     pub error_code: errors::ErrorCode,
 
@@ -94,10 +71,35 @@ pub struct GetServiceReply {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub items: Option<Vec<route::Service>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breakers: Option<Vec<route::ServiceBreaker>>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, ObjectType)]
+pub struct GetServiceErrorReply {
+    pub error_code: errors::ErrorCode,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<route::ServiceError>>,
 }
 
-impl traits::ObjectType for GetServiceReply {
-    fn get_type_name() -> &'static str {
-        "GetServiceReply"
-    }
+#[derive(Deserialize, Serialize, JsonSchema, ObjectType)]
+pub struct GetServiceSchemaReply {
+    pub error_code: errors::ErrorCode,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<route::ServiceSchema>>,
 }
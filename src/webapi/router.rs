@@ -1,12 +1,14 @@
 use super::entities::route;
 use super::{
-    access, commands, connectors, entities, errors, providers, replies, schema, traits::ObjectType,
+    access, breaker, commands, connectors, entities, errors, helth, idempotency, limiter, metrics,
+    openapi, providers, registry, replies, schema, settings, traits::ObjectType,
 };
 use bytes::buf::ext::BufExt;
 use hyper::Body;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::RwLock;
+use std::time::Duration;
 use uuid::Uuid;
 
 pub const ROUTER_SERVICE_NAME: &str = "router";
@@ -19,7 +21,18 @@ pub struct Router {
     service_path: RwLock<HashMap<String, HashMap<String, route::ServicePath>>>,
     command: RwLock<HashMap<String, entities::route::CommandRoute>>,
     subscription: RwLock<HashMap<String, Vec<entities::route::SubscriptionRoute>>>,
+    service_schema: RwLock<HashMap<String, HashMap<String, route::ServiceSchema>>>,
+    registration: Vec<route::Route>, //own routes added with lease, renewed by heartbeat
+    service_name: Option<String>,    //caller name of outgoing requests
+    lease: Option<settings::Lease>,
     pub schema: HashMap<&'static str, schemars::schema::RootSchema>,
+    pub request_type: HashMap<&'static str, Option<(&'static str, bool)>>, //by post route path
+    pub validation: settings::Validation,
+    pub metrics: Arc<metrics::Metrics>,
+    pub helth: helth::HelthChecker,
+    pub idempotency: idempotency::IdempotencyChecker,
+    pub limiter: limiter::RateLimiter,
+    pub breaker: breaker::CircuitBreakers,
     pub is_local: bool,
 }
 
@@ -134,6 +147,7 @@ impl Router {
         path.helth = path.helth.replace("{host}", host);
         path.schema = path.schema.replace("{host}", host);
         path.reply_to = path.reply_to.replace("{host}", host);
+        path.state = path.state.replace("{host}", host);
         path.error = path.error.replace("{host}", host);
         path.request = Some(path.request.as_ref().unwrap().replace("{host}", host));
         path.event = Some(path.event.as_ref().unwrap().replace("{host}", host));
@@ -146,10 +160,24 @@ impl Router {
         mut path: HashMap<String, route::ServicePath>,
         mut service: HashMap<String, route::Route>,
         host: &str,
+        validation: Option<settings::Validation>,
+        idempotency: Option<settings::Idempotency>,
+        rate_limit: Option<settings::RateLimit>,
+        circuit_breaker: Option<settings::CircuitBreaker>,
+        lease: Option<settings::Lease>,
+        is_registered: bool,
     ) -> connectors::Result<Router> {
+        registry::add_routes(&mut service);
         for item in service.iter_mut() {
             item.1.service_name = Some(item.0.to_string());
         }
+        //first one by name if several services are configured, router only if it is alone
+        let service_name = service
+            .keys()
+            .filter(|k| k.as_str() != ROUTER_SERVICE_NAME)
+            .min()
+            .or_else(|| service.keys().min())
+            .cloned();
         let mut root_command_path = HashMap::<String, String>::new();
         let mut root_subscription_path = HashMap::<String, String>::new();
         for p in path.iter_mut() {
@@ -190,7 +218,7 @@ impl Router {
                     exec_mode: c.exec_mode.clone(),
                     reply_type: c.reply_type.clone(),
                     path: np,
-                    state: c.state.clone()
+                    state: c.state.clone(),
                 });
             }
             item.command = nc;
@@ -214,12 +242,28 @@ impl Router {
             item.subscription = ns;
         }
         let is_local = remote_router.is_none();
-        let hp = providers::HttpProvider::new().await?;
+        let metrics = Arc::new(metrics::Metrics::new());
+        let hp = providers::HttpProvider::new(metrics.clone()).await?;
         let mut _service_paths = Vec::<route::ServicePath>::new();
         let mut _commands = Vec::<route::ServiceCommand>::new();
         let mut _subscriptions = Vec::<route::ServiceSubscription>::new();
+        //client only looks up routes registered by running services,
+        //routes of remote router are its own
+        let registration: Vec<route::Route> = if is_registered {
+            service
+                .iter()
+                .filter(|(name, _)| is_local || name.as_str() != ROUTER_SERVICE_NAME)
+                .map(|(_, item)| item.clone())
+                .collect()
+        } else {
+            Vec::new()
+        };
         if is_local {
-            &dc.route.add(service.values().cloned().collect()).await?;
+            if is_registered {
+                dc.route
+                    .add(registration.clone(), lease.map(|l| l.ttl))
+                    .await?;
+            }
             let p = &dc.route.get_service_path(None).await?;
             let c = &dc.route.get_command(None).await?;
             let s = &dc.route.get_subscription(None).await?;
@@ -232,7 +276,7 @@ impl Router {
                 .unwrap()
                 .contains_key(&providers::Proto::http.to_string())
             {
-                return Err(errors::UnsupportedProtoError.into());
+                return Err(errors::Error::new(errors::ErrorKind::UnsupportedProto).into());
             }
             let r = remote_router
                 .as_ref()
@@ -248,7 +292,40 @@ impl Router {
             );
             let token =
                 &ac.get_client_basic_authorization_token(&ROUTER_SERVICE_NAME.to_string())?;
-            //todo: add routes
+            //own routes are added with lease, heartbeat renews them by command executor
+            if is_registered {
+                let cid = Uuid::new_v4().to_hyphenated().to_string();
+                let mut prop = HashMap::<&str, &str>::new();
+                prop.insert("correlation_id", &cid);
+                prop.insert("object_type", commands::route::AddRoute::get_type_name());
+                let resp_route = hp
+                    .execute(
+                        r,
+                        prop,
+                        token.to_string(),
+                        Body::from(
+                            serde_json::to_string(&commands::route::AddRoute {
+                                items: registration.clone(),
+                                lease: lease.map(|l| l.ttl),
+                            })
+                            .unwrap(),
+                        ),
+                    )
+                    .await?;
+                let reader_route = hyper::body::aggregate(resp_route).await?.reader();
+                let reply_route: Option<replies::common::AddStrIdsReply> =
+                    serde_json::from_reader(reader_route).unwrap_or(None);
+                match reply_route {
+                    Some(reply) if reply.is_ok() => {}
+                    _ => {
+                        return Err(errors::Error::with_context(
+                            errors::ErrorKind::GetRemoteRouter,
+                            "add routes",
+                        )
+                        .into())
+                    }
+                }
+            }
             //todo: get service path
             //get commands
             let resp_command = hp
@@ -313,18 +390,29 @@ impl Router {
                     .unwrap()
                     .to_vec();
             } else {
-                return Err(errors::GeRemoteRouterError.into());
+                return Err(errors::Error::new(errors::ErrorKind::GetRemoteRouter).into());
             }
         }
         Ok(Router {
             data_connector: if is_local { Some(dc) } else { None },
-            access_checker: if is_local { None } else { Some(ac) },
+            access_checker: Some(ac),
             http_provider: hp,
             schema: schema::make_schema(),
+            request_type: openapi::make_request_type(),
+            validation: validation.unwrap_or_default(),
+            metrics: metrics,
+            helth: helth::HelthChecker::new(),
+            idempotency: idempotency::IdempotencyChecker::new(idempotency),
+            limiter: limiter::RateLimiter::new(rate_limit),
+            breaker: breaker::CircuitBreakers::new(circuit_breaker),
             remote_router: remote_router,
             service_path: RwLock::new(Router::make_service_path_hash_map(_service_paths)),
             command: RwLock::new(Router::make_command_hash_map(_commands)),
             subscription: RwLock::new(Router::make_subscription_hash_map(_subscriptions)),
+            service_schema: RwLock::new(HashMap::new()),
+            registration: registration,
+            service_name: service_name,
+            lease: lease,
             is_local: is_local,
         })
     }
@@ -348,12 +436,14 @@ impl Router {
             let mut sp = self.service_path.write().unwrap();
             let mut cm = self.command.write().unwrap();
             let mut ss = self.subscription.write().unwrap();
+            //service schema may change with its route
+            self.service_schema.write().unwrap().clear();
             *sp = Router::make_service_path_hash_map(service_paths);
             *cm = Router::make_command_hash_map(commands);
             *ss = Router::make_subscription_hash_map(subscriptions);
             Ok(true)
         } else {
-            Err(errors::DataIntegrityError.into())
+            Err(errors::Error::new(errors::ErrorKind::DataIntegrity).into())
         }
     }
 
@@ -368,10 +458,13 @@ impl Router {
             if sn.contains_key(&proto.to_string()) {
                 Ok(sn.get(&proto.to_string()).unwrap().clone())
             } else {
-                Err(errors::UnsupportedProtoError.into())
+                Err(errors::Error::new(errors::ErrorKind::UnsupportedProto).into())
             }
         } else {
-            Err(errors::UnknownServiceNameError.into())
+            Err(
+                errors::Error::with_context(errors::ErrorKind::UnknownServiceName, service_name)
+                    .into(),
+            )
         }
     }
 
@@ -388,7 +481,7 @@ impl Router {
                 .unwrap()
                 .clone())
         } else {
-            Err(errors::UnknownCommandError.into())
+            Err(errors::Error::with_context(errors::ErrorKind::UnknownCommand, object_type).into())
         }
     }
 
@@ -410,6 +503,382 @@ impl Router {
         }
     }
 
+    async fn call_service_schema(
+        &self,
+        service_name: &str,
+        object_type: Option<&str>,
+    ) -> connectors::Result<Body> {
+        let path = self.get_service_path(service_name, providers::Proto::http)?;
+        let cid = Uuid::new_v4().to_hyphenated().to_string();
+        let mut prop = HashMap::<&str, &str>::new();
+        prop.insert("correlation_id", &cid);
+        if object_type.is_some() {
+            prop.insert("object_type", object_type.unwrap());
+        }
+        let token = self
+            .access_checker
+            .as_ref()
+            .unwrap()
+            .get_client_basic_authorization_token(&service_name.to_string())?;
+        self.http_provider
+            .execute(&path.schema, prop, token, Body::empty())
+            .await
+    }
+
+    //cached until route update
+    pub async fn get_service_schema(
+        &self,
+        service_name: &str,
+        object_type: &str,
+    ) -> connectors::Result<route::ServiceSchema> {
+        if let Some(s) = self
+            .service_schema
+            .read()
+            .unwrap()
+            .get(service_name)
+            .and_then(|ss| ss.get(object_type))
+        {
+            return Ok(s.clone());
+        }
+        let resp = self
+            .call_service_schema(service_name, Some(object_type))
+            .await?;
+        let reader = hyper::body::aggregate(resp).await?.reader();
+        let schema: Option<serde_json::Value> = serde_json::from_reader(reader).unwrap_or(None);
+        if schema.is_none() {
+            return Err(errors::Error::with_context(
+                errors::ErrorKind::BadReplyCommand,
+                &format!("{} schema {}", service_name, object_type),
+            )
+            .into());
+        }
+        let schema = schema.unwrap();
+        let item = route::ServiceSchema {
+            service_name: Some(service_name.to_string()),
+            object_type: object_type.to_string(),
+            version: schema
+                .get(schema::SCHEMA_VERSION)
+                .and_then(|v| v.as_u64())
+                .unwrap_or(1) as u32,
+            schema: Some(schema),
+        };
+        self.service_schema
+            .write()
+            .unwrap()
+            .entry(service_name.to_string())
+            .or_insert_with(HashMap::new)
+            .insert(object_type.to_string(), item.clone());
+        Ok(item)
+    }
+
+    //own schema first, schema of remote service is cached
+    pub async fn get_command_schema(
+        &self,
+        object_type: &str,
+    ) -> connectors::Result<schemars::schema::RootSchema> {
+        if let Some(root) = self.schema.get(object_type) {
+            return Ok(root.clone());
+        }
+        let command = self.get_command(object_type)?;
+        let service_name = command.service_name.unwrap_or_default();
+        let item = self.get_service_schema(&service_name, object_type).await?;
+        match item.schema.and_then(|s| serde_json::from_value(s).ok()) {
+            Some(root) => Ok(root),
+            None => Err(errors::Error::with_context(
+                errors::ErrorKind::BadReplyCommand,
+                &format!("{} schema {}", service_name, object_type),
+            )
+            .into()),
+        }
+    }
+
+    pub async fn get_service_schema_list(
+        &self,
+        service_name: &str,
+    ) -> connectors::Result<Vec<route::ServiceSchema>> {
+        let resp = self.call_service_schema(service_name, None).await?;
+        let reader = hyper::body::aggregate(resp).await?.reader();
+        let items: Option<Vec<route::ServiceSchema>> =
+            serde_json::from_reader(reader).unwrap_or(None);
+        match items {
+            Some(mut items) => {
+                for item in &mut items {
+                    item.service_name = Some(service_name.to_string());
+                }
+                Ok(items)
+            }
+            None => Err(errors::Error::with_context(
+                errors::ErrorKind::BadReplyCommand,
+                &format!("{} schema list", service_name),
+            )
+            .into()),
+        }
+    }
+
+    fn get_schema_users(&self, object_type: &str, is_reply: bool) -> Vec<String> {
+        let mut services = Vec::<String>::new();
+        for c in self.command.read().unwrap().values() {
+            let ot = if is_reply {
+                &c.reply_type
+            } else {
+                &c.object_type
+            };
+            if ot == object_type && c.service_name.is_some() {
+                services.push(c.service_name.clone().unwrap());
+            }
+        }
+        if let Some(ss) = self.subscription.read().unwrap().get(object_type) {
+            for s in ss {
+                if s.service_name.is_some() {
+                    services.push(s.service_name.clone().unwrap());
+                }
+            }
+        }
+        services.sort();
+        services.dedup();
+        services
+    }
+
+    async fn call_route_schema(
+        &self,
+        service_name: &str,
+        schema_path: &str,
+        object_type: &str,
+    ) -> connectors::Result<serde_json::Value> {
+        let cid = Uuid::new_v4().to_hyphenated().to_string();
+        let mut prop = HashMap::<&str, &str>::new();
+        prop.insert("correlation_id", &cid);
+        prop.insert("object_type", object_type);
+        let token = self
+            .access_checker
+            .as_ref()
+            .unwrap()
+            .get_client_basic_authorization_token(&service_name.to_string())?;
+        let resp = self
+            .http_provider
+            .execute(schema_path, prop, token, Body::empty())
+            .await?;
+        let reader = hyper::body::aggregate(resp).await?.reader();
+        match serde_json::from_reader(reader) {
+            Ok(schema) => Ok(schema),
+            Err(_) => Err(errors::Error::with_context(
+                errors::ErrorKind::BadReplyCommand,
+                &format!("{} schema {}", service_name, object_type),
+            )
+            .into()),
+        }
+    }
+
+    //compares schemas of added routes with schemas of registered services using same types,
+    //unavailable schemas are skipped
+    pub async fn check_route_schema(
+        &self,
+        items: &Vec<route::Route>,
+    ) -> connectors::Result<Vec<replies::common::Violation>> {
+        let mut violations = Vec::<replies::common::Violation>::new();
+        for (i, item) in items.iter().enumerate() {
+            let service_name = item.service_name.clone().unwrap_or_default();
+            let schema_path = match item
+                .path
+                .as_ref()
+                .and_then(|p| p.get(&providers::Proto::http.to_string()))
+            {
+                Some(p) => p.schema.clone(),
+                None => continue,
+            };
+            let mut object_types = Vec::<(&str, bool)>::new();
+            for c in &item.command {
+                object_types.push((&c.object_type, false));
+                object_types.push((&c.reply_type, true));
+            }
+            for e in &item.event {
+                object_types.push((&e.object_type, false));
+            }
+            for (object_type, is_reply) in object_types {
+                let users: Vec<String> = self
+                    .get_schema_users(object_type, is_reply)
+                    .into_iter()
+                    .filter(|s| s != &service_name)
+                    .collect();
+                if users.len() == 0 {
+                    continue;
+                }
+                let new_schema = match self
+                    .call_route_schema(&service_name, &schema_path, object_type)
+                    .await
+                {
+                    Ok(s) => s,
+                    Err(e) => {
+                        warn!("check route schema {}: {}", service_name, e);
+                        continue;
+                    }
+                };
+                for user in users {
+                    let old_schema = match self.get_service_schema(&user, object_type).await {
+                        Ok(s) => s.schema.unwrap_or_default(),
+                        Err(e) => {
+                            warn!("check route schema {}: {}", user, e);
+                            continue;
+                        }
+                    };
+                    for message in schema::get_incompatibilities(&old_schema, &new_schema) {
+                        violations.push(replies::common::Violation {
+                            path: format!("/items/{}", i),
+                            message: format!(
+                                "{} {} {}, used by {}",
+                                service_name, object_type, message, user
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(violations)
+    }
+
+    //services with unavailable error path are skipped
+    pub async fn get_service_error(
+        &self,
+        services: Option<Vec<String>>,
+        lang: Option<&str>,
+    ) -> connectors::Result<Vec<route::ServiceError>> {
+        let mut paths = Vec::<(String, String)>::new();
+        for (service_name, sp) in self.service_path.read().unwrap().iter() {
+            if services.is_some() && !services.as_ref().unwrap().contains(service_name) {
+                continue;
+            }
+            if let Some(p) = sp.get(&providers::Proto::http.to_string()) {
+                paths.push((service_name.to_string(), p.error.clone()));
+            }
+        }
+        paths.sort();
+        let mut items = Vec::<route::ServiceError>::new();
+        for (service_name, path) in paths {
+            let cid = Uuid::new_v4().to_hyphenated().to_string();
+            let mut prop = HashMap::<&str, &str>::new();
+            prop.insert("correlation_id", &cid);
+            if lang.is_some() {
+                prop.insert("lang", lang.unwrap());
+            }
+            let token = self
+                .access_checker
+                .as_ref()
+                .unwrap()
+                .get_client_basic_authorization_token(&service_name)?;
+            let resp = match self
+                .http_provider
+                .execute(&path, prop, token, Body::empty())
+                .await
+            {
+                Ok(resp) => resp,
+                Err(e) => {
+                    warn!("get {} service error: {}", service_name, e);
+                    continue;
+                }
+            };
+            let reader = hyper::body::aggregate(resp).await?.reader();
+            let reply: Option<Vec<entities::error::Error>> =
+                serde_json::from_reader(reader).unwrap_or(None);
+            match reply {
+                Some(reply) => items.push(route::ServiceError {
+                    service_name: service_name,
+                    items: reply,
+                }),
+                None => warn!("get {} service error: bad reply", service_name),
+            }
+        }
+        Ok(items)
+    }
+
+    //remote router answers GetService for itself
+    pub async fn ping_remote_router(&self) -> connectors::Result<()> {
+        let r = match &self.remote_router {
+            Some(r) => r.get(&providers::Proto::http.to_string()).unwrap(),
+            None => return Ok(()),
+        };
+        let cid = Uuid::new_v4().to_hyphenated().to_string();
+        let mut prop = HashMap::<&str, &str>::new();
+        prop.insert("correlation_id", &cid);
+        prop.insert("object_type", commands::route::GetService::get_type_name());
+        let token = self
+            .access_checker
+            .as_ref()
+            .unwrap()
+            .get_client_basic_authorization_token(&ROUTER_SERVICE_NAME.to_string())?;
+        self.http_provider
+            .execute(
+                r,
+                prop,
+                token,
+                Body::from(
+                    serde_json::to_string(&commands::route::GetService {
+                        filter: None,
+                        names: Some(vec![ROUTER_SERVICE_NAME.to_string()]),
+                    })
+                    .unwrap(),
+                ),
+            )
+            .await?;
+        Ok(())
+    }
+
+    //sent as service_name param of calls and events
+    pub fn get_service_name(&self) -> Option<&str> {
+        self.service_name.as_ref().map(|s| s.as_str())
+    }
+
+    //none if own routes are not registered with lease
+    pub fn get_heartbeat(&self) -> Option<Duration> {
+        match self.lease {
+            Some(lease) if self.registration.len() > 0 => {
+                Some(Duration::from_secs(lease.heartbeat))
+            }
+            _ => None,
+        }
+    }
+
+    //own routes and lease of them, none if they are not registered with lease
+    pub fn get_registration(&self) -> Option<commands::route::AddRoute> {
+        match self.lease {
+            Some(lease) if self.registration.len() > 0 => Some(commands::route::AddRoute {
+                items: self.registration.clone(),
+                lease: Some(lease.ttl),
+            }),
+            _ => None,
+        }
+    }
+
+    //lapsed lease is registered again in local router, returns services changed by registration
+    pub async fn renew(&self) -> connectors::Result<Option<Vec<String>>> {
+        let dc = match (&self.data_connector, self.lease) {
+            (Some(dc), Some(_)) if self.registration.len() > 0 => dc,
+            _ => return Ok(None),
+        };
+        let services: Vec<String> = self
+            .registration
+            .iter()
+            .map(|r| r.service_name.clone().unwrap_or_default())
+            .collect();
+        let result = dc.route.renew(services.clone()).await?;
+        if result == errors::ErrorCode::ReplyOk {
+            return Ok(None);
+        }
+        warn!("lease of {:?} is lapsed, routes are added again", services);
+        let (result, changed) = dc
+            .route
+            .add(self.registration.clone(), self.lease.map(|l| l.ttl))
+            .await?;
+        if result == errors::ErrorCode::ReplyOk {
+            Ok(changed)
+        } else {
+            Err(errors::Error::with_context(
+                errors::ErrorKind::DataIntegrity,
+                &format!("add routes {:?}: {}", services, result.to_string()),
+            )
+            .into())
+        }
+    }
+
     pub async fn shutdown(&self) -> connectors::Result<()> {
         let mut s = Vec::<String>::new();
         for item in self.service_path.read().unwrap().keys() {
@@ -423,7 +892,13 @@ impl Router {
                 .remove(s)
                 .await?;
             debug!("remove service route");
-        } else {
+        } else if self.registration.len() > 0 {
+            //only own routes are removed from remote router
+            let s: Vec<String> = self
+                .registration
+                .iter()
+                .map(|r| r.service_name.clone().unwrap_or_default())
+                .collect();
             let r = self
                 .remote_router
                 .as_ref()
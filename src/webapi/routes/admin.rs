@@ -0,0 +1,644 @@
+use super::super::{
+    access, commands, connectors, context, errors, handlers, logger, publishers, router,
+    traits::ObjectType,
+};
+use super::path;
+use chrono::Utc;
+use hyper::{header, Body, Method, Request, Response, StatusCode};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+const PAGE_SIZE: u32 = 50;
+const SENDED_PAGE: &str = "sended_page";
+const RECEIVED_PAGE: &str = "received_page";
+const DEAD_LETTER_PAGE: &str = "dead_letter_page";
+
+pub fn is_admin_path(path: &str) -> bool {
+    [
+        path::ADMIN,
+        path::ADMIN_ROUTE_REMOVE,
+        path::ADMIN_EVENT_REPLAY,
+    ]
+    .contains(&path)
+}
+
+fn resp_with_code(code: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(code)
+        .body(Body::empty())
+        .unwrap()
+}
+
+//back to dashboard, result of action is shown there
+fn resp_redirect(message: &str) -> Response<Body> {
+    let message: String = url::form_urlencoded::byte_serialize(message.as_bytes()).collect();
+    Response::builder()
+        .status(StatusCode::SEE_OTHER)
+        .header(
+            header::LOCATION,
+            format!("{}?message={}", path::ADMIN, message),
+        )
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn get_params(req: &Request<Body>) -> HashMap<String, String> {
+    req.uri()
+        .query()
+        .map(|v| {
+            url::form_urlencoded::parse(v.as_bytes())
+                .into_owned()
+                .collect()
+        })
+        .unwrap_or_else(HashMap::new)
+}
+
+//form of other site must not post with credential cached by browser,
+//post without origin and referer is rejected too
+fn is_same_origin(req: &Request<Body>) -> bool {
+    let host = match req
+        .headers()
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+    {
+        Some(host) if host.len() > 0 => host,
+        _ => return false,
+    };
+    let origins = [format!("http://{}", host), format!("https://{}", host)];
+    let get_header = |name| req.headers().get(name).and_then(|v| v.to_str().ok());
+    match (get_header(header::ORIGIN), get_header(header::REFERER)) {
+        (Some(origin), _) => origins.iter().any(|o| o == origin),
+        (None, Some(referer)) => origins
+            .iter()
+            .any(|o| referer == o || referer.starts_with(&format!("{}/", o))),
+        (None, None) => false,
+    }
+}
+
+//router only, every page and action requires admin credential
+pub async fn route(
+    req: Request<Body>,
+    dc: &connectors::DataConnector,
+    ac: &access::AccessChecker,
+    ep: &publishers::EventPublisher,
+    rt: &router::Router,
+) -> Response<Body> {
+    if !rt.is_local || !ac.is_admin_enabled() {
+        return resp_with_code(StatusCode::NOT_FOUND);
+    }
+    let admin = match req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| ac.get_admin_name_by_header(v))
+    {
+        Some(admin) => admin.clone(),
+        None => {
+            return Response::builder()
+                .status(StatusCode::UNAUTHORIZED)
+                .header("WWW-Authenticate", "Basic realm=\"Router administration\"")
+                .body(Body::empty())
+                .unwrap()
+        }
+    };
+    if *req.method() == Method::POST && !is_same_origin(&req) {
+        warn!("admin {}: cross origin request", req.uri().path());
+        return resp_with_code(StatusCode::FORBIDDEN);
+    }
+    let params = get_params(&req);
+    let method = req.method().clone();
+    let req_path = req.uri().path().to_string();
+    match (&method, req_path.as_str()) {
+        (&Method::GET, path::ADMIN) => dashboard(dc, rt, params).await,
+        (&Method::POST, path::ADMIN_ROUTE_REMOVE) => match get_form(req).await.remove("service") {
+            Some(service) => remove_route(dc, ep, &admin, service).await,
+            None => resp_with_code(StatusCode::BAD_REQUEST),
+        },
+        (&Method::POST, path::ADMIN_EVENT_REPLAY) => match get_form(req).await.remove("id") {
+            Some(id) => replay_event(dc, ep, &admin, id).await,
+            None => resp_with_code(StatusCode::BAD_REQUEST),
+        },
+        _ => resp_with_code(StatusCode::METHOD_NOT_ALLOWED),
+    }
+}
+
+async fn get_form(req: Request<Body>) -> HashMap<String, String> {
+    match hyper::body::to_bytes(req.into_body()).await {
+        Ok(bytes) => url::form_urlencoded::parse(&bytes).into_owned().collect(),
+        Err(e) => {
+            error!("admin form: {}", e);
+            HashMap::new()
+        }
+    }
+}
+
+fn get_context(name: &str) -> context::Context {
+    context::Context::new(
+        &Uuid::new_v4().to_hyphenated().to_string(),
+        None,
+        context::Span::new(name, context::SpanKind::Internal),
+    )
+}
+
+//same as RemoveRoute command, subscribers are notified by OnRouteUpdate
+async fn remove_route(
+    dc: &connectors::DataConnector,
+    ep: &publishers::EventPublisher,
+    admin: &str,
+    service: String,
+) -> Response<Body> {
+    let ctx = get_context(path::ADMIN_ROUTE_REMOVE);
+    //boxed error is not Send, it must not live across await
+    let res = handlers::route::remove(
+        dc,
+        commands::route::RemoveRoute {
+            services: vec![service.clone()],
+        },
+    )
+    .await
+    .map_err(|e| errors::from_boxed(&e));
+    //audited as RemoveRoute command of admin
+    logger::audit(&logger::AuditRecord {
+        time: Utc::now(),
+        object_type: commands::route::RemoveRoute::get_type_name(),
+        correlation_id: Some(&ctx.correlation_id),
+        caller: None,
+        usr: Some(admin),
+        status: match &res {
+            Ok(_) => StatusCode::OK.as_u16(),
+            Err(e) => e.status().as_u16(),
+        },
+    });
+    let (reply, events) = match res {
+        Ok(r) => r,
+        Err(e) => return resp_redirect(&format!("remove route {}: {}", service, e)),
+    };
+    if !reply.is_ok() {
+        return resp_redirect(&format!(
+            "remove route {}: {}",
+            service,
+            reply.error_name.unwrap_or(reply.error_code.to_string())
+        ));
+    }
+    info!(
+        "correlation id {} admin {} removed route {}",
+        ctx.correlation_id, admin, service
+    );
+    if let Some(events) = events {
+        let res = ep
+            .send(&ctx, events)
+            .await
+            .map_err(|e| errors::from_boxed(&e));
+        if let Err(e) = res {
+            error!("event publisher: {}", e);
+        }
+    }
+    resp_redirect(&format!("route {} is removed", service))
+}
+
+async fn replay_event(
+    dc: &connectors::DataConnector,
+    ep: &publishers::EventPublisher,
+    admin: &str,
+    id: String,
+) -> Response<Body> {
+    let ctx = get_context(path::ADMIN_EVENT_REPLAY);
+    //boxed error is not Send, it must not live across await
+    let res = dc
+        .dead_letter
+        .get(Some(vec![id.clone()]))
+        .await
+        .map_err(|e| errors::from_boxed(&e));
+    let event = match res.map(|items| items.into_iter().next()) {
+        Ok(Some(event)) => event,
+        Ok(None) => return resp_redirect(&format!("replay event {}: not found", id)),
+        Err(e) => return resp_redirect(&format!("replay event {}: {}", id, e)),
+    };
+    let res = ep
+        .replay(&ctx, &id)
+        .await
+        .map_err(|e| errors::from_boxed(&e));
+    //audited as replayed event of admin
+    logger::audit(&logger::AuditRecord {
+        time: Utc::now(),
+        object_type: &event.object_type,
+        correlation_id: Some(&event.correlation_id),
+        caller: None,
+        usr: Some(admin),
+        status: match &res {
+            Ok(_) => StatusCode::OK.as_u16(),
+            Err(e) => e.status().as_u16(),
+        },
+    });
+    match res {
+        Ok(_) => {
+            info!(
+                "correlation id {} admin {} replayed event {}",
+                event.correlation_id, admin, id
+            );
+            resp_redirect(&format!("event {} is replayed", id))
+        }
+        Err(e) => resp_redirect(&format!("replay event {}: {}", id, e)),
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+//cells are escaped, except action forms
+fn table(title: &str, headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut html = format!("<h3>{}</h3>\n", title);
+    if rows.len() == 0 {
+        html.push_str("<p>none</p>\n");
+        return html;
+    }
+    html.push_str("<table>\n<tr>");
+    for h in headers {
+        html.push_str(&format!("<th>{}</th>", h));
+    }
+    html.push_str("</tr>\n");
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row {
+            html.push_str(&format!("<td>{}</td>", cell));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+fn form(action: &str, name: &str, value: &str, button: &str) -> String {
+    format!(
+        "<form method=\"post\" action=\"{}\"><input type=\"hidden\" name=\"{}\" value=\"{}\"><button>{}</button></form>",
+        action,
+        name,
+        escape(value),
+        button
+    )
+}
+
+fn get_paths(path: &Option<HashMap<String, String>>) -> String {
+    let mut items: Vec<String> = path
+        .iter()
+        .flat_map(|p| p.iter())
+        .map(|(proto, to)| format!("{} {}", escape(proto), escape(to)))
+        .collect();
+    items.sort();
+    items.join("<br>")
+}
+
+async fn dashboard(
+    dc: &connectors::DataConnector,
+    rt: &router::Router,
+    params: HashMap<String, String>,
+) -> Response<Body> {
+    let mut html = String::from(
+        "<!doctype html>\n<html>\n<head>\n<title>Router administration</title>\n\
+         <style>table{border-collapse:collapse}td,th{border:1px solid #ccc;padding:2px 6px;text-align:left}</style>\n\
+         </head>\n<body>\n<h2>Router administration</h2>\n",
+    );
+    if let Some(message) = params.get("message") {
+        html.push_str(&format!("<p><b>{}</b></p>\n", escape(message)));
+    }
+    //every section is shown, failed one shows its error
+    let readiness = rt.helth.get_readiness(dc, rt).await;
+    html.push_str(&table(
+        "Router",
+        &["Component", "State", "Message"],
+        readiness
+            .components
+            .iter()
+            .map(|c| {
+                vec![
+                    escape(&c.name),
+                    c.state.to_string(),
+                    escape(c.message.as_ref().map(|m| m.as_str()).unwrap_or("")),
+                ]
+            })
+            .collect(),
+    ));
+    html.push_str(&services_section(dc, rt).await);
+    html.push_str(&commands_section(dc).await);
+    html.push_str(&events_section(dc).await);
+    html.push_str(&async_commands_section(dc, &params).await);
+    html.push_str(&dead_letters_section(dc, &params).await);
+    html.push_str("</body>\n</html>\n");
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .body(Body::from(html))
+        .unwrap()
+}
+
+fn section_error(title: &str, e: &str) -> String {
+    format!("<h3>{}</h3>\n<p>{}</p>\n", title, escape(e))
+}
+
+async fn services_section(dc: &connectors::DataConnector, rt: &router::Router) -> String {
+    let cmd = commands::route::GetService {
+        filter: None,
+        names: None,
+    };
+    let reply = match handlers::route::get_service(dc, rt, cmd)
+        .await
+        .map_err(|e| e.to_string())
+    {
+        Ok(r) => r,
+        Err(e) => return section_error("Services", &e),
+    };
+    let breakers = reply.breakers.unwrap_or_default();
+    let rows = reply
+        .items
+        .unwrap_or_default()
+        .iter()
+        .map(|s| {
+            let failures = breakers
+                .iter()
+                .find(|b| b.service_name == s.name)
+                .map(|b| format!("{} {}", b.state.to_string(), b.failure_count))
+                .unwrap_or_else(|| "not called".to_string());
+            vec![
+                escape(&s.name),
+                escape(&s.description),
+                s.priority.to_string(),
+                handlers::route::get_service_state(rt, &s.name).to_string(),
+                failures,
+                s.added_at.to_rfc3339(),
+                form(path::ADMIN_ROUTE_REMOVE, "service", &s.name, "remove"),
+            ]
+        })
+        .collect();
+    table(
+        "Services",
+        &[
+            "Name",
+            "Description",
+            "Priority",
+            "Health",
+            "Breaker",
+            "Added",
+            "",
+        ],
+        rows,
+    )
+}
+
+async fn commands_section(dc: &connectors::DataConnector) -> String {
+    let cmd = commands::route::GetServiceCommand {
+        filter: None,
+        services: None,
+    };
+    let reply = match handlers::route::get_command(dc, cmd)
+        .await
+        .map_err(|e| e.to_string())
+    {
+        Ok(r) => r,
+        Err(e) => return section_error("Commands", &e),
+    };
+    let rows = reply
+        .items
+        .unwrap_or_default()
+        .iter()
+        .map(|c| {
+            vec![
+                escape(&c.object_type),
+                escape(c.service_name.as_ref().map(|s| s.as_str()).unwrap_or("")),
+                escape(&c.reply_type),
+                escape(&c.exec_mode),
+                get_paths(&c.path),
+            ]
+        })
+        .collect();
+    table(
+        "Commands",
+        &["Object type", "Service", "Reply", "Exec mode", "Providers"],
+        rows,
+    )
+}
+
+//events of routes with their subscribers
+async fn events_section(dc: &connectors::DataConnector) -> String {
+    let routes = match handlers::route::get(
+        dc,
+        commands::route::GetRoute {
+            filter: None,
+            services: None,
+        },
+    )
+    .await
+    .map_err(|e| e.to_string())
+    {
+        Ok(r) => r.items.unwrap_or_default(),
+        Err(e) => return section_error("Events", &e),
+    };
+    let subscriptions = match handlers::route::get_subscription(
+        dc,
+        commands::route::GetServiceSubscription {
+            filter: None,
+            services: None,
+        },
+    )
+    .await
+    .map_err(|e| e.to_string())
+    {
+        Ok(r) => r.items.unwrap_or_default(),
+        Err(e) => return section_error("Events", &e),
+    };
+    let mut events = BTreeMap::<String, (Vec<String>, Vec<String>)>::new();
+    for r in &routes {
+        for e in &r.event {
+            events
+                .entry(e.object_type.clone())
+                .or_default()
+                .0
+                .push(escape(
+                    r.service_name.as_ref().map(|s| s.as_str()).unwrap_or(""),
+                ));
+        }
+    }
+    for s in &subscriptions {
+        events
+            .entry(s.object_type.clone())
+            .or_default()
+            .1
+            .push(format!(
+                "{}<br>{}",
+                escape(s.service_name.as_ref().map(|s| s.as_str()).unwrap_or("")),
+                get_paths(&s.path)
+            ));
+    }
+    let rows = events
+        .into_iter()
+        .map(|(object_type, (publishers, subscribers))| {
+            vec![
+                escape(&object_type),
+                publishers.join("<br>"),
+                subscribers.join("<br>"),
+            ]
+        })
+        .collect();
+    table(
+        "Events",
+        &["Object type", "Publishers", "Subscribers"],
+        rows,
+    )
+}
+
+//page of list and link to next one, lists are not loaded whole
+fn get_page(params: &HashMap<String, String>, name: &str) -> u32 {
+    params
+        .get(name)
+        .and_then(|p| p.parse::<u32>().ok())
+        .unwrap_or(0)
+}
+
+fn pager(name: &str, page: u32, has_next: bool) -> String {
+    let mut links = Vec::<String>::new();
+    if page > 0 {
+        links.push(format!(
+            "<a href=\"{}?{}={}\">newer</a>",
+            path::ADMIN,
+            name,
+            page - 1
+        ));
+    }
+    if has_next {
+        links.push(format!(
+            "<a href=\"{}?{}={}\">older</a>",
+            path::ADMIN,
+            name,
+            page + 1
+        ));
+    }
+    if links.len() > 0 {
+        format!("<p>{}</p>\n", links.join(" "))
+    } else {
+        String::new()
+    }
+}
+
+//one more item is read to know if next page exists
+fn split_page<T>(mut items: Vec<T>) -> (Vec<T>, bool) {
+    let has_next = items.len() > PAGE_SIZE as usize;
+    items.truncate(PAGE_SIZE as usize);
+    (items, has_next)
+}
+
+async fn async_commands_section(
+    dc: &connectors::DataConnector,
+    params: &HashMap<String, String>,
+) -> String {
+    let mut html = String::new();
+    let page = get_page(params, SENDED_PAGE);
+    match dc
+        .sended_async_command
+        .get_page(page.saturating_mul(PAGE_SIZE), PAGE_SIZE + 1)
+        .await
+        .map_err(|e| e.to_string())
+    {
+        Ok(items) => {
+            let (items, has_next) = split_page(items);
+            html.push_str(&table(
+                "Sended async commands",
+                &["Id", "Object type", "Service", "State", "Changed"],
+                items
+                    .iter()
+                    .map(|c| {
+                        vec![
+                            escape(&c.id),
+                            escape(&c.object_type),
+                            escape(&c.service_name),
+                            escape(&c.state),
+                            c.state_changed_at.to_rfc3339(),
+                        ]
+                    })
+                    .collect(),
+            ));
+            html.push_str(&pager(SENDED_PAGE, page, has_next));
+        }
+        Err(e) => html.push_str(&section_error("Sended async commands", &e)),
+    }
+    let page = get_page(params, RECEIVED_PAGE);
+    match dc
+        .received_async_command
+        .get_page(page.saturating_mul(PAGE_SIZE), PAGE_SIZE + 1)
+        .await
+        .map_err(|e| e.to_string())
+    {
+        Ok(items) => {
+            let (items, has_next) = split_page(items);
+            html.push_str(&table(
+                "Received async commands",
+                &["Id", "Object type", "Service", "State", "Changed"],
+                items
+                    .iter()
+                    .map(|c| {
+                        vec![
+                            escape(&c.id),
+                            escape(&c.object_type),
+                            escape(&c.service_name),
+                            escape(&c.state),
+                            c.state_changed_at.to_rfc3339(),
+                        ]
+                    })
+                    .collect(),
+            ));
+            html.push_str(&pager(RECEIVED_PAGE, page, has_next));
+        }
+        Err(e) => html.push_str(&section_error("Received async commands", &e)),
+    }
+    html
+}
+
+async fn dead_letters_section(
+    dc: &connectors::DataConnector,
+    params: &HashMap<String, String>,
+) -> String {
+    let page = get_page(params, DEAD_LETTER_PAGE);
+    match dc
+        .dead_letter
+        .get_page(page.saturating_mul(PAGE_SIZE), PAGE_SIZE + 1)
+        .await
+        .map_err(|e| e.to_string())
+    {
+        Ok(items) => {
+            let (items, has_next) = split_page(items);
+            let mut html = table(
+                "Dead letter events",
+                &[
+                    "Id",
+                    "Object type",
+                    "Subscriber",
+                    "Correlation id",
+                    "Error",
+                    "Added",
+                    "",
+                ],
+                items
+                    .iter()
+                    .map(|e| {
+                        vec![
+                            escape(&e.id),
+                            escape(&e.object_type),
+                            escape(&e.service_name),
+                            escape(&e.correlation_id),
+                            escape(&e.error),
+                            e.added_at.to_rfc3339(),
+                            form(path::ADMIN_EVENT_REPLAY, "id", &e.id, "replay"),
+                        ]
+                    })
+                    .collect(),
+            );
+            html.push_str(&pager(DEAD_LETTER_PAGE, page, has_next));
+            html
+        }
+        Err(e) => section_error("Dead letter events", &e),
+    }
+}
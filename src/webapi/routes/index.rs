@@ -1,42 +1,55 @@
+use super::super::{breaker, connectors, metrics, openapi};
+use super::path;
 use hyper::{header, Body, Response, StatusCode};
-use std::env;
-use std::fs;
+use schemars::schema::RootSchema;
+use std::collections::HashMap;
 
-pub async fn spec_json() -> Response<Body> {
-    const ENV_OPENAPI_JSON: &str = "MY_BIN_OPENAPI_JSON";
-    const DEFAULT_OPENAPI_SPEC: &str = "openapi.json";
-    let file: Option<String> = {
-        match env::var(ENV_OPENAPI_JSON).is_ok() {
-            true => Some(env::var(ENV_OPENAPI_JSON).unwrap()),
-            _ => None,
-        }
-    };
+pub fn is_page_path(path: &str) -> bool {
+    [
+        "/",
+        "/openapi.json",
+        "/openapi.yaml",
+        path::LIVENESS,
+        path::READINESS,
+        path::METRICS,
+    ]
+    .contains(&path)
+}
 
+pub async fn metrics(
+    metrics: &metrics::Metrics,
+    dc: &connectors::DataConnector,
+    breaker: &breaker::CircuitBreakers,
+) -> Response<Body> {
     Response::builder()
         .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, "application/json; charset=utf-8")
+        .header(
+            header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )
         .body(Body::from(
-            fs::read_to_string(file.unwrap_or(String::from(DEFAULT_OPENAPI_SPEC))).unwrap(),
+            metrics.to_text(dc, breaker.get_breakers()).await,
         ))
         .unwrap()
 }
 
-pub async fn spec_yaml() -> Response<Body> {
-    const ENV_OPENAPI_YAML: &str = "MY_BIN_OPENAPI_YAML";
-    const DEFAULT_OPENAPI_SPEC: &str = "openapi.yaml";
-    let file: Option<String> = {
-        match env::var(ENV_OPENAPI_YAML).is_ok() {
-            true => Some(env::var(ENV_OPENAPI_YAML).unwrap()),
-            _ => None,
-        }
-    };
+pub async fn spec_json(schema: &HashMap<&'static str, RootSchema>) -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/json; charset=utf-8")
+        .body(Body::from(
+            serde_json::to_string_pretty(&openapi::make_openapi(schema)).unwrap(),
+        ))
+        .unwrap()
+}
 
+pub async fn spec_yaml(schema: &HashMap<&'static str, RootSchema>) -> Response<Body> {
     Response::builder()
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
         .header("Access-Control-Allow-Origin", "https://editor.swagger.io")
         .body(Body::from(
-            fs::read_to_string(file.unwrap_or(String::from(DEFAULT_OPENAPI_SPEC))).unwrap(),
+            serde_yaml::to_string(&openapi::make_openapi(schema)).unwrap(),
         ))
         .unwrap()
 }
@@ -52,6 +65,7 @@ pub async fn handler() -> Response<Body> {
     <h2>Microservice</h2>
     <p><a href="./openapi.json">openapi.json</a></p> 
     <p><a href="./openapi.yaml">openapi.yaml</a></p> 
+    <p><a href="./admin">admin</a></p> 
     </body>
     </html>
     "#;
@@ -1,3 +1,4 @@
+pub mod admin;
 mod index;
 pub mod path;
-pub mod service;
\ No newline at end of file
+pub mod service;
@@ -1,7 +1,13 @@
-pub const HELTH: &str = "/helth";//return uptime is body if alive
-pub const SCHEMA: &str = "/schema";//require object_type
-pub const ERROR: &str = "/error";//require error_code
-pub const STATE: &str = "/state";//require async_command_id
+pub const HELTH: &str = "/helth"; //return uptime is body if alive
+pub const SCHEMA: &str = "/schema"; //schema list if object_type is not set
+pub const ERROR: &str = "/error"; //optional error_code and lang
+pub const STATE: &str = "/state"; //require async_command_id
+pub const LIVENESS: &str = "/helth/live"; //get, without authorization
+pub const READINESS: &str = "/helth/ready"; //get, 503 if some component is unavailable
+pub const METRICS: &str = "/metrics"; //get, prometheus text format without authorization
+pub const ADMIN: &str = "/admin"; //get, router dashboard with admin authorization
+pub const ADMIN_ROUTE_REMOVE: &str = "/admin/route/remove"; //post form, service
+pub const ADMIN_EVENT_REPLAY: &str = "/admin/event/replay"; //post form, dead letter id
 
 pub const USR_ITEMS: &str = "/usrs";
 pub const USR_SIGHN_IN: &str = "/usr/signin";
@@ -10,10 +16,13 @@ pub const USR_SIGHN_UP: &str = "/usr/signup";
 pub const ROUTER_ROUTE_GET: &str = "/router/route/get";
 pub const ROUTER_ROUTE_ADD: &str = "/router/route/add";
 pub const ROUTER_ROUTE_REMOVE: &str = "/router/route/remove";
+pub const ROUTER_ROUTE_RENEW: &str = "/router/route/renew";
 pub const ROUTER_COMMAND_GET: &str = "/router/command/get";
 pub const ROUTER_EVENT_GET: &str = "/router/event/get";
 pub const ROUTER_SUBSCIBTION_GET: &str = "/router/subscription/get";
 pub const ROUTER_SERVICE_GET: &str = "/router/service/get";
+pub const ROUTER_ERROR_GET: &str = "/router/error/get";
+pub const ROUTER_SCHEMA_GET: &str = "/router/schema/get";
 pub const ROUTER_EVENT_ON_SERVICE_UNAVAILABLE: &str = "/router/event/on_service_unavailable";
 
 pub const EVENT_ON_ROUTE_UPDATE: &str = "/event/on_route_update";
@@ -24,11 +33,7 @@ pub const CAR_ADD: &str = "/car/add";
 pub const CAR_CHANGE: &str = "/car/change";
 pub const CAR_REMOVE: &str = "/car/remove";
 pub const CAR_RESERVE: &str = "/car/reserve";
+pub const CAR_RELEASE: &str = "/car/release";
 
 #[cfg(test)]
-pub const ROUTE_WITH_EMPTY_BODY: [&str; 4] = [
-    HELTH,
-    USR_SIGHN_IN,
-    USR_SIGHN_UP,
-    USR_ITEMS
-];
+pub const ROUTE_WITH_EMPTY_BODY: [&str; 4] = [HELTH, USR_SIGHN_IN, USR_SIGHN_UP, USR_ITEMS];
@@ -1,34 +1,64 @@
 use super::super::{
-    access, commands, connectors, entities::*, errors, events, executors, handlers, publishers,
-    router,
+    access, commands, connectors, context, entities, entities::*, errors, events, executors,
+    handlers, idempotency, limiter, logger, providers, publishers, replies, router, schema,
+    settings, tracer, traits::ObjectType, validator,
 };
-use super::{index, path};
-use bytes::buf::BufExt;
+use super::{admin, index, path};
+use async_trait::async_trait;
+use chrono::Utc;
 use hyper::{error::Result, header, Body, Method, Request, Response, StatusCode};
-use serde::ser;
+use serde::{de, ser};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 
-// pub type Handler = dyn FnOnce(
-//     Body,
-//     HashMap<String, String>,
-// ) -> Box<dyn Future<Output = (Body, HashMap<String, String>)>>;
+//refs a registered handler runs with
+pub struct HandlerContext<'a> {
+    pub dc: &'a connectors::DataConnector,
+    pub ce: &'a executors::CommandExecutor,
+    pub ep: &'a publishers::EventPublisher,
+    pub ctx: &'a context::Context,
+}
 
-pub type Handler = fn(Body, HashMap<String, String>) -> (Body, HashMap<String, String>);
+//typed handler of command added by binary, routed by object type of command
+#[async_trait]
+pub trait CommandHandler: Send + Sync {
+    type Command: de::DeserializeOwned + ObjectType + Send;
+    type Reply: ser::Serialize + ObjectType + Send;
 
-// pub async fn async_command_handler(
-//     body: Body,
-//     param: HashMap<String, String>,
-// ) -> (Body, HashMap<String, String>) {
-//     (Body::empty(), HashMap::<String, String>::new())
-// }
+    async fn handle(
+        &self,
+        hc: &HandlerContext<'_>,
+        cmd: Self::Command,
+    ) -> connectors::Result<Self::Reply>;
+}
 
-// pub async fn event_handler(
-//     body: Body,
-//     param: HashMap<String, String>,
-// ) -> (Body, HashMap<String, String>) {
-//     (Body::empty(), HashMap::<String, String>::new())
-// }
+//command handler with body and reply as http
+#[async_trait]
+pub trait Handler: Send + Sync {
+    async fn call(&self, hc: &HandlerContext<'_>, body: &[u8]) -> Response<Body>;
+}
+
+#[async_trait]
+impl<H: CommandHandler> Handler for H {
+    async fn call(&self, hc: &HandlerContext<'_>, body: &[u8]) -> Response<Body> {
+        let cmd: Option<H::Command> = serde_json::from_slice(body).unwrap_or(None);
+        match cmd {
+            Some(cmd) => resp(hc.dc, self.handle(hc, cmd).await),
+            None => {
+                error!(
+                    "correlation id {} {} handler: bad body",
+                    hc.ctx.correlation_id,
+                    H::Command::get_type_name()
+                );
+                resp_with_code(StatusCode::BAD_REQUEST)
+            }
+        }
+    }
+}
+
+//by object type of command
+pub type Handlers = HashMap<&'static str, Box<dyn Handler>>;
 
 pub async fn service_route(
     req: Request<Body>,
@@ -37,11 +67,267 @@ pub async fn service_route(
     ce: Arc<executors::CommandExecutor>,
     ep: Arc<publishers::EventPublisher>,
     rt: Arc<router::Router>,
-    hr: Arc<HashMap<&str, Handler>>,
+    tr: Arc<tracer::Tracer>,
+    hr: Arc<Handlers>,
+) -> Result<Response<Body>> {
+    let params = get_params(&req);
+    let ctx = get_context(&params, &req);
+    let method = req.method().to_string();
+    let path = req.uri().path().to_string();
+    //request type of route, or requested type for schema and state routes
+    let object_type = match rt.request_type.get(path.as_str()) {
+        Some(Some((name, _))) => Some(name.to_string()),
+        _ => params.get("object_type").cloned(),
+    };
+    let usr = req
+        .headers()
+        .get("Authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| ac.get_usr_name_by_header(v))
+        .cloned();
+    let mt = rt.metrics.clone();
+    let path_label = get_path_label(&method, &path, &rt);
+    let started_at = Instant::now();
+    let key = get_idempotency_key(&method, ctx.as_ref(), object_type.as_ref(), usr.as_ref());
+    let limited = limit_request(&method, object_type.as_ref(), usr.as_ref(), &dc, &rt).await;
+    let res = match (limited, &key) {
+        (Some(r), _) => Ok(r),
+        (None, Some(key)) => {
+            route_idempotent_request(req, key, ctx.as_ref(), dc, ac, ce, ep, rt, hr).await
+        }
+        (None, None) => route_request(req, ctx.as_ref(), dc, ac, ce, ep, rt, hr).await,
+    };
+    if let Ok(r) = &res {
+        let status = r.status().as_u16();
+        let elapsed = started_at.elapsed();
+        mt.add_request(&method, path_label, status, elapsed);
+        let correlation_id = ctx.as_ref().map(|c| c.correlation_id.as_str());
+        let caller = ctx
+            .as_ref()
+            .and_then(|c| c.caller.as_ref().map(|s| s.as_str()));
+        logger::access(&logger::AccessRecord {
+            time: Utc::now(),
+            method: &method,
+            path: &path,
+            object_type: object_type.as_ref().map(|s| s.as_str()),
+            correlation_id: correlation_id,
+            caller: caller,
+            usr: usr.as_ref().map(|s| s.as_str()),
+            status: status,
+            latency_ms: elapsed.as_secs_f64() * 1000.0,
+        });
+        match &object_type {
+            Some(ot) if logger::is_audited(ot) => logger::audit(&logger::AuditRecord {
+                time: Utc::now(),
+                object_type: ot,
+                correlation_id: correlation_id,
+                caller: caller,
+                usr: usr.as_ref().map(|s| s.as_str()),
+                status: status,
+            }),
+            _ => {}
+        }
+    }
+    //server span ends with response
+    if let Some(ctx) = &ctx {
+        match &res {
+            Ok(r) => tr.export(ctx, Some(r.status().as_u16()), r.status().is_server_error()),
+            Err(_) => tr.export(ctx, None, true),
+        }
+    }
+    res
+}
+
+//unknown paths share one label
+fn get_path_label<'a>(method: &str, path: &'a str, rt: &router::Router) -> &'a str {
+    let is_known = admin::is_admin_path(path)
+        || match method {
+            "POST" => rt.request_type.contains_key(path),
+            "GET" => index::is_page_path(path),
+            _ => false,
+        };
+    if is_known {
+        path
+    } else {
+        "other"
+    }
+}
+
+fn get_params(req: &Request<Body>) -> HashMap<String, String> {
+    req.uri()
+        .query()
+        .map(|v| {
+            url::form_urlencoded::parse(v.as_bytes())
+                .into_owned()
+                .collect()
+        })
+        .unwrap_or_else(HashMap::new)
+}
+
+//none if correlation_id is not set
+fn get_context(params: &HashMap<String, String>, req: &Request<Body>) -> Option<context::Context> {
+    match params.get("correlation_id") {
+        Some(correlation_id) => Some(context::Context::from_request(
+            correlation_id,
+            params.get("service_name").map(|s| s.as_str()),
+            req.headers(),
+            req.uri().path(),
+        )),
+        None => None,
+    }
+}
+
+//commands of authorized users, response is set if request is over limit
+async fn limit_request(
+    method: &str,
+    object_type: Option<&String>,
+    usr: Option<&String>,
+    dc: &connectors::DataConnector,
+    rt: &router::Router,
+) -> Option<Response<Body>> {
+    let (ot, usr) = match (object_type, usr) {
+        (Some(ot), Some(usr)) if method == "POST" => (ot, usr),
+        _ => return None,
+    };
+    //boxed error is not Send, it must not live across await
+    let check = rt
+        .limiter
+        .check(dc, usr, ot)
+        .await
+        .map_err(|e| errors::from_boxed(&e));
+    match check {
+        Ok(limiter::Check::Allowed) => None,
+        Ok(limiter::Check::Limited(retry_after)) => {
+            let e = errors::Error::with_context(
+                errors::ErrorKind::TooManyRequests,
+                &format!("{} {} is over limit", usr, ot),
+            );
+            warn!("{}, retry after {} seconds", e, retry_after);
+            let mut r = e.to_response(&dc.error);
+            r.headers_mut()
+                .insert(header::RETRY_AFTER, header::HeaderValue::from(retry_after));
+            Some(r)
+        }
+        //limits must not stop service if database is unavailable
+        Err(e) => {
+            error!("{} {} limiter: {}", usr, ot, e);
+            None
+        }
+    }
+}
+
+//mutating commands of authorized users, other requests are executed every time
+fn get_idempotency_key(
+    method: &str,
+    ctx: Option<&context::Context>,
+    object_type: Option<&String>,
+    usr: Option<&String>,
+) -> Option<entities::idempotency::IdempotencyKey> {
+    match (ctx, object_type, usr) {
+        (Some(ctx), Some(ot), Some(usr)) if method == "POST" && commands::is_mutating(ot) => {
+            Some(entities::idempotency::IdempotencyKey {
+                //declared service name is not authenticated, it only narrows key of user
+                caller: match &ctx.caller {
+                    Some(caller) => format!("{}:{}", usr, caller),
+                    None => usr.clone(),
+                },
+                object_type: ot.clone(),
+                correlation_id: ctx.correlation_id.clone(),
+            })
+        }
+        _ => None,
+    }
+}
+
+//repeated request gets reply of original one instead of being executed again
+async fn route_idempotent_request(
+    req: Request<Body>,
+    key: &entities::idempotency::IdempotencyKey,
+    ctx: Option<&context::Context>,
+    dc: Arc<connectors::DataConnector>,
+    ac: Arc<access::AccessChecker>,
+    ce: Arc<executors::CommandExecutor>,
+    ep: Arc<publishers::EventPublisher>,
+    rt: Arc<router::Router>,
+    hr: Arc<Handlers>,
 ) -> Result<Response<Body>> {
-    if hr.contains_key("s1") {
-        let f = hr.get("s1").unwrap();
-        f(Body::empty(), HashMap::<String, String>::new());
+    //boxed error is not Send, it must not live across await
+    let begin = rt
+        .idempotency
+        .begin(&dc, key)
+        .await
+        .map_err(|e| errors::from_boxed(&e));
+    match begin {
+        Ok(idempotency::Begin::New) => {}
+        Ok(idempotency::Begin::Replay(status, body)) => {
+            debug!(
+                "correlation id {} {} reply is replayed",
+                key.correlation_id, key.object_type
+            );
+            return Ok(Response::builder()
+                .status(status)
+                .header(header::CONTENT_TYPE, "application/json; charset=utf-8")
+                .header(idempotency::REPLAYED_HEADER, "true")
+                .body(Body::from(body))
+                .unwrap());
+        }
+        Ok(idempotency::Begin::InProgress) => {
+            let e = errors::Error::with_context(
+                errors::ErrorKind::DuplicateRequest,
+                &format!(
+                    "correlation id {} {} is in progress",
+                    key.correlation_id, key.object_type
+                ),
+            );
+            warn!("{}", e);
+            return Ok(e.to_response(&dc.error));
+        }
+        Err(e) => {
+            error!("correlation id {} idempotency: {}", key.correlation_id, e);
+            return Ok(e.to_response(&dc.error));
+        }
+    }
+    let res = route_request(req, ctx, dc.clone(), ac, ce, ep, rt.clone(), hr).await;
+    match res {
+        Ok(r) if r.status() == StatusCode::OK => {
+            let (parts, body) = r.into_parts();
+            let bytes = hyper::body::to_bytes(body).await?;
+            if let Err(e) = rt
+                .idempotency
+                .complete(
+                    &dc,
+                    key,
+                    parts.status.as_u16(),
+                    &String::from_utf8_lossy(&bytes),
+                )
+                .await
+            {
+                error!("correlation id {} idempotency: {}", key.correlation_id, e);
+            }
+            Ok(Response::from_parts(parts, Body::from(bytes)))
+        }
+        res => {
+            if let Err(e) = rt.idempotency.cancel(&dc, key).await {
+                error!("correlation id {} idempotency: {}", key.correlation_id, e);
+            }
+            res
+        }
+    }
+}
+
+async fn route_request(
+    req: Request<Body>,
+    ctx: Option<&context::Context>,
+    dc: Arc<connectors::DataConnector>,
+    ac: Arc<access::AccessChecker>,
+    ce: Arc<executors::CommandExecutor>,
+    ep: Arc<publishers::EventPublisher>,
+    rt: Arc<router::Router>,
+    hr: Arc<Handlers>,
+) -> Result<Response<Body>> {
+    //admin has own credential and html forms
+    if admin::is_admin_path(req.uri().path()) {
+        return Ok(admin::route(req, &dc, &ac, &ep, &rt).await);
     }
     let (parts, body) = req.into_parts();
     if parts.method == Method::POST {
@@ -72,31 +358,37 @@ pub async fn service_route(
                     .collect()
             })
             .unwrap_or_else(HashMap::new);
-        if !params.contains_key("correlation_id") {
-            return Ok(resp_with_code(StatusCode::BAD_REQUEST));
-        }
-        let correlation_id = params.get("correlation_id").unwrap();
-        let _exec_mode = if params.contains_key("exec_mode") {
-            params.get("exec_mode").unwrap().to_string()
-        } else {
-            executors::ExecMode::Any.to_string()
-        };
-        let _service_name = if params.contains_key("service_name") {
-            Some(params.get("service_name").unwrap())
-        } else {
-            None
+        let ctx = match ctx {
+            Some(ctx) => ctx,
+            None => return Ok(resp_with_code(StatusCode::BAD_REQUEST)),
         };
-        let reader = hyper::body::aggregate(body).await?.reader();
-        Ok(match parts.uri.path() {
-            path::USR_SIGHN_IN => resp(handlers::usr::signin(&dc).await),
-            path::USR_SIGHN_UP => resp(handlers::usr::signup(&dc).await),
+        let is_async = params.get("exec_mode") == Some(&executors::ExecMode::Async.to_string());
+        let bytes = hyper::body::to_bytes(body).await?;
+        if rt.validation.request {
+            let violations = validate_request(&rt, parts.uri.path(), &bytes);
+            if violations.len() > 0 {
+                error!(
+                    "{} handler: {} schema violations",
+                    parts.uri.path(),
+                    violations.len()
+                );
+                return Ok(resp_violations(&dc, violations));
+            }
+        }
+        let reader: &[u8] = &bytes;
+        let res = match parts.uri.path() {
+            path::USR_SIGHN_IN => resp(&dc, handlers::usr::signin(&dc).await),
+            path::USR_SIGHN_UP => resp(&dc, handlers::usr::signup(&dc).await),
             path::ROUTER_ROUTE_GET => {
                 let cmd: Option<commands::route::GetRoute> =
                     serde_json::from_reader(reader).unwrap_or(None);
                 if cmd.is_some() {
-                    resp(handlers::route::get(&dc, cmd.unwrap()).await)
+                    resp(&dc, handlers::route::get(&dc, cmd.unwrap()).await)
                 } else {
-                    error!("get_route handler: bad body");
+                    error!(
+                        "correlation id {} get_route handler: bad body",
+                        ctx.correlation_id
+                    );
                     return Ok(resp_with_code(StatusCode::BAD_REQUEST));
                 }
             }
@@ -104,9 +396,12 @@ pub async fn service_route(
                 let cmd: Option<commands::route::GetServiceCommand> =
                     serde_json::from_reader(reader).unwrap_or(None);
                 if cmd.is_some() {
-                    resp(handlers::route::get_command(&dc, cmd.unwrap()).await)
+                    resp(&dc, handlers::route::get_command(&dc, cmd.unwrap()).await)
                 } else {
-                    error!("get_route_command handler: bad body");
+                    error!(
+                        "correlation id {} get_route_command handler: bad body",
+                        ctx.correlation_id
+                    );
                     return Ok(resp_with_code(StatusCode::BAD_REQUEST));
                 }
             }
@@ -114,9 +409,12 @@ pub async fn service_route(
                 let cmd: Option<commands::route::GetServiceEvent> =
                     serde_json::from_reader(reader).unwrap_or(None);
                 if cmd.is_some() {
-                    resp(handlers::route::get_event(&dc, cmd.unwrap()).await)
+                    resp(&dc, handlers::route::get_event(&dc, cmd.unwrap()).await)
                 } else {
-                    error!("get_route_event handler: bad body");
+                    error!(
+                        "correlation id {} get_route_event handler: bad body",
+                        ctx.correlation_id
+                    );
                     return Ok(resp_with_code(StatusCode::BAD_REQUEST));
                 }
             }
@@ -124,9 +422,15 @@ pub async fn service_route(
                 let cmd: Option<commands::route::GetServiceSubscription> =
                     serde_json::from_reader(reader).unwrap_or(None);
                 if cmd.is_some() {
-                    resp(handlers::route::get_subscription(&dc, cmd.unwrap()).await)
+                    resp(
+                        &dc,
+                        handlers::route::get_subscription(&dc, cmd.unwrap()).await,
+                    )
                 } else {
-                    error!("get_route_subscription handler: bad body");
+                    error!(
+                        "correlation id {} get_route_subscription handler: bad body",
+                        ctx.correlation_id
+                    );
                     return Ok(resp_with_code(StatusCode::BAD_REQUEST));
                 }
             }
@@ -134,9 +438,47 @@ pub async fn service_route(
                 let cmd: Option<commands::route::GetService> =
                     serde_json::from_reader(reader).unwrap_or(None);
                 if cmd.is_some() {
-                    resp(handlers::route::get_service(&dc, cmd.unwrap()).await)
+                    resp(
+                        &dc,
+                        handlers::route::get_service(&dc, &rt, cmd.unwrap()).await,
+                    )
+                } else {
+                    error!(
+                        "correlation id {} get_service handler: bad body",
+                        ctx.correlation_id
+                    );
+                    return Ok(resp_with_code(StatusCode::BAD_REQUEST));
+                }
+            }
+            path::ROUTER_ERROR_GET => {
+                let cmd: Option<commands::route::GetServiceError> =
+                    serde_json::from_reader(reader).unwrap_or(None);
+                if cmd.is_some() {
+                    resp(
+                        &dc,
+                        handlers::route::get_service_error(&rt, cmd.unwrap()).await,
+                    )
+                } else {
+                    error!(
+                        "correlation id {} get_service_error handler: bad body",
+                        ctx.correlation_id
+                    );
+                    return Ok(resp_with_code(StatusCode::BAD_REQUEST));
+                }
+            }
+            path::ROUTER_SCHEMA_GET => {
+                let cmd: Option<commands::route::GetServiceSchema> =
+                    serde_json::from_reader(reader).unwrap_or(None);
+                if cmd.is_some() {
+                    resp(
+                        &dc,
+                        handlers::route::get_service_schema(&rt, cmd.unwrap()).await,
+                    )
                 } else {
-                    error!("get_service handler: bad body");
+                    error!(
+                        "correlation id {} get_service_schema handler: bad body",
+                        ctx.correlation_id
+                    );
                     return Ok(resp_with_code(StatusCode::BAD_REQUEST));
                 }
             }
@@ -144,25 +486,59 @@ pub async fn service_route(
                 let cmd: Option<commands::route::AddRoute> =
                     serde_json::from_reader(reader).unwrap_or(None);
                 if cmd.is_some() {
+                    if rt.validation.route != settings::RouteCheck::Off {
+                        let violations =
+                            match rt.check_route_schema(&cmd.as_ref().unwrap().items).await {
+                                Ok(r) => r,
+                                Err(e) => {
+                                    error!("handler: {}", e);
+                                    return Ok(errors::from_boxed(&e).to_response(&dc.error));
+                                }
+                            };
+                        for v in &violations {
+                            warn!("add_routes handler: {} {}", v.path, v.message);
+                        }
+                        if violations.len() > 0
+                            && rt.validation.route == settings::RouteCheck::Reject
+                        {
+                            return Ok(resp_violations(&dc, violations));
+                        }
+                    }
                     let res = match handlers::route::add(&dc, cmd.unwrap()).await {
                         Ok(r) => r,
                         Err(e) => {
                             error!("handler: {}", e);
-                            return Ok(resp_with_code(StatusCode::INTERNAL_SERVER_ERROR));
+                            return Ok(errors::from_boxed(&e).to_response(&dc.error));
                         }
                     };
-                    if res.0.is_ok() {
-                        match ep.send(correlation_id, res.1.unwrap()).await {
+                    if let (true, Some(events)) = (res.0.is_ok(), res.1) {
+                        match ep.send(ctx, events).await {
                             Ok(_) => {}
                             Err(e) => {
                                 error!("event publisher: {}", e);
-                                return Ok(resp_with_code(StatusCode::INTERNAL_SERVER_ERROR));
+                                return Ok(errors::from_boxed(&e).to_response(&dc.error));
                             }
                         }
                     }
-                    resp(Ok(res.0))
+                    resp(&dc, Ok(res.0))
+                } else {
+                    error!(
+                        "correlation id {} add_routes handler: bad body",
+                        ctx.correlation_id
+                    );
+                    return Ok(resp_with_code(StatusCode::BAD_REQUEST));
+                }
+            }
+            path::ROUTER_ROUTE_RENEW => {
+                let cmd: Option<commands::route::RenewRoute> =
+                    serde_json::from_reader(reader).unwrap_or(None);
+                if cmd.is_some() {
+                    resp(&dc, handlers::route::renew(&dc, cmd.unwrap()).await)
                 } else {
-                    error!("add_routes handler: bad body");
+                    error!(
+                        "correlation id {} renew_routes handler: bad body",
+                        ctx.correlation_id
+                    );
                     return Ok(resp_with_code(StatusCode::BAD_REQUEST));
                 }
             }
@@ -174,21 +550,24 @@ pub async fn service_route(
                         Ok(r) => r,
                         Err(e) => {
                             error!("handler: {}", e);
-                            return Ok(resp_with_code(StatusCode::INTERNAL_SERVER_ERROR));
+                            return Ok(errors::from_boxed(&e).to_response(&dc.error));
                         }
                     };
                     if res.0.is_ok() {
-                        match ep.send(correlation_id, res.1.unwrap()).await {
+                        match ep.send(ctx, res.1.unwrap()).await {
                             Ok(_) => {}
                             Err(e) => {
                                 error!("event publisher: {}", e);
-                                return Ok(resp_with_code(StatusCode::INTERNAL_SERVER_ERROR));
+                                return Ok(errors::from_boxed(&e).to_response(&dc.error));
                             }
                         }
                     }
-                    resp(Ok(res.0))
+                    resp(&dc, Ok(res.0))
                 } else {
-                    error!("remove_routes handler: bad body");
+                    error!(
+                        "correlation id {} remove_routes handler: bad body",
+                        ctx.correlation_id
+                    );
                     return Ok(resp_with_code(StatusCode::BAD_REQUEST));
                 }
             }
@@ -203,21 +582,24 @@ pub async fn service_route(
                             Ok(r) => r,
                             Err(e) => {
                                 error!("handler: {}", e);
-                                return Ok(resp_with_code(StatusCode::INTERNAL_SERVER_ERROR));
+                                return Ok(errors::from_boxed(&e).to_response(&dc.error));
                             }
                         };
                     if res.0.is_ok() {
-                        match ep.send(correlation_id, res.1.unwrap()).await {
+                        match ep.send(ctx, res.1.unwrap()).await {
                             Ok(_) => {}
                             Err(e) => {
                                 error!("event publisher: {}", e);
-                                return Ok(resp_with_code(StatusCode::INTERNAL_SERVER_ERROR));
+                                return Ok(errors::from_boxed(&e).to_response(&dc.error));
                             }
                         }
                     }
-                    resp(Ok(res.0))
+                    resp(&dc, Ok(res.0))
                 } else {
-                    error!("on_service_unavailable handler: bad body");
+                    error!(
+                        "correlation id {} on_service_unavailable handler: bad body",
+                        ctx.correlation_id
+                    );
                     resp_with_code(StatusCode::BAD_REQUEST)
                 }
             }
@@ -225,9 +607,15 @@ pub async fn service_route(
                 let events: Option<Vec<events::route::OnRouteUpdate>> =
                     serde_json::from_reader(reader).unwrap_or(None);
                 if events.is_some() {
-                    resp(handlers::route::on_route_update(&dc, &rt, events.unwrap()).await)
+                    resp(
+                        &dc,
+                        handlers::route::on_route_update(&dc, &rt, events.unwrap()).await,
+                    )
                 } else {
-                    error!("on_route_update handler: bad body");
+                    error!(
+                        "correlation id {} on_route_update handler: bad body",
+                        ctx.correlation_id
+                    );
                     return Ok(resp_with_code(StatusCode::BAD_REQUEST));
                 }
             }
@@ -236,6 +624,7 @@ pub async fn service_route(
                     serde_json::from_reader(reader).unwrap_or(None);
                 if events.is_some() {
                     resp(
+                        &dc,
                         handlers::executor::on_async_command_state_change(
                             &dc,
                             &rt,
@@ -244,61 +633,14 @@ pub async fn service_route(
                         .await,
                     )
                 } else {
-                    error!("on_async_command_state_change handler: bad body");
-                    return Ok(resp_with_code(StatusCode::BAD_REQUEST));
-                }
-            }
-            path::USR_ITEMS => resp(handlers::usr::get(&dc, None).await),
-            path::CAR_GET => {
-                let cmd: Option<commands::car::GetCar> =
-                    serde_json::from_reader(reader).unwrap_or(None);
-                if cmd.is_some() {
-                    resp(handlers::car::get(&dc, cmd.unwrap()).await)
-                } else {
-                    error!("get_car handler: bad body");
-                    return Ok(resp_with_code(StatusCode::BAD_REQUEST));
-                }
-            }
-            path::CAR_ADD => {
-                let cmd: Option<commands::car::AddCar> =
-                    serde_json::from_reader(reader).unwrap_or(None);
-                if cmd.is_some() {
-                    resp(handlers::car::add(&dc, cmd.unwrap()).await)
-                } else {
-                    error!("add_cars handler: bad body");
-                    return Ok(resp_with_code(StatusCode::BAD_REQUEST));
-                }
-            }
-            path::CAR_CHANGE => {
-                let cmd: Option<commands::car::ChangeCar> =
-                    serde_json::from_reader(reader).unwrap_or(None);
-                if cmd.is_some() {
-                    resp(handlers::car::change(&dc, &ce, cmd.unwrap()).await)
-                } else {
-                    error!("change_cars handler: bad body");
-                    return Ok(resp_with_code(StatusCode::BAD_REQUEST));
-                }
-            }
-            path::CAR_REMOVE => {
-                let cmd: Option<commands::car::RemoveCar> =
-                    serde_json::from_reader(reader).unwrap_or(None);
-                if cmd.is_some() {
-                    resp(handlers::car::remove(&dc, cmd.unwrap()).await)
-                } else {
-                    error!("remove_cars handler: bad body");
-                    return Ok(resp_with_code(StatusCode::BAD_REQUEST));
-                }
-            }
-            path::CAR_RESERVE => {
-                let cmd: Option<commands::car::ReserveCar> =
-                    serde_json::from_reader(reader).unwrap_or(None);
-                if cmd.is_some() {
-                    resp(handlers::car::reserve(&dc, cmd.unwrap()).await)
-                } else {
-                    error!("reserve_cars handler: bad body");
+                    error!(
+                        "correlation id {} on_async_command_state_change handler: bad body",
+                        ctx.correlation_id
+                    );
                     return Ok(resp_with_code(StatusCode::BAD_REQUEST));
                 }
             }
+            path::USR_ITEMS => resp(&dc, handlers::usr::get(&dc, None).await),
             path::STATE => {
                 if params.contains_key("async_command_id") {
                     match ce
@@ -307,18 +649,17 @@ pub async fn service_route(
                         )
                         .await
                     {
-                        Ok(r) => resp(Ok(r)),
+                        Ok(r) => resp(&dc, Ok(r)),
                         Err(e) => {
                             error!("state handler: {}", e);
-                            if let Some(_) = e.downcast_ref::<errors::AsyncCommandNotFoundError>() {
-                                return Ok(resp_with_code(StatusCode::BAD_REQUEST));
-                            } else {
-                                return Ok(resp_with_code(StatusCode::INTERNAL_SERVER_ERROR));
-                            }
+                            return Ok(errors::from_boxed(&e).to_response(&dc.error));
                         }
                     }
                 } else {
-                    error!("state handler: bad request");
+                    error!(
+                        "correlation id {} state handler: bad request",
+                        ctx.correlation_id
+                    );
                     return Ok(resp_with_code(StatusCode::BAD_REQUEST));
                 }
             }
@@ -328,37 +669,76 @@ pub async fn service_route(
                     if rt.schema.contains_key(ot) {
                         resp_schema(&rt.schema.get(ot))
                     } else {
-                        error!("schema handler: bad request");
+                        error!(
+                            "correlation id {} schema handler: bad request",
+                            ctx.correlation_id
+                        );
                         return Ok(resp_with_code(StatusCode::BAD_REQUEST));
                     }
                 } else {
-                    error!("schema handler: bad request");
-                    return Ok(resp_with_code(StatusCode::BAD_REQUEST));
+                    resp_schema(&schema::get_schema_list(&rt.schema))
                 }
             }
             path::ERROR => {
+                let lang = get_lang(&params, &parts.headers);
                 if params.contains_key("error_code") {
                     let ec = params.get("error_code").unwrap().as_str();
-                    match handlers::route::get_error(&dc, ec) {
-                        Ok(r) => resp(Ok(r)),
+                    match handlers::route::get_error(&dc, ec, lang.as_ref().map(|l| l.as_str())) {
+                        Ok(r) => resp(&dc, Ok(r)),
                         Err(e) => {
                             error!("error handler: {}", e);
-                            return Ok(resp_with_code(StatusCode::BAD_REQUEST));
+                            return Ok(errors::from_boxed(&e).to_response(&dc.error));
                         }
                     }
                 } else {
-                    error!("error handler: bad request");
-                    return Ok(resp_with_code(StatusCode::BAD_REQUEST));
+                    resp(
+                        &dc,
+                        handlers::route::get_errors(&dc, lang.as_ref().map(|l| l.as_str())),
+                    )
                 }
             }
-            path::HELTH => resp(handlers::route::get_helth()),
-            _ => resp_with_code(StatusCode::NOT_FOUND),
-        })
+            path::HELTH => resp(&dc, handlers::route::get_helth(&rt)),
+            //commands of binary
+            _ => match rt.request_type.get(parts.uri.path()) {
+                Some(Some((object_type, false))) => match hr.get(object_type) {
+                    Some(h) => {
+                        let hc = HandlerContext {
+                            dc: &dc,
+                            ce: &ce,
+                            ep: &ep,
+                            ctx: ctx,
+                        };
+                        h.call(&hc, &bytes).await
+                    }
+                    None => resp_with_code(StatusCode::NOT_FOUND),
+                },
+                _ => resp_with_code(StatusCode::NOT_FOUND),
+            },
+        };
+        match rt.request_type.get(parts.uri.path()) {
+            Some(Some((object_type, false))) if is_async && res.status() == StatusCode::OK => {
+                add_received_async_command(&dc, ctx, object_type, &bytes, res).await
+            }
+            _ => Ok(res),
+        }
     } else if parts.method == Method::GET {
         Ok(match parts.uri.path() {
             "/" => index::handler().await,
-            "/openapi.json" => index::spec_json().await,
-            "/openapi.yaml" => index::spec_yaml().await,
+            "/openapi.json" => index::spec_json(&rt.schema).await,
+            "/openapi.yaml" => index::spec_yaml(&rt.schema).await,
+            path::LIVENESS => resp(&dc, handlers::route::get_helth(&rt)),
+            path::READINESS => {
+                let readiness = rt.helth.get_readiness(&dc, &rt).await;
+                let status = if readiness.state == route::ServiceState::Alive {
+                    StatusCode::OK
+                } else {
+                    StatusCode::SERVICE_UNAVAILABLE
+                };
+                let mut response = resp(&dc, Ok(readiness));
+                *response.status_mut() = status;
+                response
+            }
+            path::METRICS => index::metrics(&rt.metrics, &dc, &rt.breaker).await,
             _ => resp_with_code(StatusCode::NOT_FOUND),
         })
     } else {
@@ -366,7 +746,44 @@ pub async fn service_route(
     }
 }
 
-fn resp<T>(res: connectors::Result<T>) -> Response<Body>
+//async command is executed at once, caller polls its state by correlation id
+async fn add_received_async_command(
+    dc: &connectors::DataConnector,
+    ctx: &context::Context,
+    object_type: &str,
+    request_body: &[u8],
+    res: Response<Body>,
+) -> Result<Response<Body>> {
+    let (parts, body) = res.into_parts();
+    let reply_body = hyper::body::to_bytes(body).await?;
+    let add = dc
+        .received_async_command
+        .add(vec![executor::ReceivedAsyncCommand {
+            id: ctx.correlation_id.clone(),
+            object_type: object_type.to_string(),
+            service_name: ctx.caller.clone().unwrap_or_default(),
+            request_body: String::from_utf8_lossy(request_body).to_string(),
+            state: executor::CommandSystemState::Completed.to_string(),
+            change_state_event: 0,
+            reply_body: String::from_utf8_lossy(&reply_body).to_string(),
+            proto: providers::Proto::http.to_string(),
+            added_at: Utc::now(),
+            state_changed_at: Utc::now(),
+            history: None,
+        }])
+        .await
+        .map_err(|e| errors::from_boxed(&e));
+    if let Err(e) = add {
+        error!(
+            "correlation id {} {} async command: {}",
+            ctx.correlation_id, object_type, e
+        );
+        return Ok(e.to_response(&dc.error));
+    }
+    Ok(Response::from_parts(parts, Body::from(reply_body)))
+}
+
+fn resp<T>(dc: &connectors::DataConnector, res: connectors::Result<T>) -> Response<Body>
 where
     T: ser::Serialize,
 {
@@ -377,7 +794,7 @@ where
             .unwrap(),
         Err(e) => {
             error!("handler: {}", e);
-            resp_with_code(StatusCode::INTERNAL_SERVER_ERROR)
+            errors::from_boxed(&e).to_response(&dc.error)
         }
     }
 }
@@ -392,6 +809,62 @@ where
         .unwrap()
 }
 
+//empty if route has no request schema
+fn validate_request(
+    rt: &router::Router,
+    path: &str,
+    body: &[u8],
+) -> Vec<replies::common::Violation> {
+    let (object_type, is_array) = match rt.request_type.get(path) {
+        Some(Some(t)) => *t,
+        _ => return vec![],
+    };
+    let root = match rt.schema.get(object_type) {
+        Some(root) => root,
+        None => return vec![],
+    };
+    match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(value) if is_array => validator::validate_items(root, &value),
+        Ok(value) => validator::validate(root, &value),
+        Err(e) => vec![replies::common::Violation {
+            path: "".to_string(),
+            message: e.to_string(),
+        }],
+    }
+}
+
+fn resp_violations(
+    dc: &connectors::DataConnector,
+    violations: Vec<replies::common::Violation>,
+) -> Response<Body> {
+    let ec = errors::ErrorCode::BadRequestError;
+    Response::builder()
+        .status(StatusCode::BAD_REQUEST)
+        .header(header::CONTENT_TYPE, "application/json; charset=utf-8")
+        .body(Body::from(
+            serde_json::to_string(&replies::common::BadRequestReply {
+                error_code: ec,
+                error_name: Some(errors::get_error_name(&dc.error, &ec)),
+                violations: violations,
+            })
+            .unwrap(),
+        ))
+        .unwrap()
+}
+
+//lang param first, then primary subtag of first Accept-Language tag
+fn get_lang(params: &HashMap<String, String>, headers: &header::HeaderMap) -> Option<String> {
+    if params.contains_key("lang") {
+        return Some(params.get("lang").unwrap().to_lowercase());
+    }
+    let al = headers.get(header::ACCEPT_LANGUAGE)?.to_str().ok()?;
+    let tag = al.split(',').next()?.split(';').next()?.trim();
+    match tag.split('-').next() {
+        Some(lang) if lang.len() > 0 && lang != "*" => Some(lang.to_lowercase()),
+        _ => None,
+    }
+}
+
 pub fn resp_with_code(status: StatusCode) -> Response<Body> {
     Response::builder()
         .status(status)
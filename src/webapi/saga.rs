@@ -0,0 +1,370 @@
+use super::entities::saga::SagaState;
+use super::{connectors, context, entities, errors, executors, replies, traits};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+use std::str::FromStr;
+use uuid::Uuid;
+
+//owner renews lease of its sagas, lapsed one is recovered by any instance
+const LEASE: i64 = 60; //seconds
+pub const LEASE_RENEW_INTERVAL: u64 = 20; //seconds
+
+//what steps run with, executor calls are children of ctx span
+pub struct SagaContext<'a> {
+    pub dc: &'a connectors::DataConnector,
+    pub ce: &'a executors::CommandExecutor,
+    pub ctx: &'a context::Context,
+}
+
+//step and its compensation may be repeated after restart, both must be idempotent
+#[async_trait]
+pub trait Step<T: Sync>: Send + Sync {
+    fn get_name(&self) -> &'static str;
+
+    async fn execute(
+        &self,
+        sc: &SagaContext<'_>,
+        data: &T,
+    ) -> connectors::Result<errors::ErrorCode>;
+
+    async fn compensate(
+        &self,
+        sc: &SagaContext<'_>,
+        data: &T,
+    ) -> connectors::Result<errors::ErrorCode>;
+}
+
+//command called through executor, compensated by another command
+pub struct RemoteStep<T, C, U> {
+    name: &'static str,
+    command: fn(&T) -> C,
+    compensation: fn(&T) -> U,
+}
+
+impl<T, C, U> RemoteStep<T, C, U> {
+    pub fn new(name: &'static str, command: fn(&T) -> C, compensation: fn(&T) -> U) -> Self {
+        RemoteStep {
+            name: name,
+            command: command,
+            compensation: compensation,
+        }
+    }
+}
+
+#[async_trait]
+impl<T, C, U> Step<T> for RemoteStep<T, C, U>
+where
+    T: Send + Sync + 'static,
+    C: Serialize + traits::ObjectType + Send + Sync + 'static,
+    U: Serialize + traits::ObjectType + Send + Sync + 'static,
+{
+    fn get_name(&self) -> &'static str {
+        self.name
+    }
+
+    async fn execute(
+        &self,
+        sc: &SagaContext<'_>,
+        data: &T,
+    ) -> connectors::Result<errors::ErrorCode> {
+        let reply: replies::common::StandardReply =
+            sc.ce.call(sc.ctx, (self.command)(data)).await?;
+        Ok(reply.error_code)
+    }
+
+    async fn compensate(
+        &self,
+        sc: &SagaContext<'_>,
+        data: &T,
+    ) -> connectors::Result<errors::ErrorCode> {
+        let reply: replies::common::StandardReply =
+            sc.ce.call(sc.ctx, (self.compensation)(data)).await?;
+        Ok(reply.error_code)
+    }
+}
+
+//what to do with saga left running by crash
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Recovery {
+    Resume,     //execute again from interrupted step
+    Compensate, //compensate interrupted step and every executed one
+}
+
+pub struct Saga<T> {
+    saga_type: &'static str,
+    recovery: Recovery,
+    steps: Vec<Box<dyn Step<T>>>,
+}
+
+//type erased saga, used to recover persisted ones on start
+#[async_trait]
+pub trait Recoverable: Send + Sync {
+    fn get_saga_type(&self) -> &'static str;
+
+    async fn recover(
+        &self,
+        sc: &SagaContext<'_>,
+        item: entities::saga::Saga,
+    ) -> connectors::Result<()>;
+}
+
+fn get_lease_expires_at() -> DateTime<Utc> {
+    Utc::now() + Duration::seconds(LEASE)
+}
+
+async fn change_state(
+    dc: &connectors::DataConnector,
+    id: &str,
+    state: SagaState,
+    step: usize,
+) -> connectors::Result<()> {
+    match dc.saga.change_state(id, state, step as i32).await? {
+        errors::ErrorCode::ReplyOk => Ok(()),
+        ec => Err(errors::Error::with_context(
+            errors::ErrorKind::SagaState,
+            &format!(
+                "saga {} state {} step {}: {}",
+                id,
+                state.to_string(),
+                step,
+                ec.to_string()
+            ),
+        )
+        .into()),
+    }
+}
+
+impl<T> Saga<T>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    pub fn new(
+        saga_type: &'static str,
+        recovery: Recovery,
+        steps: Vec<Box<dyn Step<T>>>,
+    ) -> Saga<T> {
+        Saga {
+            saga_type: saga_type,
+            recovery: recovery,
+            steps: steps,
+        }
+    }
+
+    //ReplyOk if every step succeeded, otherwise error code of failed step after compensation
+    pub async fn run(
+        &self,
+        sc: &SagaContext<'_>,
+        data: T,
+    ) -> connectors::Result<errors::ErrorCode> {
+        let id = Uuid::new_v4().to_hyphenated().to_string();
+        let result = sc
+            .dc
+            .saga
+            .add(entities::saga::Saga {
+                id: id.clone(),
+                saga_type: self.saga_type.to_string(),
+                correlation_id: sc.ctx.correlation_id.clone(),
+                state: SagaState::Running.to_string(),
+                step: 0,
+                data: serde_json::to_string(&data)?,
+                owner: sc.ce.get_instance_id().to_string(),
+                lease_expires_at: get_lease_expires_at(),
+                added_at: Utc::now(),
+                state_changed_at: Utc::now(),
+            })
+            .await?;
+        if result != errors::ErrorCode::ReplyOk {
+            return Ok(result);
+        }
+        self.execute(sc, &id, &data, 0).await
+    }
+
+    //step is persisted before it is executed
+    async fn execute(
+        &self,
+        sc: &SagaContext<'_>,
+        id: &str,
+        data: &T,
+        from: usize,
+    ) -> connectors::Result<errors::ErrorCode> {
+        for (i, step) in self.steps.iter().enumerate().skip(from) {
+            //boxed error is not Send, it must not live across await
+            let res = step
+                .execute(sc, data)
+                .await
+                .map_err(|e| errors::from_boxed(&e));
+            match res {
+                Ok(errors::ErrorCode::ReplyOk) => {
+                    if i + 1 < self.steps.len() {
+                        change_state(sc.dc, id, SagaState::Running, i + 1).await?;
+                    }
+                }
+                //failed step has no effect
+                Ok(ec) => {
+                    warn!(
+                        "correlation id {} saga {} {} step {}: {}",
+                        sc.ctx.correlation_id,
+                        self.saga_type,
+                        id,
+                        step.get_name(),
+                        ec.to_string()
+                    );
+                    self.compensate(sc, id, data, i).await?;
+                    return Ok(ec);
+                }
+                //unknown if remote step took effect, so it is compensated too
+                Err(e) => {
+                    error!(
+                        "correlation id {} saga {} {} step {}: {}",
+                        sc.ctx.correlation_id,
+                        self.saga_type,
+                        id,
+                        step.get_name(),
+                        e
+                    );
+                    self.compensate(sc, id, data, i + 1).await?;
+                    return Err(e.into());
+                }
+            }
+        }
+        change_state(sc.dc, id, SagaState::Completed, self.steps.len()).await?;
+        Ok(errors::ErrorCode::ReplyOk)
+    }
+
+    //compensates first count steps in reverse order, failed compensation is retried on restart
+    async fn compensate(
+        &self,
+        sc: &SagaContext<'_>,
+        id: &str,
+        data: &T,
+        count: usize,
+    ) -> connectors::Result<()> {
+        change_state(sc.dc, id, SagaState::Compensating, count).await?;
+        for i in (0..count).rev() {
+            let step = &self.steps[i];
+            let res = step
+                .compensate(sc, data)
+                .await
+                .map_err(|e| errors::from_boxed(&e));
+            match res {
+                Ok(errors::ErrorCode::ReplyOk) => {
+                    change_state(sc.dc, id, SagaState::Compensating, i).await?
+                }
+                Ok(ec) => {
+                    error!(
+                        "correlation id {} saga {} {} compensate {}: {}",
+                        sc.ctx.correlation_id,
+                        self.saga_type,
+                        id,
+                        step.get_name(),
+                        ec.to_string()
+                    );
+                    return Ok(());
+                }
+                Err(e) => {
+                    error!(
+                        "correlation id {} saga {} {} compensate {}: {}",
+                        sc.ctx.correlation_id,
+                        self.saga_type,
+                        id,
+                        step.get_name(),
+                        e
+                    );
+                    return Ok(());
+                }
+            }
+        }
+        change_state(sc.dc, id, SagaState::Compensated, 0).await
+    }
+}
+
+#[async_trait]
+impl<T> Recoverable for Saga<T>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    fn get_saga_type(&self) -> &'static str {
+        self.saga_type
+    }
+
+    async fn recover(
+        &self,
+        sc: &SagaContext<'_>,
+        item: entities::saga::Saga,
+    ) -> connectors::Result<()> {
+        let data: T = serde_json::from_str(&item.data)?;
+        let step = item.step as usize;
+        match SagaState::from_str(&item.state)? {
+            SagaState::Running => match self.recovery {
+                Recovery::Resume => {
+                    self.execute(sc, &item.id, &data, step).await?;
+                }
+                Recovery::Compensate => {
+                    let count = std::cmp::min(step + 1, self.steps.len());
+                    self.compensate(sc, &item.id, &data, count).await?;
+                }
+            },
+            SagaState::Compensating => self.compensate(sc, &item.id, &data, step).await?,
+            SagaState::Completed | SagaState::Compensated => {}
+        }
+        Ok(())
+    }
+}
+
+//extends lease of sagas run by this instance
+pub async fn renew(
+    dc: &connectors::DataConnector,
+    ce: &executors::CommandExecutor,
+) -> connectors::Result<u64> {
+    dc.saga
+        .renew(ce.get_instance_id(), get_lease_expires_at())
+        .await
+}
+
+//continues sagas whose owner crashed or stopped, sagas of alive instances are skipped
+pub async fn recover(
+    dc: &connectors::DataConnector,
+    ce: &executors::CommandExecutor,
+    sagas: &[&dyn Recoverable],
+) -> connectors::Result<()> {
+    let running = vec![SagaState::Running, SagaState::Compensating];
+    let at = Utc::now();
+    for item in dc.saga.get_expired(running, at).await? {
+        let saga = match sagas.iter().find(|s| s.get_saga_type() == item.saga_type) {
+            Some(saga) => saga,
+            None => {
+                warn!("saga {} has unknown type {}", item.id, item.saga_type);
+                continue;
+            }
+        };
+        //other instance may recover it at the same time
+        match dc
+            .saga
+            .claim(&item.id, ce.get_instance_id(), at, get_lease_expires_at())
+            .await?
+        {
+            errors::ErrorCode::ReplyOk => {}
+            _ => continue,
+        }
+        info!(
+            "correlation id {} recover saga {} {} in state {} step {}",
+            item.correlation_id, item.saga_type, item.id, item.state, item.step
+        );
+        let ctx = context::Context::new(
+            &item.correlation_id,
+            None,
+            context::Span::new(&item.saga_type, context::SpanKind::Internal),
+        );
+        let sc = SagaContext {
+            dc: dc,
+            ce: ce,
+            ctx: &ctx,
+        };
+        let id = item.id.clone();
+        if let Err(e) = saga.recover(&sc, item).await {
+            error!("recover saga {}: {}", id, e);
+        }
+    }
+    Ok(())
+}
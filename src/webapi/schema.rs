@@ -1,84 +1,159 @@
-use super::{commands, events, replies, traits::ObjectType};
-use schemars::schema_for;
+use super::entities::route;
+use super::{registry, traits::ObjectType};
+use schemars::schema::RootSchema;
+use schemars::{schema_for, JsonSchema};
+use serde_json::Value;
 use std::collections::HashMap;
 
-pub fn make_schema() -> HashMap<&'static str, schemars::schema::RootSchema> {
-    let mut hm = HashMap::<&str, schemars::schema::RootSchema>::new();
-    //commands
-    hm.insert(
-        commands::route::GetRoute::get_type_name(),
-        schema_for!(commands::route::GetRoute),
-    );
-    hm.insert(
-        commands::route::AddRoute::get_type_name(),
-        schema_for!(commands::route::AddRoute),
-    );
-    hm.insert(
-        commands::route::RemoveRoute::get_type_name(),
-        schema_for!(commands::route::RemoveRoute),
-    );
-    hm.insert(
-        commands::route::GetServiceCommand::get_type_name(),
-        schema_for!(commands::route::GetServiceCommand),
-    );
-    hm.insert(
-        commands::route::GetServiceEvent::get_type_name(),
-        schema_for!(commands::route::GetServiceEvent),
-    );
-    hm.insert(
-        commands::route::GetServiceSubscription::get_type_name(),
-        schema_for!(commands::route::GetServiceSubscription),
-    );
-    hm.insert(
-        commands::route::GetService::get_type_name(),
-        schema_for!(commands::route::GetService),
-    );
-    //replies
-    hm.insert(
-        replies::common::StandardReply::get_type_name(),
-        schema_for!(replies::common::StandardReply),
-    );
-    hm.insert(
-        replies::common::AddIntIdsReply::get_type_name(),
-        schema_for!(replies::common::AddIntIdsReply),
-    );
-    hm.insert(
-        replies::common::AddStrIdsReply::get_type_name(),
-        schema_for!(replies::common::AddStrIdsReply),
-    );
-    hm.insert(
-        replies::route::GetRouteReply::get_type_name(),
-        schema_for!(replies::route::GetRouteReply),
-    );
-    hm.insert(
-        replies::route::GetServiceCommandReply::get_type_name(),
-        schema_for!(replies::route::GetServiceCommandReply),
-    );
-    hm.insert(
-        replies::route::GetServiceEventReply::get_type_name(),
-        schema_for!(replies::route::GetServiceEventReply),
-    );
-    hm.insert(
-        replies::route::GetServiceSubscriptionReply::get_type_name(),
-        schema_for!(replies::route::GetServiceSubscriptionReply),
-    );
-    hm.insert(
-        replies::route::GetServiceReply::get_type_name(),
-        schema_for!(replies::route::GetServiceReply),
-    );
-    //events
-    hm.insert(
-        events::route::OnServiceUnavailable::get_type_name(),
-        schema_for!(events::route::OnServiceUnavailable),
-    );
-    hm.insert(
-        events::route::OnRouteUpdate::get_type_name(),
-        schema_for!(events::route::OnRouteUpdate),
-    );
-    hm.insert(
-        events::executor::OnAsyncCommandStateChange::get_type_name(),
-        schema_for!(events::executor::OnAsyncCommandStateChange),
-    );
+//openapi compatible extension keyword
+pub const SCHEMA_VERSION: &str = "x-version";
 
+pub fn make_versioned_schema<T>() -> RootSchema
+where
+    T: ObjectType + JsonSchema,
+{
+    let mut root = schema_for!(T);
+    root.schema.extensions.insert(
+        SCHEMA_VERSION.to_string(),
+        serde_json::json!(T::get_schema_version()),
+    );
+    root
+}
+
+//schema of every type declared by derive(ObjectType)
+pub fn make_schema() -> HashMap<&'static str, RootSchema> {
+    let mut hm = HashMap::<&str, RootSchema>::new();
+    for d in registry::get_declarations() {
+        hm.insert(d.object_type, (d.schema)());
+    }
     hm
 }
+
+pub fn get_schema_version(root: &RootSchema) -> u32 {
+    root.schema
+        .extensions
+        .get(SCHEMA_VERSION)
+        .and_then(|v| v.as_u64())
+        .unwrap_or(1) as u32
+}
+
+pub fn get_schema_list(schema: &HashMap<&'static str, RootSchema>) -> Vec<route::ServiceSchema> {
+    let mut items: Vec<route::ServiceSchema> = schema
+        .iter()
+        .map(|(object_type, root)| route::ServiceSchema {
+            service_name: None,
+            object_type: object_type.to_string(),
+            version: get_schema_version(root),
+            schema: None,
+        })
+        .collect();
+    items.sort_by(|a, b| a.object_type.cmp(&b.object_type));
+    items
+}
+
+//breaking changes of new schema for existing users: removed required values and type changes
+pub fn get_incompatibilities(old: &Value, new: &Value) -> Vec<String> {
+    let mut result = Vec::<String>::new();
+    compare_schema((old, old), (new, new), "", 0, &mut result);
+    result
+}
+
+//max depth of nested definitions, recursive types stop here
+const MAX_SCHEMA_DEPTH: usize = 32;
+
+fn resolve_schema<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    //Option<T> of definition is anyOf [T, null]
+    if let Some(Value::Array(items)) = schema.get("anyOf").or(schema.get("allOf")) {
+        let items: Vec<&Value> = items
+            .iter()
+            .filter(|i| i.get("type").and_then(|t| t.as_str()) != Some("null"))
+            .collect();
+        if items.len() == 1 {
+            return resolve_schema(root, items[0]);
+        }
+    }
+    match schema.get("$ref").and_then(|r| r.as_str()) {
+        Some(r) => root.pointer(r.trim_start_matches('#')).unwrap_or(schema),
+        None => schema,
+    }
+}
+
+fn get_schema_types(schema: &Value) -> Vec<String> {
+    let mut types: Vec<String> = match schema.get("type") {
+        Some(Value::String(t)) => vec![t.to_string()],
+        Some(Value::Array(ts)) => ts
+            .iter()
+            .filter_map(|t| t.as_str().map(|t| t.to_string()))
+            .collect(),
+        _ => vec![],
+    };
+    types.retain(|t| t != "null");
+    types.sort();
+    types
+}
+
+fn get_required(schema: &Value) -> Vec<&str> {
+    match schema.get("required") {
+        Some(Value::Array(items)) => items.iter().filter_map(|i| i.as_str()).collect(),
+        _ => vec![],
+    }
+}
+
+fn compare_schema(
+    old: (&Value, &Value),
+    new: (&Value, &Value),
+    path: &str,
+    depth: usize,
+    result: &mut Vec<String>,
+) {
+    if depth > MAX_SCHEMA_DEPTH {
+        return;
+    }
+    let (old_root, new_root) = (old.0, new.0);
+    let old_schema = resolve_schema(old_root, old.1);
+    let new_schema = resolve_schema(new_root, new.1);
+    let old_types = get_schema_types(old_schema);
+    let new_types = get_schema_types(new_schema);
+    if old_types.len() > 0 && new_types.len() > 0 && old_types != new_types {
+        result.push(format!(
+            "{} type changed from {} to {}",
+            if path.len() > 0 { path } else { "/" },
+            old_types.join(","),
+            new_types.join(",")
+        ));
+        return;
+    }
+    let new_required = get_required(new_schema);
+    let new_properties = new_schema.get("properties");
+    for name in get_required(old_schema) {
+        if new_properties.and_then(|p| p.get(name)).is_none() {
+            result.push(format!("{}/{} required value removed", path, name));
+        } else if !new_required.contains(&name) {
+            result.push(format!("{}/{} is not required anymore", path, name));
+        }
+    }
+    if let (Some(Value::Object(op)), Some(Value::Object(np))) =
+        (old_schema.get("properties"), new_properties)
+    {
+        for (name, os) in op {
+            if let Some(ns) = np.get(name) {
+                compare_schema(
+                    (old_root, os),
+                    (new_root, ns),
+                    &format!("{}/{}", path, name),
+                    depth + 1,
+                    result,
+                );
+            }
+        }
+    }
+    if let (Some(oi), Some(ni)) = (old_schema.get("items"), new_schema.get("items")) {
+        compare_schema(
+            (old_root, oi),
+            (new_root, ni),
+            &format!("{}/items", path),
+            depth + 1,
+            result,
+        );
+    }
+}
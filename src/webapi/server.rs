@@ -0,0 +1,350 @@
+use super::{
+    access, config, connectors, executors, publishers, router, routes, saga, tracer,
+    traits::ObjectType, workers,
+};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Error, Server as HyperServer};
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::signal;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{self, Instant};
+
+const DEFAULT_DRAIN_TIMEOUT: u64 = 10; //seconds, cloud foundry kills after 10
+const WORKER_CAPACITY: usize = 5;
+const LEASE_CHECK_INTERVAL: u64 = 5; //seconds
+const IDEMPOTENCY_CHECK_INTERVAL: u64 = 60; //seconds
+
+//service parts from loaded configuration, command handlers and sagas are added by binary
+pub struct ServerBuilder {
+    config: config::Config,
+    handler: routes::service::Handlers,
+    sagas: Vec<Box<dyn saga::Recoverable>>,
+    is_registered: bool,
+}
+
+impl ServerBuilder {
+    pub fn new(config: config::Config) -> ServerBuilder {
+        ServerBuilder {
+            config: config,
+            handler: HashMap::new(),
+            sagas: Vec::new(),
+            is_registered: true,
+        }
+    }
+
+    //command of this type posted to its route is handled by handler
+    pub fn with_handler<H>(mut self, handler: H) -> ServerBuilder
+    where
+        H: routes::service::CommandHandler + 'static,
+    {
+        self.handler
+            .insert(H::Command::get_type_name(), Box::new(handler));
+        self
+    }
+
+    //unfinished sagas of this type are recovered after start
+    pub fn with_saga(mut self, saga: Box<dyn saga::Recoverable>) -> ServerBuilder {
+        self.sagas.push(saga);
+        self
+    }
+
+    //routes of service are not added, for clients sharing settings of service
+    pub fn without_registration(mut self) -> ServerBuilder {
+        self.is_registered = false;
+        self
+    }
+
+    pub async fn build(self) -> connectors::Result<Server> {
+        let app_settings = self.config.app_settings;
+        let dc = connectors::DataConnector::new(
+            app_settings.error,
+            &self.config.database,
+            &app_settings.access.authentication,
+        )
+        .await?;
+        let ac =
+            access::AccessChecker::from_data_connector(&dc, &app_settings.access.authentication)
+                .await?;
+        let dc = Arc::new(dc);
+        let ac = Arc::new(ac);
+        let rt = Arc::new(
+            router::Router::new(
+                dc.clone(),
+                ac.clone(),
+                app_settings.router,
+                app_settings.path,
+                app_settings.service,
+                &self.config.host,
+                app_settings.validation,
+                app_settings.idempotency,
+                app_settings.rate_limit,
+                app_settings.circuit_breaker,
+                app_settings.lease,
+                self.is_registered,
+            )
+            .await?,
+        );
+        let tr = Arc::new(tracer::Tracer::new(app_settings.tracing)?);
+        let (ce_sender, ce_receiver, ce_state) =
+            workers::channel("command executor", WORKER_CAPACITY);
+        let (ep_sender, ep_receiver, ep_state) =
+            workers::channel("event publisher", WORKER_CAPACITY);
+        rt.helth.add_worker(ce_state.clone());
+        rt.helth.add_worker(ep_state.clone());
+        let ce = Arc::new(
+            executors::CommandExecutor::new(
+                dc.clone(),
+                ac.clone(),
+                rt.clone(),
+                tr.clone(),
+                ce_sender.clone(),
+            )
+            .await?,
+        );
+        let ep = Arc::new(
+            publishers::EventPublisher::new(
+                dc.clone(),
+                ac.clone(),
+                rt.clone(),
+                tr.clone(),
+                ep_sender.clone(),
+            )
+            .await?,
+        );
+        Ok(Server {
+            dc: dc,
+            ac: ac,
+            rt: rt,
+            ce: ce,
+            ep: ep,
+            tr: tr,
+            addr: self.config.addr,
+            drain_timeout: Duration::from_secs(
+                app_settings
+                    .shutdown
+                    .map(|s| s.drain_timeout)
+                    .unwrap_or(DEFAULT_DRAIN_TIMEOUT),
+            ),
+            handler: Arc::new(self.handler),
+            sagas: self.sagas,
+            event_publisher: (ep_sender, ep_receiver, ep_state),
+            command_executor: (ce_sender, ce_receiver, ce_state),
+        })
+    }
+}
+
+//built service, parts are shared with own tasks of binary
+pub struct Server {
+    pub dc: Arc<connectors::DataConnector>,
+    pub ac: Arc<access::AccessChecker>,
+    pub rt: Arc<router::Router>,
+    pub ce: Arc<executors::CommandExecutor>,
+    pub ep: Arc<publishers::EventPublisher>,
+    pub tr: Arc<tracer::Tracer>,
+    addr: SocketAddr,
+    drain_timeout: Duration,
+    handler: Arc<routes::service::Handlers>,
+    sagas: Vec<Box<dyn saga::Recoverable>>,
+    event_publisher: Worker,
+    command_executor: Worker,
+}
+
+type Worker = (
+    workers::SignalSender,
+    mpsc::Receiver<workers::SignalCode>,
+    Arc<workers::WorkerState>,
+);
+
+impl Server {
+    //serves until shutdown completes, then drains requests and queued jobs
+    pub async fn run<F>(self, shutdown: F) -> connectors::Result<()>
+    where
+        F: Future<Output = ()>,
+    {
+        let dc = self.dc.clone();
+        let ac = self.ac.clone();
+        let ce = self.ce.clone();
+        let ep = self.ep.clone();
+        let rt = self.rt.clone();
+        let tr = self.tr.clone();
+        let hr = self.handler.clone();
+        let make_svc = make_service_fn(move |_| {
+            let dc = dc.clone();
+            let ac = ac.clone();
+            let ce = ce.clone();
+            let ep = ep.clone();
+            let rt = rt.clone();
+            let tr = tr.clone();
+            let hr = hr.clone();
+            async move {
+                Ok::<_, Error>(service_fn(move |req| {
+                    routes::service::service_route(
+                        req,
+                        dc.clone(),
+                        ac.clone(),
+                        ce.clone(),
+                        ep.clone(),
+                        rt.clone(),
+                        tr.clone(),
+                        hr.clone(),
+                    )
+                }))
+            }
+        });
+        info!("starting up");
+        debug!("start hyper server");
+        let (stop_sender, stop_receiver) = oneshot::channel::<()>();
+        let graceful = HyperServer::try_bind(&self.addr)?
+            .serve(make_svc)
+            .with_graceful_shutdown(async {
+                stop_receiver.await.ok();
+            });
+        let server_handle = tokio::spawn(graceful);
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        //after server start, saga steps may call this service
+        tokio::spawn(workers::saga_lease_worker(
+            cancel_flag.clone(),
+            self.dc.clone(),
+            self.ce.clone(),
+            Duration::from_secs(saga::LEASE_RENEW_INTERVAL),
+        ));
+        tokio::spawn(workers::saga_recovery_worker(
+            cancel_flag.clone(),
+            self.dc.clone(),
+            self.ce.clone(),
+            self.sagas,
+            Duration::from_secs(saga::LEASE_RENEW_INTERVAL),
+        ));
+        tokio::spawn(workers::idempotency_expiry_worker(
+            cancel_flag.clone(),
+            self.dc.clone(),
+            self.rt.clone(),
+            Duration::from_secs(IDEMPOTENCY_CHECK_INTERVAL),
+        ));
+        if self.rt.is_local {
+            tokio::spawn(workers::lease_expiry_worker(
+                cancel_flag.clone(),
+                self.dc.clone(),
+                self.ep.clone(),
+                Duration::from_secs(LEASE_CHECK_INTERVAL),
+            ));
+        }
+        let heartbeat = match self.rt.get_heartbeat() {
+            Some(interval) => {
+                let (sender, receiver) = oneshot::channel::<()>();
+                let handle = tokio::spawn(workers::heartbeat_worker(
+                    receiver,
+                    self.rt.clone(),
+                    self.ce.clone(),
+                    self.ep.clone(),
+                    interval,
+                ));
+                Some((sender, handle))
+            }
+            None => None,
+        };
+        let (ep_sender, ep_receiver, ep_state) = self.event_publisher;
+        let (ce_sender, ce_receiver, ce_state) = self.command_executor;
+        let control_senders = vec![ep_sender, ce_sender];
+        let ep_cancel_flag = cancel_flag.clone();
+        let ce_cancel_flag = cancel_flag.clone();
+        let ep_metrics = self.rt.metrics.clone();
+        let ce_metrics = self.rt.metrics.clone();
+        let workers_handle = tokio::spawn(async move {
+            futures::join!(
+                tokio::spawn(async move {
+                    if let Err(e) = workers::event_publisher_worker(
+                        ep_cancel_flag,
+                        ep_receiver,
+                        ep_metrics,
+                        ep_state,
+                    )
+                    .await
+                    {
+                        error!("event publisher: {}", e);
+                        return "error";
+                    }
+                    "ok"
+                }),
+                tokio::spawn(async move {
+                    if let Err(e) = workers::command_executor_worker(
+                        ce_cancel_flag,
+                        ce_receiver,
+                        ce_metrics,
+                        ce_state,
+                    )
+                    .await
+                    {
+                        error!("command executor: {}", e);
+                        return "error";
+                    }
+                    "ok"
+                })
+            )
+        });
+
+        shutdown.await;
+        let deadline = Instant::now() + self.drain_timeout;
+        info!("shutting down, drain timeout {:?}", self.drain_timeout);
+        self.rt.helth.set_shutting_down();
+        //renewal must not add routes again after deregistration
+        if let Some((sender, handle)) = heartbeat {
+            sender.send(()).ok();
+            if let Err(e) = handle.await {
+                error!("heartbeat task: {}", e);
+            }
+        }
+        //no new requests are routed here after deregistration
+        if let Err(e) = self.rt.shutdown().await {
+            error!("router shutdown: {}", e);
+        }
+        //stop accepting, in-flight requests are finished
+        stop_sender.send(()).ok();
+        match time::timeout_at(deadline, server_handle).await {
+            Ok(Ok(Ok(_))) => debug!("stop hyper server with result: ok"),
+            Ok(Ok(Err(e))) => error!("hyper server: {}", e),
+            Ok(Err(e)) => error!("hyper server task: {}", e),
+            Err(_) => warn!("drain timeout expired, in-flight requests are dropped"),
+        }
+        //workers exit after queued signals are handled
+        cancel_flag.store(true, Ordering::SeqCst);
+        for mut s in control_senders {
+            if let Err(e) = s.send(workers::SignalCode::Exit).await {
+                error!("stop worker: {}", e);
+            }
+        }
+        match time::timeout_at(deadline, workers_handle).await {
+            Ok(Ok(res)) => {
+                debug!("stop event publisher with result: {}", res.0.unwrap());
+                debug!("stop command executor with result: {}", res.1.unwrap());
+            }
+            Ok(Err(e)) => error!("workers task: {}", e),
+            Err(_) => warn!("drain timeout expired, pending jobs are dropped"),
+        }
+        info!("shutdown");
+        Ok(())
+    }
+}
+
+//sigterm is sent by cloud foundry on stop, sigint by ctrl+c
+#[cfg(unix)]
+pub async fn shutdown_signal() {
+    let mut terminate = signal::unix::signal(signal::unix::SignalKind::terminate())
+        .expect("failed to install SIGTERM signal handler");
+    tokio::select! {
+        res = signal::ctrl_c() => res.expect("failed to install CTRL+C signal handler"),
+        _ = terminate.recv() => {}
+    }
+}
+
+#[cfg(not(unix))]
+pub async fn shutdown_signal() {
+    signal::ctrl_c()
+        .await
+        .expect("failed to install CTRL+C signal handler");
+}
@@ -8,6 +8,13 @@ pub struct AppSettings {
     pub max_async_command_reply_wait_in_hours: u16,
     pub max_sync_command_reply_wait_in_seconds: u16,
     pub error: Option<HashMap<String, String>>,
+    pub validation: Option<Validation>,
+    pub tracing: Option<Tracing>,
+    pub shutdown: Option<Shutdown>,
+    pub idempotency: Option<Idempotency>,
+    pub rate_limit: Option<RateLimit>,
+    pub circuit_breaker: Option<CircuitBreaker>,
+    pub lease: Option<Lease>,
     pub access: Access,
     pub database: HashMap<String, Database>,
     pub router: Option<HashMap<String, String>>,
@@ -15,6 +22,86 @@ pub struct AppSettings {
     pub service: HashMap<String, route::Route>,
 }
 
+//json schema validation, disabled if not set
+#[derive(Deserialize, Clone, Copy, Default)]
+pub struct Validation {
+    pub request: bool, //incoming commands and events
+    pub reply: bool,   //replies of called commands
+    #[serde(default)]
+    pub route: RouteCheck, //schemas of added routes against registered services
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone, Copy)]
+pub enum RouteCheck {
+    Off,
+    Warn,   //log incompatible schemas
+    Reject, //reply bad request with incompatible schemas
+}
+
+impl Default for RouteCheck {
+    fn default() -> RouteCheck {
+        RouteCheck::Off
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub struct Shutdown {
+    pub drain_timeout: u64, //seconds to finish requests and queued jobs
+}
+
+//replies of mutating commands by caller, object type and correlation id
+#[derive(Deserialize, Clone, Copy)]
+pub struct Idempotency {
+    pub retention: u64,    //seconds stored reply is returned to repeated request
+    pub wait_timeout: u64, //seconds repeated request waits for original one in progress
+}
+
+//token bucket by authorized user and object type, disabled if not set
+#[derive(Deserialize, Clone)]
+pub struct RateLimit {
+    #[serde(flatten)]
+    pub default: RateLimitRule,
+    #[serde(default)]
+    pub route: HashMap<String, RateLimitRule>, //by object type, replaces default
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone, Copy)]
+pub struct RateLimitRule {
+    pub rate: f64,                //requests per second
+    pub burst: u32,               //requests allowed at once
+    pub daily_quota: Option<i64>, //requests per utc day, stored in database
+}
+
+//calls of remote services, defaults are used if not set
+#[derive(Deserialize, Clone, Copy)]
+pub struct CircuitBreaker {
+    pub failure_threshold: u32, //consecutive failed calls to open breaker
+    pub cool_down: u64,         //seconds before trial call of open breaker
+    pub call_timeout: u64,      //seconds, longer call is failed
+}
+
+//routes of service are registered with lease and renewed by heartbeat, never expire if not set
+#[derive(Deserialize, Clone, Copy)]
+pub struct Lease {
+    pub ttl: u32,       //seconds routes live without renewal
+    pub heartbeat: u64, //seconds between renewals, less than ttl
+}
+
+//span export, disabled if not set
+#[derive(Deserialize, Clone)]
+pub struct Tracing {
+    pub service_name: String, //service.name of exported spans
+    pub exporter: TraceExporter,
+    pub file: Option<String>, //required by file exporter
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone, Copy)]
+pub enum TraceExporter {
+    Off,
+    Stdout,
+    File,
+}
+
 #[derive(Deserialize)]
 pub struct Access {
     pub authentication: Authentication,
@@ -23,10 +110,17 @@ pub struct Access {
 #[derive(Deserialize)]
 pub struct Authentication {
     pub server: HashMap<String, String>,
-    pub client: Vec<route::ClientAccess>
+    pub client: Vec<route::ClientAccess>,
+    pub admin: Option<Admin>, //admin dashboard is disabled if not set
+}
+
+#[derive(Deserialize)]
+pub struct Admin {
+    pub usr_name: String,
+    pub usr_password: String,
 }
 
 #[derive(Deserialize)]
 pub struct Database {
     pub connection_string: String,
-}
\ No newline at end of file
+}
@@ -1,4 +0,0 @@
-pub mod executor;
-pub mod route;
-pub mod usr;
-pub mod car;
@@ -1,103 +1,143 @@
-/*
 use super::super::{
-    access, connectors, executors, publishers, router, routes::*, settings, workers,
+    access, commands, connectors, context, entities, entities::executor, errors, events, executors,
+    idempotency, openapi, publishers, replies, router, routes::*, saga, schema, settings, tracer,
+    traits::ObjectType, workers,
 };
+use super::{car, TEST_USR_NAME, TEST_USR_PASSWORD};
+use chrono::Utc;
 use hyper::service::{make_service_fn, service_fn};
-use hyper::{Body, Client, Error, Method, Request, Response, Server, StatusCode};
-use rand::prelude::Rng;
+use hyper::{header, Body, Client, Error, Method, Request, Response, Server, StatusCode};
+use serde::de;
+use std::collections::HashMap;
 use std::fs;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
+use uuid::Uuid;
 
-fn get_basic_authorization_token(user: &String, password: &String) -> String {
-    format!(
-        "Basic {}",
-        base64::encode(&format!("{}:{}", user, password))
-    )
+const TEST_APP_SETTINGS: &str = "appsettings.test.json";
+const TEST_DB: &str = "memory";
+
+fn get_random_listener() -> TcpListener {
+    TcpListener::bind(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0))
+        .expect("error while bind random port")
+}
+
+//what binary adds by ServerBuilder::with_handler
+fn get_car_handlers() -> service::Handlers {
+    let mut hr = service::Handlers::new();
+    hr.insert(
+        commands::car::GetCar::get_type_name(),
+        Box::new(car::GetCarHandler),
+    );
+    hr.insert(
+        commands::car::AddCar::get_type_name(),
+        Box::new(car::AddCarHandler),
+    );
+    hr.insert(
+        commands::car::ChangeCar::get_type_name(),
+        Box::new(car::ChangeCarHandler),
+    );
+    hr.insert(
+        commands::car::RemoveCar::get_type_name(),
+        Box::new(car::RemoveCarHandler),
+    );
+    hr.insert(
+        commands::car::ReserveCar::get_type_name(),
+        Box::new(car::ReserveCarHandler),
+    );
+    hr.insert(
+        commands::car::ReleaseCar::get_type_name(),
+        Box::new(car::ReleaseCarHandler),
+    );
+    hr
+}
+
+//boot service_route on a random port, return its address and data connector
+async fn start_service() -> (SocketAddr, Arc<connectors::DataConnector>) {
+    let (addr, dc, _) = start_service_with_executor().await;
+    (addr, dc)
 }
 
-async fn get_settings() -> (
+//same as start_service, executor is used to run steps outside of requests
+async fn start_service_with_executor() -> (
     SocketAddr,
     Arc<connectors::DataConnector>,
-    Arc<access::AccessChecker>,
     Arc<executors::CommandExecutor>,
-    Arc<publishers::EventPublisher>,
-    Arc<router::Router>,
 ) {
-    let mut rng = rand::thread_rng();
     let app_settings: settings::AppSettings =
-        serde_json::from_str(&fs::read_to_string("appsettings.test.json").unwrap()).unwrap();
+        serde_json::from_str(&fs::read_to_string(TEST_APP_SETTINGS).unwrap()).unwrap();
+    let listener = get_random_listener();
+    let addr = listener.local_addr().unwrap();
+    let host = format!("{}:{}", addr.ip(), addr.port());
     let data_connector = connectors::DataConnector::new(
         app_settings.error,
-        Some(app_settings.pg_db),
-        Some(app_settings.my_sql_db),
+        app_settings.database.get(TEST_DB).unwrap(),
+        &app_settings.access.authentication,
     )
     .await
     .expect("error while initialize data connector");
-    let access_checker = access::AccessChecker::_from_app_settings(&app_settings.access)
-        .await
-        .expect("error while initialize access checker");
-    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), rng.gen_range(15000, 25000));
-    let host = format!("{}:{}", &addr.ip(), &addr.port());
-    let router = router::Router::new_local(
+    let access_checker = access::AccessChecker::from_data_connector(
         &data_connector,
-        app_settings.router.local.unwrap(),
+        &app_settings.access.authentication,
+    )
+    .await
+    .expect("error while initialize access checker");
+    let data_connector_arc = Arc::new(data_connector);
+    let access_checker_arc = Arc::new(access_checker);
+    let router = router::Router::new(
+        data_connector_arc.clone(),
+        access_checker_arc.clone(),
+        app_settings.router,
+        app_settings.path,
         app_settings.service,
         &host,
+        app_settings.validation,
+        app_settings.idempotency,
+        app_settings.rate_limit,
+        app_settings.circuit_breaker,
+        app_settings.lease,
+        true,
     )
     .await
     .expect("error while local router initialize");
-    let access_checker_arc = Arc::new(access_checker);
     let router_arc = Arc::new(router);
-    let (command_sender, _command_receiver) = mpsc::channel::<workers::SignalCode>(10);
-    let command_executor = executors::CommandExecutor::new(access_checker_arc.clone(), router_arc.clone(), command_sender)
-        .await
-        .expect("error while initialize command executor");
-    let (event_sender, _event_receiver) = mpsc::channel::<workers::SignalCode>(10);
-    let event_publisher = publishers::EventPublisher::new(access_checker_arc.clone(), router_arc.clone(), event_sender)
-        .await
-        .expect("error while event publisher initialize");
-    (
-        addr,
-        Arc::new(data_connector),
-        access_checker_arc,
-        Arc::new(command_executor),
-        Arc::new(event_publisher),
-        router_arc,
+    let tracer = tracer::Tracer::new(app_settings.tracing).expect("error while initialize tracer");
+    let tracer_arc = Arc::new(tracer);
+    let (command_sender, _command_receiver, _) = workers::channel("command executor", 10);
+    let command_executor = executors::CommandExecutor::new(
+        data_connector_arc.clone(),
+        access_checker_arc.clone(),
+        router_arc.clone(),
+        tracer_arc.clone(),
+        command_sender,
     )
-}
-
-async fn call_service(method: hyper::Method, port: u16, path: &str, body: Body) -> Response<Body> {
-    let req = Request::builder()
-        .method(method)
-        .uri(format!("http://{}:{}{}", Ipv4Addr::LOCALHOST, port, path))
-        .header(
-            "Authorization",
-            get_basic_authorization_token(&"test".to_string(), &"1234567890".to_string()),
-        )
-        .body(body)
-        .expect("request builder");
-    let client = Client::new();
-    client.request(req).await.unwrap()
-}
-
-#[tokio::test(threaded_scheduler)]
-async fn test_index_ok() {
-    let (
-        addr,
-        data_connector_arc,
-        access_checker_arc,
-        command_executor_arc,
-        event_publisher_arc,
-        router_arc,
-    ) = get_settings().await;
+    .await
+    .expect("error while initialize command executor");
+    let (event_sender, _event_receiver, _) = workers::channel("event publisher", 10);
+    let event_publisher = publishers::EventPublisher::new(
+        data_connector_arc.clone(),
+        access_checker_arc.clone(),
+        router_arc.clone(),
+        tracer_arc.clone(),
+        event_sender,
+    )
+    .await
+    .expect("error while event publisher initialize");
+    let command_executor_arc = Arc::new(command_executor);
+    let event_publisher_arc = Arc::new(event_publisher);
+    let handler_arc = Arc::new(get_car_handlers());
+    let dc_arc = data_connector_arc.clone();
+    let ce_arc = command_executor_arc.clone();
     let make_svc = make_service_fn(move |_| {
         let dc = data_connector_arc.clone();
         let ac = access_checker_arc.clone();
         let ce = command_executor_arc.clone();
         let ep = event_publisher_arc.clone();
         let rt = router_arc.clone();
+        let tr = tracer_arc.clone();
+        let hr = handler_arc.clone();
         async move {
             Ok::<_, Error>(service_fn(move |req| {
                 service::service_route(
@@ -107,186 +147,1336 @@ async fn test_index_ok() {
                     ce.clone(),
                     ep.clone(),
                     rt.clone(),
+                    tr.clone(),
+                    hr.clone(),
                 )
             }))
         }
     });
-    let app = Server::bind(&addr).serve(make_svc);
-
+    let app = Server::from_tcp(listener).unwrap().serve(make_svc);
     tokio::spawn(async move {
         if let Err(err) = app.await {
             error!("server: {}", err);
         }
     });
-
-    let resp = call_service(Method::GET, addr.port(), "/", Body::empty()).await;
-    assert_eq!(resp.status(), StatusCode::OK);
+    (addr, dc_arc, ce_arc)
 }
 
-#[tokio::test(threaded_scheduler)]
-async fn test_spec_json_ok() {
-    let (
-        addr,
-        data_connector_arc,
-        access_checker_arc,
-        command_executor_arc,
-        event_publisher_arc,
-        router_arc,
-    ) = get_settings().await;
+//second in-process service, forward every received event as (path and query, body)
+async fn start_subscriber() -> (SocketAddr, mpsc::UnboundedReceiver<(String, String)>) {
+    let listener = get_random_listener();
+    let addr = listener.local_addr().unwrap();
+    let (sender, receiver) = mpsc::unbounded_channel::<(String, String)>();
     let make_svc = make_service_fn(move |_| {
-        let dc = data_connector_arc.clone();
-        let ac = access_checker_arc.clone();
-        let ce = command_executor_arc.clone();
-        let ep = event_publisher_arc.clone();
-        let rt = router_arc.clone();
+        let s = sender.clone();
         async move {
-            Ok::<_, Error>(service_fn(move |req| {
-                service::service_route(
-                    req,
-                    dc.clone(),
-                    ac.clone(),
-                    ce.clone(),
-                    ep.clone(),
-                    rt.clone(),
-                )
+            Ok::<_, Error>(service_fn(move |req: Request<Body>| {
+                let s = s.clone();
+                async move {
+                    let (parts, body) = req.into_parts();
+                    let bytes = hyper::body::to_bytes(body).await?;
+                    s.send((
+                        parts.uri.to_string(),
+                        String::from_utf8(bytes.to_vec()).unwrap(),
+                    ))
+                    .unwrap();
+                    Ok::<_, Error>(Response::new(Body::empty()))
+                }
             }))
         }
     });
-    let app = Server::bind(&addr).serve(make_svc);
-
+    let app = Server::from_tcp(listener).unwrap().serve(make_svc);
     tokio::spawn(async move {
         if let Err(err) = app.await {
-            error!("server: {}", err);
+            error!("subscriber: {}", err);
         }
     });
-
-    let resp = call_service(Method::GET, addr.port(), "/openapi.json", Body::empty()).await;
-    assert_eq!(resp.status(), StatusCode::OK);
+    (addr, receiver)
 }
 
-#[tokio::test(threaded_scheduler)]
-async fn test_spec_yaml_ok() {
-    let (
-        addr,
-        data_connector_arc,
-        access_checker_arc,
-        command_executor_arc,
-        event_publisher_arc,
-        router_arc,
-    ) = get_settings().await;
+//schema path of registered service, replies object_type schema or 400 if unknown
+async fn start_schema_service(schemas: HashMap<String, serde_json::Value>) -> SocketAddr {
+    let listener = get_random_listener();
+    let addr = listener.local_addr().unwrap();
+    let schemas = Arc::new(schemas);
     let make_svc = make_service_fn(move |_| {
-        let dc = data_connector_arc.clone();
-        let ac = access_checker_arc.clone();
-        let ce = command_executor_arc.clone();
-        let ep = event_publisher_arc.clone();
-        let rt = router_arc.clone();
+        let schemas = schemas.clone();
         async move {
-            Ok::<_, Error>(service_fn(move |req| {
-                service::service_route(
-                    req,
-                    dc.clone(),
-                    ac.clone(),
-                    ce.clone(),
-                    ep.clone(),
-                    rt.clone(),
-                )
+            Ok::<_, Error>(service_fn(move |req: Request<Body>| {
+                let schemas = schemas.clone();
+                async move {
+                    let params: HashMap<String, String> = req
+                        .uri()
+                        .query()
+                        .map(|v| {
+                            url::form_urlencoded::parse(v.as_bytes())
+                                .into_owned()
+                                .collect()
+                        })
+                        .unwrap_or_else(HashMap::new);
+                    let schema = params.get("object_type").and_then(|ot| schemas.get(ot));
+                    Ok::<_, Error>(match schema {
+                        Some(s) => Response::new(Body::from(s.to_string())),
+                        None => service::resp_with_code(StatusCode::BAD_REQUEST),
+                    })
+                }
             }))
         }
     });
-    let app = Server::bind(&addr).serve(make_svc);
-
+    let app = Server::from_tcp(listener).unwrap().serve(make_svc);
     tokio::spawn(async move {
         if let Err(err) = app.await {
-            error!("server: {}", err);
+            error!("schema service: {}", err);
         }
     });
+    addr
+}
+
+async fn call_service_with_token(
+    method: Method,
+    addr: &SocketAddr,
+    path: &str,
+    query: &str,
+    token: Option<String>,
+    body: Body,
+) -> Response<Body> {
+    let mut builder = Request::builder()
+        .method(method)
+        .uri(format!("http://{}{}?{}", addr, path, query));
+    if token.is_some() {
+        builder = builder.header("Authorization", token.unwrap());
+    }
+    let req = builder.body(body).expect("request builder");
+    let client = Client::new();
+    client.request(req).await.unwrap()
+}
+
+async fn call_service(addr: &SocketAddr, path: &str, query: &str, body: &str) -> Response<Body> {
+    call_service_with_token(
+        Method::POST,
+        addr,
+        path,
+        &format!(
+            "correlation_id={}{}",
+            Uuid::new_v4().to_hyphenated().to_string(),
+            query
+        ),
+        Some(access::AccessChecker::get_basic_authorization_token(
+            &TEST_USR_NAME.to_string(),
+            &TEST_USR_PASSWORD.to_string(),
+        )),
+        Body::from(body.to_string()),
+    )
+    .await
+}
 
-    let resp = call_service(Method::GET, addr.port(), "/openapi.yaml", Body::empty()).await;
+async fn get_reply<T>(resp: Response<Body>) -> T
+where
+    T: de::DeserializeOwned,
+{
     assert_eq!(resp.status(), StatusCode::OK);
+    let bytes = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+async fn get_event(
+    receiver: &mut mpsc::UnboundedReceiver<(String, String)>,
+) -> (String, Vec<events::route::OnRouteUpdate>) {
+    let (uri, body) = tokio::time::timeout(Duration::from_secs(5), receiver.recv())
+        .await
+        .expect("event timeout")
+        .unwrap();
+    (uri, serde_json::from_str(&body).unwrap())
+}
+
+fn get_route_body(service_name: &str, subscription_to: Option<&str>) -> String {
+    let subscription = match subscription_to {
+        Some(to) => serde_json::json!([{
+            "object_type": "OnRouteUpdate",
+            "path": { "http": to }
+        }]),
+        None => serde_json::json!([]),
+    };
+    serde_json::json!({
+        "items": [{
+            "service_name": service_name,
+            "description": "test service",
+            "priority": 1,
+            "command": [],
+            "event": [],
+            "subscription": subscription,
+            "path": {
+                "http": {
+                    "helth": "http://localhost/helth",
+                    "schema": "http://localhost/schema",
+                    "reply_to": "http://localhost/reply",
+                    "state": "http://localhost/state",
+                    "error": "http://localhost/error"
+                }
+            }
+        }]
+    })
+    .to_string()
 }
 
 #[tokio::test(threaded_scheduler)]
-async fn test_route_ok() {
-    let (
-        addr,
-        data_connector_arc,
-        access_checker_arc,
-        command_executor_arc,
-        event_publisher_arc,
-        router_arc,
-    ) = get_settings().await;
-    let make_svc = make_service_fn(move |_| {
-        let dc = data_connector_arc.clone();
-        let ac = access_checker_arc.clone();
-        let ce = command_executor_arc.clone();
-        let ep = event_publisher_arc.clone();
-        let rt = router_arc.clone();
-        async move {
-            Ok::<_, Error>(service_fn(move |req| {
-                service::service_route(
-                    req,
-                    dc.clone(),
-                    ac.clone(),
-                    ce.clone(),
-                    ep.clone(),
-                    rt.clone(),
-                )
-            }))
-        }
-    });
-    let app = Server::bind(&addr).serve(make_svc);
+async fn test_index_ok() {
+    let (addr, _) = start_service().await;
+    for p in ["/", "/openapi.json", "/openapi.yaml"].iter() {
+        let resp = call_service_with_token(Method::GET, &addr, p, "", None, Body::empty()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+}
 
-    tokio::spawn(async move {
-        if let Err(err) = app.await {
-            error!("server: {}", err);
-        }
-    });
+#[tokio::test(threaded_scheduler)]
+async fn test_openapi_ok() {
+    let (addr, _) = start_service().await;
+    let resp =
+        call_service_with_token(Method::GET, &addr, "/openapi.json", "", None, Body::empty()).await;
+    let spec: serde_json::Value = get_reply(resp).await;
+    assert_eq!(spec["openapi"], openapi::OPENAPI_VERSION);
+    assert_eq!(
+        spec["components"]["securitySchemes"]["basicAuth"]["scheme"],
+        "basic"
+    );
+    for p in [path::ROUTER_ROUTE_ADD, path::CAR_GET, path::SCHEMA].iter() {
+        let params = spec["paths"][p]["post"]["parameters"].as_array().unwrap();
+        assert!(params
+            .iter()
+            .any(|v| v["$ref"] == "#/components/parameters/correlation_id"));
+    }
+    assert_eq!(
+        spec["components"]["parameters"]["correlation_id"]["required"],
+        true
+    );
+    assert!(spec["paths"].get("/api/signin").is_none());
+    //command and event operations are declared by registry
+    assert_eq!(spec["paths"][path::CAR_GET]["post"]["tags"][0], "car");
+    assert_eq!(
+        spec["paths"][path::EVENT_ON_ASYNC_COMMAND_STATE_CHANGE]["post"]["tags"][0],
+        "event"
+    );
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_helth_probe_ok() {
+    let (addr, _) = start_service().await;
+    let resp =
+        call_service_with_token(Method::GET, &addr, path::LIVENESS, "", None, Body::empty()).await;
+    let helth: entities::route::ServiceHelth = get_reply(resp).await;
+    assert_eq!(helth.state, entities::route::ServiceState::Alive);
+    assert!(helth.uptime >= 0);
+    let resp =
+        call_service_with_token(Method::GET, &addr, path::READINESS, "", None, Body::empty()).await;
+    let readiness: entities::route::ServiceReadiness = get_reply(resp).await;
+    assert_eq!(readiness.state, entities::route::ServiceState::Alive);
+    //local router, workers are not spawned by test service
+    assert_eq!(readiness.components.len(), 1);
+    assert_eq!(readiness.components[0].name, "database");
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_metrics_ok() {
+    let (addr, _) = start_service().await;
+    let _: replies::car::GetCarReply =
+        get_reply(call_service(&addr, path::CAR_GET, "", r#"{"filter":null,"ids":null}"#).await)
+            .await;
+    let resp = call_service(&addr, "/fake", "", "{}").await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    //change calls ReserveCar through http provider
+    call_service(&addr, path::CAR_CHANGE, "", r#"{"items":[]}"#).await;
+    let resp =
+        call_service_with_token(Method::GET, &addr, path::METRICS, "", None, Body::empty()).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let text = String::from_utf8(
+        hyper::body::to_bytes(resp.into_body())
+            .await
+            .unwrap()
+            .to_vec(),
+    )
+    .unwrap();
+    assert!(text.contains(&format!(
+        r#"webapi_http_requests_total{{method="POST",path="{}",status="200"}} 1"#,
+        path::CAR_GET
+    )));
+    assert!(
+        text.contains(r#"webapi_http_requests_total{method="POST",path="other",status="404"} 1"#)
+    );
+    assert!(text.contains(&format!(
+        r#"webapi_http_request_duration_seconds_count{{method="POST",path="{}"}} 1"#,
+        path::CAR_GET
+    )));
+    assert!(text.contains(&format!(
+        r#"webapi_outbound_requests_total{{target="{}",status="200"}} 1"#,
+        addr
+    )));
+    assert!(text.contains("# TYPE webapi_event_delivery_failures_total counter"));
+    assert!(text.contains(r#"webapi_circuit_breaker_state{service="webapi",state="Closed"} 1"#));
+    assert!(!text.contains("webapi_db_pool_connections"));
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_auth_err() {
+    let (addr, _) = start_service().await;
+    let resp = call_service_with_token(
+        Method::POST,
+        &addr,
+        path::HELTH,
+        "correlation_id=1",
+        None,
+        Body::empty(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let resp = call_service_with_token(
+        Method::POST,
+        &addr,
+        path::HELTH,
+        "correlation_id=1",
+        Some(access::AccessChecker::get_basic_authorization_token(
+            &TEST_USR_NAME.to_string(),
+            &"fake".to_string(),
+        )),
+        Body::empty(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
 
-    for route in path::ROUTE_WITH_EMPTY_BODY.iter() {
-        let resp = call_service(Method::POST, addr.port(), route, Body::empty()).await;
+#[tokio::test(threaded_scheduler)]
+async fn test_correlation_id_err() {
+    let (addr, _) = start_service().await;
+    let resp = call_service_with_token(
+        Method::POST,
+        &addr,
+        path::HELTH,
+        "",
+        Some(access::AccessChecker::get_basic_authorization_token(
+            &TEST_USR_NAME.to_string(),
+            &TEST_USR_PASSWORD.to_string(),
+        )),
+        Body::empty(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_route_with_empty_body_ok() {
+    let (addr, _) = start_service().await;
+    for p in path::ROUTE_WITH_EMPTY_BODY.iter() {
+        let resp = call_service(&addr, p, "", "").await;
         assert_eq!(resp.status(), StatusCode::OK);
     }
 }
 
+#[tokio::test(threaded_scheduler)]
+async fn test_route_ok() {
+    let (addr, _) = start_service().await;
+    let calls = vec![
+        (path::SCHEMA, "&object_type=GetRoute", ""),
+        (path::SCHEMA, "&object_type=GetCar", ""),
+        (path::SCHEMA, "", ""),
+        (path::ERROR, "&error_code=DatabaseError", ""),
+        (path::ERROR, "", ""),
+        (
+            path::ROUTER_ROUTE_GET,
+            "",
+            r#"{"filter":null,"services":null}"#,
+        ),
+        (
+            path::ROUTER_COMMAND_GET,
+            "",
+            r#"{"filter":null,"services":null}"#,
+        ),
+        (
+            path::ROUTER_EVENT_GET,
+            "",
+            r#"{"filter":null,"services":null}"#,
+        ),
+        (
+            path::ROUTER_SUBSCIBTION_GET,
+            "",
+            r#"{"filter":null,"services":null}"#,
+        ),
+        (
+            path::ROUTER_SERVICE_GET,
+            "",
+            r#"{"filter":null,"names":null}"#,
+        ),
+        (
+            path::ROUTER_EVENT_ON_SERVICE_UNAVAILABLE,
+            "",
+            r#"[{"services":["fake"]}]"#,
+        ),
+        (
+            path::EVENT_ON_ROUTE_UPDATE,
+            "",
+            r#"[{"services":["webapi"]}]"#,
+        ),
+        (
+            path::EVENT_ON_ASYNC_COMMAND_STATE_CHANGE,
+            "",
+            r#"[{"commands":[]}]"#,
+        ),
+        (path::CAR_RESERVE, "", r#"{"services":[1]}"#),
+        (path::CAR_RELEASE, "", r#"{"services":[1]}"#),
+    ];
+    for (p, query, body) in calls {
+        let resp = call_service(&addr, p, query, body).await;
+        assert_eq!(resp.status(), StatusCode::OK, "path {}", p);
+    }
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_error_lang_ok() {
+    let (addr, _) = start_service().await;
+    let resp = call_service(&addr, path::ERROR, "&error_code=DatabaseError&lang=ru", "").await;
+    let reply: entities::error::Error = get_reply(resp).await;
+    assert_eq!(reply.lang, "ru");
+    let req = Request::builder()
+        .method(Method::POST)
+        .uri(format!(
+            "http://{}{}?correlation_id={}&error_code=DatabaseError",
+            addr,
+            path::ERROR,
+            Uuid::new_v4().to_hyphenated().to_string()
+        ))
+        .header(
+            "Authorization",
+            access::AccessChecker::get_basic_authorization_token(
+                &TEST_USR_NAME.to_string(),
+                &TEST_USR_PASSWORD.to_string(),
+            ),
+        )
+        .header("Accept-Language", "ru-RU,ru;q=0.9,en;q=0.8")
+        .body(Body::empty())
+        .expect("request builder");
+    let resp = Client::new().request(req).await.unwrap();
+    let reply: entities::error::Error = get_reply(resp).await;
+    assert_eq!(reply.lang, "ru");
+    let resp = call_service(
+        &addr,
+        path::ERROR,
+        "&error_code=DatabaseError&lang=fake",
+        "",
+    )
+    .await;
+    let reply: entities::error::Error = get_reply(resp).await;
+    assert_eq!(reply.lang, connectors::DEFAULT_LANG);
+    let resp = call_service(&addr, path::ERROR, "&lang=ru", "").await;
+    let items: Vec<entities::error::Error> = get_reply(resp).await;
+    assert!(items.iter().all(|item| item.lang == "ru"));
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_router_error_get_ok() {
+    let (addr, _) = start_service().await;
+    let resp = call_service(
+        &addr,
+        path::ROUTER_ERROR_GET,
+        "",
+        r#"{"filter":null,"services":null,"lang":"ru"}"#,
+    )
+    .await;
+    let reply: replies::route::GetServiceErrorReply = get_reply(resp).await;
+    assert_eq!(reply.error_code, errors::ErrorCode::ReplyOk);
+    let items = reply.items.unwrap();
+    assert_eq!(
+        items
+            .iter()
+            .map(|s| s.service_name.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["router", "webapi"]
+    );
+    assert!(items.iter().all(|s| s.items.iter().all(|e| e.lang == "ru")));
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_router_schema_get_ok() {
+    let (addr, _) = start_service().await;
+    let resp = call_service(
+        &addr,
+        path::ROUTER_SCHEMA_GET,
+        "",
+        r#"{"service_name":"webapi","object_type":null}"#,
+    )
+    .await;
+    let reply: replies::route::GetServiceSchemaReply = get_reply(resp).await;
+    let items = reply.items.unwrap();
+    assert!(items
+        .iter()
+        .any(|s| s.object_type == "GetCarReply" && s.version == 1 && s.schema.is_none()));
+    let resp = call_service(
+        &addr,
+        path::ROUTER_SCHEMA_GET,
+        "",
+        r#"{"service_name":"webapi","object_type":"GetCar"}"#,
+    )
+    .await;
+    let reply: replies::route::GetServiceSchemaReply = get_reply(resp).await;
+    let item = &reply.items.unwrap()[0];
+    assert_eq!(item.service_name, Some("webapi".to_string()));
+    assert_eq!(item.schema.as_ref().unwrap()["title"], "GetCar");
+    let resp = call_service(
+        &addr,
+        path::ROUTER_SCHEMA_GET,
+        "",
+        r#"{"service_name":"fake","object_type":"GetCar"}"#,
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_validation_err() {
+    let (addr, _) = start_service().await;
+    let resp = call_service(&addr, path::CAR_ADD, "", r#"{"items":[{"car_name":1}]}"#).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let bytes = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+    let reply: replies::common::BadRequestReply = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(reply.error_code, errors::ErrorCode::BadRequestError);
+    assert_eq!(reply.violations.len(), 1);
+    assert_eq!(reply.violations[0].path, "/items/0/car_name");
+    let resp = call_service(&addr, path::EVENT_ON_ROUTE_UPDATE, "", r#"[{}]"#).await;
+    let bytes = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+    let reply: replies::common::BadRequestReply = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(reply.violations[0].path, "/0/services");
+}
+
+fn get_command_route_body(service_name: &str, schema_addr: &SocketAddr) -> String {
+    serde_json::json!({
+        "items": [{
+            "service_name": service_name,
+            "description": "test service",
+            "priority": 1,
+            "command": [{
+                "object_type": "GetCar",
+                "reply_type": "GetCarReply",
+                "exec_mode": "Sync",
+                "description": "",
+                "path": { "http": "http://localhost/car/get" }
+            }],
+            "event": [],
+            "subscription": [],
+            "path": {
+                "http": {
+                    "helth": "http://localhost/helth",
+                    "schema": format!("http://{}/schema", schema_addr),
+                    "reply_to": "http://localhost/reply",
+                    "state": "http://localhost/state",
+                    "error": "http://localhost/error"
+                }
+            }
+        }]
+    })
+    .to_string()
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_route_schema_check() {
+    let (addr, _) = start_service().await;
+    let registry = schema::make_schema();
+    let mut schemas = HashMap::<String, serde_json::Value>::new();
+    schemas.insert(
+        "GetCar".to_string(),
+        serde_json::to_value(registry.get("GetCar").unwrap()).unwrap(),
+    );
+    let schema_addr = start_schema_service(schemas.clone()).await;
+    let resp = call_service(
+        &addr,
+        path::ROUTER_ROUTE_ADD,
+        "",
+        &get_command_route_body("svc_ok", &schema_addr),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    //ids changed from array to string
+    schemas.get_mut("GetCar").unwrap()["properties"]["ids"] =
+        serde_json::json!({ "type": "string" });
+    let schema_addr = start_schema_service(schemas).await;
+    let resp = call_service(
+        &addr,
+        path::ROUTER_ROUTE_ADD,
+        "",
+        &get_command_route_body("svc_bad", &schema_addr),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let bytes = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+    let reply: replies::common::BadRequestReply = serde_json::from_slice(&bytes).unwrap();
+    assert!(reply.violations.len() > 0);
+    assert!(reply
+        .violations
+        .iter()
+        .all(|v| v.path == "/items/0" && v.message.contains("/ids type changed")));
+}
+
 #[tokio::test(threaded_scheduler)]
 async fn test_route_err() {
-    let (
-        addr,
-        data_connector_arc,
-        access_checker_arc,
-        command_executor_arc,
-        event_publisher_arc,
-        router_arc,
-    ) = get_settings().await;
-    let make_svc = make_service_fn(move |_| {
-        let dc = data_connector_arc.clone();
-        let ac = access_checker_arc.clone();
-        let ce = command_executor_arc.clone();
-        let ep = event_publisher_arc.clone();
-        let rt = router_arc.clone();
-        async move {
-            Ok::<_, Error>(service_fn(move |req| {
-                service::service_route(
-                    req,
-                    dc.clone(),
-                    ac.clone(),
-                    ce.clone(),
-                    ep.clone(),
-                    rt.clone(),
-                )
-            }))
-        }
-    });
-    let app = Server::bind(&addr).serve(make_svc);
+    let (addr, _) = start_service().await;
+    let resp = call_service(&addr, "/fake", "", "{}").await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let resp = call_service(&addr, path::CAR_GET, "", "fake").await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = call_service(&addr, path::SCHEMA, "&object_type=Fake", "").await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = call_service(&addr, path::ERROR, "&error_code=Fake", "").await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
 
-    tokio::spawn(async move {
-        if let Err(err) = app.await {
-            error!("server: {}", err);
+#[tokio::test(threaded_scheduler)]
+async fn test_idempotent_replay_ok() {
+    let (addr, dc) = start_service().await;
+    let query = format!(
+        "correlation_id={}",
+        Uuid::new_v4().to_hyphenated().to_string()
+    );
+    let mut ids = Vec::<Vec<i32>>::new();
+    let mut replayed = Vec::<bool>::new();
+    //retried AddCar is not executed again
+    for _ in 0..2 {
+        let resp = call_service_with_token(
+            Method::POST,
+            &addr,
+            path::CAR_ADD,
+            &query,
+            Some(access::AccessChecker::get_basic_authorization_token(
+                &TEST_USR_NAME.to_string(),
+                &TEST_USR_PASSWORD.to_string(),
+            )),
+            Body::from(r#"{"items":[{"id":null,"car_name":"retried"}]}"#),
+        )
+        .await;
+        replayed.push(resp.headers().contains_key(idempotency::REPLAYED_HEADER));
+        let reply: replies::common::AddIntIdsReply = get_reply(resp).await;
+        assert_eq!(reply.error_code, errors::ErrorCode::ReplyOk);
+        ids.push(reply.ids.unwrap());
+    }
+    assert_eq!(ids[0], ids[1]);
+    assert_eq!(replayed, vec![false, true]);
+    assert_eq!(dc.car.get(None).await.unwrap().len(), 1);
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_idempotent_caller_ok() {
+    let (addr, dc) = start_service().await;
+    //service name is declared by caller, user of other service must not get its reply
+    let query = format!(
+        "correlation_id={}&service_name=webapi",
+        Uuid::new_v4().to_hyphenated().to_string()
+    );
+    let mut replayed = Vec::<bool>::new();
+    for (usr_name, usr_password) in &[(TEST_USR_NAME, TEST_USR_PASSWORD), ("other", "1234567891")] {
+        let resp = call_service_with_token(
+            Method::POST,
+            &addr,
+            path::CAR_ADD,
+            &query,
+            Some(access::AccessChecker::get_basic_authorization_token(
+                &usr_name.to_string(),
+                &usr_password.to_string(),
+            )),
+            Body::from(r#"{"items":[{"id":null,"car_name":"spoofed"}]}"#),
+        )
+        .await;
+        replayed.push(resp.headers().contains_key(idempotency::REPLAYED_HEADER));
+        let reply: replies::common::AddIntIdsReply = get_reply(resp).await;
+        assert_eq!(reply.error_code, errors::ErrorCode::ReplyOk);
+    }
+    assert_eq!(replayed, vec![false, false]);
+    assert_eq!(dc.car.get(None).await.unwrap().len(), 2);
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_rate_limit_ok() {
+    let (addr, _) = start_service().await;
+    let mut statuses = Vec::<StatusCode>::new();
+    //GetService route allows one request at once
+    for _ in 0..2 {
+        let resp = call_service(
+            &addr,
+            path::ROUTER_SERVICE_GET,
+            "",
+            r#"{"filter":null,"names":null}"#,
+        )
+        .await;
+        statuses.push(resp.status());
+        if resp.status() == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after: u64 = resp.headers()[header::RETRY_AFTER]
+                .to_str()
+                .unwrap()
+                .parse()
+                .unwrap();
+            assert!(retry_after > 0);
+            let bytes = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+            let reply: replies::common::StandardReply = serde_json::from_slice(&bytes).unwrap();
+            assert_eq!(reply.error_code, errors::ErrorCode::TooManyRequestsError);
         }
-    });
+    }
+    assert_eq!(
+        statuses,
+        vec![StatusCode::OK, StatusCode::TOO_MANY_REQUESTS]
+    );
+    //other object types use default rule
+    let resp = call_service(&addr, path::CAR_GET, "", r#"{"filter":null,"ids":null}"#).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
 
-    let resp = call_service(Method::POST, addr.port(), "/fake", Body::from("{}")).await;
-    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+#[tokio::test(threaded_scheduler)]
+async fn test_saga_compensate_ok() {
+    let (addr, dc, ce) = start_service_with_executor().await;
+    let reply: replies::common::AddIntIdsReply = get_reply(
+        call_service(
+            &addr,
+            path::CAR_ADD,
+            "",
+            r#"{"items":[{"id":null,"car_name":"saga1"}]}"#,
+        )
+        .await,
+    )
+    .await;
+    let id = reply.ids.unwrap()[0];
+    //ReserveCar is done, local change fails and reservation is released
+    let reply: replies::common::StandardReply = get_reply(
+        call_service(
+            &addr,
+            path::CAR_CHANGE,
+            "",
+            &format!(r#"{{"items":[{{"id":{},"car_name":"saga2"}}]}}"#, id + 1),
+        )
+        .await,
+    )
+    .await;
+    assert_eq!(reply.error_code, errors::ErrorCode::NotFoundError);
+    let items = dc.saga.get(None).await.unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(
+        items[0].state,
+        entities::saga::SagaState::Compensated.to_string()
+    );
+    assert_eq!(items[0].step, 0);
+    //crash after local change, both steps are compensated on restart
+    let car = entities::car::Car {
+        id: Some(id),
+        car_name: "saga1".to_string(),
+    };
+    let changed = entities::car::Car {
+        id: Some(id),
+        car_name: "saga2".to_string(),
+    };
+    let data = car::ChangeCarData {
+        items: vec![changed.clone()],
+        previous: vec![car],
+    };
+    let interrupted = entities::saga::Saga {
+        id: "interrupted".to_string(),
+        saga_type: "ChangeCar".to_string(),
+        correlation_id: Uuid::new_v4().to_hyphenated().to_string(),
+        state: entities::saga::SagaState::Running.to_string(),
+        step: 1,
+        data: serde_json::to_string(&data).unwrap(),
+        owner: "crashed".to_string(),
+        lease_expires_at: Utc::now() - chrono::Duration::seconds(1),
+        added_at: Utc::now(),
+        state_changed_at: Utc::now(),
+    };
+    //saga of alive instance is not recovered
+    let mut running = interrupted.clone();
+    running.id = "running".to_string();
+    running.owner = "alive".to_string();
+    running.lease_expires_at = Utc::now() + chrono::Duration::seconds(60);
+    dc.saga.add(interrupted).await.unwrap();
+    dc.saga.add(running).await.unwrap();
+    dc.car.change(vec![changed]).await.unwrap();
+    let change_car = car::get_change_saga();
+    saga::recover(&dc, &ce, &[&change_car]).await.unwrap();
+    let items = dc
+        .saga
+        .get(Some(vec!["interrupted".to_string()]))
+        .await
+        .unwrap();
+    assert_eq!(
+        items[0].state,
+        entities::saga::SagaState::Compensated.to_string()
+    );
+    assert_eq!(items[0].owner, ce.get_instance_id());
+    let items = dc
+        .saga
+        .get(Some(vec!["running".to_string()]))
+        .await
+        .unwrap();
+    assert_eq!(
+        items[0].state,
+        entities::saga::SagaState::Running.to_string()
+    );
+    assert_eq!(items[0].owner, "alive");
+    //owner renews its unfinished sagas only
+    assert_eq!(saga::renew(&dc, &ce).await.unwrap(), 0);
+    let cars = dc.car.get(Some(vec![id])).await.unwrap();
+    assert_eq!(cars[0].car_name, "saga1");
 }
 
-*/
\ No newline at end of file
+#[tokio::test(threaded_scheduler)]
+async fn test_car_ok() {
+    let (addr, _) = start_service().await;
+    let reply: replies::common::AddIntIdsReply = get_reply(
+        call_service(
+            &addr,
+            path::CAR_ADD,
+            "",
+            r#"{"items":[{"id":null,"car_name":"car1"}]}"#,
+        )
+        .await,
+    )
+    .await;
+    assert_eq!(reply.error_code, errors::ErrorCode::ReplyOk);
+    let id = reply.ids.unwrap()[0];
+    //change call ReserveCar through command executor
+    let reply: replies::common::StandardReply = get_reply(
+        call_service(
+            &addr,
+            path::CAR_CHANGE,
+            "",
+            &format!(r#"{{"items":[{{"id":{},"car_name":"car2"}}]}}"#, id),
+        )
+        .await,
+    )
+    .await;
+    assert!(reply.is_ok());
+    let reply: replies::car::GetCarReply =
+        get_reply(call_service(&addr, path::CAR_GET, "", r#"{"filter":null,"ids":null}"#).await)
+            .await;
+    let items = reply.items.unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].car_name, "car2");
+    let reply: replies::common::StandardReply = get_reply(
+        call_service(
+            &addr,
+            path::CAR_REMOVE,
+            "",
+            &format!(r#"{{"ids":[{}]}}"#, id),
+        )
+        .await,
+    )
+    .await;
+    assert!(reply.is_ok());
+    let reply: replies::common::StandardReply = get_reply(
+        call_service(
+            &addr,
+            path::CAR_REMOVE,
+            "",
+            &format!(r#"{{"ids":[{}]}}"#, id),
+        )
+        .await,
+    )
+    .await;
+    assert!(!reply.is_ok());
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_trace_ok() {
+    let (addr, _) = start_service().await;
+    let reply: replies::common::AddIntIdsReply = get_reply(
+        call_service(
+            &addr,
+            path::CAR_ADD,
+            "",
+            r#"{"items":[{"id":null,"car_name":"trace1"}]}"#,
+        )
+        .await,
+    )
+    .await;
+    let id = reply.ids.unwrap()[0];
+    let trace_id = Uuid::new_v4().to_simple().to_string();
+    let parent_id = &Uuid::new_v4().to_simple().to_string()[..16];
+    let correlation_id = Uuid::new_v4().to_hyphenated().to_string();
+    let req = Request::builder()
+        .method(Method::POST)
+        .uri(format!(
+            "http://{}{}?correlation_id={}",
+            addr,
+            path::CAR_CHANGE,
+            correlation_id
+        ))
+        .header(
+            "Authorization",
+            access::AccessChecker::get_basic_authorization_token(
+                &TEST_USR_NAME.to_string(),
+                &TEST_USR_PASSWORD.to_string(),
+            ),
+        )
+        .header(
+            context::TRACEPARENT,
+            format!("00-{}-{}-01", trace_id, parent_id),
+        )
+        .body(Body::from(format!(
+            r#"{{"items":[{{"id":{},"car_name":"trace2"}}]}}"#,
+            id
+        )))
+        .unwrap();
+    let reply: replies::common::StandardReply =
+        get_reply(Client::new().request(req).await.unwrap()).await;
+    assert!(reply.is_ok());
+    let app_settings: settings::AppSettings =
+        serde_json::from_str(&fs::read_to_string(TEST_APP_SETTINGS).unwrap()).unwrap();
+    let spans: Vec<serde_json::Value> =
+        fs::read_to_string(app_settings.tracing.unwrap().file.unwrap())
+            .unwrap()
+            .lines()
+            .filter_map(|l| serde_json::from_str::<serde_json::Value>(l).ok())
+            .filter(|s| s["traceId"] == trace_id.as_str())
+            .collect();
+    //server CAR_CHANGE, client ReserveCar, server ReserveCar
+    assert_eq!(spans.len(), 3);
+    assert!(spans
+        .iter()
+        .all(|s| s["attributes"]["correlation_id"] == correlation_id.as_str()));
+    let change = spans
+        .iter()
+        .find(|s| s["name"] == path::CAR_CHANGE)
+        .unwrap();
+    assert_eq!(change["parentSpanId"], *parent_id);
+    let client = spans
+        .iter()
+        .find(|s| s["kind"] == "SPAN_KIND_CLIENT")
+        .unwrap();
+    assert_eq!(client["parentSpanId"], change["spanId"]);
+    let reserve = spans
+        .iter()
+        .find(|s| s["name"] == path::CAR_RESERVE)
+        .unwrap();
+    assert_eq!(reserve["parentSpanId"], client["spanId"]);
+    assert_eq!(reserve["attributes"]["caller"], "webapi");
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_route_add_remove_ok() {
+    let (addr, _) = start_service().await;
+    let (subscriber_addr, mut receiver) = start_subscriber().await;
+    let subscriber_to = format!("http://{}{}", subscriber_addr, path::EVENT_ON_ROUTE_UPDATE);
+    //router reloads subscriptions on its own OnRouteUpdate, so next updates reach subscriber
+    let reply: replies::common::AddStrIdsReply = get_reply(
+        call_service(
+            &addr,
+            path::ROUTER_ROUTE_ADD,
+            "",
+            &get_route_body("subscriber", Some(&subscriber_to)),
+        )
+        .await,
+    )
+    .await;
+    assert!(reply.is_ok());
+    let reply: replies::common::AddStrIdsReply = get_reply(
+        call_service(
+            &addr,
+            path::ROUTER_ROUTE_ADD,
+            "",
+            &get_route_body("svc2", None),
+        )
+        .await,
+    )
+    .await;
+    assert!(reply.is_ok());
+    let (uri, events) = get_event(&mut receiver).await;
+    assert!(uri.contains("object_type=OnRouteUpdate"));
+    assert_eq!(events[0].services, vec!["svc2".to_string()]);
+    let reply: replies::route::GetRouteReply = get_reply(
+        call_service(
+            &addr,
+            path::ROUTER_ROUTE_GET,
+            "",
+            r#"{"filter":null,"services":["subscriber","svc2"]}"#,
+        )
+        .await,
+    )
+    .await;
+    assert_eq!(reply.items.unwrap().len(), 2);
+    let reply: replies::common::StandardReply = get_reply(
+        call_service(
+            &addr,
+            path::ROUTER_ROUTE_REMOVE,
+            "",
+            r#"{"services":["svc2"]}"#,
+        )
+        .await,
+    )
+    .await;
+    assert!(reply.is_ok());
+    let (_, events) = get_event(&mut receiver).await;
+    assert_eq!(events[0].services, vec!["svc2".to_string()]);
+    let reply: replies::common::StandardReply = get_reply(
+        call_service(
+            &addr,
+            path::ROUTER_ROUTE_REMOVE,
+            "",
+            r#"{"services":["svc2"]}"#,
+        )
+        .await,
+    )
+    .await;
+    assert!(!reply.is_ok());
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_route_upsert_ok() {
+    let (addr, _) = start_service().await;
+    let (subscriber_addr, mut receiver) = start_subscriber().await;
+    let subscriber_to = format!("http://{}{}", subscriber_addr, path::EVENT_ON_ROUTE_UPDATE);
+    let body = get_route_body("subscriber", Some(&subscriber_to));
+    let reply: replies::common::AddStrIdsReply =
+        get_reply(call_service(&addr, path::ROUTER_ROUTE_ADD, "", &body).await).await;
+    assert!(reply.is_ok());
+    //same route again, subscribers are not notified
+    let reply: replies::common::AddStrIdsReply =
+        get_reply(call_service(&addr, path::ROUTER_ROUTE_ADD, "", &body).await).await;
+    assert!(reply.is_ok());
+    assert_eq!(reply.ids, Some(vec!["subscriber".to_string()]));
+    let body = get_route_body("svc2", None).replace("test service", "changed service");
+    let reply: replies::common::AddStrIdsReply =
+        get_reply(call_service(&addr, path::ROUTER_ROUTE_ADD, "", &body).await).await;
+    assert!(reply.is_ok());
+    let (_, events) = get_event(&mut receiver).await;
+    assert_eq!(events[0].services, vec!["svc2".to_string()]);
+    let body = get_route_body("svc2", None);
+    let reply: replies::common::AddStrIdsReply =
+        get_reply(call_service(&addr, path::ROUTER_ROUTE_ADD, "", &body).await).await;
+    assert!(reply.is_ok());
+    let (_, events) = get_event(&mut receiver).await;
+    assert_eq!(events[0].services, vec!["svc2".to_string()]);
+    let reply: replies::route::GetRouteReply = get_reply(
+        call_service(
+            &addr,
+            path::ROUTER_ROUTE_GET,
+            "",
+            r#"{"filter":null,"services":["svc2"]}"#,
+        )
+        .await,
+    )
+    .await;
+    assert_eq!(reply.items.unwrap()[0].description, "test service");
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_route_renew_ok() {
+    let (addr, _) = start_service().await;
+    let mut body: serde_json::Value = serde_json::from_str(&get_route_body("svc2", None)).unwrap();
+    body["lease"] = serde_json::json!(60);
+    let reply: replies::common::AddStrIdsReply =
+        get_reply(call_service(&addr, path::ROUTER_ROUTE_ADD, "", &body.to_string()).await).await;
+    assert!(reply.is_ok());
+    let reply: replies::common::StandardReply = get_reply(
+        call_service(
+            &addr,
+            path::ROUTER_ROUTE_RENEW,
+            "",
+            r#"{"services":["svc2"]}"#,
+        )
+        .await,
+    )
+    .await;
+    assert!(reply.is_ok());
+    //registered without lease
+    let reply: replies::common::StandardReply = get_reply(
+        call_service(
+            &addr,
+            path::ROUTER_ROUTE_RENEW,
+            "",
+            r#"{"services":["webapi"]}"#,
+        )
+        .await,
+    )
+    .await;
+    assert_eq!(reply.error_code, errors::ErrorCode::NotFoundError);
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_router_heartbeat_ok() {
+    let app_settings: settings::AppSettings =
+        serde_json::from_str(&fs::read_to_string(TEST_APP_SETTINGS).unwrap()).unwrap();
+    let dc = Arc::new(super::get_memory_data_connector().await);
+    let ac = Arc::new(
+        access::AccessChecker::from_data_connector(&dc, &app_settings.access.authentication)
+            .await
+            .unwrap(),
+    );
+    let rt = router::Router::new(
+        dc.clone(),
+        ac,
+        None,
+        app_settings.path,
+        app_settings.service,
+        "localhost",
+        None,
+        None,
+        None,
+        None,
+        Some(settings::Lease {
+            ttl: 60,
+            heartbeat: 20,
+        }),
+        true,
+    )
+    .await
+    .unwrap();
+    assert_eq!(rt.get_heartbeat(), Some(Duration::from_secs(20)));
+    //same registration is renewed in remote router
+    let registration = rt.get_registration().unwrap();
+    assert_eq!(registration.lease, Some(60));
+    assert!(registration
+        .items
+        .iter()
+        .any(|r| r.service_name == Some("webapi".to_string())));
+    //caller name does not depend on tracing settings
+    assert_eq!(rt.get_service_name(), Some("webapi"));
+    assert_eq!(rt.renew().await.unwrap(), None);
+    //lapsed lease, routes were removed by router
+    dc.route.remove(vec!["webapi".to_string()]).await.unwrap();
+    assert_eq!(rt.renew().await.unwrap(), Some(vec!["webapi".to_string()]));
+    assert_eq!(
+        dc.route.renew(vec!["webapi".to_string()]).await.unwrap(),
+        errors::ErrorCode::ReplyOk
+    );
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_async_command_state_ok() {
+    let (addr, dc) = start_service().await;
+    let resp = call_service(&addr, path::STATE, "&async_command_id=fake", "").await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = call_service(&addr, path::STATE, "", "").await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let id = Uuid::new_v4().to_hyphenated().to_string();
+    dc.received_async_command
+        .add(vec![executor::ReceivedAsyncCommand {
+            id: id.clone(),
+            object_type: "AddCar".to_string(),
+            service_name: "webapi".to_string(),
+            request_body: r#"{"items":[]}"#.to_string(),
+            state: executor::CommandSystemState::Initial.to_string(),
+            change_state_event: 0,
+            reply_body: "".to_string(),
+            proto: "http".to_string(),
+            added_at: Utc::now(),
+            state_changed_at: Utc::now(),
+            history: None,
+        }])
+        .await
+        .unwrap();
+    let reply: executor::AsyncCommandState =
+        get_reply(call_service(&addr, path::STATE, &format!("&async_command_id={}", id), "").await)
+            .await;
+    assert_eq!(reply.id, id);
+    assert_eq!(
+        reply.state,
+        executor::CommandSystemState::Initial.to_string()
+    );
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_call_value_ok() {
+    let (addr, dc, ce) = start_service_with_executor().await;
+    let cid = Uuid::new_v4().to_hyphenated().to_string();
+    let ctx = context::Context::new(
+        &cid,
+        None,
+        context::Span::new("GetCar", context::SpanKind::Internal),
+    );
+    let reply = ce
+        .call_value(
+            &ctx,
+            "GetCar",
+            executors::ExecMode::Any,
+            &serde_json::json!({"filter": null, "ids": null}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(reply["error_code"], "ReplyOk");
+    //request is checked before call
+    let e = ce
+        .call_value(
+            &ctx,
+            "GetCar",
+            executors::ExecMode::Any,
+            &serde_json::json!({"ids": "1"}),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        errors::from_boxed(&e).kind(),
+        errors::ErrorKind::BadRequestCommand
+    );
+    //command is declared sync only
+    let e = ce
+        .call_value(
+            &ctx,
+            "GetCar",
+            executors::ExecMode::Async,
+            &serde_json::json!({"filter": null, "ids": null}),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        errors::from_boxed(&e).kind(),
+        errors::ErrorKind::UnsupportedExecMode
+    );
+    //service keeps state of async command, sender polls it by correlation id
+    let resp = call_service_with_token(
+        Method::POST,
+        &addr,
+        path::CAR_GET,
+        &format!("correlation_id={}&exec_mode=Async", cid),
+        Some(access::AccessChecker::get_basic_authorization_token(
+            &TEST_USR_NAME.to_string(),
+            &TEST_USR_PASSWORD.to_string(),
+        )),
+        Body::from(r#"{"filter":null,"ids":null}"#),
+    )
+    .await;
+    let reply: replies::car::GetCarReply = get_reply(resp).await;
+    assert_eq!(reply.error_code, errors::ErrorCode::ReplyOk);
+    dc.sended_async_command
+        .add(vec![executor::SendedAsyncCommand {
+            id: cid.clone(),
+            object_type: "GetCar".to_string(),
+            service_name: "webapi".to_string(),
+            state: executor::CommandSystemState::Initial.to_string(),
+            change_state_event: 0,
+            added_at: Utc::now(),
+            state_changed_at: Utc::now(),
+            history: None,
+        }])
+        .await
+        .unwrap();
+    let state = ce.get_sended_async_command_state(&cid).await.unwrap();
+    assert_eq!(
+        state.state,
+        executor::CommandSystemState::Completed.to_string()
+    );
+}
+
+//admin form as posted by browser, with origin or referer header if set
+async fn post_admin_form(
+    addr: &SocketAddr,
+    path: &str,
+    token: &str,
+    source: Option<(header::HeaderName, &str)>,
+    body: &str,
+) -> Response<Body> {
+    let mut builder = Request::builder()
+        .method(Method::POST)
+        .uri(format!("http://{}{}", addr, path))
+        .header(header::AUTHORIZATION, token);
+    if let Some((name, value)) = source {
+        builder = builder.header(name, value);
+    }
+    let req = builder
+        .body(Body::from(body.to_string()))
+        .expect("request builder");
+    Client::new().request(req).await.unwrap()
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_admin_ok() {
+    let (addr, dc) = start_service().await;
+    let token = access::AccessChecker::get_basic_authorization_token(
+        &"admin".to_string(),
+        &"0987654321".to_string(),
+    );
+    let resp =
+        call_service_with_token(Method::GET, &addr, path::ADMIN, "", None, Body::empty()).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    //service credential is not admin one
+    let resp = call_service_with_token(
+        Method::GET,
+        &addr,
+        path::ADMIN,
+        "",
+        Some(access::AccessChecker::get_basic_authorization_token(
+            &TEST_USR_NAME.to_string(),
+            &TEST_USR_PASSWORD.to_string(),
+        )),
+        Body::empty(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let id = Uuid::new_v4().to_hyphenated().to_string();
+    dc.dead_letter
+        .add(entities::dead_letter::DeadLetterEvent {
+            id: id.clone(),
+            object_type: "OnRouteUpdate".to_string(),
+            service_name: "webapi".to_string(),
+            correlation_id: Uuid::new_v4().to_hyphenated().to_string(),
+            event_body: r#"[{"services":["webapi"]}]"#.to_string(),
+            error: "connection refused".to_string(),
+            added_at: Utc::now(),
+        })
+        .await
+        .unwrap();
+    let resp = call_service_with_token(
+        Method::GET,
+        &addr,
+        path::ADMIN,
+        "",
+        Some(token.clone()),
+        Body::empty(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let html = String::from_utf8(
+        hyper::body::to_bytes(resp.into_body())
+            .await
+            .unwrap()
+            .to_vec(),
+    )
+    .unwrap();
+    assert!(html.contains("webapi"));
+    assert!(html.contains("GetCar"));
+    assert!(html.contains(&id));
+    //health of service not called yet is not taken from stored state
+    assert!(html.contains("<td>Alive</td><td>not called</td>"));
+    //form of unknown origin is rejected
+    let resp = post_admin_form(
+        &addr,
+        path::ADMIN_EVENT_REPLAY,
+        &token,
+        None,
+        &format!("id={}", id),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = post_admin_form(
+        &addr,
+        path::ADMIN_EVENT_REPLAY,
+        &token,
+        Some((header::ORIGIN, "http://example.com")),
+        &format!("id={}", id),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    assert_eq!(dc.dead_letter.get(None).await.unwrap().len(), 1);
+    let origin = format!("http://{}", addr);
+    let resp = post_admin_form(
+        &addr,
+        path::ADMIN_EVENT_REPLAY,
+        &token,
+        Some((header::ORIGIN, &origin)),
+        &format!("id={}", id),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::SEE_OTHER);
+    assert!(dc.dead_letter.get(None).await.unwrap().is_empty());
+    //unknown dead letter is reported on dashboard
+    let referer = format!("http://{}{}", addr, path::ADMIN);
+    let resp = post_admin_form(
+        &addr,
+        path::ADMIN_EVENT_REPLAY,
+        &token,
+        Some((header::REFERER, &referer)),
+        &format!("id={}", id),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::SEE_OTHER);
+    let location = resp.headers()[header::LOCATION].to_str().unwrap();
+    assert!(location.starts_with(&format!("{}?message=", path::ADMIN)));
+}
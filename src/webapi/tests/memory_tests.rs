@@ -0,0 +1,272 @@
+use super::super::{
+    commands, connectors, entities::car, entities::dead_letter, entities::route, errors, events,
+    handlers, replies,
+};
+use super::get_memory_data_connector;
+use chrono::{Duration, Utc};
+use std::collections::HashMap;
+
+fn get_route(service_name: &str) -> route::Route {
+    let mut command_path = HashMap::<String, String>::new();
+    command_path.insert("http".to_string(), "/api/request".to_string());
+    let mut path = HashMap::<String, route::ServicePath>::new();
+    path.insert(
+        "http".to_string(),
+        route::ServicePath {
+            service_name: None,
+            proto: None,
+            helth: "/api/helth".to_string(),
+            schema: "/api/schema".to_string(),
+            reply_to: "/api/reply".to_string(),
+            state: "/api/state".to_string(),
+            error: "/api/error".to_string(),
+            request: None,
+            event: None,
+        },
+    );
+    route::Route {
+        service_name: Some(service_name.to_string()),
+        description: "test service".to_string(),
+        priority: 1,
+        command: vec![route::ServiceCommand {
+            service_name: None,
+            priority: None,
+            object_type: "GetCar".to_string(),
+            description: "get car".to_string(),
+            reply_type: "GetCarReply".to_string(),
+            exec_mode: "Sync".to_string(),
+            state: None,
+            path: Some(command_path),
+        }],
+        event: vec![],
+        subscription: vec![],
+        path: Some(path),
+    }
+}
+
+async fn add_route(
+    dc: &connectors::DataConnector,
+    item: route::Route,
+    lease: Option<u32>,
+) -> (
+    replies::common::AddStrIdsReply,
+    Option<Vec<events::route::OnRouteUpdate>>,
+) {
+    handlers::route::add(
+        dc,
+        commands::route::AddRoute {
+            items: vec![item],
+            lease: lease,
+        },
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_usr_get_ok() {
+    let dc = get_memory_data_connector().await;
+    let items = dc.usr.get(None).await.unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].usr_name, "test");
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_car_add_change_remove_ok() {
+    let dc = get_memory_data_connector().await;
+    let (result, ids) = dc
+        .car
+        .add(vec![car::Car {
+            id: None,
+            car_name: "car1".to_string(),
+        }])
+        .await
+        .unwrap();
+    assert_eq!(result, errors::ErrorCode::ReplyOk);
+    let id = ids.unwrap()[0];
+    let result = dc
+        .car
+        .change(vec![car::Car {
+            id: Some(id),
+            car_name: "car2".to_string(),
+        }])
+        .await
+        .unwrap();
+    assert_eq!(result, errors::ErrorCode::ReplyOk);
+    let items = dc.car.get(Some(vec![id])).await.unwrap();
+    assert_eq!(items[0].car_name, "car2");
+    let result = dc.car.remove(vec![id]).await.unwrap();
+    assert_eq!(result, errors::ErrorCode::ReplyOk);
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_car_add_duplicate_err() {
+    let dc = get_memory_data_connector().await;
+    let items = vec![car::Car {
+        id: None,
+        car_name: "car1".to_string(),
+    }];
+    let (result, _) = dc.car.add(items.clone()).await.unwrap();
+    assert_eq!(result, errors::ErrorCode::ReplyOk);
+    let (result, _) = dc.car.add(items).await.unwrap();
+    assert_eq!(result, errors::ErrorCode::DatabaseError);
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_car_remove_err() {
+    let dc = get_memory_data_connector().await;
+    let result = dc.car.remove(vec![1]).await.unwrap();
+    assert_eq!(result, errors::ErrorCode::NotFoundError);
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_route_add_remove_ok() {
+    let dc = get_memory_data_connector().await;
+    let (reply, events) = handlers::route::add(
+        &dc,
+        commands::route::AddRoute {
+            items: vec![get_route("svc1")],
+            lease: None,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(reply.error_code, errors::ErrorCode::ReplyOk);
+    assert!(events.is_some());
+    let commands = dc.route.get_command(None).await.unwrap();
+    assert_eq!(commands.len(), 1);
+    assert_eq!(commands[0].service_name, Some("svc1".to_string()));
+    let paths = dc.route.get_service_path(None).await.unwrap();
+    assert_eq!(paths.len(), 1);
+    let (reply, _) = handlers::route::remove(
+        &dc,
+        commands::route::RemoveRoute {
+            services: vec!["svc1".to_string()],
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(reply.error_code, errors::ErrorCode::ReplyOk);
+    assert_eq!(dc.route.get(None).await.unwrap().len(), 0);
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_route_upsert_ok() {
+    let dc = get_memory_data_connector().await;
+    let (reply, events) = add_route(&dc, get_route("svc1"), None).await;
+    assert_eq!(reply.error_code, errors::ErrorCode::ReplyOk);
+    assert_eq!(events.unwrap()[0].services, vec!["svc1".to_string()]);
+    //restart without shutdown registers same route again
+    let (reply, events) = add_route(&dc, get_route("svc1"), None).await;
+    assert_eq!(reply.error_code, errors::ErrorCode::ReplyOk);
+    assert_eq!(reply.ids, Some(vec!["svc1".to_string()]));
+    assert!(events.is_none());
+    let mut item = get_route("svc1");
+    item.description = "changed".to_string();
+    item.command.clear();
+    let (reply, events) = add_route(&dc, item, None).await;
+    assert_eq!(reply.error_code, errors::ErrorCode::ReplyOk);
+    assert_eq!(events.unwrap()[0].services, vec!["svc1".to_string()]);
+    let routes = dc.route.get(None).await.unwrap();
+    assert_eq!(routes.len(), 1);
+    assert_eq!(routes[0].description, "changed");
+    assert_eq!(dc.route.get_command(None).await.unwrap().len(), 0);
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_route_lease_ok() {
+    let dc = get_memory_data_connector().await;
+    let (reply, _) = add_route(&dc, get_route("svc1"), Some(60)).await;
+    assert_eq!(reply.error_code, errors::ErrorCode::ReplyOk);
+    assert_eq!(dc.route.expire(Utc::now()).await.unwrap().len(), 0);
+    let reply = handlers::route::renew(
+        &dc,
+        commands::route::RenewRoute {
+            services: vec!["svc1".to_string()],
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(reply.error_code, errors::ErrorCode::ReplyOk);
+    let reply = handlers::route::renew(
+        &dc,
+        commands::route::RenewRoute {
+            services: vec!["svc2".to_string()],
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(reply.error_code, errors::ErrorCode::NotFoundError);
+    assert!(handlers::route::expire(&dc).await.unwrap().is_none());
+    //lapsed lease, re-registration replaces ttl
+    add_route(&dc, get_route("svc1"), Some(0)).await;
+    let events = handlers::route::expire(&dc).await.unwrap().unwrap();
+    assert_eq!(events[0].services, vec!["svc1".to_string()]);
+    assert_eq!(dc.route.get(None).await.unwrap().len(), 0);
+    let history = dc.route.get_history(None).await.unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].service_name, "svc1");
+    assert_eq!(
+        history[0].state,
+        route::ServiceState::Unavailable.to_string()
+    );
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_route_lease_renew_ok() {
+    let dc = get_memory_data_connector().await;
+    add_route(&dc, get_route("svc1"), Some(0)).await;
+    //expiry started before renewal, renewed route is kept
+    let at = Utc::now();
+    assert_eq!(
+        dc.route.renew(vec!["svc1".to_string()]).await.unwrap(),
+        errors::ErrorCode::ReplyOk
+    );
+    assert_eq!(dc.route.expire(at).await.unwrap().len(), 0);
+    assert_eq!(dc.route.get(None).await.unwrap().len(), 1);
+    assert_eq!(dc.route.get_history(None).await.unwrap().len(), 0);
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_route_remove_err() {
+    let dc = get_memory_data_connector().await;
+    let (reply, events) = handlers::route::remove(
+        &dc,
+        commands::route::RemoveRoute {
+            services: vec!["svc1".to_string()],
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(reply.error_code, errors::ErrorCode::NotFoundError);
+    assert!(events.is_none());
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_dead_letter_page_ok() {
+    let dc = get_memory_data_connector().await;
+    let now = Utc::now();
+    for i in 0..3 {
+        dc.dead_letter
+            .add(dead_letter::DeadLetterEvent {
+                id: i.to_string(),
+                object_type: "OnCarAdded".to_string(),
+                service_name: "svc1".to_string(),
+                correlation_id: i.to_string(),
+                event_body: "[]".to_string(),
+                error: "connection refused".to_string(),
+                added_at: now + Duration::seconds(i),
+            })
+            .await
+            .unwrap();
+    }
+    //newest first
+    let items = dc.dead_letter.get_page(0, 2).await.unwrap();
+    assert_eq!(
+        items.iter().map(|e| e.id.as_str()).collect::<Vec<&str>>(),
+        vec!["2", "1"]
+    );
+    let items = dc.dead_letter.get_page(2, 2).await.unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].id, "0");
+}
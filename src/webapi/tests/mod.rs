@@ -1,8 +1,35 @@
+use super::{connectors, settings};
+use std::collections::HashMap;
+
 #[cfg(test)]
-pub mod fakes;
+mod memory_tests;
 
 #[cfg(test)]
 mod unit_tests;
 
 #[cfg(test)]
 mod integration_tests;
+
+//sample commands of binary, handled by test service
+#[cfg(test)]
+#[path = "../../car.rs"]
+mod car;
+
+pub const TEST_USR_NAME: &str = "test";
+pub const TEST_USR_PASSWORD: &str = "1234567890";
+
+pub async fn get_memory_data_connector() -> connectors::DataConnector {
+    let db = settings::Database {
+        connection_string: connectors::MEMORY_CONNECTION_STRING.to_string(),
+    };
+    let mut server = HashMap::<String, String>::new();
+    server.insert(TEST_USR_NAME.to_string(), TEST_USR_PASSWORD.to_string());
+    let authentication = settings::Authentication {
+        server: server,
+        client: vec![],
+        admin: None,
+    };
+    connectors::DataConnector::new(None, &db, &authentication)
+        .await
+        .expect("error while initialize data connector")
+}
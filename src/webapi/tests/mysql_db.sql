@@ -7,20 +7,91 @@ CREATE TABLE `car` (
 CREATE UNIQUE INDEX  car_car_name_IDX USING BTREE ON `car` (car_name);
 /
 CREATE TABLE `error` (
-  `id` int NOT NULL,
+  `error_code` varchar(100) NOT NULL,
+  `lang` varchar(10) NOT NULL,
   `error_name` varchar(100) NOT NULL,
-  CONSTRAINT error_pk PRIMARY KEY (id)
+  CONSTRAINT error_pk PRIMARY KEY (error_code, lang)
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_0900_ai_ci;
 /
-CREATE UNIQUE INDEX error_error_name_IDX USING BTREE ON `error` (error_name);
+INSERT INTO `error`
+(error_code, lang, error_name)
+VALUES('ReplyOk', 'en', 'Ok');
+/
+INSERT INTO `error`
+(error_code, lang, error_name)
+VALUES('ReplyOk', 'ru', 'Успешно');
+/
+INSERT INTO `error`
+(error_code, lang, error_name)
+VALUES('AsyncOk', 'en', 'Async command registered');
+/
+INSERT INTO `error`
+(error_code, lang, error_name)
+VALUES('AsyncOk', 'ru', 'Асинхронная команда зарегистрирована');
+/
+INSERT INTO `error`
+(error_code, lang, error_name)
+VALUES('UnsupportedExecModeError', 'en', 'Unsupported exec mode error');
+/
+INSERT INTO `error`
+(error_code, lang, error_name)
+VALUES('UnsupportedExecModeError', 'ru', 'Неподдерживаемый режим выполнения');
+/
+INSERT INTO `error`
+(error_code, lang, error_name)
+VALUES('TooManyRequestsError', 'en', 'Too many requests error');
+/
+INSERT INTO `error`
+(error_code, lang, error_name)
+VALUES('TooManyRequestsError', 'ru', 'Слишком много запросов');
+/
+INSERT INTO `error`
+(error_code, lang, error_name)
+VALUES('TimeoutExpiredError', 'en', 'Timeout expired error');
+/
+INSERT INTO `error`
+(error_code, lang, error_name)
+VALUES('TimeoutExpiredError', 'ru', 'Истекло время ожидания');
+/
+INSERT INTO `error`
+(error_code, lang, error_name)
+VALUES('DatabaseError', 'en', 'Database error');
+/
+INSERT INTO `error`
+(error_code, lang, error_name)
+VALUES('DatabaseError', 'ru', 'Ошибка базы данных');
+/
+INSERT INTO `error`
+(error_code, lang, error_name)
+VALUES('NotFoundError', 'en', 'Some items with specified id is not found');
+/
+INSERT INTO `error`
+(error_code, lang, error_name)
+VALUES('NotFoundError', 'ru', 'Некоторые элементы с указанным id не найдены');
+/
+INSERT INTO `error`
+(error_code, lang, error_name)
+VALUES('BadRequestError', 'en', 'Bad request error');
+/
+INSERT INTO `error`
+(error_code, lang, error_name)
+VALUES('BadRequestError', 'ru', 'Некорректный запрос');
+/
+INSERT INTO `error`
+(error_code, lang, error_name)
+VALUES('RemoteServiceError', 'en', 'Remote service error');
 /
 INSERT INTO `error`
-(id, error_name)
-VALUES(-1, 'Database error');
+(error_code, lang, error_name)
+VALUES('RemoteServiceError', 'ru', 'Ошибка удаленного сервиса');
 /
 INSERT INTO `error`
-(id, error_name)
-VALUES(-100, 'Some items with specified id is not found');
+(error_code, lang, error_name)
+VALUES('InternalError', 'en', 'Internal error');
+/
+INSERT INTO `error`
+(error_code, lang, error_name)
+VALUES('InternalError', 'ru', 'Внутренняя ошибка');
 /
 CREATE TABLE `usr` (
   `id` int NOT NULL AUTO_INCREMENT,
@@ -48,4 +119,58 @@ CREATE TABLE `subscription` (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_0900_ai_ci;
 /
 CREATE UNIQUE INDEX subs_obj_env_cb_idx USING BTREE ON `subscription` (object_name, event_name, call_back);
+/
+CREATE TABLE `saga` (
+  `id` varchar(100) NOT NULL,
+  `saga_type` varchar(100) NOT NULL,
+  `correlation_id` varchar(100) NOT NULL,
+  `state` varchar(100) NOT NULL,
+  `step` int NOT NULL,
+  `data` text NOT NULL,
+  `owner` varchar(100) NOT NULL DEFAULT '',
+  `lease_expires_at` timestamp NOT NULL DEFAULT '1970-01-02 00:00:00', -- lowest timestamp in any time zone
+  `added_at` timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  `state_changed_at` timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT saga_pk PRIMARY KEY (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_0900_ai_ci;
+/
+CREATE INDEX saga_state_IDX USING BTREE ON `saga` (state, added_at);
+/
+CREATE INDEX saga_owner_IDX USING BTREE ON `saga` (`owner`, state);
+/
+CREATE TABLE `idempotent_reply` (
+  `caller` varchar(100) NOT NULL,
+  `object_type` varchar(100) NOT NULL,
+  `correlation_id` varchar(100) NOT NULL,
+  `state` varchar(100) NOT NULL,
+  `status` int NOT NULL,
+  `reply_body` text NOT NULL,
+  `added_at` timestamp NOT NULL,
+  CONSTRAINT idempotent_reply_pk PRIMARY KEY (`caller`, `object_type`, `correlation_id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_0900_ai_ci;
+/
+CREATE INDEX idempotent_reply_added_at_IDX USING BTREE ON `idempotent_reply` (added_at);
+/
+CREATE TABLE `quota_usage` (
+  `caller` varchar(100) NOT NULL,
+  `object_type` varchar(100) NOT NULL,
+  `day` varchar(10) NOT NULL,
+  `usage_count` bigint NOT NULL,
+  CONSTRAINT quota_usage_pk PRIMARY KEY (`caller`, `object_type`, `day`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_0900_ai_ci;
+/
+CREATE INDEX quota_usage_day_IDX USING BTREE ON `quota_usage` (`day`);
+/
+CREATE TABLE `dead_letter_event` (
+  `id` varchar(100) NOT NULL,
+  `object_type` varchar(100) NOT NULL,
+  `service_name` varchar(100) NOT NULL,
+  `correlation_id` varchar(100) NOT NULL,
+  `event_body` text NOT NULL,
+  `error` text NOT NULL,
+  `added_at` timestamp NOT NULL,
+  CONSTRAINT dead_letter_event_pk PRIMARY KEY (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_0900_ai_ci;
+/
+CREATE INDEX dead_letter_event_added_at_IDX USING BTREE ON `dead_letter_event` (added_at);
 /
\ No newline at end of file
@@ -13,6 +13,93 @@ CREATE TABLE car (
 /
 CREATE UNIQUE INDEX car_car_name_idx ON car USING btree (car_name);
 /
+CREATE TABLE error (
+	error_code text NOT NULL,
+	lang text NOT NULL,
+	error_name text NOT NULL,
+	CONSTRAINT error_pk PRIMARY KEY (error_code, lang)
+);
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('ReplyOk', 'en', 'Ok');
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('ReplyOk', 'ru', 'Успешно');
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('AsyncOk', 'en', 'Async command registered');
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('AsyncOk', 'ru', 'Асинхронная команда зарегистрирована');
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('UnsupportedExecModeError', 'en', 'Unsupported exec mode error');
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('UnsupportedExecModeError', 'ru', 'Неподдерживаемый режим выполнения');
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('TooManyRequestsError', 'en', 'Too many requests error');
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('TooManyRequestsError', 'ru', 'Слишком много запросов');
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('TimeoutExpiredError', 'en', 'Timeout expired error');
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('TimeoutExpiredError', 'ru', 'Истекло время ожидания');
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('DatabaseError', 'en', 'Database error');
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('DatabaseError', 'ru', 'Ошибка базы данных');
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('NotFoundError', 'en', 'Some items with specified id is not found');
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('NotFoundError', 'ru', 'Некоторые элементы с указанным id не найдены');
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('BadRequestError', 'en', 'Bad request error');
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('BadRequestError', 'ru', 'Некорректный запрос');
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('RemoteServiceError', 'en', 'Remote service error');
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('RemoteServiceError', 'ru', 'Ошибка удаленного сервиса');
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('InternalError', 'en', 'Internal error');
+/
+INSERT INTO error
+(error_code, lang, error_name)
+VALUES('InternalError', 'ru', 'Внутренняя ошибка');
+/
 CREATE TABLE usr (
 	id int4 NOT NULL GENERATED ALWAYS AS IDENTITY,
 	usr_name text UNIQUE NOT NULL,
@@ -90,14 +177,72 @@ CREATE TABLE received_async_command_state_history (
 CREATE INDEX received_async_command_state_history_added_at_idx ON received_async_command_state_history 
 	USING btree (command_id, added_at);
 /
+CREATE TABLE saga (
+	id text NOT NULL,
+	saga_type text NOT NULL,
+	correlation_id text NOT NULL,
+	state text NOT NULL,
+	step int4 NOT NULL,
+	data text NOT NULL,
+	"owner" text NOT NULL DEFAULT '',
+	lease_expires_at TIMESTAMPTZ NOT NULL DEFAULT '1970-01-01 00:00:00+00',
+	added_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+	state_changed_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+	CONSTRAINT saga_pk PRIMARY KEY (id)
+);
+/
+CREATE INDEX saga_state_idx ON saga USING btree (state, added_at);
+/
+CREATE INDEX saga_owner_idx ON saga USING btree ("owner", state);
+/
+CREATE TABLE idempotent_reply (
+	caller text NOT NULL,
+	object_type text NOT NULL,
+	correlation_id text NOT NULL,
+	state text NOT NULL,
+	status int4 NOT NULL,
+	reply_body text NOT NULL,
+	added_at TIMESTAMPTZ NOT NULL,
+	CONSTRAINT idempotent_reply_pk PRIMARY KEY (caller, object_type, correlation_id)
+);
+/
+CREATE INDEX idempotent_reply_added_at_idx ON idempotent_reply USING btree (added_at);
+/
+CREATE TABLE quota_usage (
+	caller text NOT NULL,
+	object_type text NOT NULL,
+	"day" text NOT NULL,
+	usage_count int8 NOT NULL,
+	CONSTRAINT quota_usage_pk PRIMARY KEY (caller, object_type, "day")
+);
+/
+CREATE INDEX quota_usage_day_idx ON quota_usage USING btree ("day");
+/
+CREATE TABLE dead_letter_event (
+	id text NOT NULL,
+	object_type text NOT NULL,
+	service_name text NOT NULL,
+	correlation_id text NOT NULL,
+	event_body text NOT NULL,
+	error text NOT NULL,
+	added_at TIMESTAMPTZ NOT NULL,
+	CONSTRAINT dead_letter_event_pk PRIMARY KEY (id)
+);
+/
+CREATE INDEX dead_letter_event_added_at_idx ON dead_letter_event USING btree (added_at);
+/
 CREATE table "service" (
 	"name" text NOT NULL,
 	"description" text NOT NULL,
 	"priority" int4 NOT NULL,
 	added_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+	lease int4 NULL,
+	lease_expires_at TIMESTAMPTZ NULL,
 	CONSTRAINT service_pk PRIMARY KEY ("name")
 );
 /
+CREATE INDEX service_lease_expires_at_idx ON service USING btree (lease_expires_at);
+/
 CREATE table service_path (
 	"service_name" text NOT NULL,
 	proto text NOT NULL,
@@ -165,6 +310,15 @@ CREATE table service_subscription_path (
 		REFERENCES webapi.service_subscription("service_name", object_type)
 );
 /
+CREATE table service_history (
+	"service_name" text NOT NULL,
+	"state" text NOT NULL,
+	"description" text NOT NULL,
+	added_at TIMESTAMPTZ NOT NULL
+);
+/
+CREATE INDEX service_history_service_name_idx ON service_history USING btree ("service_name", added_at);
+/
 -- VIEWS
 CREATE OR REPLACE VIEW v_sended_async_command
 AS SELECT id,
@@ -1,39 +1,701 @@
-
-/*
-use super::super::{handlers::*, errors, connectors};
+use super::super::{
+    breaker, commands, config, connectors, context, entities, errors, handlers::*, idempotency,
+    limiter, logger, metrics, openapi, providers, registry, schema, settings, validator, workers,
+};
+use super::get_memory_data_connector;
+use hyper::StatusCode;
+use std::collections::HashMap;
+use strum::IntoEnumIterator;
 
 #[tokio::test(threaded_scheduler)]
 async fn test_signin_ok() {
-    let dc = connectors::DataConnector::new(None, None, None).await.unwrap();
+    let dc = get_memory_data_connector().await;
     let reply = usr::signin(&dc).await.unwrap();
     assert_eq!(reply.error_code, errors::ErrorCode::ReplyOk);
 }
 
 #[tokio::test(threaded_scheduler)]
 async fn test_signin_err() {
-    let dc = connectors::DataConnector::new(None, None, None).await.unwrap();
+    let dc = get_memory_data_connector().await;
     let reply = usr::signin(&dc).await.unwrap();
     assert_ne!(reply.error_code, errors::ErrorCode::DatabaseError);
 }
 
 #[tokio::test(threaded_scheduler)]
 async fn test_signup_ok() {
-    let dc = connectors::DataConnector::new(None, None, None).await.unwrap();
-    let reply = usr::signin(&dc).await.unwrap();
+    let dc = get_memory_data_connector().await;
+    let reply = usr::signup(&dc).await.unwrap();
     assert_eq!(reply.error_code, errors::ErrorCode::ReplyOk);
 }
 
 #[tokio::test(threaded_scheduler)]
 async fn test_signup_err() {
-    let dc = connectors::DataConnector::new(None, None, None).await.unwrap();
-    let reply = usr::signin(&dc).await.unwrap();
+    let dc = get_memory_data_connector().await;
+    let reply = usr::signup(&dc).await.unwrap();
     assert_ne!(reply.error_code, errors::ErrorCode::DatabaseError);
 }
 
 #[tokio::test(threaded_scheduler)]
 async fn test_car_get_ok() {
-    let dc = connectors::DataConnector::new(None, None, None).await.unwrap();
-    let reply = car::get(&dc, None).await.unwrap();
-    assert_eq!(reply.len(), 1);
+    let dc = get_memory_data_connector().await;
+    let reply = car::get(
+        &dc,
+        commands::car::GetCar {
+            filter: None,
+            ids: None,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(reply.error_code, errors::ErrorCode::ReplyOk);
+    assert_eq!(reply.items.unwrap().len(), 0);
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_error_ok() {
+    let dc = get_memory_data_connector().await;
+    let reply = route::get_error(&dc, &errors::ErrorCode::DatabaseError.to_string(), None).unwrap();
+    assert_eq!(
+        reply.error_code,
+        errors::ErrorCode::DatabaseError.to_string()
+    );
+    assert_eq!(reply.lang, connectors::DEFAULT_LANG);
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_error_lang_ok() {
+    let dc = get_memory_data_connector().await;
+    let ec = errors::ErrorCode::DatabaseError.to_string();
+    let reply = route::get_error(&dc, &ec, Some("ru")).unwrap();
+    assert_eq!(reply.lang, "ru");
+    assert_eq!(reply.error_name, "Ошибка базы данных");
+    //unknown language fallback to default
+    let reply = route::get_error(&dc, &ec, Some("fake")).unwrap();
+    assert_eq!(reply.lang, connectors::DEFAULT_LANG);
+    assert_eq!(reply.error_name, "Database error");
+    let items = route::get_errors(&dc, Some("ru")).unwrap();
+    assert_eq!(items.len(), errors::ErrorCode::iter().count());
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_error_err() {
+    let dc = get_memory_data_connector().await;
+    assert!(route::get_error(&dc, "FakeError", None).is_err());
+}
+
+#[test]
+fn test_error_name_fallback_ok() {
+    let error = HashMap::<String, String>::new();
+    assert_eq!(
+        errors::get_error_name(&error, &errors::ErrorCode::NotFoundError),
+        errors::ErrorCode::NotFoundError.to_string()
+    );
+}
+
+#[test]
+fn test_error_catalog_coverage_ok() {
+    let mut catalog = HashMap::<String, HashMap<String, String>>::new();
+    for item in providers::get_default_errors() {
+        catalog
+            .entry(item.lang)
+            .or_insert_with(HashMap::<String, String>::new)
+            .insert(item.error_code, item.error_name);
+    }
+    assert!(connectors::DataConnector::check_error_catalog(&catalog).is_ok());
+    catalog
+        .get_mut(connectors::DEFAULT_LANG)
+        .unwrap()
+        .remove(&errors::ErrorCode::InternalError.to_string());
+    assert!(connectors::DataConnector::check_error_catalog(&catalog).is_err());
+    //missing names in other languages are only warned
+    catalog.remove("ru");
+    catalog.get_mut(connectors::DEFAULT_LANG).unwrap().insert(
+        errors::ErrorCode::InternalError.to_string(),
+        "Internal error".to_string(),
+    );
+    assert!(connectors::DataConnector::check_error_catalog(&catalog).is_ok());
+}
+
+#[test]
+fn test_error_status_ok() {
+    let e = errors::Error::with_context(errors::ErrorKind::UnknownCommand, "FakeCommand");
+    assert_eq!(e.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(e.error_code(), errors::ErrorCode::BadRequestError);
+    assert_eq!(e.to_string(), "unknown command error: FakeCommand");
+    let reply = e.to_reply(&HashMap::<String, String>::new());
+    assert_eq!(reply.error_name.unwrap(), e.to_string());
+    let e = errors::Error::new(errors::ErrorKind::TooManyRequests);
+    assert_eq!(e.status(), StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[test]
+fn test_error_from_boxed_ok() {
+    let e: Box<dyn std::error::Error> =
+        errors::Error::new(errors::ErrorKind::AsyncCommandNotFound).into();
+    assert_eq!(
+        errors::from_boxed(&e).kind(),
+        errors::ErrorKind::AsyncCommandNotFound
+    );
+    let e: Box<dyn std::error::Error> = "fake".into();
+    assert_eq!(
+        errors::from_boxed(&e).status(),
+        StatusCode::INTERNAL_SERVER_ERROR
+    );
+}
+
+//every schema reference of generated document must be resolvable
+fn check_openapi_ref(spec: &serde_json::Value, value: &serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (k, v) in map {
+                if k == "$ref" {
+                    let pointer = v.as_str().unwrap().trim_start_matches('#');
+                    assert!(spec.pointer(pointer).is_some(), "{}", pointer);
+                } else {
+                    check_openapi_ref(spec, v);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                check_openapi_ref(spec, item);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[test]
+fn test_openapi_ref_ok() {
+    let spec = openapi::make_openapi(&schema::make_schema());
+    check_openapi_ref(&spec, &spec);
+    assert!(!spec.to_string().contains("#/definitions/"));
+}
+
+#[test]
+fn test_schema_registry_ok() {
+    let schema = schema::make_schema();
+    let mut app_settings: settings::AppSettings =
+        serde_json::from_str(&std::fs::read_to_string("appsettings.test.json").unwrap()).unwrap();
+    registry::add_routes(&mut app_settings.service);
+    //every routed command, reply and event is registered
+    for route in app_settings.service.values() {
+        for c in &route.command {
+            assert!(
+                schema.contains_key(c.object_type.as_str()),
+                "{}",
+                c.object_type
+            );
+            assert!(
+                schema.contains_key(c.reply_type.as_str()),
+                "{}",
+                c.reply_type
+            );
+        }
+        for e in &route.event {
+            assert!(
+                schema.contains_key(e.object_type.as_str()),
+                "{}",
+                e.object_type
+            );
+        }
+    }
+    let items = schema::get_schema_list(&schema);
+    assert_eq!(items.len(), schema.len());
+    assert!(items.iter().all(|s| s.version == 1));
+}
+
+#[test]
+fn test_registry_ok() {
+    let items = registry::get_declarations();
+    let get_car = items.iter().find(|d| d.object_type == "GetCar").unwrap();
+    match get_car.kind {
+        registry::Kind::Command { service, reply, .. } => {
+            assert_eq!(service, Some("webapi"));
+            assert_eq!(reply, "GetCarReply");
+        }
+        _ => panic!("GetCar is not a command"),
+    }
+    let mut service = HashMap::<String, entities::route::Route>::new();
+    registry::add_routes(&mut service);
+    let webapi = service.get("webapi").unwrap();
+    let reserve = webapi
+        .command
+        .iter()
+        .find(|c| c.object_type == "ReserveCar")
+        .unwrap();
+    assert_eq!(
+        reserve.path.as_ref().unwrap().get("http").unwrap(),
+        "http://{host}/car/reserve"
+    );
+    assert!(webapi
+        .subscription
+        .iter()
+        .any(|s| s.object_type == "OnRouteUpdate"));
+    //not routed command has schema only
+    assert!(!service
+        .values()
+        .any(|r| r.command.iter().any(|c| c.object_type == "GetService")));
+    //settings win over declaration
+    let mut service = HashMap::<String, entities::route::Route>::new();
+    registry::add_routes(&mut service);
+    service
+        .get_mut("webapi")
+        .unwrap()
+        .command
+        .iter_mut()
+        .for_each(|c| c.exec_mode = "Async".to_string());
+    registry::add_routes(&mut service);
+    let webapi = service.get("webapi").unwrap();
+    assert_eq!(webapi.command.len(), 6);
+    assert!(webapi.command.iter().all(|c| c.exec_mode == "Async"));
+}
+
+#[test]
+fn test_validator_ok() {
+    let schema = schema::make_schema();
+    let root = schema.get("GetCar").unwrap();
+    let value = serde_json::json!({"filter": null, "ids": [1, 2]});
+    assert_eq!(validator::validate(root, &value).len(), 0);
+    let value = serde_json::json!({"ids": ["1", 2.5]});
+    let violations = validator::validate(root, &value);
+    assert_eq!(violations.len(), 2);
+    assert_eq!(violations[0].path, "/ids/0");
+    assert_eq!(violations[1].path, "/ids/1");
+    //nested definitions
+    let root = schema.get("AddRoute").unwrap();
+    let value = serde_json::json!({"items": [{"description": "", "priority": 0}]});
+    let violations = validator::validate(root, &value);
+    assert!(violations.iter().any(|v| v.path == "/items/0/command"));
+    let root = schema.get("OnRouteUpdate").unwrap();
+    let value = serde_json::json!([{"services": ["webapi"]}]);
+    assert_eq!(validator::validate_items(root, &value).len(), 0);
+    assert_eq!(
+        validator::validate_items(root, &serde_json::json!({})).len(),
+        1
+    );
+}
+
+#[test]
+fn test_schema_compatibility() {
+    let schema = schema::make_schema();
+    let old = serde_json::to_value(schema.get("AddRoute").unwrap()).unwrap();
+    assert_eq!(schema::get_incompatibilities(&old, &old).len(), 0);
+    let mut new = old.clone();
+    //nested definition loses required value
+    new["definitions"]["Route"]["required"] = serde_json::json!(["command", "event"]);
+    new["definitions"]["Route"]["properties"]["priority"] = serde_json::json!({"type": "string"});
+    let issues = schema::get_incompatibilities(&old, &new);
+    assert!(issues.contains(&"/items/items/description is not required anymore".to_string()));
+    assert!(issues
+        .iter()
+        .any(|i| i.starts_with("/items/items/priority type changed")));
+    assert!(issues
+        .iter()
+        .any(|i| i.starts_with("/items/items/subscription")));
+}
+
+#[test]
+fn test_traceparent_ok() {
+    let parent = context::Span::new("parent", context::SpanKind::Server);
+    let traceparent = parent.to_traceparent();
+    assert_eq!(traceparent.len(), 55);
+    let span =
+        context::Span::from_traceparent(&traceparent, "child", context::SpanKind::Server).unwrap();
+    assert_eq!(span.trace_id, parent.trace_id);
+    assert_eq!(span.parent_span_id, Some(parent.span_id.clone()));
+    assert_ne!(span.span_id, parent.span_id);
+    let child = span.child("call", context::SpanKind::Client);
+    assert_eq!(child.trace_id, parent.trace_id);
+    assert_eq!(child.parent_span_id, Some(span.span_id));
+    for bad in &[
+        "",
+        "01-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01",
+        "00-00000000000000000000000000000000-b7ad6b7169203331-01",
+        "00-0AF7651916CD43DD8448EB211C80319C-b7ad6b7169203331-01",
+        "00-0af7651916cd43dd8448eb211c80319c-b7ad6b71-01",
+    ] {
+        assert!(context::Span::from_traceparent(bad, "bad", context::SpanKind::Server).is_none());
+    }
+}
+
+#[test]
+fn test_audit_record_ok() {
+    assert!(logger::is_audited("AddRoute"));
+    assert!(logger::is_audited("RemoveCar"));
+    assert!(!logger::is_audited("GetCar"));
+    let line = serde_json::to_value(&logger::AuditRecord {
+        time: chrono::Utc::now(),
+        object_type: "AddCar",
+        correlation_id: Some("1"),
+        caller: None,
+        usr: Some("test"),
+        status: 200,
+    })
+    .unwrap();
+    assert_eq!(line["object_type"], "AddCar");
+    assert_eq!(line["usr"], "test");
+    assert!(line["caller"].is_null());
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_worker_state_ok() {
+    let (mut sender, mut receiver, state) = workers::channel("test", 2);
+    assert!(!state.is_running());
+    sender.send(workers::SignalCode::JobNo1).await.unwrap();
+    assert!(!state.is_saturated());
+    sender.send(workers::SignalCode::JobNo1).await.unwrap();
+    assert_eq!(state.pending(), 2);
+    assert!(state.is_saturated());
+    drop(receiver);
+    //failed send is not counted as pending
+    assert!(sender.send(workers::SignalCode::Exit).await.is_err());
+    assert_eq!(state.pending(), 2);
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_worker_drain_ok() {
+    let (mut sender, receiver, state) = workers::channel("test", 5);
+    sender.send(workers::SignalCode::JobNo1).await.unwrap();
+    sender.send(workers::SignalCode::Exit).await.unwrap();
+    let metrics = std::sync::Arc::new(metrics::Metrics::new());
+    //queued signals are handled, then worker exits without cancel flag
+    workers::command_executor_worker(
+        std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)),
+        receiver,
+        metrics.clone(),
+        state.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(state.pending(), 0);
+    assert!(!state.is_running());
+    assert!(sender.send(workers::SignalCode::JobNo1).await.is_err());
+}
+
+fn get_test_settings_value() -> serde_json::Value {
+    serde_json::from_str(&std::fs::read_to_string("appsettings.test.json").unwrap()).unwrap()
+}
+
+#[test]
+fn test_config_layers_ok() {
+    let mut value = get_test_settings_value();
+    config::merge(
+        &mut value,
+        serde_json::json!({"validation": {"reply": false}, "shutdown": {"drain_timeout": 7}}),
+    );
+    assert_eq!(value["validation"]["request"], true);
+    assert_eq!(value["validation"]["reply"], false);
+    let overrides = config::get_env_overrides(
+        vec![
+            (
+                "MY_APP__shutdown__drain_timeout".to_string(),
+                "9".to_string(),
+            ),
+            ("PATH".to_string(), "/bin".to_string()),
+        ]
+        .into_iter(),
+    );
+    assert_eq!(
+        overrides,
+        vec![("shutdown.drain_timeout".to_string(), "9".to_string())]
+    );
+    for (k, v) in &overrides {
+        config::set_value(&mut value, k, v).unwrap();
+    }
+    config::set_value(&mut value, "tracing.service_name", "svc").unwrap();
+    //numeric password stays string
+    config::set_value(
+        &mut value,
+        "access.authentication.server.test",
+        "1234567890",
+    )
+    .unwrap();
+    config::set_value(&mut value, "lease", r#"json:{"ttl": 60, "heartbeat": 20}"#).unwrap();
+    assert!(config::set_value(&mut value, "lease", "json:{").is_err());
+    let config = config::parse(value, None, Some("4000")).unwrap();
+    assert_eq!(config.app_settings.shutdown.unwrap().drain_timeout, 9);
+    assert_eq!(config.app_settings.tracing.unwrap().service_name, "svc");
+    assert_eq!(
+        config.app_settings.access.authentication.server["test"],
+        "1234567890"
+    );
+    assert_eq!(config.app_settings.lease.unwrap().ttl, 60);
+    assert_eq!(config.host, "127.0.0.1:4000");
+    assert_eq!(
+        config.database.connection_string,
+        get_test_settings_value()["database"][config::DATABASE_KEY]["connection_string"]
+    );
+}
+
+#[test]
+fn test_config_errors_name_key() {
+    let mut value = get_test_settings_value();
+    config::set_value(&mut value, "shutdown.drain_timeout", "\"soon\"").unwrap();
+    let errors = config::parse(value, None, None).err().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].key, "shutdown.drain_timeout");
+    let mut value = get_test_settings_value();
+    config::set_value(&mut value, "shutdown.drain_timeout", "0").unwrap();
+    value["database"]
+        .as_object_mut()
+        .unwrap()
+        .remove(config::DATABASE_KEY);
+    let errors = config::parse(value, None, Some("port")).err().unwrap();
+    let keys: Vec<&str> = errors.iter().map(|e| e.key.as_str()).collect();
+    assert!(keys.contains(&"shutdown.drain_timeout"));
+    assert!(keys.contains(&format!("database.{}", config::DATABASE_KEY).as_str()));
+    assert!(keys.contains(&"PORT"));
+}
+
+#[test]
+fn test_config_secrets_ok() {
+    std::env::set_var("MY_TEST_SECRET", "s3cret");
+    let mut value = serde_json::json!({
+        "access": {"password": "${env:MY_TEST_SECRET}", "plain": "${not a secret}"},
+        "items": ["${env:MY_TEST_SECRET_UNSET}"]
+    });
+    let mut errors = Vec::<config::ConfigError>::new();
+    config::resolve_secrets(&mut value, "", &mut errors);
+    assert_eq!(value["access"]["password"], "s3cret");
+    assert_eq!(value["access"]["plain"], "${not a secret}");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].key, "items.0");
+}
+
+#[test]
+fn test_config_args_ok() {
+    let args: Vec<String> = vec!["--set", "a.b=1", "--check-config", "--env", "dev"]
+        .into_iter()
+        .map(|a| a.to_string())
+        .collect();
+    let options = config::Options::from_env_and_args(&args).unwrap();
+    assert!(options.check_config);
+    assert_eq!(options.env, Some("dev".to_string()));
+    assert_eq!(
+        options.overrides.last(),
+        Some(&("a.b".to_string(), "1".to_string()))
+    );
+    let args = vec!["--set".to_string()];
+    let errors = config::Options::from_env_and_args(&args).err().unwrap();
+    assert_eq!(errors[0].key, "--set");
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_idempotency_ok() {
+    let dc = get_memory_data_connector().await;
+    let checker = idempotency::IdempotencyChecker::new(Some(settings::Idempotency {
+        retention: 3600,
+        wait_timeout: 1,
+    }));
+    let key = entities::idempotency::IdempotencyKey {
+        caller: "webapi".to_string(),
+        object_type: "AddCar".to_string(),
+        correlation_id: "1".to_string(),
+    };
+    assert_eq!(
+        checker.begin(&dc, &key).await.unwrap(),
+        idempotency::Begin::New
+    );
+    assert_eq!(
+        checker.begin(&dc, &key).await.unwrap(),
+        idempotency::Begin::InProgress
+    );
+    //original request is abandoned after wait timeout, retry takes it over
+    tokio::time::delay_for(std::time::Duration::from_millis(1100)).await;
+    assert_eq!(
+        checker.begin(&dc, &key).await.unwrap(),
+        idempotency::Begin::New
+    );
+    checker.complete(&dc, &key, 200, "{}").await.unwrap();
+    assert_eq!(
+        checker.begin(&dc, &key).await.unwrap(),
+        idempotency::Begin::Replay(200, "{}".to_string())
+    );
+    //other caller has own key, canceled key is taken again
+    let other = entities::idempotency::IdempotencyKey {
+        caller: "other".to_string(),
+        ..key.clone()
+    };
+    assert_eq!(
+        checker.begin(&dc, &other).await.unwrap(),
+        idempotency::Begin::New
+    );
+    checker.cancel(&dc, &other).await.unwrap();
+    assert_eq!(
+        checker.begin(&dc, &other).await.unwrap(),
+        idempotency::Begin::New
+    );
+    //stored replies expire
+    tokio::time::delay_for(std::time::Duration::from_millis(10)).await;
+    let checker = idempotency::IdempotencyChecker::new(Some(settings::Idempotency {
+        retention: 0,
+        wait_timeout: 0,
+    }));
+    checker.remove_expired(&dc).await.unwrap();
+    assert_eq!(
+        checker.begin(&dc, &key).await.unwrap(),
+        idempotency::Begin::New
+    );
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_rate_limit_ok() {
+    let dc = get_memory_data_connector().await;
+    let rule = settings::RateLimitRule {
+        rate: 0.01,
+        burst: 2,
+        daily_quota: None,
+    };
+    let mut route = HashMap::<String, settings::RateLimitRule>::new();
+    route.insert(
+        "AddCar".to_string(),
+        settings::RateLimitRule {
+            rate: 1000.0,
+            burst: 1000,
+            daily_quota: Some(2),
+        },
+    );
+    let limiter = limiter::RateLimiter::new(Some(settings::RateLimit {
+        default: rule,
+        route: route,
+    }));
+    assert_eq!(limiter.get_rule("GetCar"), Some(&rule));
+    //burst is spent, token is added in 100 seconds
+    for _ in 0..2 {
+        assert_eq!(
+            limiter.check(&dc, "test", "GetCar").await.unwrap(),
+            limiter::Check::Allowed
+        );
+    }
+    assert_eq!(
+        limiter.check(&dc, "test", "GetCar").await.unwrap(),
+        limiter::Check::Limited(100)
+    );
+    //other caller has own bucket
+    assert_eq!(
+        limiter.check(&dc, "other", "GetCar").await.unwrap(),
+        limiter::Check::Allowed
+    );
+    //daily quota is counted in store
+    for _ in 0..2 {
+        assert_eq!(
+            limiter.check(&dc, "test", "AddCar").await.unwrap(),
+            limiter::Check::Allowed
+        );
+    }
+    match limiter.check(&dc, "test", "AddCar").await.unwrap() {
+        limiter::Check::Limited(retry_after) => assert!(retry_after > 0 && retry_after <= 86400),
+        c => panic!("unexpected {:?}", c),
+    }
+    let usage = dc
+        .quota
+        .get("test", &chrono::Utc::now().format("%Y-%m-%d").to_string())
+        .await
+        .unwrap();
+    assert_eq!(usage.len(), 1);
+    assert_eq!(usage[0].usage_count, 3);
+    //limits are disabled without settings
+    let limiter = limiter::RateLimiter::new(None);
+    assert_eq!(limiter.get_rule("GetCar"), None);
+    assert_eq!(
+        limiter.check(&dc, "test", "GetCar").await.unwrap(),
+        limiter::Check::Allowed
+    );
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_circuit_breaker_ok() {
+    let cb = breaker::CircuitBreakers::new(Some(settings::CircuitBreaker {
+        failure_threshold: 2,
+        cool_down: 3600,
+        call_timeout: 1,
+    }));
+    let fail = || async {
+        let e: Box<dyn std::error::Error> =
+            errors::Error::new(errors::ErrorKind::ProtoProvider).into();
+        Err::<(), _>(e)
+    };
+    assert!(cb.call("webapi", async { Ok(()) }).await.is_ok());
+    assert!(cb.call("webapi", fail()).await.is_err());
+    assert_eq!(
+        cb.get_state("webapi"),
+        entities::route::BreakerState::Closed
+    );
+    assert!(cb.call("webapi", fail()).await.is_err());
+    assert_eq!(cb.get_state("webapi"), entities::route::BreakerState::Open);
+    //open breaker fails fast, call is not started
+    let mut is_called = false;
+    let e = cb
+        .call("webapi", async {
+            is_called = true;
+            Ok(())
+        })
+        .await
+        .map_err(|e| errors::from_boxed(&e))
+        .unwrap_err();
+    assert_eq!(e.kind(), errors::ErrorKind::CircuitOpen);
+    assert!(!is_called);
+    //other service has own breaker
+    assert!(cb.call("other", async { Ok(()) }).await.is_ok());
+    let breakers = cb.get_breakers();
+    assert_eq!(breakers.len(), 2);
+    assert_eq!(breakers[1].service_name, "webapi");
+    assert_eq!(breakers[1].failure_count, 2);
+    //trial call after cool down closes or opens breaker again
+    let cb = breaker::CircuitBreakers::new(Some(settings::CircuitBreaker {
+        failure_threshold: 1,
+        cool_down: 0,
+        call_timeout: 0,
+    }));
+    assert!(cb.call("webapi", fail()).await.is_err());
+    assert_eq!(cb.get_state("webapi"), entities::route::BreakerState::Open);
+    assert!(cb.call("webapi", fail()).await.is_err());
+    assert_eq!(cb.get_state("webapi"), entities::route::BreakerState::Open);
+    assert!(cb.call("webapi", async { Ok(()) }).await.is_ok());
+    assert_eq!(
+        cb.get_state("webapi"),
+        entities::route::BreakerState::Closed
+    );
+    //hung call is failed by timeout
+    let e = cb
+        .call("webapi", async {
+            tokio::time::delay_for(std::time::Duration::from_millis(50)).await;
+            Ok(())
+        })
+        .await
+        .map_err(|e| errors::from_boxed(&e))
+        .unwrap_err();
+    assert_eq!(e.kind(), errors::ErrorKind::TimeoutExpired);
+    assert_eq!(cb.get_state("webapi"), entities::route::BreakerState::Open);
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_circuit_breaker_rejected_ok() {
+    let cb = breaker::CircuitBreakers::new(Some(settings::CircuitBreaker {
+        failure_threshold: 1,
+        cool_down: 3600,
+        call_timeout: 1,
+    }));
+    //4xx reply is error of request, service is alive
+    for _ in 0..3 {
+        let e = cb
+            .call("webapi", async {
+                let e: Box<dyn std::error::Error> = errors::Error::with_context(
+                    errors::ErrorKind::RejectedRequest,
+                    "http://localhost/api/request reply 400 Bad Request",
+                )
+                .into();
+                Err::<(), _>(e)
+            })
+            .await
+            .map_err(|e| errors::from_boxed(&e))
+            .unwrap_err();
+        assert_eq!(e.kind(), errors::ErrorKind::RejectedRequest);
+    }
+    assert_eq!(
+        cb.get_state("webapi"),
+        entities::route::BreakerState::Closed
+    );
+    assert_eq!(cb.get_breakers()[0].failure_count, 0);
 }
-*/
\ No newline at end of file
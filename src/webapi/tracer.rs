@@ -0,0 +1,103 @@
+use super::{connectors, context, errors, settings};
+use chrono::Utc;
+use serde_json::json;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::Mutex;
+
+enum Exporter {
+    Off,
+    Stdout,
+    File(Mutex<File>),
+}
+
+//finished spans as json lines in opentelemetry span format
+pub struct Tracer {
+    service_name: Option<String>,
+    exporter: Exporter,
+}
+
+impl Tracer {
+    pub fn new(tracing: Option<settings::Tracing>) -> connectors::Result<Tracer> {
+        let tracing = match tracing {
+            Some(t) => t,
+            None => {
+                return Ok(Tracer {
+                    service_name: None,
+                    exporter: Exporter::Off,
+                })
+            }
+        };
+        let exporter = match tracing.exporter {
+            settings::TraceExporter::Off => Exporter::Off,
+            settings::TraceExporter::Stdout => Exporter::Stdout,
+            settings::TraceExporter::File => {
+                let file = OpenOptions::new().create(true).append(true).open(
+                    tracing.file.as_ref().ok_or_else(|| {
+                        errors::Error::with_context(
+                            errors::ErrorKind::UnsetRequiredValue,
+                            "tracing file",
+                        )
+                    })?,
+                )?;
+                Exporter::File(Mutex::new(file))
+            }
+        };
+        Ok(Tracer {
+            service_name: Some(tracing.service_name),
+            exporter: exporter,
+        })
+    }
+
+    pub fn export(&self, ctx: &context::Context, status: Option<u16>, is_error: bool) {
+        let line = match self.exporter {
+            Exporter::Off => return,
+            _ => self.to_line(ctx, status, is_error),
+        };
+        match &self.exporter {
+            Exporter::Stdout => println!("{}", line),
+            Exporter::File(file) => {
+                //single write per span, lines of concurrent requests are not mixed
+                if let Err(e) = file
+                    .lock()
+                    .unwrap()
+                    .write_all(format!("{}\n", line).as_bytes())
+                {
+                    warn!("trace export: {}", e);
+                }
+            }
+            Exporter::Off => {}
+        }
+    }
+
+    fn to_line(&self, ctx: &context::Context, status: Option<u16>, is_error: bool) -> String {
+        let span = &ctx.span;
+        let mut attributes = json!({ "correlation_id": ctx.correlation_id });
+        if let Some(caller) = &ctx.caller {
+            attributes["caller"] = json!(caller);
+        }
+        if let Some(status) = status {
+            attributes["http.status_code"] = json!(status);
+        }
+        json!({
+            "resource": { "service.name": self.service_name },
+            "traceId": span.trace_id,
+            "spanId": span.span_id,
+            "parentSpanId": span.parent_span_id,
+            "name": span.name,
+            "kind": match span.kind {
+                context::SpanKind::Server => "SPAN_KIND_SERVER",
+                context::SpanKind::Client => "SPAN_KIND_CLIENT",
+                context::SpanKind::Producer => "SPAN_KIND_PRODUCER",
+                context::SpanKind::Internal => "SPAN_KIND_INTERNAL",
+            },
+            "startTimeUnixNano": span.start.timestamp_nanos().to_string(),
+            "endTimeUnixNano": Utc::now().timestamp_nanos().to_string(),
+            "attributes": attributes,
+            "status": {
+                "code": if is_error { "STATUS_CODE_ERROR" } else { "STATUS_CODE_OK" }
+            }
+        })
+        .to_string()
+    }
+}
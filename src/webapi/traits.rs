@@ -1,9 +1,15 @@
-// use super::connectors;
-// use hyper::Body;
-// use std::collections::HashMap;
+use super::entities::{car, dead_letter, error, executor, idempotency, quota, route, saga, usr};
+use super::{connectors, errors};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 
 pub trait ObjectType {
     fn get_type_name() -> &'static str;
+
+    //increment on incompatible schema change
+    fn get_schema_version() -> u32 {
+        1
+    }
 }
 
 /*pub trait CommandState {
@@ -21,4 +27,203 @@ pub trait Provider {
         body: Body,
     ) -> connectors::Result<Body>;
 }
-*/
\ No newline at end of file
+*/
+
+#[async_trait]
+pub trait UsrStore: Send + Sync {
+    async fn get(&self, ids: Option<Vec<i32>>) -> connectors::Result<Vec<usr::Usr>>;
+}
+
+#[async_trait]
+pub trait ErrorStore: Send + Sync {
+    async fn get(&self, langs: Option<Vec<String>>) -> connectors::Result<Vec<error::Error>>;
+}
+
+#[async_trait]
+pub trait CarStore: Send + Sync {
+    async fn get(&self, ids: Option<Vec<i32>>) -> connectors::Result<Vec<car::Car>>;
+
+    async fn add(
+        &self,
+        items: Vec<car::Car>,
+    ) -> connectors::Result<(errors::ErrorCode, Option<Vec<i32>>)>;
+
+    async fn change(&self, items: Vec<car::Car>) -> connectors::Result<errors::ErrorCode>;
+
+    async fn remove(&self, ids: Vec<i32>) -> connectors::Result<errors::ErrorCode>;
+}
+
+#[async_trait]
+pub trait RouteStore: Send + Sync {
+    async fn get(&self, services: Option<Vec<String>>) -> connectors::Result<Vec<route::Route>>;
+
+    async fn get_command(
+        &self,
+        services: Option<Vec<String>>,
+    ) -> connectors::Result<Vec<route::ServiceCommand>>;
+
+    async fn get_event(
+        &self,
+        services: Option<Vec<String>>,
+    ) -> connectors::Result<Vec<route::ServiceEvent>>;
+
+    async fn get_subscription(
+        &self,
+        services: Option<Vec<String>>,
+    ) -> connectors::Result<Vec<route::ServiceSubscription>>;
+
+    async fn get_service(
+        &self,
+        names: Option<Vec<String>>,
+    ) -> connectors::Result<Vec<route::Service>>;
+
+    async fn get_service_path(
+        &self,
+        services: Option<Vec<String>>,
+    ) -> connectors::Result<Vec<route::ServicePath>>;
+
+    //replaces route set of each service in one transaction, returns changed services
+    async fn add(
+        &self,
+        items: Vec<route::Route>,
+        lease: Option<u32>,
+    ) -> connectors::Result<(errors::ErrorCode, Option<Vec<String>>)>;
+
+    async fn remove(&self, services: Vec<String>) -> connectors::Result<errors::ErrorCode>;
+
+    //NotFoundError if some service is not registered with lease
+    async fn renew(&self, services: Vec<String>) -> connectors::Result<errors::ErrorCode>;
+
+    //removes routes with lease lapsed before at and records it in history, returns removed services
+    async fn expire(&self, at: DateTime<Utc>) -> connectors::Result<Vec<String>>;
+
+    async fn get_history(
+        &self,
+        services: Option<Vec<String>>,
+    ) -> connectors::Result<Vec<route::ServiceHistory>>;
+}
+
+//T is executor::SendedAsyncCommand or executor::ReceivedAsyncCommand
+#[async_trait]
+pub trait AsyncCommandStore<T: Send + 'static>: Send + Sync {
+    async fn get(&self, ids: Option<Vec<String>>) -> connectors::Result<Vec<T>>;
+
+    //newest first
+    async fn get_page(&self, offset: u32, limit: u32) -> connectors::Result<Vec<T>>;
+
+    async fn add(
+        &self,
+        items: Vec<T>,
+    ) -> connectors::Result<(errors::ErrorCode, Option<Vec<String>>)>;
+
+    async fn remove(&self, ids: Vec<String>) -> connectors::Result<errors::ErrorCode>;
+}
+
+pub type SendedAsyncCommandStore = dyn AsyncCommandStore<executor::SendedAsyncCommand>;
+pub type ReceivedAsyncCommandStore = dyn AsyncCommandStore<executor::ReceivedAsyncCommand>;
+
+//finished sagas are kept, state and step are changed in place
+#[async_trait]
+pub trait SagaStore: Send + Sync {
+    async fn get(&self, ids: Option<Vec<String>>) -> connectors::Result<Vec<saga::Saga>>;
+
+    //sagas in states with lease lapsed before at, owner of them is gone
+    async fn get_expired(
+        &self,
+        states: Vec<saga::SagaState>,
+        at: DateTime<Utc>,
+    ) -> connectors::Result<Vec<saga::Saga>>;
+
+    async fn add(&self, item: saga::Saga) -> connectors::Result<errors::ErrorCode>;
+
+    //owner takes saga if lease is still lapsed, NotFoundError if other one took it first
+    async fn claim(
+        &self,
+        id: &str,
+        owner: &str,
+        at: DateTime<Utc>,
+        lease_expires_at: DateTime<Utc>,
+    ) -> connectors::Result<errors::ErrorCode>;
+
+    //extends lease of unfinished sagas of owner, returns count of them
+    async fn renew(&self, owner: &str, lease_expires_at: DateTime<Utc>) -> connectors::Result<u64>;
+
+    async fn change_state(
+        &self,
+        id: &str,
+        state: saga::SagaState,
+        step: i32,
+    ) -> connectors::Result<errors::ErrorCode>;
+}
+
+//one reply per key, key is taken by first request
+#[async_trait]
+pub trait IdempotencyStore: Send + Sync {
+    //stored reply if key is taken, otherwise item is added and none returned
+    async fn get_or_add(
+        &self,
+        item: idempotency::IdempotentReply,
+    ) -> connectors::Result<Option<idempotency::IdempotentReply>>;
+
+    async fn complete(
+        &self,
+        key: &idempotency::IdempotencyKey,
+        status: i32,
+        reply_body: &str,
+    ) -> connectors::Result<errors::ErrorCode>;
+
+    async fn remove(
+        &self,
+        key: &idempotency::IdempotencyKey,
+    ) -> connectors::Result<errors::ErrorCode>;
+
+    //in progress reply added before given time is taken by retry, false if it is not abandoned
+    async fn take_over(
+        &self,
+        key: &idempotency::IdempotencyKey,
+        added_before: DateTime<Utc>,
+    ) -> connectors::Result<bool>;
+
+    //returns count of removed replies
+    async fn remove_expired(&self, added_before: DateTime<Utc>) -> connectors::Result<u64>;
+}
+
+//daily usage counters, survive restarts
+#[async_trait]
+pub trait QuotaStore: Send + Sync {
+    //adds one request, returns count of day including it
+    async fn add_usage(
+        &self,
+        caller: &str,
+        object_type: &str,
+        day: &str,
+    ) -> connectors::Result<i64>;
+
+    async fn get(&self, caller: &str, day: &str) -> connectors::Result<Vec<quota::QuotaUsage>>;
+
+    //returns count of removed counters
+    async fn remove_expired(&self, day_before: &str) -> connectors::Result<u64>;
+}
+
+//events not delivered to subscriber, one item per subscriber
+#[async_trait]
+pub trait DeadLetterStore: Send + Sync {
+    async fn get(
+        &self,
+        ids: Option<Vec<String>>,
+    ) -> connectors::Result<Vec<dead_letter::DeadLetterEvent>>;
+
+    //newest first
+    async fn get_page(
+        &self,
+        offset: u32,
+        limit: u32,
+    ) -> connectors::Result<Vec<dead_letter::DeadLetterEvent>>;
+
+    async fn add(
+        &self,
+        item: dead_letter::DeadLetterEvent,
+    ) -> connectors::Result<errors::ErrorCode>;
+
+    async fn remove(&self, id: &str) -> connectors::Result<errors::ErrorCode>;
+}
@@ -0,0 +1,222 @@
+use super::replies::common::Violation;
+use schemars::schema::{InstanceType, RootSchema, Schema, SchemaObject, SingleOrVec};
+use serde_json::Value;
+
+const DEFINITIONS_REF: &str = "#/definitions/";
+
+//draft-07 subset generated by schemars, string patterns and formats are not checked
+pub fn validate(root: &RootSchema, value: &Value) -> Vec<Violation> {
+    let mut violations = Vec::<Violation>::new();
+    validate_object(root, &root.schema, value, "", &mut violations);
+    violations
+}
+
+//event bodies are arrays of events
+pub fn validate_items(root: &RootSchema, value: &Value) -> Vec<Violation> {
+    let mut violations = Vec::<Violation>::new();
+    match value {
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                validate_object(
+                    root,
+                    &root.schema,
+                    item,
+                    &format!("/{}", i),
+                    &mut violations,
+                );
+            }
+        }
+        _ => add_violation(&mut violations, "", "expected array".to_string()),
+    }
+    violations
+}
+
+fn add_violation(violations: &mut Vec<Violation>, path: &str, message: String) {
+    violations.push(Violation {
+        path: path.to_string(),
+        message: message,
+    });
+}
+
+fn is_valid(root: &RootSchema, schema: &Schema, value: &Value) -> bool {
+    let mut violations = Vec::<Violation>::new();
+    validate_schema(root, schema, value, "", &mut violations);
+    violations.len() == 0
+}
+
+fn is_type(instance_type: &InstanceType, value: &Value) -> bool {
+    match instance_type {
+        InstanceType::Null => value.is_null(),
+        InstanceType::Boolean => value.is_boolean(),
+        InstanceType::Object => value.is_object(),
+        InstanceType::Array => value.is_array(),
+        InstanceType::Number => value.is_number(),
+        InstanceType::String => value.is_string(),
+        InstanceType::Integer => value.is_i64() || value.is_u64(),
+    }
+}
+
+fn get_type_name(instance_type: &InstanceType) -> String {
+    serde_json::to_value(instance_type)
+        .ok()
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+        .unwrap_or_default()
+}
+
+fn validate_schema(
+    root: &RootSchema,
+    schema: &Schema,
+    value: &Value,
+    path: &str,
+    violations: &mut Vec<Violation>,
+) {
+    match schema {
+        Schema::Bool(true) => {}
+        Schema::Bool(false) => add_violation(violations, path, "value is not allowed".to_string()),
+        Schema::Object(schema) => validate_object(root, schema, value, path, violations),
+    }
+}
+
+fn validate_object(
+    root: &RootSchema,
+    schema: &SchemaObject,
+    value: &Value,
+    path: &str,
+    violations: &mut Vec<Violation>,
+) {
+    //other keywords are ignored next to $ref
+    if let Some(reference) = &schema.reference {
+        let name = reference.trim_start_matches(DEFINITIONS_REF);
+        match root.definitions.get(name) {
+            Some(s) => validate_schema(root, s, value, path, violations),
+            None => add_violation(violations, path, format!("unknown reference {}", reference)),
+        }
+        return;
+    }
+    if let Some(instance_type) = &schema.instance_type {
+        let (is_valid_type, names) = match instance_type {
+            SingleOrVec::Single(t) => (is_type(t, value), get_type_name(t)),
+            SingleOrVec::Vec(ts) => (
+                ts.iter().any(|t| is_type(t, value)),
+                ts.iter()
+                    .map(get_type_name)
+                    .collect::<Vec<String>>()
+                    .join(" or "),
+            ),
+        };
+        if !is_valid_type {
+            add_violation(violations, path, format!("expected {}", names));
+            return;
+        }
+    }
+    if let Some(enum_values) = &schema.enum_values {
+        if !enum_values.contains(value) {
+            add_violation(
+                violations,
+                path,
+                format!("expected one of {:?}", enum_values),
+            );
+        }
+    }
+    if let Some(const_value) = &schema.const_value {
+        if const_value != value {
+            add_violation(violations, path, format!("expected {}", const_value));
+        }
+    }
+    if let Some(sub) = &schema.subschemas {
+        if let Some(all_of) = &sub.all_of {
+            for s in all_of {
+                validate_schema(root, s, value, path, violations);
+            }
+        }
+        if let Some(any_of) = &sub.any_of {
+            if !any_of.iter().any(|s| is_valid(root, s, value)) {
+                add_violation(
+                    violations,
+                    path,
+                    "value does not match any schema".to_string(),
+                );
+            }
+        }
+        if let Some(one_of) = &sub.one_of {
+            if one_of.iter().filter(|s| is_valid(root, s, value)).count() != 1 {
+                add_violation(
+                    violations,
+                    path,
+                    "value must match exactly one schema".to_string(),
+                );
+            }
+        }
+    }
+    if let (Some(number), Some(n)) = (&schema.number, value.as_f64()) {
+        if number.minimum.map_or(false, |m| n < m)
+            || number.exclusive_minimum.map_or(false, |m| n <= m)
+        {
+            add_violation(violations, path, format!("{} is less than minimum", n));
+        }
+        if number.maximum.map_or(false, |m| n > m)
+            || number.exclusive_maximum.map_or(false, |m| n >= m)
+        {
+            add_violation(violations, path, format!("{} is greater than maximum", n));
+        }
+    }
+    if let (Some(string), Some(s)) = (&schema.string, value.as_str()) {
+        let len = s.chars().count() as u32;
+        if string.min_length.map_or(false, |m| len < m) {
+            add_violation(
+                violations,
+                path,
+                format!("length is less than {}", string.min_length.unwrap()),
+            );
+        }
+        if string.max_length.map_or(false, |m| len > m) {
+            add_violation(
+                violations,
+                path,
+                format!("length is greater than {}", string.max_length.unwrap()),
+            );
+        }
+    }
+    if let (Some(array), Value::Array(items)) = (&schema.array, value) {
+        let len = items.len() as u32;
+        if array.min_items.map_or(false, |m| len < m) || array.max_items.map_or(false, |m| len > m)
+        {
+            add_violation(violations, path, format!("unexpected item count {}", len));
+        }
+        match &array.items {
+            Some(SingleOrVec::Single(s)) => {
+                for (i, item) in items.iter().enumerate() {
+                    validate_schema(root, s, item, &format!("{}/{}", path, i), violations);
+                }
+            }
+            Some(SingleOrVec::Vec(ss)) => {
+                for (i, (s, item)) in ss.iter().zip(items.iter()).enumerate() {
+                    validate_schema(root, s, item, &format!("{}/{}", path, i), violations);
+                }
+            }
+            None => {}
+        }
+    }
+    if let (Some(object), Value::Object(map)) = (&schema.object, value) {
+        for name in &object.required {
+            if !map.contains_key(name) {
+                add_violation(
+                    violations,
+                    &format!("{}/{}", path, name),
+                    "required value is missing".to_string(),
+                );
+            }
+        }
+        for (name, item) in map {
+            let item_path = format!("{}/{}", path, name);
+            match object.properties.get(name) {
+                Some(s) => validate_schema(root, s, item, &item_path, violations),
+                None => {
+                    if let Some(s) = &object.additional_properties {
+                        validate_schema(root, s, item, &item_path, violations);
+                    }
+                }
+            }
+        }
+    }
+}
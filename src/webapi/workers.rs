@@ -1,37 +1,130 @@
-use super::{connectors, errors};
-use std::sync::atomic::{AtomicBool, Ordering};
+use super::{
+    commands, connectors, context, errors, events, executors, handlers, metrics, publishers,
+    replies, router, saga,
+};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time;
+use uuid::Uuid;
 
 #[derive(Debug)]
 pub enum SignalCode {
     Exit = 0,
-    JobNo1 = 1
+    JobNo1 = 1,
+}
+
+//worker liveness and queued signals, checked by readiness probe
+pub struct WorkerState {
+    pub name: &'static str,
+    pub capacity: usize,
+    is_running: AtomicBool,
+    pending: AtomicUsize,
+}
+
+impl WorkerState {
+    pub fn is_running(&self) -> bool {
+        self.is_running.load(Ordering::SeqCst)
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    //full queue blocks senders
+    pub fn is_saturated(&self) -> bool {
+        self.pending() >= self.capacity
+    }
+
+    fn start(state: &Arc<WorkerState>) -> RunningGuard {
+        state.is_running.store(true, Ordering::SeqCst);
+        RunningGuard(state.clone())
+    }
+}
+
+//reset running flag on any worker exit, including panic
+struct RunningGuard(Arc<WorkerState>);
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.0.is_running.store(false, Ordering::SeqCst);
+    }
+}
+
+//counts signals queued to worker
+#[derive(Clone)]
+pub struct SignalSender {
+    sender: mpsc::Sender<SignalCode>,
+    state: Arc<WorkerState>,
+}
+
+impl SignalSender {
+    pub async fn send(
+        &mut self,
+        signal_code: SignalCode,
+    ) -> Result<(), mpsc::error::SendError<SignalCode>> {
+        self.state.pending.fetch_add(1, Ordering::SeqCst);
+        let res = self.sender.send(signal_code).await;
+        if res.is_err() {
+            self.state.pending.fetch_sub(1, Ordering::SeqCst);
+        }
+        res
+    }
+}
+
+pub fn channel(
+    name: &'static str,
+    capacity: usize,
+) -> (SignalSender, mpsc::Receiver<SignalCode>, Arc<WorkerState>) {
+    let (sender, receiver) = mpsc::channel::<SignalCode>(capacity);
+    let state = Arc::new(WorkerState {
+        name: name,
+        capacity: capacity,
+        is_running: AtomicBool::new(false),
+        pending: AtomicUsize::new(0),
+    });
+    (
+        SignalSender {
+            sender: sender,
+            state: state.clone(),
+        },
+        receiver,
+        state,
+    )
 }
 
 pub async fn event_publisher_worker(
     cancel_flag: Arc<AtomicBool>,
     mut receiver: mpsc::Receiver<SignalCode>,
+    metrics: Arc<metrics::Metrics>,
+    state: Arc<WorkerState>,
 ) -> connectors::Result<()> {
     const TASK: &str = "event publisher";
     debug!("start {}", TASK);
+    let _running = WorkerState::start(&state);
+    let mut is_exit = false;
     loop {
         match receiver.recv().await {
             Some(m) => {
+                state.pending.fetch_sub(1, Ordering::SeqCst);
                 debug!("{} receive signal {:?}", TASK, m);
+                metrics.add_worker_signal(TASK, &format!("{:?}", m));
                 match m {
                     SignalCode::Exit => {
-                        return Ok({});
+                        //handle already queued signals before exit
+                        receiver.close();
+                        is_exit = true;
                     }
                     _ => {}
                 };
-            },
+            }
             None => {
-                if cancel_flag.load(Ordering::SeqCst) {
+                if is_exit || cancel_flag.load(Ordering::SeqCst) {
                     debug!("{} cancel flag", TASK);
                     return Ok({});
                 } else {
-                    return Err(errors::ChannelError.into());
+                    return Err(errors::Error::new(errors::ErrorKind::Channel).into());
                 }
             }
         };
@@ -41,28 +134,241 @@ pub async fn event_publisher_worker(
 pub async fn command_executor_worker(
     cancel_flag: Arc<AtomicBool>,
     mut receiver: mpsc::Receiver<SignalCode>,
+    metrics: Arc<metrics::Metrics>,
+    state: Arc<WorkerState>,
 ) -> connectors::Result<()> {
     const TASK: &str = "command executor";
     debug!("start {}", TASK);
+    let _running = WorkerState::start(&state);
+    let mut is_exit = false;
     loop {
         match receiver.recv().await {
             Some(m) => {
+                state.pending.fetch_sub(1, Ordering::SeqCst);
                 debug!("{} receive signal {:?}", TASK, m);
+                metrics.add_worker_signal(TASK, &format!("{:?}", m));
                 match m {
                     SignalCode::Exit => {
-                        return Ok({});
+                        //handle already queued signals before exit
+                        receiver.close();
+                        is_exit = true;
                     }
                     _ => {}
                 };
-            },
+            }
             None => {
-                if cancel_flag.load(Ordering::SeqCst) {
+                if is_exit || cancel_flag.load(Ordering::SeqCst) {
                     debug!("{} cancel flag", TASK);
                     return Ok({});
                 } else {
-                    return Err(errors::ChannelError.into());
+                    return Err(errors::Error::new(errors::ErrorKind::Channel).into());
                 }
             }
         };
     }
 }
+
+//router removes routes of services which stopped heartbeat
+pub async fn lease_expiry_worker(
+    cancel_flag: Arc<AtomicBool>,
+    dc: Arc<connectors::DataConnector>,
+    ep: Arc<publishers::EventPublisher>,
+    interval: Duration,
+) {
+    const TASK: &str = "lease expiry";
+    debug!("start {}", TASK);
+    while !cancel_flag.load(Ordering::SeqCst) {
+        time::delay_for(interval).await;
+        //boxed error is not Send, it must not live across await
+        let res = handlers::route::expire(&dc)
+            .await
+            .map_err(|e| errors::from_boxed(&e));
+        let events = match res {
+            Ok(Some(events)) => events,
+            Ok(None) => continue,
+            Err(e) => {
+                error!("{}: {}", TASK, e);
+                continue;
+            }
+        };
+        let ctx = context::Context::new(
+            &Uuid::new_v4().to_hyphenated().to_string(),
+            None,
+            context::Span::new(TASK, context::SpanKind::Internal),
+        );
+        let res = ep
+            .send(&ctx, events)
+            .await
+            .map_err(|e| errors::from_boxed(&e));
+        if let Err(e) = res {
+            error!("{} event publisher: {}", TASK, e);
+        }
+    }
+    debug!("stop {}", TASK);
+}
+
+//stored idempotent replies are removed after retention
+pub async fn idempotency_expiry_worker(
+    cancel_flag: Arc<AtomicBool>,
+    dc: Arc<connectors::DataConnector>,
+    rt: Arc<router::Router>,
+    interval: Duration,
+) {
+    const TASK: &str = "idempotency expiry";
+    debug!("start {}", TASK);
+    while !cancel_flag.load(Ordering::SeqCst) {
+        time::delay_for(interval).await;
+        //boxed error is not Send, it must not live across await
+        let res = rt
+            .idempotency
+            .remove_expired(&dc)
+            .await
+            .map_err(|e| errors::from_boxed(&e));
+        if let Err(e) = res {
+            error!("{}: {}", TASK, e);
+        }
+    }
+    debug!("stop {}", TASK);
+}
+
+//lapsed lease is registered again in remote router, it publishes route update itself
+async fn renew_remote(
+    rt: &router::Router,
+    ce: &executors::CommandExecutor,
+    ctx: &context::Context,
+) -> connectors::Result<()> {
+    let registration = match rt.get_registration() {
+        Some(registration) => registration,
+        None => return Ok(()),
+    };
+    let services: Vec<String> = registration
+        .items
+        .iter()
+        .map(|r| r.service_name.clone().unwrap_or_default())
+        .collect();
+    let reply: replies::common::StandardReply = ce
+        .call(
+            ctx,
+            commands::route::RenewRoute {
+                services: services.clone(),
+            },
+        )
+        .await?;
+    if reply.error_code == errors::ErrorCode::ReplyOk {
+        return Ok(());
+    }
+    warn!("lease of {:?} is lapsed, routes are added again", services);
+    let reply: replies::common::AddStrIdsReply = ce.call(ctx, registration).await?;
+    if reply.is_ok() {
+        Ok(())
+    } else {
+        Err(errors::Error::with_context(
+            errors::ErrorKind::DataIntegrity,
+            &format!(
+                "add routes {:?}: {}",
+                services,
+                reply.error_code.to_string()
+            ),
+        )
+        .into())
+    }
+}
+
+//service renews lease of own routes, stopped before routes are removed on shutdown
+pub async fn heartbeat_worker(
+    mut stop: oneshot::Receiver<()>,
+    rt: Arc<router::Router>,
+    ce: Arc<executors::CommandExecutor>,
+    ep: Arc<publishers::EventPublisher>,
+    interval: Duration,
+) {
+    const TASK: &str = "heartbeat";
+    debug!("start {}", TASK);
+    loop {
+        tokio::select! {
+            _ = time::delay_for(interval) => {}
+            _ = &mut stop => break,
+        }
+        let ctx = context::Context::new(
+            &Uuid::new_v4().to_hyphenated().to_string(),
+            None,
+            context::Span::new(TASK, context::SpanKind::Internal),
+        );
+        if !rt.is_local {
+            //boxed error is not Send, it must not live across await
+            let res = renew_remote(&rt, &ce, &ctx)
+                .await
+                .map_err(|e| errors::from_boxed(&e));
+            if let Err(e) = res {
+                error!("{}: {}", TASK, e);
+            }
+            continue;
+        }
+        //boxed error is not Send, it must not live across await
+        let res = rt.renew().await.map_err(|e| errors::from_boxed(&e));
+        let services = match res {
+            Ok(Some(services)) if services.len() > 0 => services,
+            Ok(_) => continue,
+            Err(e) => {
+                error!("{}: {}", TASK, e);
+                continue;
+            }
+        };
+        let res = ep
+            .send(
+                &ctx,
+                vec![events::route::OnRouteUpdate { services: services }],
+            )
+            .await
+            .map_err(|e| errors::from_boxed(&e));
+        if let Err(e) = res {
+            error!("{} event publisher: {}", TASK, e);
+        }
+    }
+    debug!("stop {}", TASK);
+}
+
+//owner keeps leases of its running sagas while recovery of others may take long
+pub async fn saga_lease_worker(
+    cancel_flag: Arc<AtomicBool>,
+    dc: Arc<connectors::DataConnector>,
+    ce: Arc<executors::CommandExecutor>,
+    interval: Duration,
+) {
+    const TASK: &str = "saga lease";
+    debug!("start {}", TASK);
+    while !cancel_flag.load(Ordering::SeqCst) {
+        time::delay_for(interval).await;
+        //boxed error is not Send, it must not live across await
+        let res = saga::renew(&dc, &ce)
+            .await
+            .map_err(|e| errors::from_boxed(&e));
+        if let Err(e) = res {
+            error!("{}: {}", TASK, e);
+        }
+    }
+    debug!("stop {}", TASK);
+}
+
+//sagas of crashed or stopped instances are recovered after their lease lapses
+pub async fn saga_recovery_worker(
+    cancel_flag: Arc<AtomicBool>,
+    dc: Arc<connectors::DataConnector>,
+    ce: Arc<executors::CommandExecutor>,
+    sagas: Vec<Box<dyn saga::Recoverable>>,
+    interval: Duration,
+) {
+    const TASK: &str = "saga recovery";
+    debug!("start {}", TASK);
+    let sagas: Vec<&dyn saga::Recoverable> = sagas.iter().map(|s| s.as_ref()).collect();
+    while !cancel_flag.load(Ordering::SeqCst) {
+        let res = saga::recover(&dc, &ce, &sagas)
+            .await
+            .map_err(|e| errors::from_boxed(&e));
+        if let Err(e) = res {
+            error!("{}: {}", TASK, e);
+        }
+        time::delay_for(interval).await;
+    }
+    debug!("stop {}", TASK);
+}